@@ -1,7 +1,13 @@
 use unicode_width::UnicodeWidthChar as _;
 
-// chosen to make the size of the cell struct 32 bytes
-const CONTENT_BYTES: usize = 22;
+// chosen to make the size of the cell struct 32 bytes, together with the
+// pointer to a spilled cluster
+const CONTENT_BYTES: usize = 14;
+
+// grapheme clusters longer than this are truncated; the longest standard
+// emoji ZWJ sequences are around 40 bytes, anything past that is a stream
+// of stacked combining marks
+const MAX_CLUSTER_BYTES: usize = 64;
 
 const IS_WIDE: u8 = 0b1000_0000;
 const IS_WIDE_CONTINUATION: u8 = 0b0100_0000;
@@ -13,6 +19,11 @@ pub struct Cell {
     contents: [u8; CONTENT_BYTES],
     len: u8,
     attrs: crate::attrs::Attrs,
+    // grapheme clusters that don't fit in contents (long ZWJ emoji
+    // sequences, stacks of combining marks). Arc<String> rather than
+    // Arc<str> keeps this a thin pointer, and the text is freed once the
+    // last cell holding it is overwritten.
+    spill: Option<std::sync::Arc<String>>,
 }
 const _: () = assert!(std::mem::size_of::<Cell>() == 32);
 
@@ -24,8 +35,7 @@ impl PartialEq<Self> for Cell {
         if self.attrs != other.attrs {
             return false;
         }
-        let len = self.len();
-        self.contents[..len] == other.contents[..len]
+        self.contents() == other.contents()
     }
 }
 
@@ -35,6 +45,7 @@ impl Cell {
             contents: Default::default(),
             len: 0,
             attrs: crate::attrs::Attrs::default(),
+            spill: None,
         }
     }
 
@@ -44,24 +55,40 @@ impl Cell {
 
     pub(crate) fn set(&mut self, c: char, a: crate::attrs::Attrs) {
         self.len = 0;
+        self.spill = None;
         self.append_char(0, c);
         // strings in this context should always be an arbitrary character
-        // followed by zero or more zero-width characters, so we should only
-        // have to look at the first character
+        // followed by zero or more characters extending the same grapheme
+        // cluster, so we should only have to look at the first character.
+        // the screen adjusts this afterwards for ambiguous-width characters
+        // and emoji presentation selectors.
         self.set_wide(c.width().unwrap_or(1) > 1);
         self.attrs = a;
     }
 
     pub(crate) fn append(&mut self, c: char) {
         let len = self.len();
-        if len >= CONTENT_BYTES - 4 {
-            return;
-        }
         if len == 0 {
             self.contents[0] = b' ';
             self.len += 1;
         }
 
+        if self.contents().len() + c.len_utf8() > MAX_CLUSTER_BYTES {
+            return;
+        }
+
+        if let Some(spill) = &mut self.spill {
+            std::sync::Arc::make_mut(spill).push(c);
+            return;
+        }
+
+        if self.len() + c.len_utf8() > CONTENT_BYTES {
+            let mut cluster = self.contents().to_string();
+            cluster.push(c);
+            self.spill = Some(std::sync::Arc::new(cluster));
+            return;
+        }
+
         // we already checked that we have space for another codepoint
         self.append_char(self.len(), c);
     }
@@ -75,18 +102,22 @@ impl Cell {
 
     pub(crate) fn clear(&mut self, attrs: crate::attrs::Attrs) {
         self.len = 0;
+        self.spill = None;
         self.attrs = attrs;
     }
 
     /// Returns the text contents of the cell.
     ///
-    /// Can include multiple unicode characters if combining characters are
-    /// used, but will contain at most one character with a non-zero character
-    /// width.
+    /// Contains a single grapheme cluster: a base character followed by any
+    /// combining marks, variation selectors, skin tone modifiers or
+    /// zero-width-joined characters that belong to it.
     // Since contents has been constructed by appending chars encoded as UTF-8 it will be valid UTF-8
     #[allow(clippy::missing_panics_doc)]
     #[must_use]
     pub fn contents(&self) -> &str {
+        if let Some(spill) = &self.spill {
+            return spill;
+        }
         std::str::from_utf8(&self.contents[..self.len()]).unwrap()
     }
 
@@ -110,7 +141,7 @@ impl Cell {
         self.len & IS_WIDE_CONTINUATION != 0
     }
 
-    fn set_wide(&mut self, wide: bool) {
+    pub(crate) fn set_wide(&mut self, wide: bool) {
         if wide {
             self.len |= IS_WIDE;
         } else {
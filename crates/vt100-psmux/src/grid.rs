@@ -13,6 +13,7 @@ pub struct Grid {
     scrollback: std::collections::VecDeque<crate::row::Row>,
     scrollback_len: usize,
     scrollback_offset: usize,
+    scrollback_dropped: usize,
 }
 
 impl Grid {
@@ -29,6 +30,7 @@ impl Grid {
             scrollback: std::collections::VecDeque::new(),
             scrollback_len,
             scrollback_offset: 0,
+            scrollback_dropped: 0,
         }
     }
 
@@ -123,6 +125,11 @@ impl Grid {
         self.origin_mode = self.saved_origin_mode;
     }
 
+    pub fn reset_saved_cursor(&mut self) {
+        self.saved_pos = Pos::default();
+        self.saved_origin_mode = false;
+    }
+
     pub fn visible_rows(&self) -> impl Iterator<Item = &crate::row::Row> {
         let scrollback_len = self.scrollback.len();
         let rows_len = self.rows.len();
@@ -191,6 +198,23 @@ impl Grid {
         self.scrollback_len
     }
 
+    pub fn history_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    pub fn history_dropped(&self) -> usize {
+        self.scrollback_dropped
+    }
+
+    pub fn history_row(&self, line: usize) -> Option<&crate::row::Row> {
+        let len = self.scrollback.len();
+        if line < len {
+            self.scrollback.get(line)
+        } else {
+            self.rows.get(line - len)
+        }
+    }
+
     pub fn scrollback(&self) -> usize {
         self.scrollback_offset
     }
@@ -567,6 +591,7 @@ impl Grid {
                 self.scrollback.push_back(removed);
                 while self.scrollback.len() > self.scrollback_len {
                     self.scrollback.pop_front();
+                    self.scrollback_dropped += 1;
                 }
                 if self.scrollback_offset > 0 {
                     self.scrollback_offset =
@@ -1,8 +1,24 @@
 /// A parser for terminal output which produces an in-memory representation of
 /// the terminal contents.
 pub struct Parser<CB: crate::callbacks::Callbacks = ()> {
-    parser: vte::Parser,
+    vte: vte::Parser,
     screen: crate::perform::WrappedScreen<CB>,
+    pending: Vec<u8>,
+}
+
+/// Longest incomplete escape sequence kept for
+/// [`pending_input`](Parser::pending_input).
+const PENDING_MAX: usize = 4096;
+
+/// Returns whether `seq`, which starts with ESC, is a complete escape
+/// sequence.
+fn sequence_complete(seq: &[u8]) -> bool {
+    match seq.get(1) {
+        None => false,
+        Some(b'[') => seq[2..].iter().any(|b| (0x40..=0x7e).contains(b)),
+        Some(b']' | b'P' | b'_' | b'^' | b'X') => seq[2..].contains(&0x07),
+        Some(_) => seq[1..].iter().any(|b| (0x30..=0x7e).contains(b)),
+    }
 }
 
 impl Parser {
@@ -11,12 +27,13 @@ impl Parser {
     #[must_use]
     pub fn new(rows: u16, cols: u16, scrollback_len: usize) -> Self {
         Self {
-            parser: vte::Parser::new(),
+            vte: vte::Parser::new(),
             screen: crate::perform::WrappedScreen::new(
                 rows,
                 cols,
                 scrollback_len,
             ),
+            pending: Vec::new(),
         }
     }
 }
@@ -33,20 +50,44 @@ impl<CB: crate::callbacks::Callbacks> Parser<CB> {
         callbacks: CB,
     ) -> Self {
         Self {
-            parser: vte::Parser::new(),
+            vte: vte::Parser::new(),
             screen: crate::perform::WrappedScreen::new_with_callbacks(
                 rows,
                 cols,
                 scrollback_len,
                 callbacks,
             ),
+            pending: Vec::new(),
         }
     }
 
     /// Processes the contents of the given byte string, and updates the
     /// in-memory terminal state.
     pub fn process(&mut self, bytes: &[u8]) {
-        self.parser.advance(&mut self.screen, bytes);
+        self.vte.advance(&mut self.screen, bytes);
+        self.track_pending(bytes);
+    }
+
+    /// Returns the bytes of an escape sequence that has started but not yet
+    /// finished, i.e. output the parser is still waiting to complete.
+    #[must_use]
+    pub fn pending_input(&self) -> &[u8] {
+        &self.pending
+    }
+
+    fn track_pending(&mut self, bytes: &[u8]) {
+        if let Some(i) = bytes.iter().rposition(|&b| b == 0x1b) {
+            self.pending.clear();
+            self.pending.extend_from_slice(&bytes[i..]);
+        } else if !self.pending.is_empty() {
+            self.pending.extend_from_slice(bytes);
+        } else {
+            return;
+        }
+        if sequence_complete(&self.pending) || self.pending.len() > PENDING_MAX
+        {
+            self.pending.clear();
+        }
     }
 
     /// Returns a reference to a [`Screen`](crate::Screen) object containing
@@ -56,7 +56,9 @@ impl<CB: crate::callbacks::Callbacks> vte::Perform for WrappedScreen<CB> {
     }
 
     fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, b: u8) {
-        if let Some(i) = intermediates.first() {
+        if intermediates == b"#" && b == b'8' {
+            self.screen.decaln();
+        } else if let Some(i) = intermediates.first() {
             self.callbacks.unhandled_escape(
                 &mut self.screen,
                 Some(*i),
@@ -123,6 +125,7 @@ impl<CB: crate::callbacks::Callbacks> vte::Perform for WrappedScreen<CB> {
                 'S' => self.screen.su(canonicalize_params_1(params, 1)),
                 'T' => self.screen.sd(canonicalize_params_1(params, 1)),
                 'X' => self.screen.ech(canonicalize_params_1(params, 1)),
+                'b' => self.screen.rep(canonicalize_params_1(params, 1)),
                 'd' => self.screen.vpa(canonicalize_params_1(params, 1)),
                 'm' => self.screen.sgr(params, unhandled),
                 'n' => {
@@ -191,6 +194,14 @@ impl<CB: crate::callbacks::Callbacks> vte::Perform for WrappedScreen<CB> {
                     );
                 }
             },
+            Some(b'!') if c == 'p' && intermediates.len() == 1 => {
+                self.screen.decstr();
+            }
+            Some(b' ') if c == 'q' && intermediates.len() == 1 => {
+                self.screen.decscusr(
+                    params.iter().next().map_or(0, |x| *x.first().unwrap_or(&0)),
+                );
+            }
             Some(i) => {
                 self.callbacks.unhandled_csi(
                     &mut self.screen,
@@ -49,11 +49,65 @@ fn hex_val(b: u8) -> Option<u8> {
     }
 }
 
+const ZWJ: char = '\u{200d}';
+const VS16: char = '\u{fe0f}';
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+fn is_emoji_modifier(c: char) -> bool {
+    ('\u{1f3fb}'..='\u{1f3ff}').contains(&c)
+}
+
+/// Returns whether `c` continues the grapheme cluster `prev` rather than
+/// starting a new one. Zero-width characters are handled by the caller; this
+/// covers the cases where the joined character has a width of its own.
+fn extends_cluster(prev: &str, c: char) -> bool {
+    let Some(last) = prev.chars().next_back() else {
+        return false;
+    };
+    if last == ZWJ {
+        return true;
+    }
+    if is_emoji_modifier(c) {
+        // skin tones only attach to a pictographic base
+        return prev.chars().next().is_some_and(|base| {
+            base >= '\u{2600}' && !is_emoji_modifier(base)
+        });
+    }
+    if is_regional_indicator(c) {
+        // flags are pairs of regional indicators
+        let mut chars = prev.chars();
+        return chars.next().is_some_and(is_regional_indicator)
+            && chars.next().is_none();
+    }
+    false
+}
+
+/// Returns whether the grapheme cluster `s` should occupy two columns
+/// regardless of the width of its first character.
+fn cluster_is_wide(s: &str, variation_selector_always_wide: bool) -> bool {
+    let mut chars = s.chars();
+    let Some(base) = chars.next() else {
+        return false;
+    };
+    if is_regional_indicator(base)
+        && s.chars().nth(1).is_some_and(is_regional_indicator)
+    {
+        return true;
+    }
+    variation_selector_always_wide && chars.any(|c| c == VS16)
+}
+
 const MODE_APPLICATION_KEYPAD: u8 = 0b0000_0001;
 const MODE_APPLICATION_CURSOR: u8 = 0b0000_0010;
 const MODE_HIDE_CURSOR: u8 = 0b0000_0100;
 const MODE_ALTERNATE_SCREEN: u8 = 0b0000_1000;
 const MODE_BRACKETED_PASTE: u8 = 0b0001_0000;
+const MODE_FOCUS_EVENTS: u8 = 0b0010_0000;
+// inverted so that alternate scroll (DECSET 1007) defaults to on
+const MODE_NO_ALTERNATE_SCROLL: u8 = 0b0100_0000;
 
 /// The xterm mouse handling mode currently in use.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
@@ -95,7 +149,12 @@ pub enum MouseProtocolEncoding {
 
     /// SGR-like encoding.
     Sgr,
-    // Urxvt,
+
+    /// urxvt-style decimal encoding (`CSI Cb ; Cx ; Cy M`).
+    Urxvt,
+
+    /// SGR-like encoding reporting pixel coordinates instead of cells.
+    SgrPixels,
 }
 
 /// Represents the overall terminal state.
@@ -114,6 +173,23 @@ pub struct Screen {
     /// Path announced by the shell via OSC 7 (`\e]7;file://host/path\a`).
     /// Used as a fallback for CWD when PEB walking fails (SSH, WSL).
     osc7_path: Option<String>,
+
+    /// Treat East Asian ambiguous-width characters as two columns wide.
+    ambiguous_width_wide: bool,
+    /// Widen any character followed by an emoji presentation selector
+    /// (U+FE0F) to two columns, matching tmux's
+    /// `variation-selector-always-wide`.
+    variation_selector_always_wide: bool,
+
+    /// Cursor style requested via DECSCUSR (`CSI Ps SP q`), `None` until
+    /// the application sets one.
+    cursor_shape: Option<u8>,
+    /// Cursor style in effect on the primary screen while the alternate
+    /// screen is active.
+    saved_cursor_shape: Option<u8>,
+
+    /// Last graphic character written, repeated by REP (`CSI Ps b`).
+    last_char: Option<char>,
 }
 
 impl Screen {
@@ -134,6 +210,14 @@ impl Screen {
             mouse_protocol_mode: MouseProtocolMode::default(),
             mouse_protocol_encoding: MouseProtocolEncoding::default(),
             osc7_path: None,
+
+            ambiguous_width_wide: false,
+            variation_selector_always_wide: true,
+
+            cursor_shape: None,
+            saved_cursor_shape: None,
+
+            last_char: None,
         }
     }
 
@@ -153,6 +237,37 @@ impl Screen {
         (size.rows, size.cols)
     }
 
+    /// Sets whether East Asian ambiguous-width characters (box drawing, some
+    /// symbols and punctuation) occupy two columns. This
+    /// should match the width the outer terminal uses for them.
+    ///
+    /// Only affects text written after the call.
+    pub fn set_ambiguous_width_wide(&mut self, wide: bool) {
+        self.ambiguous_width_wide = wide;
+    }
+
+    /// Returns whether East Asian ambiguous-width characters occupy two
+    /// columns.
+    #[must_use]
+    pub fn ambiguous_width_wide(&self) -> bool {
+        self.ambiguous_width_wide
+    }
+
+    /// Sets whether a character followed by an emoji presentation selector
+    /// (U+FE0F) is always treated as two columns wide. Defaults to `true`.
+    ///
+    /// Only affects text written after the call.
+    pub fn set_variation_selector_always_wide(&mut self, wide: bool) {
+        self.variation_selector_always_wide = wide;
+    }
+
+    /// Returns whether a character followed by an emoji presentation
+    /// selector is always treated as two columns wide.
+    #[must_use]
+    pub fn variation_selector_always_wide(&self) -> bool {
+        self.variation_selector_always_wide
+    }
+
     /// Scrolls to the given position in the scrollback.
     ///
     /// This position indicates the offset from the top of the screen, and
@@ -176,6 +291,61 @@ impl Screen {
         self.grid().scrollback()
     }
 
+    /// Returns the number of lines currently stored in the scrollback
+    /// buffer (at most the scrollback length the parser was created with).
+    #[must_use]
+    pub fn history_len(&self) -> usize {
+        self.grid().history_len()
+    }
+
+    /// Returns how many lines have been dropped off the top of the
+    /// scrollback buffer since the parser was created.  Adding this to a
+    /// history line number gives a position that stays put as new output
+    /// pushes old lines out.
+    #[must_use]
+    pub fn history_dropped(&self) -> usize {
+        self.grid().history_dropped()
+    }
+
+    /// Returns the cell at `col` of an absolute history line, ignoring the
+    /// scrollback offset.  Line `0` is the oldest scrollback line; lines
+    /// `history_len()..history_len() + rows` are the screen itself.
+    #[must_use]
+    pub fn history_cell(&self, line: usize, col: u16) -> Option<&crate::Cell> {
+        self.grid().history_row(line).and_then(|r| r.get(col))
+    }
+
+    /// Returns whether absolute history line `line` (numbered as for
+    /// [`history_cell`](Self::history_cell)) wraps onto the next line.
+    #[must_use]
+    pub fn history_row_wrapped(&self, line: usize) -> bool {
+        self.grid()
+            .history_row(line)
+            .is_some_and(crate::row::Row::wrapped)
+    }
+
+    /// While the alternate screen is in use, returns the cell at the given
+    /// location of the primary screen it hides.  Returns `None` when the
+    /// primary screen is the one in use.
+    #[must_use]
+    pub fn saved_cell(&self, row: u16, col: u16) -> Option<&crate::Cell> {
+        if !self.mode(MODE_ALTERNATE_SCREEN) {
+            return None;
+        }
+        self.grid.drawing_row(row).and_then(|r| r.get(col))
+    }
+
+    /// Returns whether row `row` of the hidden primary screen wraps, as for
+    /// [`saved_cell`](Self::saved_cell).
+    #[must_use]
+    pub fn saved_row_wrapped(&self, row: u16) -> bool {
+        self.mode(MODE_ALTERNATE_SCREEN)
+            && self
+                .grid
+                .drawing_row(row)
+                .is_some_and(crate::row::Row::wrapped)
+    }
+
     /// Returns the text contents of the terminal.
     ///
     /// This will not include any formatting information, and will be in plain
@@ -602,6 +772,18 @@ impl Screen {
         self.mode(MODE_ALTERNATE_SCREEN)
     }
 
+    /// Returns the cursor style most recently requested with DECSCUSR
+    /// (`CSI Ps SP q`), as its parameter: 0 or 1 for a blinking block, 2 for
+    /// a steady block, 3/4 for a blinking/steady underline and 5/6 for a
+    /// blinking/steady bar. Returns `None` if the application never set one.
+    ///
+    /// The alternate screen has its own cursor style: leaving it restores
+    /// the style that was in effect on the primary screen.
+    #[must_use]
+    pub fn cursor_shape(&self) -> Option<u8> {
+        self.cursor_shape
+    }
+
     /// Returns whether the terminal should be in application keypad mode.
     #[must_use]
     pub fn application_keypad(&self) -> bool {
@@ -626,6 +808,20 @@ impl Screen {
         self.mode(MODE_BRACKETED_PASTE)
     }
 
+    /// Returns whether the application asked for focus in/out reports
+    /// (DECSET 1004).
+    #[must_use]
+    pub fn focus_events(&self) -> bool {
+        self.mode(MODE_FOCUS_EVENTS)
+    }
+
+    /// Returns whether mouse wheel events on the alternate screen should be
+    /// sent as cursor up/down keys (DECSET 1007).  Enabled by default.
+    #[must_use]
+    pub fn alternate_scroll(&self) -> bool {
+        !self.mode(MODE_NO_ALTERNATE_SCROLL)
+    }
+
     /// Returns the currently active [`MouseProtocolMode`].
     #[must_use]
     pub fn mouse_protocol_mode(&self) -> MouseProtocolMode {
@@ -719,12 +915,23 @@ impl Screen {
     }
 
     fn enter_alternate_grid(&mut self) {
+        if !self.mode(MODE_ALTERNATE_SCREEN) {
+            self.saved_cursor_shape = self.cursor_shape;
+        }
         self.grid_mut().set_scrollback(0);
         self.set_mode(MODE_ALTERNATE_SCREEN);
         self.alternate_grid.allocate_rows();
     }
 
     fn exit_alternate_grid(&mut self) {
+        if self.mode(MODE_ALTERNATE_SCREEN) {
+            // if the primary screen never chose a style but the full screen
+            // application did, fall back to the terminal default rather than
+            // leaving the application's style behind
+            self.cursor_shape = self
+                .saved_cursor_shape
+                .or_else(|| self.cursor_shape.map(|_| 0));
+        }
         self.clear_mode(MODE_ALTERNATE_SCREEN);
     }
 
@@ -773,15 +980,42 @@ impl Screen {
 
 impl Screen {
     pub(crate) fn text(&mut self, c: char) {
-        let pos = self.grid().pos();
-        let size = self.grid().size();
         let attrs = self.attrs;
 
-        let width = c.width();
+        let width = if self.ambiguous_width_wide {
+            c.width_cjk()
+        } else {
+            c.width()
+        };
         if width.is_none() && (u32::from(c)) < 256 {
             // don't even try to draw control characters
             return;
         }
+
+        // characters which continue the grapheme cluster in the previous
+        // cell (combining marks, variation selectors, zero width joiners and
+        // whatever follows them, skin tone modifiers, the second half of a
+        // regional indicator pair) are stored in that cell rather than
+        // taking up a cell of their own
+        if let Some(prev_pos) = self.prev_cell_pos() {
+            let extends = self
+                .grid()
+                .drawing_cell(prev_pos)
+                .is_some_and(|cell| {
+                    width == Some(0)
+                        || extends_cluster(cell.contents(), c)
+                });
+            if extends {
+                self.extend_cluster(prev_pos, c);
+                return;
+            }
+        }
+        if width == Some(0) {
+            return;
+        }
+
+        let pos = self.grid().pos();
+        let size = self.grid().size();
         let width = width
             .unwrap_or(1)
             .try_into()
@@ -815,157 +1049,167 @@ impl Screen {
         self.grid_mut().col_wrap(width, wrap);
         let pos = self.grid().pos();
 
-        if width == 0 {
-            if pos.col > 0 {
-                let mut prev_cell = self
+        // After a resize, cells may be in inconsistent states (e.g.
+        // a wide char at the last column without its continuation).
+        // Use safe accessors to avoid panics on out-of-bounds.
+        if let Some(cell_ref) = self.grid().drawing_cell(pos) {
+            if cell_ref.is_wide_continuation() {
+                if let Some(prev_cell) = self
                     .grid_mut()
                     .drawing_cell_mut(crate::grid::Pos {
                         row: pos.row,
                         col: pos.col - 1,
                     })
-                    // pos.row is valid, since it comes directly from
-                    // self.grid().pos() which we assume to always have a
-                    // valid row value. pos.col - 1 is valid because we just
-                    // checked for pos.col > 0.
-                    .unwrap();
-                if prev_cell.is_wide_continuation() {
-                    prev_cell = self
-                        .grid_mut()
-                        .drawing_cell_mut(crate::grid::Pos {
-                            row: pos.row,
-                            col: pos.col - 2,
-                        })
-                        // pos.row is valid, since it comes directly from
-                        // self.grid().pos() which we assume to always have a
-                        // valid row value. we know pos.col - 2 is valid
-                        // because the cell at pos.col - 1 is a wide
-                        // continuation character, which means there must be
-                        // the first half of the wide character before it.
-                        .unwrap();
-                }
-                prev_cell.append(c);
-            } else if pos.row > 0 {
-                let prev_row = self
-                    .grid()
-                    .drawing_row(pos.row - 1)
-                    // pos.row is valid, since it comes directly from
-                    // self.grid().pos() which we assume to always have a
-                    // valid row value. pos.row - 1 is valid because we just
-                    // checked for pos.row > 0.
-                    .unwrap();
-                if prev_row.wrapped() {
-                    let mut prev_cell = self
-                        .grid_mut()
-                        .drawing_cell_mut(crate::grid::Pos {
-                            row: pos.row - 1,
-                            col: size.cols - 1,
-                        })
-                        // pos.row is valid, since it comes directly from
-                        // self.grid().pos() which we assume to always have a
-                        // valid row value. pos.row - 1 is valid because we
-                        // just checked for pos.row > 0. col of size.cols - 1
-                        // is always valid.
-                        .unwrap();
-                    if prev_cell.is_wide_continuation() {
-                        prev_cell = self
-                            .grid_mut()
-                            .drawing_cell_mut(crate::grid::Pos {
-                                row: pos.row - 1,
-                                col: size.cols - 2,
-                            })
-                            // pos.row is valid, since it comes directly from
-                            // self.grid().pos() which we assume to always
-                            // have a valid row value. pos.row - 1 is valid
-                            // because we just checked for pos.row > 0. col of
-                            // size.cols - 2 is valid because the cell at
-                            // size.cols - 1 is a wide continuation character,
-                            // so it must have the first half of the wide
-                            // character before it.
-                            .unwrap();
-                    }
-                    prev_cell.append(c);
+                {
+                    prev_cell.clear(attrs);
                 }
             }
-        } else {
-            // After a resize, cells may be in inconsistent states (e.g.
-            // a wide char at the last column without its continuation).
-            // Use safe accessors to avoid panics on out-of-bounds.
-            if let Some(cell_ref) = self.grid().drawing_cell(pos) {
-                if cell_ref.is_wide_continuation() {
-                    if let Some(prev_cell) = self
-                        .grid_mut()
-                        .drawing_cell_mut(crate::grid::Pos {
-                            row: pos.row,
-                            col: pos.col - 1,
-                        })
-                    {
-                        prev_cell.clear(attrs);
-                    }
-                }
+        }
+
+        let is_wide_at_pos = self
+            .grid()
+            .drawing_cell(pos)
+            .map_or(false, |c| c.is_wide());
+        if is_wide_at_pos {
+            if let Some(next_cell) = self
+                .grid_mut()
+                .drawing_cell_mut(crate::grid::Pos {
+                    row: pos.row,
+                    col: pos.col + 1,
+                })
+            {
+                next_cell.set(' ', attrs);
             }
+        }
 
-            let is_wide_at_pos = self
+        if let Some(cell) = self
+            .grid_mut()
+            .drawing_cell_mut(pos)
+        {
+            cell.set(c, attrs);
+            cell.set_wide(width > 1);
+        } else {
+            return;
+        }
+        self.last_char = Some(c);
+        self.grid_mut().col_inc(1);
+        if width > 1 {
+            let pos = self.grid().pos();
+            let is_wide_here = self
                 .grid()
                 .drawing_cell(pos)
                 .map_or(false, |c| c.is_wide());
-            if is_wide_at_pos {
-                if let Some(next_cell) = self
+            if is_wide_here {
+                let next_next_pos = crate::grid::Pos {
+                    row: pos.row,
+                    col: pos.col + 1,
+                };
+                if let Some(next_next_cell) = self
                     .grid_mut()
-                    .drawing_cell_mut(crate::grid::Pos {
-                        row: pos.row,
-                        col: pos.col + 1,
-                    })
+                    .drawing_cell_mut(next_next_pos)
                 {
-                    next_cell.set(' ', attrs);
+                    next_next_cell.clear(attrs);
+                    if next_next_pos.col == size.cols - 1 {
+                        if let Some(row) = self.grid_mut()
+                            .drawing_row_mut(pos.row)
+                        {
+                            row.wrap(false);
+                        }
+                    }
                 }
             }
-
-            if let Some(cell) = self
+            if let Some(next_cell) = self
                 .grid_mut()
                 .drawing_cell_mut(pos)
             {
-                cell.set(c, attrs);
-            } else {
-                return;
+                next_cell.clear(crate::attrs::Attrs::default());
+                next_cell.set_wide_continuation(true);
             }
             self.grid_mut().col_inc(1);
-            if width > 1 {
-                let pos = self.grid().pos();
-                let is_wide_here = self
-                    .grid()
-                    .drawing_cell(pos)
-                    .map_or(false, |c| c.is_wide());
-                if is_wide_here {
-                    let next_next_pos = crate::grid::Pos {
-                        row: pos.row,
-                        col: pos.col + 1,
-                    };
-                    if let Some(next_next_cell) = self
-                        .grid_mut()
-                        .drawing_cell_mut(next_next_pos)
-                    {
-                        next_next_cell.clear(attrs);
-                        if next_next_pos.col == size.cols - 1 {
-                            if let Some(row) = self.grid_mut()
-                                .drawing_row_mut(pos.row)
-                            {
-                                row.wrap(false);
-                            }
-                        }
-                    }
-                }
-                if let Some(next_cell) = self
-                    .grid_mut()
-                    .drawing_cell_mut(pos)
-                {
-                    next_cell.clear(crate::attrs::Attrs::default());
-                    next_cell.set_wide_continuation(true);
-                }
-                self.grid_mut().col_inc(1);
+        }
+    }
+
+    // Returns the position of the cell holding the character most recently
+    // written before the cursor, following wide characters back to their
+    // first half and wrapped rows back to the end of the previous row.
+    fn prev_cell_pos(&self) -> Option<crate::grid::Pos> {
+        let pos = self.grid().pos();
+        let size = self.grid().size();
+        let prev_pos = if pos.col > 0 {
+            crate::grid::Pos {
+                row: pos.row,
+                col: pos.col - 1,
+            }
+        } else if pos.row > 0
+            && self
+                .grid()
+                .drawing_row(pos.row - 1)
+                .is_some_and(crate::row::Row::wrapped)
+        {
+            crate::grid::Pos {
+                row: pos.row - 1,
+                col: size.cols - 1,
             }
+        } else {
+            return None;
+        };
+        let prev_cell = self.grid().drawing_cell(prev_pos)?;
+        if prev_cell.is_wide_continuation() && prev_pos.col > 0 {
+            Some(crate::grid::Pos {
+                row: prev_pos.row,
+                col: prev_pos.col - 1,
+            })
+        } else {
+            Some(prev_pos)
         }
     }
 
+    fn extend_cluster(&mut self, prev_pos: crate::grid::Pos, c: char) {
+        let vs_wide = self.variation_selector_always_wide;
+        let Some(prev_cell) = self.grid_mut().drawing_cell_mut(prev_pos)
+        else {
+            return;
+        };
+        prev_cell.append(c);
+        if prev_cell.is_wide()
+            || !cluster_is_wide(prev_cell.contents(), vs_wide)
+        {
+            return;
+        }
+
+        // the cluster became wide (an emoji presentation selector or a
+        // complete flag), so it needs to claim the cell after it. this is
+        // only possible if the cursor is still sitting directly after it -
+        // like tmux, don't try to rewrap text that has already been drawn.
+        let pos = self.grid().pos();
+        let size = self.grid().size();
+        if pos.row != prev_pos.row
+            || pos.col != prev_pos.col + 1
+            || pos.col >= size.cols
+        {
+            return;
+        }
+        let attrs = self.attrs;
+        if self.grid().drawing_cell(pos).is_some_and(crate::Cell::is_wide) {
+            if let Some(next_cell) =
+                self.grid_mut().drawing_cell_mut(crate::grid::Pos {
+                    row: pos.row,
+                    col: pos.col + 1,
+                })
+            {
+                next_cell.clear(attrs);
+            }
+        }
+        if let Some(prev_cell) = self.grid_mut().drawing_cell_mut(prev_pos) {
+            prev_cell.set_wide(true);
+        }
+        if let Some(next_cell) = self.grid_mut().drawing_cell_mut(pos) {
+            next_cell.clear(crate::attrs::Attrs::default());
+            next_cell.set_wide_continuation(true);
+        }
+        self.grid_mut().col_inc(1);
+    }
+
     // control codes
 
     pub(crate) fn bs(&mut self) {
@@ -1021,7 +1265,33 @@ impl Screen {
 
     // ESC c
     pub(crate) fn ris(&mut self) {
+        let ambiguous_width_wide = self.ambiguous_width_wide;
+        let variation_selector_always_wide =
+            self.variation_selector_always_wide;
+        // an explicit default (0) rather than None, so whoever is mirroring
+        // the cursor style also resets it
+        let cursor_shape = self.cursor_shape.map(|_| 0);
         *self = Self::new(self.grid.size(), self.grid.scrollback_len());
+        self.ambiguous_width_wide = ambiguous_width_wide;
+        self.variation_selector_always_wide = variation_selector_always_wide;
+        self.cursor_shape = cursor_shape;
+    }
+
+    // ESC # 8
+    pub(crate) fn decaln(&mut self) {
+        let size = self.grid().size();
+        self.grid_mut().set_scroll_region(0, size.rows - 1);
+        self.grid_mut().set_origin_mode(false);
+        let attrs = crate::attrs::Attrs::default();
+        for row in self.grid_mut().drawing_rows_mut() {
+            row.wrap(false);
+            for col in 0..size.cols {
+                if let Some(cell) = row.get_mut(col) {
+                    cell.set('E', attrs);
+                }
+            }
+        }
+        self.grid_mut().set_pos(crate::grid::Pos { row: 0, col: 0 });
     }
 
     // csi codes
@@ -1180,12 +1450,20 @@ impl Screen {
                     self.set_mouse_mode(MouseProtocolMode::ButtonMotion);
                 }
                 [1003] => self.set_mouse_mode(MouseProtocolMode::AnyMotion),
+                [1004] => self.set_mode(MODE_FOCUS_EVENTS),
                 [1005] => {
                     self.set_mouse_encoding(MouseProtocolEncoding::Utf8);
                 }
                 [1006] => {
                     self.set_mouse_encoding(MouseProtocolEncoding::Sgr);
                 }
+                [1007] => self.clear_mode(MODE_NO_ALTERNATE_SCROLL),
+                [1015] => {
+                    self.set_mouse_encoding(MouseProtocolEncoding::Urxvt);
+                }
+                [1016] => {
+                    self.set_mouse_encoding(MouseProtocolEncoding::SgrPixels);
+                }
                 [1049] => {
                     self.decsc();
                     self.alternate_grid.clear();
@@ -1221,12 +1499,22 @@ impl Screen {
                 [1003] => {
                     self.clear_mouse_mode(MouseProtocolMode::AnyMotion);
                 }
+                [1004] => self.clear_mode(MODE_FOCUS_EVENTS),
                 [1005] => {
                     self.clear_mouse_encoding(MouseProtocolEncoding::Utf8);
                 }
                 [1006] => {
                     self.clear_mouse_encoding(MouseProtocolEncoding::Sgr);
                 }
+                [1007] => self.set_mode(MODE_NO_ALTERNATE_SCROLL),
+                [1015] => {
+                    self.clear_mouse_encoding(MouseProtocolEncoding::Urxvt);
+                }
+                [1016] => {
+                    self.clear_mouse_encoding(
+                        MouseProtocolEncoding::SgrPixels,
+                    );
+                }
                 [1049] => {
                     self.exit_alternate_grid();
                     self.decrc();
@@ -1373,6 +1661,43 @@ impl Screen {
     pub(crate) fn decstbm(&mut self, (top, bottom): (u16, u16)) {
         self.grid_mut().set_scroll_region(top - 1, bottom - 1);
     }
+
+    // CSI b
+    pub(crate) fn rep(&mut self, count: u16) {
+        if let Some(c) = self.last_char {
+            for _ in 0..count {
+                self.text(c);
+            }
+        }
+    }
+
+    // CSI ! p
+    pub(crate) fn decstr(&mut self) {
+        // soft reset: the modes and cursor state from xterm's list, leaving
+        // screen contents, the alternate screen and mouse reporting alone
+        let pos = self.grid().pos();
+        self.clear_mode(MODE_HIDE_CURSOR);
+        self.clear_mode(MODE_APPLICATION_KEYPAD);
+        self.clear_mode(MODE_APPLICATION_CURSOR);
+        let size = self.grid().size();
+        // both of these home the cursor, which a soft reset must not do
+        self.grid_mut().set_scroll_region(0, size.rows - 1);
+        self.grid_mut().set_origin_mode(false);
+        self.attrs = crate::attrs::Attrs::default();
+        // the saved cursor goes back to the home position with default
+        // attributes, like xterm
+        self.grid_mut().reset_saved_cursor();
+        self.saved_attrs = crate::attrs::Attrs::default();
+        self.grid_mut().set_pos(pos);
+        self.cursor_shape = self.cursor_shape.map(|_| 0);
+    }
+
+    // CSI SP q
+    pub(crate) fn decscusr(&mut self, shape: u16) {
+        if let Some(shape) = u16_to_u8(shape).filter(|&shape| shape <= 6) {
+            self.cursor_shape = Some(shape);
+        }
+    }
 }
 
 fn u16_to_u8(i: u16) -> Option<u8> {
@@ -1535,4 +1860,318 @@ mod tests {
         parser.process(b"\x1b]0;my-title\x07");
         assert!(parser.screen().path().is_none());
     }
+
+    // ── Grapheme clusters and width options ───────────────────
+
+    #[test]
+    fn zwj_sequence_stays_in_one_cell() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        // woman + ZWJ + laptop
+        parser.process("\u{1f469}\u{200d}\u{1f4bb}x".as_bytes());
+        let cell = parser.screen().cell(0, 0).unwrap();
+        assert_eq!(cell.contents(), "\u{1f469}\u{200d}\u{1f4bb}");
+        assert!(cell.is_wide());
+        assert_eq!(parser.screen().cell(0, 2).unwrap().contents(), "x");
+        assert_eq!(parser.screen().cursor_position(), (0, 3));
+    }
+
+    #[test]
+    fn skin_tone_modifier_joins_base() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process("\u{1f44d}\u{1f3fd}x".as_bytes());
+        assert_eq!(
+            parser.screen().cell(0, 0).unwrap().contents(),
+            "\u{1f44d}\u{1f3fd}"
+        );
+        assert_eq!(parser.screen().cell(0, 2).unwrap().contents(), "x");
+    }
+
+    #[test]
+    fn regional_indicator_pair_is_one_wide_cell() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process("\u{1f1eb}\u{1f1f7}\u{1f1e9}\u{1f1ea}".as_bytes());
+        let screen = parser.screen();
+        assert_eq!(screen.cell(0, 0).unwrap().contents(), "\u{1f1eb}\u{1f1f7}");
+        assert!(screen.cell(0, 0).unwrap().is_wide());
+        assert!(screen.cell(0, 1).unwrap().is_wide_continuation());
+        assert_eq!(screen.cell(0, 2).unwrap().contents(), "\u{1f1e9}\u{1f1ea}");
+        assert_eq!(screen.cursor_position(), (0, 4));
+    }
+
+    #[test]
+    fn variation_selector_widens_narrow_base() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        // heavy black heart + VS16
+        parser.process("\u{2764}\u{fe0f}x".as_bytes());
+        let screen = parser.screen();
+        assert!(screen.cell(0, 0).unwrap().is_wide());
+        assert!(screen.cell(0, 1).unwrap().is_wide_continuation());
+        assert_eq!(screen.cell(0, 2).unwrap().contents(), "x");
+    }
+
+    #[test]
+    fn variation_selector_always_wide_off_keeps_width() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.screen_mut().set_variation_selector_always_wide(false);
+        parser.process("\u{2764}\u{fe0f}x".as_bytes());
+        let screen = parser.screen();
+        assert_eq!(screen.cell(0, 0).unwrap().contents(), "\u{2764}\u{fe0f}");
+        assert!(!screen.cell(0, 0).unwrap().is_wide());
+        assert_eq!(screen.cell(0, 1).unwrap().contents(), "x");
+    }
+
+    #[test]
+    fn ambiguous_width_wide() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process("\u{2500}x".as_bytes());
+        assert_eq!(parser.screen().cell(0, 1).unwrap().contents(), "x");
+
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.screen_mut().set_ambiguous_width_wide(true);
+        parser.process("\u{2500}x".as_bytes());
+        assert!(parser.screen().cell(0, 0).unwrap().is_wide());
+        assert_eq!(parser.screen().cell(0, 2).unwrap().contents(), "x");
+    }
+
+    #[test]
+    fn width_options_survive_reset() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.screen_mut().set_ambiguous_width_wide(true);
+        parser.screen_mut().set_variation_selector_always_wide(false);
+        parser.process(b"\x1bc");
+        assert!(parser.screen().ambiguous_width_wide());
+        assert!(!parser.screen().variation_selector_always_wide());
+    }
+
+    #[test]
+    fn long_cluster_spills_out_of_cell() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        // family: man, woman, girl, boy joined with ZWJs (25 bytes)
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}";
+        parser.process(format!("{family}{family}x").as_bytes());
+        let screen = parser.screen();
+        assert_eq!(screen.cell(0, 0).unwrap().contents(), family);
+        assert_eq!(screen.cell(0, 2).unwrap().contents(), family);
+        assert_eq!(screen.cell(0, 0).unwrap(), screen.cell(0, 2).unwrap());
+        assert_eq!(screen.cell(0, 4).unwrap().contents(), "x");
+        assert!(screen.contents().starts_with(family));
+    }
+
+    #[test]
+    fn long_runs_of_combining_marks_are_capped() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        let marks = "\u{301}".repeat(1000);
+        parser.process(format!("e{marks}x").as_bytes());
+        let cell = parser.screen().cell(0, 0).unwrap();
+        assert!(cell.contents().starts_with("e\u{301}\u{301}"));
+        assert!(cell.contents().len() <= 64);
+        assert_eq!(parser.screen().cell(0, 1).unwrap().contents(), "x");
+        // overwriting the cell drops the spilled cluster
+        parser.process(b"\x1b[Hy");
+        assert_eq!(parser.screen().cell(0, 0).unwrap().contents(), "y");
+    }
+
+    #[test]
+    fn combining_marks_still_attach() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process("e\u{301}x".as_bytes());
+        assert_eq!(parser.screen().cell(0, 0).unwrap().contents(), "e\u{301}");
+        assert_eq!(parser.screen().cell(0, 1).unwrap().contents(), "x");
+    }
+
+    // ── DECSCUSR / DECSTR / DECALN / REP ──────────────────────
+
+    #[test]
+    fn decscusr_sets_cursor_shape() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        assert_eq!(parser.screen().cursor_shape(), None);
+        parser.process(b"\x1b[5 q");
+        assert_eq!(parser.screen().cursor_shape(), Some(5));
+        parser.process(b"\x1b[ q");
+        assert_eq!(parser.screen().cursor_shape(), Some(0));
+        // out of range values are ignored
+        parser.process(b"\x1b[2 q\x1b[9 q");
+        assert_eq!(parser.screen().cursor_shape(), Some(2));
+    }
+
+    #[test]
+    fn decscusr_split_across_reads() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process(b"\x1b[6");
+        parser.process(b" ");
+        parser.process(b"q");
+        assert_eq!(parser.screen().cursor_shape(), Some(6));
+    }
+
+    #[test]
+    fn cursor_shape_restored_with_alternate_screen() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process(b"\x1b[6 q\x1b[?1049h\x1b[2 q");
+        assert_eq!(parser.screen().cursor_shape(), Some(2));
+        parser.process(b"\x1b[?1049l");
+        assert_eq!(parser.screen().cursor_shape(), Some(6));
+
+        // never set on the primary screen: back to the terminal default
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process(b"\x1b[?1049h\x1b[4 q\x1b[?1049l");
+        assert_eq!(parser.screen().cursor_shape(), Some(0));
+    }
+
+    #[test]
+    fn rep_repeats_last_character() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process(b"ab\x1b[3b");
+        assert_eq!(parser.screen().contents(), "abbbb");
+        parser.process(b"\x1b[b");
+        assert_eq!(parser.screen().contents(), "abbbbb");
+    }
+
+    #[test]
+    fn rep_without_previous_character_is_noop() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process(b"\x1b[5b");
+        assert_eq!(parser.screen().contents(), "");
+        assert_eq!(parser.screen().cursor_position(), (0, 0));
+    }
+
+    #[test]
+    fn decaln_fills_screen() {
+        let mut parser = crate::Parser::new(3, 4, 0);
+        parser.process(b"\x1b[2;3r\x1b[2;2Hxy\x1b#8");
+        assert_eq!(parser.screen().contents(), "EEEE\nEEEE\nEEEE");
+        assert_eq!(parser.screen().cursor_position(), (0, 0));
+        // scroll region was reset to the full screen
+        parser.process(b"\x1b[3;1H\n");
+        assert_eq!(parser.screen().contents(), "EEEE\nEEEE");
+    }
+
+    #[test]
+    fn decstr_soft_reset() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process(b"\x1b[?25l\x1b[?1h\x1b=\x1b[31m\x1b[5;10r\x1b[3 qhello");
+        parser.process(b"\x1b[!p");
+        let screen = parser.screen();
+        assert!(!screen.hide_cursor());
+        assert!(!screen.application_cursor());
+        assert!(!screen.application_keypad());
+        assert_eq!(screen.cursor_shape(), Some(0));
+        // contents and cursor position are preserved
+        assert_eq!(screen.contents(), "\n\n\n\nhello");
+        assert_eq!(screen.cursor_position(), (4, 5));
+        parser.process(b"x");
+        assert_eq!(parser.screen().cell(4, 5).unwrap().fgcolor(), crate::Color::Default);
+        // DECRC after DECSTR goes home with default attributes, even when
+        // a cursor was saved before the reset
+        parser.process(b"\x1b[3;7H\x1b[32m\x1b7\x1b[!p\x1b[31m\x1b8y");
+        assert_eq!(parser.screen().cursor_position(), (0, 1));
+        assert_eq!(parser.screen().cell(0, 0).unwrap().contents(), "y");
+        assert_eq!(parser.screen().cell(0, 0).unwrap().fgcolor(), crate::Color::Default);
+    }
+
+    // ── Focus reporting / mouse encodings / alternate scroll ──
+
+    #[test]
+    fn focus_events_mode() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        assert!(!parser.screen().focus_events());
+        parser.process(b"\x1b[?1004h");
+        assert!(parser.screen().focus_events());
+        parser.process(b"\x1b[?1004l");
+        assert!(!parser.screen().focus_events());
+    }
+
+    #[test]
+    fn urxvt_and_sgr_pixels_encodings() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        parser.process(b"\x1b[?1015h");
+        assert_eq!(
+            parser.screen().mouse_protocol_encoding(),
+            MouseProtocolEncoding::Urxvt
+        );
+        parser.process(b"\x1b[?1016h");
+        assert_eq!(
+            parser.screen().mouse_protocol_encoding(),
+            MouseProtocolEncoding::SgrPixels
+        );
+        // resetting an encoding that isn't active is a no-op
+        parser.process(b"\x1b[?1015l");
+        assert_eq!(
+            parser.screen().mouse_protocol_encoding(),
+            MouseProtocolEncoding::SgrPixels
+        );
+        parser.process(b"\x1b[?1016l");
+        assert_eq!(
+            parser.screen().mouse_protocol_encoding(),
+            MouseProtocolEncoding::Default
+        );
+    }
+
+    #[test]
+    fn alternate_scroll_defaults_on() {
+        let mut parser = crate::Parser::new(24, 80, 0);
+        assert!(parser.screen().alternate_scroll());
+        parser.process(b"\x1b[?1007l");
+        assert!(!parser.screen().alternate_scroll());
+        parser.process(b"\x1b[?1007h");
+        assert!(parser.screen().alternate_scroll());
+    }
+
+    // ── Absolute history access ───────────────────────────────
+
+    #[test]
+    fn history_cell_ignores_scrollback_offset() {
+        let mut parser = crate::Parser::new(2, 10, 10);
+        parser.process(b"one\r\ntwo\r\nthree\r\nfour");
+        let screen = parser.screen();
+        assert_eq!(screen.history_len(), 2);
+        assert_eq!(screen.history_cell(0, 0).unwrap().contents(), "o");
+        assert_eq!(screen.history_cell(2, 0).unwrap().contents(), "t");
+        assert_eq!(screen.history_cell(3, 1).unwrap().contents(), "o");
+        assert!(screen.history_cell(4, 0).is_none());
+        parser.screen_mut().set_scrollback(2);
+        assert_eq!(parser.screen().history_cell(3, 0).unwrap().contents(), "f");
+    }
+
+    #[test]
+    fn history_dropped_counts_evicted_lines() {
+        let mut parser = crate::Parser::new(2, 10, 2);
+        parser.process(b"one\r\ntwo\r\nthree");
+        assert_eq!(parser.screen().history_dropped(), 0);
+        parser.process(b"\r\nfour\r\nfive");
+        let screen = parser.screen();
+        assert_eq!(screen.history_len(), 2);
+        assert_eq!(screen.history_dropped(), 1);
+        assert_eq!(screen.history_cell(0, 0).unwrap().contents(), "t");
+    }
+
+    #[test]
+    fn history_wrap_and_saved_screen() {
+        let mut parser = crate::Parser::new(2, 4, 10);
+        parser.process(b"abcdef\r\ngh");
+        let screen = parser.screen();
+        assert_eq!(screen.history_len(), 1);
+        assert!(screen.history_row_wrapped(0));
+        assert!(!screen.history_row_wrapped(1));
+        assert!(screen.saved_cell(0, 0).is_none());
+        parser.process(b"\x1b[?1049hxy");
+        let screen = parser.screen();
+        assert_eq!(screen.cell(0, 0).unwrap().contents(), "x");
+        assert_eq!(screen.saved_cell(0, 0).unwrap().contents(), "e");
+        assert_eq!(screen.saved_cell(1, 1).unwrap().contents(), "h");
+    }
+
+    #[test]
+    fn pending_input_holds_unfinished_sequence() {
+        let mut parser = crate::Parser::new(2, 10, 0);
+        parser.process(b"ok\x1b[3");
+        assert_eq!(parser.pending_input(), b"\x1b[3");
+        parser.process(b"1");
+        assert_eq!(parser.pending_input(), b"\x1b[31");
+        parser.process(b"mred");
+        assert!(parser.pending_input().is_empty());
+        parser.process(b"\x1b]0;title");
+        assert_eq!(parser.pending_input(), b"\x1b]0;title");
+        parser.process(b"\x07");
+        assert!(parser.pending_input().is_empty());
+    }
 }
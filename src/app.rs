@@ -23,8 +23,7 @@ use crate::rendering::{render_window, parse_status, centered_rect};
 use crate::style::{parse_tmux_style, parse_inline_styles, spans_visual_width};
 use crate::config::load_config;
 use crate::cli::parse_target;
-use crate::copy_mode::{enter_copy_mode, move_copy_cursor, current_prompt_pos, yank_selection,
-    capture_active_pane_text, capture_active_pane_range, capture_active_pane_styled};
+use crate::copy_mode::{enter_copy_mode, move_copy_cursor, current_prompt_pos, yank_selection};
 use crate::layout::dump_layout_json;
 use crate::window_ops::{toggle_zoom, remote_mouse_down, remote_mouse_drag, remote_mouse_up,
     remote_mouse_button, remote_mouse_motion, remote_scroll_up, remote_scroll_down};
@@ -333,6 +332,14 @@ mod bracket_paste_detect {
     }
 }
 
+/// Cell size in pixels of the local terminal, for SGR-pixel mouse reports.
+/// Queried once at startup and again on resize rather than per mouse event.
+fn local_cell_size() -> Option<(u16, u16)> {
+    crossterm::terminal::window_size().ok()
+        .filter(|ws| ws.width > 0 && ws.height > 0 && ws.columns > 0 && ws.rows > 0)
+        .map(|ws| (ws.width / ws.columns, ws.height / ws.rows))
+}
+
 pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
     let pty_system = native_pty_system();
 
@@ -391,8 +398,6 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
                 let mut target_win: Option<usize> = global_target_win;
                 let mut target_pane: Option<usize> = global_target_pane;
                 let mut pane_is_id = global_pane_is_id;
-                let mut start_line: Option<i32> = None;
-                let mut end_line: Option<i32> = None;
                 let mut i = 0;
                 while i < args.len() {
                     if args[i] == "-t" {
@@ -406,12 +411,6 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
                             }
                         }
                         i += 2; continue;
-                    } else if args[i] == "-S" {
-                        if let Some(v) = args.get(i+1) { if let Ok(n) = v.parse::<i32>() { start_line = Some(n); } }
-                        i += 2; continue;
-                    } else if args[i] == "-E" {
-                        if let Some(v) = args.get(i+1) { if let Ok(n) = v.parse::<i32>() { end_line = Some(n); } }
-                        i += 2; continue;
                     }
                     i += 1;
                 }
@@ -478,16 +477,13 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
                         let _ = stream.flush();
                     }
                     "capture-pane" => {
-                        let escape_seqs = args.iter().any(|a| *a == "-e");
-                        let (rtx, rrx) = mpsc::channel::<String>();
-                        if escape_seqs {
-                            let _ = tx.send(CtrlReq::CapturePaneStyled(rtx, start_line, end_line));
-                        } else if start_line.is_some() || end_line.is_some() {
-                            let _ = tx.send(CtrlReq::CapturePaneRange(rtx, start_line, end_line));
-                        } else {
-                            let _ = tx.send(CtrlReq::CapturePane(rtx));
+                        let (rtx, rrx) = mpsc::channel::<Result<String, String>>();
+                        let _ = tx.send(CtrlReq::CapturePane(crate::capture::parse_args(&args), rtx));
+                        match rrx.recv() {
+                            Ok(Ok(text)) => { let _ = write!(stream, "{}", text); }
+                            Ok(Err(e)) => { let _ = write!(stream, "{}\n", e); }
+                            Err(_) => {}
                         }
-                        if let Ok(text) = rrx.recv() { let _ = write!(stream, "{}", text); }
                     }
                     "client-attach" => { let _ = tx.send(CtrlReq::ClientAttach(0)); let _ = write!(stream, "ok\n"); }
                     "client-detach" => { let _ = tx.send(CtrlReq::ClientDetach(0)); let _ = write!(stream, "ok\n"); }
@@ -503,6 +499,8 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
         }
     });
 
+    // In-process mode: the local terminal is the client.
+    app.client_cell_size = local_cell_size();
     let mut last_resize = Instant::now();
     let mut last_reap = Instant::now();
     let mut quit = false;
@@ -553,6 +551,7 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
                 Mode::ConfirmMode { .. } => "CONFIRM",
                 Mode::ClockMode => "CLOCK",
                 Mode::BufferChooser { .. } => "BUF",
+                Mode::HintMode { .. } => "HINT",
             };
             let time_str = Local::now().format("%H:%M").to_string();
 
@@ -687,66 +686,22 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
                 }
             }
 
-            // Command prompt — render at bottom (tmux style), not centered popup
-            if let Mode::CommandPrompt { input, cursor } = &app.mode {
-                let msg_style = parse_tmux_style(&app.message_command_style);
-                let prompt_text = format!(":{}", input);
-                let prompt_area = status_chunk; // Replace the status bar line
-                let para = Paragraph::new(prompt_text).style(msg_style);
-                f.render_widget(Clear, prompt_area);
-                f.render_widget(para, prompt_area);
-                // Place cursor at the right position in the prompt
-                let cx = prompt_area.x + 1 + *cursor as u16; // +1 for ':'
-                f.set_cursor_position((cx, prompt_area.y));
+            // Command prompt — render over the status line (tmux style)
+            if let Mode::CommandPrompt { prompt } = &app.mode {
+                let view = crate::prompt::view(&app, prompt);
+                crate::rendering::render_command_prompt(f, content_chunk, status_chunk, &view);
             }
 
-            if let Mode::WindowChooser { selected, ref tree } = app.mode {
-                let mut lines: Vec<Line> = Vec::new();
-                for (i, entry) in tree.iter().enumerate() {
-                    let marker = if i == selected { ">" } else { " " };
-                    if entry.is_session_header {
-                        let tag = if entry.is_current_session { " (attached)" } else { "" };
-                        lines.push(Line::from(format!("{} {} {}{}",
-                            marker,
-                            if entry.is_current_session { "▼" } else { "▶" },
-                            entry.session_name,
-                            tag,
-                        )).style(Style::default().fg(Color::Yellow).add_modifier(ratatui::style::Modifier::BOLD)));
-                    } else {
-                        let active_mark = if entry.is_active_window { "*" } else { " " };
-                        let wi = entry.window_index.unwrap_or(0);
-                        lines.push(Line::from(format!("{}   {}: {}{} ({} panes) [{}]",
-                            marker, wi, entry.window_name, active_mark,
-                            entry.window_panes, entry.window_size,
-                        )));
-                    }
-                }
-                // Cap overlay height to available terminal space
-                let height = (lines.len() as u16 + 2)
-                    .min(20)
-                    .min(area.height.saturating_sub(2));
-                let overlay = Paragraph::new(Text::from(lines)).block(Block::default().borders(Borders::ALL).title("choose-tree"));
-                let oa = centered_rect(70, height, area);
-                f.render_widget(Clear, oa);
-                f.render_widget(overlay, oa);
+            if let Mode::WindowChooser { chooser } = &app.mode {
+                let view = crate::choose_tree::view(&app, chooser);
+                let sel_style = crate::rendering::parse_tmux_style(&app.mode_style);
+                crate::rendering::render_tree_chooser(f, area, &view, sel_style);
             }
 
-            if let Mode::BufferChooser { selected } = app.mode {
-                let mut lines: Vec<Line> = Vec::new();
-                if app.paste_buffers.is_empty() {
-                    lines.push(Line::from("  (no buffers)"));
-                } else {
-                    for (i, buf) in app.paste_buffers.iter().enumerate() {
-                        let marker = if i == selected { ">" } else { " " };
-                        let preview: String = buf.chars().take(40).map(|c| if c == '\n' { '↵' } else { c }).collect();
-                        lines.push(Line::from(format!("{} {:>2}: {:>5} bytes  {}", marker, i, buf.len(), preview)));
-                    }
-                }
-                let height = (lines.len() as u16 + 2).min(15);
-                let overlay = Paragraph::new(Text::from(lines)).block(Block::default().borders(Borders::ALL).title("choose-buffer (enter=paste, d=delete, esc=close)"));
-                let oa = centered_rect(70, height, area);
-                f.render_widget(Clear, oa);
-                f.render_widget(overlay, oa);
+            if let Mode::BufferChooser { chooser } = &app.mode {
+                let view = crate::buffers::chooser_view(&app, chooser);
+                let sel_style = crate::rendering::parse_tmux_style(&app.mode_style);
+                crate::rendering::render_buffer_chooser(f, area, &view, sel_style);
             }
 
             if let Mode::RenamePrompt { input } = &app.mode {
@@ -787,69 +742,29 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
 
             // Render Menu mode
             if let Mode::MenuMode { menu } = &app.mode {
-                let item_count = menu.items.len();
-                let height = (item_count as u16 + 2).min(20);
-                let width = menu.items.iter().map(|i| i.name.len()).max().unwrap_or(10).max(menu.title.len()) as u16 + 8;
-                
-                // Calculate position based on x/y or center
-                let menu_area = if let (Some(x), Some(y)) = (menu.x, menu.y) {
-                    let x = if x < 0 { (area.width as i16 + x).max(0) as u16 } else { x as u16 };
-                    let y = if y < 0 { (area.height as i16 + y).max(0) as u16 } else { y as u16 };
-                    Rect { x: x.min(area.width.saturating_sub(width)), y: y.min(area.height.saturating_sub(height)), width, height }
-                } else {
-                    centered_rect((width * 100 / area.width.max(1)).max(30), height, area)
-                };
-                
-                let title = if menu.title.is_empty() { "Menu" } else { &menu.title };
-                let block = Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Cyan))
-                    .title(title);
-                
-                let mut lines: Vec<Line> = Vec::new();
-                for (i, item) in menu.items.iter().enumerate() {
-                    if item.is_separator {
-                        lines.push(Line::from("─".repeat(width.saturating_sub(2) as usize)));
-                    } else {
-                        let marker = if i == menu.selected { ">" } else { " " };
-                        let key_str = item.key.map(|k| format!("({})", k)).unwrap_or_default();
-                        let style = if i == menu.selected {
-                            Style::default().bg(Color::Blue).fg(Color::White)
-                        } else {
-                            Style::default()
-                        };
-                        lines.push(Line::from(Span::styled(
-                            format!("{} {} {}", marker, item.name, key_str),
-                            style
-                        )));
-                    }
+                for view in crate::menu::views(menu) {
+                    crate::rendering::render_menu(f, content_chunk, &view);
                 }
-                
-                let para = Paragraph::new(Text::from(lines)).block(block);
-                f.render_widget(Clear, menu_area);
-                f.render_widget(para, menu_area);
             }
 
             // Render Popup mode
-            if let Mode::PopupMode { command, output, width, height, ref popup_pty, .. } = &app.mode {
-                let w = (*width).min(area.width.saturating_sub(4));
-                let h = (*height).min(area.height.saturating_sub(4));
+            if let Mode::PopupMode { popup } = &app.mode {
+                let crate::popup::Popup { title, output, x, y, width, height, style, border_style, border_lines, .. } = popup;
+                let w = (*width).min(area.width);
+                let h = (*height).min(area.height);
                 let popup_area = Rect {
-                    x: (area.width.saturating_sub(w)) / 2,
-                    y: (area.height.saturating_sub(h)) / 2,
+                    x: (*x).min(area.width.saturating_sub(w)),
+                    y: (*y).min(area.height.saturating_sub(h)),
                     width: w,
                     height: h,
                 };
                 
-                let title = if command.is_empty() { "Popup" } else { command };
-                let block = Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Yellow))
-                    .title(title);
+                let title = if title.is_empty() { "Popup".to_string() } else { title.clone() };
+                let block = crate::style::popup_block(title, style, border_style, border_lines);
                 
                 // If we have a PTY, render its VT output
-                let content = if let Some(pty) = popup_pty {
-                    if let Ok(parser) = pty.term.lock() {
+                let content = if let Some(term) = popup.term() {
+                    if let Ok(parser) = term.lock() {
                         let screen = parser.screen();
                         let inner_h = h.saturating_sub(2);
                         let inner_w = w.saturating_sub(2);
@@ -928,16 +843,15 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
             }
 
             // Render Copy-mode search prompt
-            if let Mode::CopySearch { input, forward } = &app.mode {
-                let dir = if *forward { "/" } else { "?" };
-                let width = (input.len() as u16 + 10).min(80).max(30);
+            if let Mode::CopySearch { input, forward, search } = &app.mode {
+                let text = crate::copy_mode::search_prompt_text(input, *forward, search.as_ref());
+                let width = (text.len() as u16 + 10).min(80).max(30);
                 let search_area = Rect {
                     x: area.x,
                     y: area.y + area.height.saturating_sub(2),
                     width: width.min(area.width),
                     height: 1,
                 };
-                let text = format!("{}{}", dir, input);
                 let para = Paragraph::new(text)
                     .style(Style::default().fg(Color::Yellow).bg(Color::Black));
                 f.render_widget(para, search_area);
@@ -958,7 +872,9 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
         {
             let win = &app.windows[app.active_idx];
             if let Some(pane) = crate::tree::active_pane(&win.root, &win.active_path) {
-                let shape = pane.cursor_shape.load(std::sync::atomic::Ordering::Relaxed);
+                let shape = pane.term.lock().ok()
+                    .and_then(|parser| parser.screen().cursor_shape())
+                    .unwrap_or(crate::pane::CURSOR_SHAPE_UNSET);
                 let effective = if shape <= 6 {
                     shape
                 } else {
@@ -1065,6 +981,7 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
                     }
                 }
                 Event::Resize(cols, rows) => {
+                    app.client_cell_size = local_cell_size();
                     if last_resize.elapsed() > Duration::from_millis(50) {
                         let win = &mut app.windows[app.active_idx];
                         if let Some(pane) = active_pane_mut(&mut win.root, &win.active_path) {
@@ -1112,14 +1029,8 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
                 CtrlReq::SplitWindow(k, cmd, _detached, start_dir, _size_pct, resp) => { let _ = resp.send(if let Err(e) = split_active_with_command(&mut app, k, cmd.as_deref(), Some(&*pty_system), start_dir.as_deref()) { format!("{e}") } else { String::new() }); resize_all_panes(&mut app); }
                 CtrlReq::KillPane => { let _ = kill_active_pane(&mut app); resize_all_panes(&mut app); }
                 CtrlReq::KillPaneById(pid) => { let _ = kill_pane_by_id(&mut app, pid); resize_all_panes(&mut app); }
-                CtrlReq::CapturePane(resp) => {
-                    if let Some(text) = capture_active_pane_text(&mut app)? { let _ = resp.send(text); } else { let _ = resp.send(String::new()); }
-                }
-                CtrlReq::CapturePaneStyled(resp, s, e) => {
-                    if let Some(text) = capture_active_pane_styled(&mut app, s, e)? { let _ = resp.send(text); } else { let _ = resp.send(String::new()); }
-                }
-                CtrlReq::CapturePaneRange(resp, s, e) => {
-                    if let Some(text) = capture_active_pane_range(&mut app, s, e)? { let _ = resp.send(text); } else { let _ = resp.send(String::new()); }
+                CtrlReq::CapturePane(opts, resp) => {
+                    let _ = resp.send(crate::capture::capture_pane(&app, &opts));
                 }
                 CtrlReq::FocusWindow(wid) => { if let Some(idx) = find_window_index_by_id(&app, wid) { app.active_idx = idx; } }
                 CtrlReq::FocusWindowTemp(wid) => { if let Some(idx) = find_window_index_by_id(&app, wid) { app.active_idx = idx; } }
@@ -1164,6 +1075,7 @@ pub fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<
                     app.last_window_area = Rect { x: 0, y: 0, width: w, height: h }; 
                     resize_all_panes(&mut app);
                 }
+                CtrlReq::ClientCellSize(cw, ch) => { app.client_cell_size = Some((cw, ch)); }
                 CtrlReq::FocusPaneCmd(pid) => { focus_pane_by_id(&mut app, pid); }
                 CtrlReq::FocusWindowCmd(wid) => { if let Some(idx) = find_window_index_by_id(&app, wid) { app.active_idx = idx; } }
                 CtrlReq::MouseDown(x,y) => { remote_mouse_down(&mut app, x, y); }
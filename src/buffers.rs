@@ -0,0 +1,557 @@
+//! Paste buffers.
+//!
+//! Buffers are kept most recent first.  Copies create *automatic* buffers
+//! named `bufferN`; `set-buffer -b`/`-n` create or rename *named* buffers.
+//! Only automatic buffers count towards `buffer-limit`, and the oldest of
+//! them is evicted first, like tmux.
+
+use std::io;
+
+use chrono::Local;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{AppState, BufferChooser, BufferEdit, Mode, PasteBuffer};
+
+/// Default for the `buffer-limit` option.
+pub const DEFAULT_BUFFER_LIMIT: usize = 50;
+
+/// Find a buffer by its exact name.
+pub fn find(app: &AppState, name: &str) -> Option<usize> {
+    app.paste_buffers.iter().position(|b| b.name == name)
+}
+
+/// Find a buffer to read from.  A bare number that names no buffer is taken
+/// as an index (0 = most recent) for compatibility with older scripts; the
+/// write paths match names exactly so `set-buffer -b 3` makes a buffer "3".
+fn find_for_read(app: &AppState, name: &str) -> Option<usize> {
+    find(app, name)
+        .or_else(|| name.parse::<usize>().ok().filter(|&i| i < app.paste_buffers.len()))
+}
+
+/// The named buffer, or the most recent one when `name` is `None`.
+pub fn get<'a>(app: &'a AppState, name: Option<&str>) -> Option<&'a PasteBuffer> {
+    match name {
+        Some(n) => find_for_read(app, n).map(|i| &app.paste_buffers[i]),
+        None => app.paste_buffers.first(),
+    }
+}
+
+/// Text of the named buffer, or of the most recent one.
+pub fn get_data(app: &AppState, name: Option<&str>) -> Option<String> {
+    get(app, name).map(|b| b.data.clone())
+}
+
+/// Add a new automatic buffer on top and apply `buffer-limit`.
+pub fn add_automatic(app: &mut AppState, data: String) {
+    let name = format!("buffer{}", app.next_buffer_id);
+    app.next_buffer_id += 1;
+    app.paste_buffers.insert(0, PasteBuffer { name, data, created: Local::now(), automatic: true });
+    enforce_limit(app);
+}
+
+/// `set-buffer`: replace (or with `append`, extend) the named buffer,
+/// creating it if needed.  Without a name, `append` extends the most recent
+/// buffer and a plain set adds an automatic buffer.  The buffer moves to the
+/// top.  Returns the buffer's resulting text.
+pub fn set_buffer(app: &mut AppState, name: Option<&str>, data: &str, append: bool) -> String {
+    let existing = match name {
+        Some(n) => find(app, n),
+        None if append && !app.paste_buffers.is_empty() => Some(0),
+        None => None,
+    };
+    let Some(idx) = existing else {
+        match name {
+            Some(n) => {
+                app.paste_buffers.insert(0, PasteBuffer {
+                    name: n.to_string(), data: data.to_string(), created: Local::now(), automatic: false,
+                });
+            }
+            None => add_automatic(app, data.to_string()),
+        }
+        return data.to_string();
+    };
+    let mut buf = app.paste_buffers.remove(idx);
+    if append { buf.data.push_str(data); } else { buf.data = data.to_string(); }
+    let text = buf.data.clone();
+    app.paste_buffers.insert(0, buf);
+    text
+}
+
+/// `set-buffer -n`: rename the named (or most recent) buffer.  An existing
+/// buffer with the new name is replaced.  A renamed buffer becomes a named
+/// buffer and no longer counts towards `buffer-limit`.
+pub fn rename(app: &mut AppState, old: Option<&str>, new_name: &str) -> Result<(), String> {
+    let idx = match old {
+        Some(n) => find(app, n).ok_or_else(|| format!("no buffer {}", n))?,
+        None if !app.paste_buffers.is_empty() => 0,
+        None => return Err("no buffers".to_string()),
+    };
+    if app.paste_buffers[idx].name == new_name { return Ok(()); }
+    let mut idx = idx;
+    if let Some(dup) = app.paste_buffers.iter().position(|b| b.name == new_name) {
+        app.paste_buffers.remove(dup);
+        if dup < idx { idx -= 1; }
+    }
+    let buf = &mut app.paste_buffers[idx];
+    buf.name = new_name.to_string();
+    buf.automatic = false;
+    Ok(())
+}
+
+/// `delete-buffer`: remove the named (or most recent) buffer.
+pub fn delete(app: &mut AppState, name: Option<&str>) -> bool {
+    let idx = match name {
+        Some(n) => find(app, n),
+        None if !app.paste_buffers.is_empty() => Some(0),
+        None => None,
+    };
+    match idx {
+        Some(i) => { app.paste_buffers.remove(i); true }
+        None => false,
+    }
+}
+
+/// Evict the oldest automatic buffers until no more than `buffer-limit`
+/// remain.  Named buffers are never evicted.
+pub fn enforce_limit(app: &mut AppState) {
+    let mut automatic = app.paste_buffers.iter().filter(|b| b.automatic).count();
+    while automatic > app.buffer_limit {
+        match app.paste_buffers.iter().rposition(|b| b.automatic) {
+            Some(i) => { app.paste_buffers.remove(i); automatic -= 1; }
+            None => break,
+        }
+    }
+}
+
+// ─── choose-buffer ──────────────────────────────────────────────────────────
+
+/// Default `choose-buffer -F` item format.
+pub const DEFAULT_CHOOSER_FORMAT: &str = "#{buffer_size} bytes: \"#{buffer_sample}\"";
+
+/// Open `choose-buffer` with its command-line flags
+/// (`-F format`, `-O order`, `-r`, `-f filter`, `-t target`, `-Z`, `-N`).
+pub fn open_chooser(app: &mut AppState, args: &[&str]) {
+    let mut chooser = BufferChooser {
+        selected: 0,
+        tagged: Vec::new(),
+        format: DEFAULT_CHOOSER_FORMAT.to_string(),
+        sort_order: "time".to_string(),
+        reverse: false,
+        filter: String::new(),
+        target: None,
+        zoomed: false,
+        preview: true,
+    };
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1).map(|v| v.to_string());
+        match args[i] {
+            "-F" => { if let Some(v) = value { chooser.format = v; } i += 1; }
+            "-O" => { if let Some(v) = value { chooser.sort_order = v; } i += 1; }
+            "-f" => { if let Some(v) = value { chooser.filter = v; } i += 1; }
+            "-t" => { chooser.target = value; i += 1; }
+            "-r" => chooser.reverse = true,
+            "-Z" => chooser.zoomed = true,
+            "-N" => chooser.preview = false,
+            _ => {}
+        }
+        i += 1;
+    }
+    app.mode = Mode::BufferChooser { chooser };
+}
+
+fn expand_for_buffer(app: &AppState, fmt: &str, idx: usize) -> String {
+    crate::format::set_buffer_idx_override(Some(idx));
+    let out = crate::format::expand_format(fmt, app);
+    crate::format::set_buffer_idx_override(None);
+    out
+}
+
+/// Indices into `paste_buffers` of the items the chooser shows, filtered
+/// by `-f` and sorted by `-O` / `-r`.
+pub fn chooser_items(app: &AppState, chooser: &BufferChooser) -> Vec<usize> {
+    let mut items: Vec<usize> = (0..app.paste_buffers.len())
+        .filter(|&i| chooser.filter.is_empty()
+            || crate::format::is_truthy(&expand_for_buffer(app, &chooser.filter, i)))
+        .collect();
+    let bufs = &app.paste_buffers;
+    match chooser.sort_order.as_str() {
+        "name" => items.sort_by(|&a, &b| bufs[a].name.cmp(&bufs[b].name)),
+        "size" => items.sort_by(|&a, &b| bufs[b].data.len().cmp(&bufs[a].data.len())),
+        _ => items.sort_by(|&a, &b| bufs[b].created.cmp(&bufs[a].created)),
+    }
+    if chooser.reverse { items.reverse(); }
+    items
+}
+
+/// What a chooser overlay draws: the title, one `(text, tagged)` line per
+/// item and the preview lines of the selected buffer.  The server sends it
+/// to clients inside the state dump.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ChooserView {
+    pub title: String,
+    pub items: Vec<(String, bool)>,
+    pub selected: usize,
+    pub preview: Vec<String>,
+    pub zoomed: bool,
+}
+
+pub fn chooser_view(app: &AppState, chooser: &BufferChooser) -> ChooserView {
+    let items = chooser_items(app, chooser);
+    let lines = items.iter().map(|&i| {
+        let b = &app.paste_buffers[i];
+        let text = format!("{}: {}", b.name, expand_for_buffer(app, &chooser.format, i).replace('\n', "↵"));
+        (text, chooser.tagged.contains(&b.name))
+    }).collect();
+    let selected = chooser.selected.min(items.len().saturating_sub(1));
+    let preview = if chooser.preview {
+        items.get(selected).map(|&i| app.paste_buffers[i].data.lines().map(|l| l.to_string()).collect()).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    ChooserView {
+        title: format!("choose-buffer (sort: {}{})", chooser.sort_order, if chooser.reverse { ", reversed" } else { "" }),
+        items: lines,
+        selected,
+        preview,
+        zoomed: chooser.zoomed,
+    }
+}
+
+/// Handle a key in `choose-buffer`.
+pub fn chooser_key(app: &mut AppState, key: KeyEvent) -> io::Result<()> {
+    let Mode::BufferChooser { chooser } = &app.mode else { return Ok(()) };
+    let items = chooser_items(app, chooser);
+    let names: Vec<String> = items.iter().map(|&i| app.paste_buffers[i].name.clone()).collect();
+    let current = names.get(chooser.selected).cloned();
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => { app.mode = Mode::Passthrough; }
+        KeyCode::Char('c') if ctrl => { app.mode = Mode::Passthrough; }
+        KeyCode::Up | KeyCode::Char('k') => with_chooser(app, |c| c.selected = c.selected.saturating_sub(1)),
+        KeyCode::Down | KeyCode::Char('j') => {
+            let max = names.len().saturating_sub(1);
+            with_chooser(app, |c| c.selected = (c.selected + 1).min(max));
+        }
+        KeyCode::Home | KeyCode::Char('g') => with_chooser(app, |c| c.selected = 0),
+        KeyCode::End | KeyCode::Char('G') => {
+            let max = names.len().saturating_sub(1);
+            with_chooser(app, |c| c.selected = max);
+        }
+        KeyCode::Char('t') if ctrl => with_chooser(app, |c| c.tagged = names.clone()),
+        KeyCode::Char('t') => {
+            let Some(name) = current else { return Ok(()) };
+            let max = names.len().saturating_sub(1);
+            with_chooser(app, |c| {
+                match c.tagged.iter().position(|n| *n == name) {
+                    Some(pos) => { c.tagged.remove(pos); }
+                    None => c.tagged.push(name),
+                }
+                c.selected = (c.selected + 1).min(max);
+            });
+        }
+        KeyCode::Char('T') => with_chooser(app, |c| c.tagged.clear()),
+        KeyCode::Char('v') => with_chooser(app, |c| c.preview = !c.preview),
+        KeyCode::Char('r') => with_chooser(app, |c| c.reverse = !c.reverse),
+        KeyCode::Char('O') => with_chooser(app, |c| {
+            c.sort_order = match c.sort_order.as_str() { "time" => "name", "name" => "size", _ => "time" }.to_string();
+        }),
+        KeyCode::Char('d') | KeyCode::Delete => {
+            if let Some(name) = current { delete(app, Some(&name)); }
+            close_if_empty(app);
+        }
+        KeyCode::Char('D') => {
+            let Mode::BufferChooser { chooser } = &app.mode else { return Ok(()) };
+            let tagged = if chooser.tagged.is_empty() { current.into_iter().collect() } else { chooser.tagged.clone() };
+            for name in tagged { delete(app, Some(&name)); }
+            with_chooser(app, |c| c.tagged.clear());
+            close_if_empty(app);
+        }
+        KeyCode::Char('e') => {
+            if let Some(name) = current { edit_in_popup(app, &name); }
+        }
+        KeyCode::Enter => {
+            let Mode::BufferChooser { chooser } = std::mem::replace(&mut app.mode, Mode::Passthrough) else { return Ok(()) };
+            let chosen: Vec<String> = if chooser.tagged.is_empty() {
+                current.into_iter().collect()
+            } else {
+                names.into_iter().filter(|n| chooser.tagged.contains(n)).collect()
+            };
+            for name in chosen {
+                if let Some(text) = get_data(app, Some(&name)) {
+                    paste_into(app, &text, chooser.target.as_deref())?;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn with_chooser(app: &mut AppState, f: impl FnOnce(&mut BufferChooser)) {
+    if let Mode::BufferChooser { chooser } = &mut app.mode { f(chooser); }
+}
+
+/// Keep the highlight on a real item after deleting; close when nothing is
+/// left to choose.
+fn close_if_empty(app: &mut AppState) {
+    let Mode::BufferChooser { chooser } = &app.mode else { return };
+    let count = chooser_items(app, chooser).len();
+    if count == 0 {
+        app.mode = Mode::Passthrough;
+    } else {
+        with_chooser(app, |c| c.selected = c.selected.min(count - 1));
+    }
+}
+
+/// Paste text into the pane named by a `-t` target, or the active pane.
+pub fn paste_into(app: &mut AppState, text: &str, target: Option<&str>) -> io::Result<()> {
+    let saved_idx = app.active_idx;
+    let Some(target) = target else { return crate::input::send_paste_to_active(app, text) };
+    let Some((win_idx, path)) = crate::tree::resolve_pane_target(app, target) else { return Ok(()) };
+    let saved_path = std::mem::replace(&mut app.windows[win_idx].active_path, path);
+    app.active_idx = win_idx;
+    let result = crate::input::send_paste_to_active(app, text);
+    app.windows[win_idx].active_path = saved_path;
+    app.active_idx = saved_idx;
+    result
+}
+
+/// `e` in choose-buffer: write the buffer to a temporary file and open it
+/// in `$EDITOR` inside a popup.  `finish_edit` saves it back once the popup
+/// closes.
+fn edit_in_popup(app: &mut AppState, name: &str) {
+    let Some(data) = get_data(app, Some(name)) else { return };
+    let Ok(path) = write_private_temp(&data) else { return };
+    let editor = std::env::var("EDITOR").or_else(|_| std::env::var("VISUAL"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+    let Mode::BufferChooser { chooser } = std::mem::replace(&mut app.mode, Mode::Passthrough) else {
+        let _ = std::fs::remove_file(&path);
+        return;
+    };
+    let cmd = format!("display-popup -E {} '{}'", editor, path.display());
+    let _ = crate::commands::execute_command_string(app, &cmd);
+    app.buffer_edit = Some(BufferEdit { name: name.to_string(), path, chooser });
+}
+
+/// Write `data` to a new file in the temp directory that only the current
+/// user can read.  The file is created exclusively, so an existing file or
+/// symlink with the same name is never followed or overwritten.
+fn write_private_temp(data: &str) -> io::Result<std::path::PathBuf> {
+    use std::io::Write;
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos()).unwrap_or(0);
+    for attempt in 0..16u32 {
+        let name = format!("psmux-buffer-{}-{:08x}.txt", std::process::id(), nanos.wrapping_add(attempt.wrapping_mul(0x9e37_79b9)));
+        let path = std::env::temp_dir().join(name);
+        let mut opts = std::fs::OpenOptions::new();
+        opts.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(0o600);
+        }
+        match opts.open(&path) {
+            Ok(mut file) => {
+                if let Err(e) = file.write_all(data.as_bytes()) {
+                    let _ = std::fs::remove_file(&path);
+                    return Err(e);
+                }
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "no free temporary file name"))
+}
+
+/// Save an edited buffer back once its editor popup has closed and reopen
+/// the chooser.  Returns true when an edit was finished.
+pub fn finish_edit(app: &mut AppState) -> bool {
+    if app.buffer_edit.is_none() || matches!(app.mode, Mode::PopupMode { .. }) { return false; }
+    let Some(edit) = app.buffer_edit.take() else { return false };
+    if let Ok(text) = std::fs::read_to_string(&edit.path) {
+        if get_data(app, Some(&edit.name)).map_or(true, |old| old != text) {
+            set_buffer(app, Some(&edit.name), &text, false);
+        }
+    }
+    let _ = std::fs::remove_file(&edit.path);
+    if matches!(app.mode, Mode::Passthrough) {
+        app.mode = Mode::BufferChooser { chooser: edit.chooser };
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_evicts_oldest_automatic_and_keeps_named() {
+        let mut app = AppState::new("t".to_string());
+        app.buffer_limit = 2;
+        set_buffer(&mut app, Some("keep"), "named", false);
+        add_automatic(&mut app, "a".into());
+        add_automatic(&mut app, "b".into());
+        add_automatic(&mut app, "c".into());
+        let names: Vec<&str> = app.paste_buffers.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["buffer2", "buffer1", "keep"]);
+    }
+
+    #[test]
+    fn append_and_rename() {
+        let mut app = AppState::new("t".to_string());
+        set_buffer(&mut app, Some("x"), "foo", false);
+        add_automatic(&mut app, "other".into());
+        assert_eq!(set_buffer(&mut app, Some("x"), "bar", true), "foobar");
+        assert_eq!(app.paste_buffers[0].name, "x");
+        rename(&mut app, None, "y").unwrap();
+        assert_eq!(get_data(&app, Some("y")).as_deref(), Some("foobar"));
+        assert!(rename(&mut app, Some("missing"), "z").is_err());
+        assert!(delete(&mut app, Some("y")));
+        assert_eq!(get_data(&app, None).as_deref(), Some("other"));
+    }
+
+    #[test]
+    fn numbers_index_buffers_only_when_reading() {
+        let mut app = AppState::new("t".to_string());
+        add_automatic(&mut app, "old".into());
+        add_automatic(&mut app, "new".into());
+        assert_eq!(get_data(&app, Some("1")).as_deref(), Some("old"));
+        // Writing to "1" creates a buffer of that name instead of replacing "old"
+        set_buffer(&mut app, Some("1"), "one", false);
+        assert_eq!(app.paste_buffers[0].name, "1");
+        assert_eq!(get_data(&app, Some("1")).as_deref(), Some("one"));
+        assert_eq!(get_data(&app, Some("2")).as_deref(), Some("old"));
+        assert!(rename(&mut app, Some("2"), "x").is_err());
+        assert!(!delete(&mut app, Some("2")));
+        assert_eq!(app.paste_buffers.len(), 3);
+        assert!(delete(&mut app, Some("1")));
+        assert_eq!(get_data(&app, None).as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn quoted_buffer_text_survives_the_command_line() {
+        for text in [r#"say "hi""#, r"C:\temp\", r"\\server\share", r#"a\"b"#] {
+            let quoted = format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+            let args = crate::commands::parse_command_line(&format!("set-buffer -b x {}", quoted));
+            assert_eq!(args, ["set-buffer", "-b", "x", text]);
+        }
+    }
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// Buffers `c`, `b`, `a`, newest first.  Formats only expand with a
+    /// window to expand them for, so there is one.
+    fn three_buffers() -> AppState {
+        let mut app = AppState::new("t".to_string());
+        crate::pane::test_window(&mut app, 5, 20, b"");
+        for (name, data) in [("a", "short"), ("b", "a longer one"), ("c", "mid text")] {
+            set_buffer(&mut app, Some(name), data, false);
+        }
+        for (age, buf) in app.paste_buffers.iter_mut().enumerate() {
+            buf.created = Local::now() - chrono::Duration::seconds(age as i64);
+        }
+        app
+    }
+
+    fn shown(app: &AppState) -> Vec<String> {
+        let Mode::BufferChooser { chooser } = &app.mode else { panic!("chooser closed") };
+        chooser_items(app, chooser).iter().map(|&i| app.paste_buffers[i].name.clone()).collect()
+    }
+
+    #[test]
+    fn chooser_sorts_reverses_and_filters() {
+        let mut app = three_buffers();
+        open_chooser(&mut app, &[]);
+        assert_eq!(shown(&app), ["c", "b", "a"]);
+        open_chooser(&mut app, &["-O", "name"]);
+        assert_eq!(shown(&app), ["a", "b", "c"]);
+        open_chooser(&mut app, &["-O", "size", "-r"]);
+        assert_eq!(shown(&app), ["a", "c", "b"]);
+        open_chooser(&mut app, &["-f", "#{==:#{buffer_name},b}"]);
+        assert_eq!(shown(&app), ["b"]);
+    }
+
+    #[test]
+    fn chooser_keys_move_tag_and_delete() {
+        let mut app = three_buffers();
+        open_chooser(&mut app, &[]);
+        let selected = |app: &AppState| match &app.mode { Mode::BufferChooser { chooser } => chooser.selected, _ => usize::MAX };
+        chooser_key(&mut app, key('j')).unwrap();
+        assert_eq!(selected(&app), 1);
+        chooser_key(&mut app, key('G')).unwrap();
+        assert_eq!(selected(&app), 2);
+        chooser_key(&mut app, key('j')).unwrap();
+        assert_eq!(selected(&app), 2);
+        chooser_key(&mut app, key('g')).unwrap();
+        chooser_key(&mut app, key('t')).unwrap();
+        chooser_key(&mut app, key('t')).unwrap();
+        assert_eq!(selected(&app), 2);
+        chooser_key(&mut app, key('O')).unwrap();
+        assert_eq!(shown(&app), ["a", "b", "c"]);
+        chooser_key(&mut app, key('D')).unwrap();
+        assert_eq!(shown(&app), ["a"]);
+        assert_eq!(selected(&app), 0);
+        chooser_key(&mut app, key('d')).unwrap();
+        assert!(matches!(app.mode, Mode::Passthrough));
+        assert!(app.paste_buffers.is_empty());
+    }
+
+    #[test]
+    fn chooser_view_renders_items_and_preview() {
+        use ratatui::{backend::TestBackend, layout::Rect, style::{Modifier, Style}, Terminal};
+        let mut app = three_buffers();
+        open_chooser(&mut app, &[]);
+        chooser_key(&mut app, key('t')).unwrap();
+        let Mode::BufferChooser { chooser } = &app.mode else { unreachable!() };
+        let view = chooser_view(&app, chooser);
+        assert_eq!(view.title, "choose-buffer (sort: time)");
+        assert_eq!(view.selected, 1);
+        assert_eq!(view.items.iter().map(|(_, tagged)| *tagged).collect::<Vec<_>>(), [true, false, false]);
+        assert_eq!(view.preview, ["a longer one"]);
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|f| {
+            crate::rendering::render_buffer_chooser(f, Rect::new(0, 0, 60, 20), &view, Style::default().add_modifier(Modifier::REVERSED));
+        }).unwrap();
+        let text: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(text.contains("choose-buffer (sort: time)"));
+        assert!(text.contains("* c: 8 bytes"));
+        assert!(text.contains("  b: 12 bytes"));
+        assert!(text.contains("a longer one"));
+    }
+
+    #[test]
+    fn pane_targets_resolve_to_window_and_path() {
+        let mut app = AppState::new("t".to_string());
+        crate::pane::test_window(&mut app, 5, 20, b"");
+        crate::pane::test_window(&mut app, 5, 20, b"");
+        let first_pane = match &app.windows[0].root { crate::types::Node::Leaf(p) => p.id, _ => unreachable!() };
+        let second_win = app.windows[1].id;
+        assert_eq!(crate::tree::resolve_pane_target(&app, &format!("%{}", first_pane)), Some((0, vec![])));
+        assert_eq!(crate::tree::resolve_pane_target(&app, ":0.0"), Some((0, vec![])));
+        assert_eq!(crate::tree::resolve_pane_target(&app, &format!("@{}", second_win)), Some((1, vec![])));
+        assert_eq!(crate::tree::resolve_pane_target(&app, ".0"), Some((1, vec![])));
+        assert_eq!(crate::tree::resolve_pane_target(&app, ":5"), None);
+        assert_eq!(crate::tree::resolve_pane_target(&app, "%999"), None);
+    }
+
+    #[test]
+    fn edit_file_is_private_and_never_reused() {
+        let path = write_private_temp("secret").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "secret");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let other = write_private_temp("other").unwrap();
+        assert_ne!(path, other);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&other);
+    }
+}
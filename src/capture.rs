@@ -0,0 +1,340 @@
+//! `capture-pane`, with tmux's flag semantics.
+//!
+//! Lines are numbered like tmux: `0` is the first visible line, negative
+//! numbers reach back into the history and `-` means the start of the
+//! history (`-S`) or the end of the screen (`-E`).  Without `-J` or `-N`
+//! trailing spaces are trimmed; without `-J` or `-T` cleared cells at the
+//! end of a line are captured as spaces (and then trimmed unless `-N`).
+//! `-J` keeps trailing spaces and joins soft-wrapped lines.
+//!
+//! `-F` picks the output format: `text` (the default), `ansi` (like `-e`),
+//! or `html` and `json` from [`crate::export`].
+
+use crate::tree::{active_pane, resolve_pane_target};
+use crate::types::AppState;
+
+#[derive(Clone, Default)]
+pub struct CaptureOptions {
+    /// `-S`: first line; `-` is stored as `i64::MIN`.
+    pub start: Option<i64>,
+    /// `-E`: last line; `-` is stored as `i64::MAX`.
+    pub end: Option<i64>,
+    /// `-a`: the primary screen hidden behind the alternate screen.
+    pub alternate: bool,
+    /// `-e`: include SGR sequences for text and background attributes.
+    pub escapes: bool,
+    /// `-C`: escape non-printable characters as octal `\xxx`.
+    pub octal: bool,
+    /// `-J`: join wrapped lines and keep trailing spaces.
+    pub join: bool,
+    /// `-N`: keep trailing spaces.
+    pub keep_trailing: bool,
+    /// `-T`: stop at the last used cell rather than the pane width.
+    pub trim_empty: bool,
+    /// `-P`: capture only the start of an unfinished escape sequence.
+    pub pending: bool,
+    /// `-q`: do not report a missing alternate screen.
+    pub quiet: bool,
+    /// `-p`: print instead of saving to a buffer.
+    pub print: bool,
+    /// `-b`: the buffer to save to.
+    pub buffer: Option<String>,
+    /// `-t`: the pane to capture; the active pane when `None`.
+    pub target: Option<String>,
+    /// `-F`: `text`, `ansi`, `html` or `json`; `None` is `text`.
+    pub format: Option<String>,
+}
+
+/// Parse `capture-pane` arguments getopt style, so `-pJS -` works.
+/// Unknown flags are ignored; an invalid line number falls back to the
+/// default, as in tmux.
+pub fn parse_args(args: &[&str]) -> CaptureOptions {
+    let mut o = CaptureOptions::default();
+    let mut i = 0;
+    while i < args.len() {
+        let a = args[i].trim_matches('"');
+        i += 1;
+        let Some(flags) = a.strip_prefix('-').filter(|f| !f.is_empty()) else { continue };
+        for (pos, f) in flags.char_indices() {
+            match f {
+                'a' => o.alternate = true,
+                'e' => o.escapes = true,
+                'C' => o.octal = true,
+                'J' => o.join = true,
+                'N' => o.keep_trailing = true,
+                'T' => o.trim_empty = true,
+                'P' => o.pending = true,
+                'q' => o.quiet = true,
+                'p' => o.print = true,
+                'S' | 'E' | 'b' | 't' | 'F' => {
+                    let rest = &flags[pos + 1..];
+                    let value = if !rest.is_empty() {
+                        rest.to_string()
+                    } else {
+                        let v = args.get(i).map(|s| s.trim_matches('"').to_string()).unwrap_or_default();
+                        i += 1;
+                        v
+                    };
+                    match f {
+                        'S' => o.start = if value == "-" { Some(i64::MIN) } else { value.parse().ok() },
+                        'E' => o.end = if value == "-" { Some(i64::MAX) } else { value.parse().ok() },
+                        'b' => o.buffer = Some(value),
+                        'F' => o.format = Some(value),
+                        _ => o.target = Some(value),
+                    }
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+    o
+}
+
+/// Capture the pane named by `opts.target`, or the active pane.
+pub fn capture_pane(app: &AppState, opts: &CaptureOptions) -> Result<String, String> {
+    let pane = match &opts.target {
+        Some(t) => resolve_pane_target(app, t)
+            .and_then(|(wi, path)| active_pane(&app.windows[wi].root, &path))
+            .ok_or_else(|| format!("can't find pane: {}", t))?,
+        None => {
+            let Some(win) = app.windows.get(app.active_idx) else { return Ok(String::new()) };
+            let Some(p) = active_pane(&win.root, &win.active_path) else { return Ok(String::new()) };
+            p
+        }
+    };
+    let Ok(parser) = pane.term.lock() else { return Ok(String::new()) };
+    if opts.pending {
+        let bytes = parser.pending_input();
+        return Ok(if opts.octal { escape_octal(bytes) } else { String::from_utf8_lossy(bytes).into_owned() });
+    }
+    capture_screen(parser.screen(), opts)
+}
+
+/// tmux's line numbering: `n` relative to the top of the screen, clamped
+/// to the history and screen.
+fn line_index(n: i64, hsize: usize, max: usize) -> usize {
+    if n < 0 && n.unsigned_abs() > hsize as u64 { return 0; }
+    (hsize as i64).saturating_add(n).clamp(0, max as i64) as usize
+}
+
+/// The lines a capture covers, absolute history line numbers `top` to
+/// `bottom`, read from the screen or from the primary screen hidden behind
+/// the alternate screen (`-a`).
+pub struct Lines<'a> {
+    pub screen: &'a vt100::Screen,
+    pub saved: bool,
+    pub top: usize,
+    pub bottom: usize,
+}
+
+impl<'a> Lines<'a> {
+    /// Resolve `-S`, `-E` and `-a`.  `Ok(None)` means there is nothing to
+    /// capture (`-aq` without an alternate screen).
+    pub fn new(screen: &'a vt100::Screen, opts: &CaptureOptions) -> Result<Option<Self>, String> {
+        let saved = opts.alternate;
+        if saved && !screen.alternate_screen() {
+            return if opts.quiet { Ok(None) } else { Err("no alternate screen".to_string()) };
+        }
+        let hsize = if saved { 0 } else { screen.history_len() };
+        let max = hsize + (screen.size().0.max(1) as usize) - 1;
+        let mut top = opts.start.map_or(hsize, |n| line_index(n, hsize, max));
+        let mut bottom = opts.end.map_or(max, |n| line_index(n, hsize, max));
+        if bottom < top { std::mem::swap(&mut top, &mut bottom); }
+        Ok(Some(Lines { screen, saved, top, bottom }))
+    }
+
+    pub fn cols(&self) -> u16 {
+        self.screen.size().1
+    }
+
+    /// tmux's number for `line`: 0 is the top of the screen.
+    pub fn number(&self, line: usize) -> i64 {
+        let hsize = if self.saved { 0 } else { self.screen.history_len() };
+        line as i64 - hsize as i64
+    }
+
+    pub fn cell(&self, line: usize, col: u16) -> Option<&'a vt100::Cell> {
+        if self.saved { self.screen.saved_cell(line as u16, col) } else { self.screen.history_cell(line, col) }
+    }
+
+    pub fn wrapped(&self, line: usize) -> bool {
+        if self.saved { self.screen.saved_row_wrapped(line as u16) } else { self.screen.history_row_wrapped(line) }
+    }
+}
+
+/// Capture lines of `screen` in the format `-F` asks for.
+pub fn capture_screen(screen: &vt100::Screen, opts: &CaptureOptions) -> Result<String, String> {
+    let Some(lines) = Lines::new(screen, opts)? else { return Ok(String::new()) };
+    match opts.format.as_deref() {
+        None | Some("text") => Ok(capture_text(&lines, opts, opts.escapes)),
+        Some("ansi") => Ok(capture_text(&lines, opts, true)),
+        Some("html") => Ok(crate::export::html(&lines, opts.join)),
+        Some("json") => Ok(crate::export::json(&lines)),
+        Some(other) => Err(format!("unknown format: {}", other)),
+    }
+}
+
+fn capture_text(lines: &Lines, opts: &CaptureOptions, escapes: bool) -> String {
+    let empty_cells = !opts.join && !opts.trim_empty;
+    let trim_spaces = !opts.join && !opts.keep_trailing;
+
+    let mut text = String::new();
+    let mut attrs = Attrs::default();
+    for line in lines.top..=lines.bottom {
+        // (cell text, cell was written, attributes)
+        let mut cells: Vec<(&str, bool, Attrs)> = Vec::new();
+        for col in 0..lines.cols() {
+            cells.push(match lines.cell(line, col) {
+                Some(c) if c.is_wide_continuation() => continue,
+                Some(c) if c.has_contents() => (c.contents(), true, Attrs::of(c)),
+                Some(c) => (" ", false, Attrs::of(c)),
+                None => (" ", false, Attrs::default()),
+            });
+        }
+        let mut end = if empty_cells { cells.len() } else { cells.iter().rposition(|c| c.1).map_or(0, |i| i + 1) };
+        if trim_spaces {
+            while end > 0 && cells[end - 1].0 == " " { end -= 1; }
+        }
+        let mut out = String::new();
+        for &(s, _, a) in &cells[..end] {
+            if escapes && a != attrs {
+                out.push_str(&a.sgr());
+                attrs = a;
+            }
+            out.push_str(s);
+        }
+        let joined = opts.join && lines.wrapped(line);
+        if !joined && attrs != Attrs::default() {
+            out.push_str("\x1b[0m");
+            attrs = Attrs::default();
+        }
+        if opts.octal { out = escape_octal(out.as_bytes()); }
+        text.push_str(&out);
+        if !joined { text.push('\n'); }
+    }
+    text
+}
+
+/// `-C`: control characters as `\ooo` and backslashes doubled.
+pub fn escape_octal(bytes: &[u8]) -> String {
+    let mut out = Vec::with_capacity(bytes.len());
+    for &b in bytes {
+        match b {
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            0..=0x1f => out.extend_from_slice(format!("\\{:03o}", b).as_bytes()),
+            _ => out.push(b),
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The cell attributes `-e` reproduces.
+#[derive(Clone, Copy, PartialEq, Default)]
+struct Attrs {
+    fg: Option<vt100::Color>,
+    bg: Option<vt100::Color>,
+    flags: u8,
+}
+
+impl Attrs {
+    fn of(c: &vt100::Cell) -> Self {
+        let flags = [c.bold(), c.dim(), c.italic(), c.underline(), c.blink(), c.inverse(), c.hidden()]
+            .iter().enumerate()
+            .fold(0u8, |acc, (i, &on)| if on { acc | 1 << i } else { acc });
+        let color = |c: vt100::Color| (c != vt100::Color::Default).then_some(c);
+        Attrs { fg: color(c.fgcolor()), bg: color(c.bgcolor()), flags }
+    }
+
+    /// A full SGR sequence for these attributes, starting from a reset.
+    fn sgr(&self) -> String {
+        let mut params = vec!["0".to_string()];
+        for (i, code) in ["1", "2", "3", "4", "5", "7", "8"].iter().enumerate() {
+            if self.flags & (1 << i) != 0 { params.push(code.to_string()); }
+        }
+        for (color, base) in [(self.fg, 30), (self.bg, 40)] {
+            match color {
+                Some(vt100::Color::Idx(n)) if n < 8 => params.push(format!("{}", base + n as u16)),
+                Some(vt100::Color::Idx(n)) if n < 16 => params.push(format!("{}", base + 60 + n as u16 - 8)),
+                Some(vt100::Color::Idx(n)) => params.push(format!("{};5;{}", base + 8, n)),
+                Some(vt100::Color::Rgb(r, g, b)) => params.push(format!("{};2;{};{};{}", base + 8, r, g, b)),
+                _ => {}
+            }
+        }
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(rows: u16, cols: u16, input: &[u8]) -> vt100::Parser {
+        let mut parser = vt100::Parser::new(rows, cols, 100);
+        parser.process(input);
+        parser
+    }
+
+    fn capture(parser: &vt100::Parser, args: &[&str]) -> Result<String, String> {
+        capture_screen(parser.screen(), &parse_args(args))
+    }
+
+    #[test]
+    fn parses_combined_flags() {
+        let o = parse_args(&["-pJS", "-", "-E-3", "-b", "saved", "-t", "%2"]);
+        assert!(o.print && o.join);
+        assert_eq!(o.start, Some(i64::MIN));
+        assert_eq!(o.end, Some(-3));
+        assert_eq!(o.buffer.as_deref(), Some("saved"));
+        assert_eq!(o.target.as_deref(), Some("%2"));
+        assert_eq!(parse_args(&["-S", "nope"]).start, None);
+    }
+
+    #[test]
+    fn line_ranges_follow_tmux_numbering() {
+        let p = screen(2, 10, b"one\r\ntwo\r\nthree\r\nfour");
+        assert_eq!(capture(&p, &[]).unwrap(), "three\nfour\n");
+        assert_eq!(capture(&p, &["-S", "-"]).unwrap(), "one\ntwo\nthree\nfour\n");
+        assert_eq!(capture(&p, &["-S", "-1", "-E", "0"]).unwrap(), "two\nthree\n");
+        assert_eq!(capture(&p, &["-S", "-99", "-E", "-2"]).unwrap(), "one\n");
+        assert_eq!(capture(&p, &["-S", "1", "-E", "-1"]).unwrap(), "two\nthree\nfour\n");
+        assert_eq!(capture(&p, &["-E", "-"]).unwrap(), "three\nfour\n");
+    }
+
+    #[test]
+    fn join_follows_soft_wraps() {
+        let p = screen(3, 5, b"abcdefgh\r\nxy");
+        assert_eq!(capture(&p, &[]).unwrap(), "abcde\nfgh\nxy\n");
+        assert_eq!(capture(&p, &["-J"]).unwrap(), "abcdefgh\nxy\n");
+        let p = screen(2, 4, b"abcdefgh");
+        assert_eq!(capture(&p, &["-pJS", "-"]).unwrap(), "abcdefgh\n");
+    }
+
+    #[test]
+    fn trailing_spaces_and_empty_cells() {
+        let p = screen(1, 6, b"ab  ");
+        assert_eq!(capture(&p, &[]).unwrap(), "ab\n");
+        assert_eq!(capture(&p, &["-N"]).unwrap(), "ab    \n");
+        assert_eq!(capture(&p, &["-NT"]).unwrap(), "ab  \n");
+        assert_eq!(capture(&p, &["-J"]).unwrap(), "ab  \n");
+    }
+
+    #[test]
+    fn escapes_and_octal() {
+        let p = screen(1, 12, b"\x1b[31mred\x1b[0m a\\b");
+        assert_eq!(capture(&p, &["-e"]).unwrap(), "\x1b[0;31mred\x1b[0m a\\b\n");
+        assert_eq!(capture(&p, &["-eC"]).unwrap(), "\\033[0;31mred\\033[0m a\\\\b\n");
+        let p = screen(1, 12, b"\x1b[1;44mbold");
+        assert_eq!(capture(&p, &["-e"]).unwrap(), "\x1b[0;1;44mbold\x1b[0m\n");
+    }
+
+    #[test]
+    fn alternate_screen_capture() {
+        let mut p = screen(2, 10, b"shell");
+        assert_eq!(capture(&p, &["-a"]), Err("no alternate screen".to_string()));
+        assert_eq!(capture(&p, &["-aq"]).unwrap(), "");
+        p.process(b"\x1b[?1049hvim");
+        assert_eq!(capture(&p, &[]).unwrap(), "vim\n\n");
+        assert_eq!(capture(&p, &["-a"]).unwrap(), "shell\n\n");
+    }
+}
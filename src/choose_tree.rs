@@ -0,0 +1,975 @@
+//! `choose-tree` (`Prefix w`): tmux's tree mode.
+//!
+//! Sessions, windows and panes are shown as a tree with a live preview of
+//! the highlighted item underneath.  The local session is read straight
+//! from `AppState` each time the tree is drawn; the other sessions of the
+//! same `-L` namespace are asked for their windows and panes by a
+//! background thread that `tick` restarts every couple of seconds.
+//!
+//! Choosing items runs the command template with `%%` replaced by each
+//! item's target.  Commands go to the server of the item, except
+//! `switch-client`, which always goes to this session so that the
+//! attached client is the one that moves.
+
+use std::io;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthChar;
+
+use crate::commands::execute_command_string;
+use crate::format::{expand_format, expand_format_for_pane, expand_format_for_window, is_truthy, set_format_type_override};
+use crate::session::{is_warm_session, read_session_key, send_auth_cmd_response, send_control_to_port};
+use crate::tree::{active_pane, compute_rects, get_active_pane_id};
+use crate::types::{AppState, Mode, Node, Pane, TreeChooser, TreeFetch, TreeItem, TreeKind, TreePrompt};
+
+/// Default `choose-tree -F` format.
+pub const DEFAULT_FORMAT: &str = "#{?pane_format,#{pane_current_command} \"#{pane_title}\",#{?window_format,#{window_name}#{window_flags} (#{window_panes} panes),#{session_windows} windows#{?session_attached, (attached),}}}";
+
+/// Command run for the chosen item when none is given.
+pub const DEFAULT_TEMPLATE: &str = "switch-client -t '%%'";
+
+/// How often the other sessions are asked for their trees.
+const REFRESH: Duration = Duration::from_secs(2);
+
+/// Narrowest window column in a session preview.
+const MIN_COLUMN: u16 = 20;
+
+const SEP: char = '\x1f';
+
+/// A session with its windows, each with its panes.
+type Tree = Vec<(TreeItem, Vec<(TreeItem, Vec<TreeItem>)>)>;
+
+/// Parse the `choose-tree` flags (`-F format`, `-f filter`, `-K key-format`,
+/// `-O order`, `-r`, `-N`, `-Z`, `-s`, `-w`) and the optional command
+/// template that follows them.
+fn parse_args(args: &[&str]) -> TreeChooser {
+    let mut chooser = TreeChooser {
+        selected: String::new(),
+        tagged: Vec::new(),
+        toggled: Vec::new(),
+        collapse: TreeKind::Pane,
+        format: DEFAULT_FORMAT.to_string(),
+        filter: String::new(),
+        key_format: None,
+        sort_order: "index".to_string(),
+        reverse: false,
+        search: String::new(),
+        prompt: None,
+        template: DEFAULT_TEMPLATE.to_string(),
+        zoomed: false,
+        preview: true,
+        preview_offset: 0,
+        remote: Vec::new(),
+        remote_preview: None,
+        fetch: None,
+        fetched_at: None,
+    };
+    // Split grouped flags such as -Zw
+    let args: Vec<String> = args.iter().flat_map(|a| match a.strip_prefix('-') {
+        Some(flags) if flags.len() > 1 && flags.chars().all(|c| "rNZswGy".contains(c)) => {
+            flags.chars().map(|c| format!("-{}", c)).collect()
+        }
+        _ => vec![a.to_string()],
+    }).collect();
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1).cloned();
+        match args[i].as_str() {
+            "-F" => { if let Some(v) = value { chooser.format = v; } i += 1; }
+            "-f" => { if let Some(v) = value { chooser.filter = v; } i += 1; }
+            "-K" => { chooser.key_format = value; i += 1; }
+            "-O" => { if let Some(v) = value { chooser.sort_order = v; } i += 1; }
+            "-t" => i += 1,
+            "-r" => chooser.reverse = true,
+            "-N" => chooser.preview = false,
+            "-Z" => chooser.zoomed = true,
+            "-s" => chooser.collapse = TreeKind::Session,
+            "-w" => chooser.collapse = TreeKind::Window,
+            "-G" | "-y" => {}
+            _ => {
+                chooser.template = args[i..].join(" ");
+                break;
+            }
+        }
+        i += 1;
+    }
+    chooser
+}
+
+/// Open `choose-tree` on the attached session.
+pub fn open(app: &mut AppState, args: &[&str]) {
+    let mut chooser = parse_args(args);
+    // Start on the deepest visible item of the attached session
+    chooser.selected = rows(app, &chooser).iter().rev()
+        .find(|r| r.item.current)
+        .map(|r| r.item.target.clone())
+        .unwrap_or_default();
+    app.mode = Mode::WindowChooser { chooser };
+}
+
+// ─── Items ──────────────────────────────────────────────────────────────────
+
+fn expand(app: &AppState, fmt: &str, kind: TreeKind, win_idx: usize, pane_pos: usize) -> String {
+    set_format_type_override(Some(kind));
+    let out = match kind {
+        TreeKind::Session => expand_format(fmt, app),
+        TreeKind::Window => expand_format_for_window(fmt, app, win_idx),
+        TreeKind::Pane => expand_format_for_pane(fmt, app, win_idx, pane_pos),
+    };
+    set_format_type_override(None);
+    out
+}
+
+fn leaves<'a>(node: &'a Node, out: &mut Vec<&'a Pane>) {
+    match node {
+        Node::Leaf(p) => out.push(p),
+        Node::Split { children, .. } => { for c in children { leaves(c, out); } }
+    }
+}
+
+/// The local session as a tree item with its windows and panes.
+fn local_tree(app: &AppState, chooser: &TreeChooser) -> (TreeItem, Vec<(TreeItem, Vec<TreeItem>)>) {
+    let item = |kind, target: String, index, id, name: String, activity, wi, pos, current| {
+        let matches = chooser.filter.is_empty() || is_truthy(&expand(app, &chooser.filter, kind, wi, pos));
+        TreeItem {
+            kind, target, session: app.session_name.clone(), index, id, name, activity,
+            text: expand(app, &chooser.format, kind, wi, pos),
+            matches, current, server: None,
+        }
+    };
+    let session = item(TreeKind::Session, app.session_name.clone(), 0, 0, app.session_name.clone(),
+        crate::format::session_activity(app).timestamp(), app.active_idx, 0, true);
+    let windows = app.windows.iter().enumerate().map(|(wi, win)| {
+        let index = wi + app.window_base_index;
+        let activity = crate::format::window_activity(win).timestamp();
+        let target = format!("{}:{}", app.session_name, index);
+        let active_id = get_active_pane_id(&win.root, &win.active_path);
+        let mut panes = Vec::new();
+        leaves(&win.root, &mut panes);
+        let panes = panes.iter().enumerate().map(|(pos, p)| {
+            item(TreeKind::Pane, format!("{}.{}", target, pos), pos, p.id, p.title.clone(), activity,
+                wi, pos, wi == app.active_idx && active_id == Some(p.id))
+        }).collect();
+        (item(TreeKind::Window, target, index, win.id, win.name.clone(), activity, wi, 0, wi == app.active_idx), panes)
+    }).collect();
+    (session, windows)
+}
+
+/// Group the flat session, window, pane list of a fetch back into a tree.
+fn group(items: &[TreeItem]) -> Tree {
+    let mut tree: Tree = Vec::new();
+    for item in items {
+        match item.kind {
+            TreeKind::Session => tree.push((item.clone(), Vec::new())),
+            TreeKind::Window => { if let Some(s) = tree.last_mut() { s.1.push((item.clone(), Vec::new())); } }
+            TreeKind::Pane => {
+                if let Some(w) = tree.last_mut().and_then(|s| s.1.last_mut()) { w.1.push(item.clone()); }
+            }
+        }
+    }
+    tree
+}
+
+/// Sort sessions and the windows of each session by `-O`; panes keep
+/// their order.
+fn sort_tree(tree: &mut Tree, order: &str, reverse: bool) {
+    let cmp = |a: &TreeItem, b: &TreeItem| match order {
+        "name" => a.name.cmp(&b.name),
+        "time" => b.activity.cmp(&a.activity),
+        _ => a.index.cmp(&b.index).then_with(|| a.name.cmp(&b.name)),
+    };
+    tree.sort_by(|a, b| cmp(&a.0, &b.0));
+    for (_, windows) in tree.iter_mut() {
+        windows.sort_by(|a, b| cmp(&a.0, &b.0));
+        if reverse { windows.reverse(); }
+    }
+    if reverse { tree.reverse(); }
+}
+
+fn build_tree(app: &AppState, chooser: &TreeChooser) -> Tree {
+    let mut tree = vec![local_tree(app, chooser)];
+    tree.extend(group(&chooser.remote));
+    sort_tree(&mut tree, &chooser.sort_order, chooser.reverse);
+    tree
+}
+
+/// A visible line of the tree.
+pub struct Row {
+    pub item: TreeItem,
+    /// Tree lines and the `+`/`-` expansion marker.
+    pub lead: String,
+    pub children: bool,
+    pub expanded: bool,
+}
+
+fn expanded(chooser: &TreeChooser, item: &TreeItem) -> bool {
+    !chooser.search.is_empty() || ((item.kind < chooser.collapse) != chooser.toggled.contains(&item.target))
+}
+
+/// The lines shown: items matching `-f` and the `/` search, their
+/// ancestors, and the children of expanded items.
+fn visible(tree: Tree, chooser: &TreeChooser) -> Vec<Row> {
+    let search = chooser.search.to_lowercase();
+    let hit = |i: &TreeItem| i.matches && (search.is_empty()
+        || i.text.to_lowercase().contains(&search)
+        || i.name.to_lowercase().contains(&search)
+        || i.target.to_lowercase().contains(&search));
+    let marker = |children: bool, open: bool| if !children { "  " } else if open { "- " } else { "+ " };
+    let mut out = Vec::new();
+    for (session, windows) in tree {
+        let windows: Vec<(TreeItem, Vec<TreeItem>)> = windows.into_iter().filter_map(|(w, panes)| {
+            let panes: Vec<TreeItem> = panes.into_iter().filter(|p| hit(p)).collect();
+            (hit(&w) || !panes.is_empty()).then_some((w, panes))
+        }).collect();
+        if !hit(&session) && windows.is_empty() { continue; }
+        let open = expanded(chooser, &session);
+        out.push(Row { lead: marker(!windows.is_empty(), open).to_string(), children: !windows.is_empty(), expanded: open, item: session });
+        if !open { continue; }
+        let nw = windows.len();
+        for (wi, (window, panes)) in windows.into_iter().enumerate() {
+            let last_w = wi + 1 == nw;
+            let open = expanded(chooser, &window);
+            let lead = format!("{}{}", if last_w { "└─> " } else { "├─> " }, marker(!panes.is_empty(), open));
+            out.push(Row { lead, children: !panes.is_empty(), expanded: open, item: window });
+            if !open { continue; }
+            let np = panes.len();
+            for (pi, pane) in panes.into_iter().enumerate() {
+                let lead = format!("{}{}", if last_w { "    " } else { "│   " }, if pi + 1 == np { "└─> " } else { "├─> " });
+                out.push(Row { lead, children: false, expanded: false, item: pane });
+            }
+        }
+    }
+    out
+}
+
+pub fn rows(app: &AppState, chooser: &TreeChooser) -> Vec<Row> {
+    visible(build_tree(app, chooser), chooser)
+}
+
+/// Shortcut key of the `line`th row: 0-9, then M-a to M-z, or `-K`.
+fn row_key(app: &AppState, chooser: &TreeChooser, line: usize) -> String {
+    if let Some(fmt) = &chooser.key_format {
+        return expand_format(&fmt.replace("#{line}", &line.to_string()), app);
+    }
+    match line {
+        0..=9 => line.to_string(),
+        10..=35 => format!("M-{}", (b'a' + (line - 10) as u8) as char),
+        _ => String::new(),
+    }
+}
+
+// ─── View ───────────────────────────────────────────────────────────────────
+
+/// One line of the tree as the overlay draws it.
+#[derive(Serialize, Deserialize, Default)]
+pub struct TreeRow {
+    pub key: String,
+    pub lead: String,
+    pub text: String,
+    pub tagged: bool,
+    pub current: bool,
+}
+
+/// What the choose-tree overlay draws.  The server sends it to clients
+/// inside the state dump.
+#[derive(Serialize, Deserialize, Default)]
+pub struct TreeView {
+    pub title: String,
+    pub rows: Vec<TreeRow>,
+    pub selected: usize,
+    pub preview: Vec<String>,
+    pub zoomed: bool,
+    pub prompt: Option<String>,
+}
+
+/// The list and preview areas of the overlay inside `area`.  Shared by the
+/// server, which sizes the preview, and the renderer.
+pub fn areas(area: Rect, rows: usize, zoomed: bool, preview: bool) -> (Rect, Option<Rect>) {
+    let list_h = (rows.max(1) as u16).saturating_add(2);
+    let outer = if zoomed {
+        area
+    } else if preview {
+        crate::rendering::centered_rect(80, area.height.saturating_mul(4) / 5, area)
+    } else {
+        crate::rendering::centered_rect(80, list_h.min(area.height.saturating_sub(2)), area)
+    };
+    if !preview { return (outer, None); }
+    let list_h = list_h.min(outer.height / 2).max(3.min(outer.height));
+    let list = Rect { height: list_h, ..outer };
+    let rest = Rect { y: outer.y + list_h, height: outer.height - list_h, ..outer };
+    (list, Some(rest))
+}
+
+/// Inside size of the preview box, if it is shown.
+fn preview_size(app: &AppState, chooser: &TreeChooser, rows: usize) -> Option<(u16, u16)> {
+    if !chooser.preview { return None; }
+    let (_, p) = areas(app.last_window_area, rows, chooser.zoomed, true);
+    p.map(|p| (p.width.saturating_sub(2), p.height.saturating_sub(2)))
+}
+
+pub fn view(app: &AppState, chooser: &TreeChooser) -> TreeView {
+    let rows = rows(app, chooser);
+    let selected = rows.iter().position(|r| r.item.target == chooser.selected).unwrap_or(0);
+    let preview = match (rows.get(selected), preview_size(app, chooser, rows.len())) {
+        (Some(row), Some((w, h))) => preview(app, chooser, &row.item, w, h),
+        _ => Vec::new(),
+    };
+    let prompt = match &chooser.prompt {
+        Some(TreePrompt::Search(s)) => Some(format!("(search) {}", s)),
+        Some(TreePrompt::Command(s)) => Some(format!(":{}", s)),
+        Some(TreePrompt::Kill(targets)) if targets.len() == 1 => Some(format!("Kill {}? (y/n)", targets[0])),
+        Some(TreePrompt::Kill(targets)) => Some(format!("Kill {} tagged items? (y/n)", targets.len())),
+        None => None,
+    };
+    let mut title = format!("choose-tree (sort: {}{})", chooser.sort_order, if chooser.reverse { ", reversed" } else { "" });
+    if !chooser.search.is_empty() { title = format!("{} (search: {})", title, chooser.search); }
+    TreeView {
+        title,
+        rows: rows.iter().enumerate().map(|(i, r)| {
+            let label = match r.item.kind { TreeKind::Session => r.item.session.clone(), _ => r.item.index.to_string() };
+            TreeRow {
+                key: row_key(app, chooser, i),
+                lead: r.lead.clone(),
+                text: format!("{}: {}", label, r.item.text),
+                tagged: chooser.tagged.contains(&r.item.target),
+                current: r.item.current,
+            }
+        }).collect(),
+        selected,
+        preview,
+        zoomed: chooser.zoomed,
+        prompt,
+    }
+}
+
+// ─── Preview ────────────────────────────────────────────────────────────────
+
+/// A character grid the preview is drawn on.  The cell after a wide
+/// character holds `'\0'`.
+struct Canvas {
+    cells: Vec<Vec<char>>,
+}
+
+impl Canvas {
+    fn new(w: u16, h: u16) -> Self {
+        Canvas { cells: vec![vec![' '; w as usize]; h as usize] }
+    }
+
+    fn put(&mut self, x: u16, y: u16, text: &str, width: u16) {
+        let Some(line) = self.cells.get_mut(y as usize) else { return };
+        let end = (x as usize + width as usize).min(line.len());
+        let mut col = x as usize;
+        for c in text.chars() {
+            let w = c.width().unwrap_or(0);
+            if w == 0 { continue; }
+            if col + w > end { break; }
+            line[col] = c;
+            if w == 2 { line[col + 1] = '\0'; }
+            col += w;
+        }
+    }
+
+    fn lines(self) -> Vec<String> {
+        self.cells.into_iter()
+            .map(|l| l.into_iter().filter(|&c| c != '\0').collect::<String>().trim_end().to_string())
+            .collect()
+    }
+}
+
+/// Draw the bottom of a pane's screen, ending at the cursor row, into `r`.
+fn draw_pane(canvas: &mut Canvas, pane: &Pane, r: Rect) {
+    let Ok(parser) = pane.term.lock() else { return };
+    let screen = parser.screen();
+    let (cursor_row, _) = screen.cursor_position();
+    let skip = (cursor_row as usize + 1).saturating_sub(r.height as usize);
+    for (i, text) in screen.rows(0, r.width).skip(skip).take(r.height as usize).enumerate() {
+        canvas.put(r.x, r.y + i as u16, &text, r.width);
+    }
+}
+
+/// Draw a window's layout scaled into `r`, with lines between the panes.
+fn draw_window(canvas: &mut Canvas, root: &Node, r: Rect) {
+    let mut rects = Vec::new();
+    compute_rects(root, r, &mut rects);
+    let mut covered = vec![vec![false; r.width as usize]; r.height as usize];
+    for (path, pr) in &rects {
+        if let Some(p) = active_pane(root, path) { draw_pane(canvas, p, *pr); }
+        for y in pr.y..pr.y + pr.height {
+            for x in pr.x..pr.x + pr.width {
+                if let Some(c) = covered.get_mut((y - r.y) as usize).and_then(|l| l.get_mut((x - r.x) as usize)) { *c = true; }
+            }
+        }
+    }
+    let at = |x: i32, y: i32| y >= 0 && x >= 0 && covered.get(y as usize).and_then(|l| l.get(x as usize)).copied().unwrap_or(false);
+    for y in 0..r.height as i32 {
+        for x in 0..r.width as i32 {
+            if at(x, y) { continue; }
+            let beside = at(x - 1, y) || at(x + 1, y);
+            let above = at(x, y - 1) || at(x, y + 1);
+            let c = match (beside, above) { (true, false) => "│", (false, true) => "─", _ => "┼" };
+            canvas.put(r.x + x as u16, r.y + y as u16, c, 1);
+        }
+    }
+}
+
+/// Draw a session's windows side by side, each labelled and showing its
+/// active pane, starting at `offset`.
+fn draw_session(canvas: &mut Canvas, windows: &[(String, Option<&Pane>)], offset: usize, w: u16, h: u16) {
+    let n = windows.len();
+    if n == 0 || w == 0 || h == 0 { return; }
+    let shown = (((w + 1) / (MIN_COLUMN + 1)) as usize).clamp(1, n);
+    let offset = offset.min(n - shown);
+    let col_w = (w + 1) / shown as u16 - 1;
+    for (j, (label, pane)) in windows[offset..offset + shown].iter().enumerate() {
+        let x = j as u16 * (col_w + 1);
+        let label = format!(" {} ", label);
+        let lw = label.chars().count() as u16;
+        canvas.put(x + col_w.saturating_sub(lw) / 2, 0, &label, col_w);
+        if let Some(p) = pane { draw_pane(canvas, p, Rect { x, y: 1, width: col_w, height: h - 1 }); }
+        if j + 1 < shown {
+            for y in 0..h { canvas.put(x + col_w, y, "│", 1); }
+        }
+    }
+    if offset > 0 { canvas.put(0, h / 2, "<", 1); }
+    if offset + shown < n { canvas.put(w - 1, h / 2, ">", 1); }
+}
+
+fn preview(app: &AppState, chooser: &TreeChooser, item: &TreeItem, w: u16, h: u16) -> Vec<String> {
+    let mut canvas = Canvas::new(w, h);
+    let full = Rect { x: 0, y: 0, width: w, height: h };
+    if item.server.is_some() {
+        // Only the captured text of another session's pane is known
+        if let Some((target, lines)) = &chooser.remote_preview {
+            if *target == item.target {
+                let end = lines.iter().rposition(|l| !l.trim().is_empty()).map_or(0, |i| i + 1);
+                for (y, line) in lines[end.saturating_sub(h as usize)..end].iter().enumerate() {
+                    canvas.put(0, y as u16, line, w);
+                }
+            }
+        }
+        return canvas.lines();
+    }
+    match item.kind {
+        TreeKind::Pane => {
+            let pane = app.windows.iter().find_map(|win| {
+                let mut panes = Vec::new();
+                leaves(&win.root, &mut panes);
+                panes.into_iter().find(|p| p.id == item.id)
+            });
+            if let Some(p) = pane { draw_pane(&mut canvas, p, full); }
+        }
+        TreeKind::Window => {
+            if let Some(win) = app.windows.iter().find(|win| win.id == item.id) {
+                draw_window(&mut canvas, &win.root, full);
+            }
+        }
+        TreeKind::Session => {
+            let windows: Vec<(String, Option<&Pane>)> = app.windows.iter().enumerate().map(|(i, win)| {
+                (format!("{}:{}", i + app.window_base_index, win.name), active_pane(&win.root, &win.active_path))
+            }).collect();
+            draw_session(&mut canvas, &windows, chooser.preview_offset, w, h);
+        }
+    }
+    canvas.lines()
+}
+
+// ─── Other sessions ─────────────────────────────────────────────────────────
+
+/// Poll the background fetch and start the next one when due, or when the
+/// selected remote item has no captured preview yet.  Returns true when the
+/// tree changed.
+pub fn tick(app: &mut AppState) -> bool {
+    let prefix = app.socket_name.as_ref().map(|sn| format!("{}__", sn));
+    let own = app.port_file_base();
+    let Mode::WindowChooser { chooser } = &mut app.mode else { return false };
+    let mut changed = false;
+    if let Some(rx) = &chooser.fetch {
+        match rx.try_recv() {
+            Ok(fetch) => {
+                chooser.remote = fetch.items;
+                if fetch.preview.is_some() { chooser.remote_preview = fetch.preview; }
+                chooser.fetch = None;
+                changed = true;
+            }
+            Err(mpsc::TryRecvError::Disconnected) => chooser.fetch = None,
+            Err(mpsc::TryRecvError::Empty) => {}
+        }
+    }
+    if chooser.fetch.is_some() { return changed; }
+    let selected = chooser.remote.iter().find(|i| i.target == chooser.selected);
+    let stale = chooser.preview && selected.is_some()
+        && chooser.remote_preview.as_ref().map_or(true, |(t, _)| *t != chooser.selected);
+    if stale || chooser.fetched_at.map_or(true, |t| t.elapsed() >= REFRESH) {
+        let format = chooser.format.clone();
+        let filter = if chooser.filter.is_empty() { "1".to_string() } else { chooser.filter.clone() };
+        let capture = selected.filter(|_| chooser.preview)
+            .and_then(|i| i.server.clone().map(|s| (s, i.target.clone())));
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(fetch(prefix.as_deref(), &own, &format, &filter, capture));
+        });
+        chooser.fetch = Some(rx);
+        chooser.fetched_at = Some(Instant::now());
+    }
+    changed
+}
+
+fn home_dir() -> String {
+    std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")).unwrap_or_default()
+}
+
+fn server_port(base: &str) -> Option<u16> {
+    std::fs::read_to_string(format!("{}\\.psmux\\{}.port", home_dir(), base)).ok()?.trim().parse().ok()
+}
+
+fn query(base: &str, cmd: &str, fmt: &str) -> Vec<Vec<String>> {
+    let Some(port) = server_port(base) else { return Vec::new() };
+    let key = read_session_key(base).unwrap_or_default();
+    let line = format!("{} -F \"{}\"\n", cmd, fmt.replace('"', "\\\""));
+    let text = send_auth_cmd_response(&format!("127.0.0.1:{}", port), &key, line.as_bytes()).unwrap_or_default();
+    text.lines().filter(|l| l.contains(SEP)).map(|l| l.split(SEP).map(|f| f.to_string()).collect()).collect()
+}
+
+/// The other sessions of the namespace (`prefix` is `name__` under `-L`)
+/// as (port file base, session name), from their port files.
+pub fn servers(prefix: Option<&str>, own: &str) -> Vec<(String, String)> {
+    let mut bases = Vec::new();
+    if let Ok(dir) = std::fs::read_dir(format!("{}\\.psmux", home_dir())) {
+        for e in dir.flatten() {
+            let Some(fname) = e.file_name().to_str().map(|s| s.to_string()) else { continue };
+            let Some(base) = fname.strip_suffix(".port") else { continue };
+            if base == own || is_warm_session(base) { continue; }
+            let name = match prefix {
+                Some(p) => match base.strip_prefix(p) { Some(n) => n, None => continue },
+                None if base.contains("__") => continue,
+                None => base,
+            };
+            bases.push((base.to_string(), name.to_string()));
+        }
+    }
+    bases
+}
+
+/// Ask every other server of the namespace (`prefix` is `name__` under
+/// `-L`) for its session, windows and panes, and capture `capture`'s
+/// target for the preview.
+fn fetch(prefix: Option<&str>, own: &str, format: &str, filter: &str, capture: Option<(String, String)>) -> TreeFetch {
+    let mut items = Vec::new();
+    for (base, name) in servers(prefix, own) {
+        let remote = |kind, target: String, index, id, name: String, activity: &str, text: &str, matches: &str| TreeItem {
+            kind, target, session: String::new(), index, id, name,
+            activity: activity.parse().unwrap_or(0),
+            text: text.to_string(),
+            matches: is_truthy(matches),
+            current: false,
+            server: Some(base.clone()),
+        };
+        let session = query(&base, "list-sessions", &format!("#{{session_activity}}{s}{}{s}{}", format, filter, s = SEP));
+        let Some(s) = session.first().filter(|f| f.len() >= 3) else { continue };
+        let mut item = remote(TreeKind::Session, name.clone(), 0, 0, name.clone(), &s[0], &s[1], &s[2]);
+        item.session = name.clone();
+        items.push(item);
+        let windows = query(&base, "list-windows", &format!(
+            "#{{window_index}}{s}#{{window_id}}{s}#{{window_name}}{s}#{{window_activity}}{s}{}{s}{}", format, filter, s = SEP));
+        let panes = query(&base, "list-panes -a", &format!(
+            "#{{window_index}}{s}#{{pane_id}}{s}#{{pane_title}}{s}#{{window_activity}}{s}{}{s}{}", format, filter, s = SEP));
+        for w in windows.iter().filter(|f| f.len() >= 6) {
+            let target = format!("{}:{}", name, w[0]);
+            let mut item = remote(TreeKind::Window, target.clone(), w[0].parse().unwrap_or(0),
+                w[1].trim_start_matches('@').parse().unwrap_or(0), w[2].clone(), &w[3], &w[4], &w[5]);
+            item.session = name.clone();
+            items.push(item);
+            for (pos, p) in panes.iter().filter(|p| p.len() >= 6 && p[0] == w[0]).enumerate() {
+                let mut item = remote(TreeKind::Pane, format!("{}.{}", target, pos), pos,
+                    p[1].trim_start_matches('%').parse().unwrap_or(0), p[2].clone(), &p[3], &p[4], &p[5]);
+                item.session = name.clone();
+                items.push(item);
+            }
+        }
+    }
+    let preview = capture.and_then(|(base, target)| {
+        let port = server_port(&base)?;
+        let key = read_session_key(&base).unwrap_or_default();
+        let text = send_auth_cmd_response(&format!("127.0.0.1:{}", port), &key,
+            format!("capture-pane -p -t \"{}\"\n", target).as_bytes()).ok()?;
+        Some((target, text.lines().map(|l| l.to_string()).collect()))
+    });
+    TreeFetch { items, preview }
+}
+
+// ─── Keys and commands ──────────────────────────────────────────────────────
+
+fn with_chooser(app: &mut AppState, f: impl FnOnce(&mut TreeChooser)) {
+    if let Mode::WindowChooser { chooser } = &mut app.mode { f(chooser); }
+}
+
+/// Run each item's command on its server, one batch per server so they run
+/// in order.  This session's commands run here; other servers' batches are
+/// sent from a background thread, like `fetch`.  `switch-client` always
+/// runs in this session.
+fn send_commands(app: &mut AppState, commands: Vec<(Option<String>, String)>) {
+    let mut local: Vec<String> = Vec::new();
+    let mut remote: Vec<(String, String)> = Vec::new();
+    for (server, cmd) in commands {
+        let server = if matches!(cmd.split_whitespace().next(), Some("switch-client" | "switchc")) { None } else { server };
+        match server {
+            None => local.push(cmd),
+            Some(base) => match remote.iter_mut().find(|(s, _)| *s == base) {
+                Some((_, batch)) => { batch.push_str(&cmd); batch.push('\n'); }
+                None => remote.push((base, format!("{}\n", cmd))),
+            },
+        }
+    }
+    for cmd in local {
+        let _ = execute_command_string(app, &cmd);
+    }
+    if !remote.is_empty() {
+        std::thread::spawn(move || {
+            for (base, batch) in remote {
+                if let Some(port) = server_port(&base) {
+                    let _ = send_control_to_port(port, &batch, &read_session_key(&base).unwrap_or_default());
+                }
+            }
+        });
+    }
+}
+
+/// Run `template` for each item with `%%` replaced by its target.
+fn run(app: &mut AppState, template: &str, items: &[TreeItem]) {
+    let commands = items.iter()
+        .map(|i| (i.server.clone(), template.replace("%%", &i.target)))
+        .collect();
+    send_commands(app, commands);
+}
+
+/// Kill the items; windows go highest index first so the rest keep theirs.
+fn kill(app: &mut AppState, mut items: Vec<TreeItem>) {
+    items.sort_by(|a, b| b.kind.partial_cmp(&a.kind).unwrap_or(std::cmp::Ordering::Equal).then(b.index.cmp(&a.index)));
+    let commands = items.into_iter().map(|i| {
+        let cmd = match i.kind {
+            TreeKind::Session => "kill-session".to_string(),
+            TreeKind::Window => format!("kill-window -t {}", i.target),
+            TreeKind::Pane => format!("kill-pane -t %{}", i.id),
+        };
+        (i.server, cmd)
+    }).collect();
+    send_commands(app, commands);
+}
+
+/// Tagged items, or the selected one if nothing is tagged.
+fn chosen(chooser: &TreeChooser, rows: &[Row]) -> Vec<TreeItem> {
+    if chooser.tagged.is_empty() {
+        rows.iter().filter(|r| r.item.target == chooser.selected).map(|r| r.item.clone()).collect()
+    } else {
+        rows.iter().filter(|r| chooser.tagged.contains(&r.item.target)).map(|r| r.item.clone()).collect()
+    }
+}
+
+fn all_items(app: &AppState, chooser: &TreeChooser) -> Vec<TreeItem> {
+    build_tree(app, chooser).into_iter().flat_map(|(s, windows)| {
+        std::iter::once(s).chain(windows.into_iter().flat_map(|(w, panes)| std::iter::once(w).chain(panes)))
+    }).collect()
+}
+
+/// The target of the session or window an item belongs to.
+fn parent(item: &TreeItem) -> Option<String> {
+    match item.kind {
+        TreeKind::Session => None,
+        TreeKind::Window => Some(item.session.clone()),
+        TreeKind::Pane => item.target.rsplit_once('.').map(|(w, _)| w.to_string()),
+    }
+}
+
+/// Move the selection to the nearest visible item after rows were hidden
+/// by collapsing, searching or killing.
+fn fix_selection(app: &mut AppState) {
+    let Mode::WindowChooser { chooser } = &app.mode else { return };
+    let rows = rows(app, chooser);
+    let visible = |t: &str| rows.iter().any(|r| r.item.target == t);
+    if visible(&chooser.selected) { return; }
+    let items = all_items(app, chooser);
+    let mut target = chooser.selected.clone();
+    let found = loop {
+        match items.iter().find(|i| i.target == target).and_then(parent) {
+            Some(p) if visible(&p) => break Some(p),
+            Some(p) => target = p,
+            None => break rows.first().map(|r| r.item.target.clone()),
+        }
+    };
+    if let Some(t) = found { with_chooser(app, |c| c.selected = t); }
+}
+
+fn edit(text: &mut String, key: KeyEvent) {
+    match key.code {
+        KeyCode::Backspace => { text.pop(); }
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => text.clear(),
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => text.push(c),
+        _ => {}
+    }
+}
+
+/// Handle a key typed at the choose-tree prompt, which `key` has already
+/// taken off the chooser.
+fn prompt_key(app: &mut AppState, prompt: TreePrompt, key: KeyEvent) -> io::Result<()> {
+    let cancel = key.code == KeyCode::Esc
+        || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
+    match prompt {
+        TreePrompt::Kill(targets) => {
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                let Mode::WindowChooser { chooser } = &app.mode else { return Ok(()) };
+                let items: Vec<TreeItem> = all_items(app, chooser).into_iter().filter(|i| targets.contains(&i.target)).collect();
+                kill(app, items);
+                with_chooser(app, |c| {
+                    c.tagged.retain(|t| !targets.contains(t));
+                    c.fetched_at = None;
+                });
+                fix_selection(app);
+            }
+        }
+        TreePrompt::Search(mut text) => {
+            if cancel {
+                with_chooser(app, |c| c.search.clear());
+            } else if key.code != KeyCode::Enter {
+                edit(&mut text, key);
+                with_chooser(app, |c| {
+                    c.search = text.clone();
+                    c.prompt = Some(TreePrompt::Search(text));
+                });
+            }
+            fix_selection(app);
+        }
+        TreePrompt::Command(mut text) => {
+            if key.code == KeyCode::Enter {
+                let Mode::WindowChooser { chooser } = &app.mode else { return Ok(()) };
+                let items = chosen(chooser, &rows(app, chooser));
+                run(app, &text, &items);
+            } else if !cancel {
+                edit(&mut text, key);
+                with_chooser(app, |c| c.prompt = Some(TreePrompt::Command(text)));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Close the tree and run the command template for `items`.
+fn choose(app: &mut AppState, items: &[TreeItem]) {
+    let Mode::WindowChooser { chooser } = std::mem::replace(&mut app.mode, Mode::Passthrough) else { return };
+    run(app, &chooser.template, items);
+}
+
+/// Handle a key in `choose-tree`.
+pub fn key(app: &mut AppState, key: KeyEvent) -> io::Result<()> {
+    let Mode::WindowChooser { chooser } = &mut app.mode else { return Ok(()) };
+    if let Some(prompt) = chooser.prompt.take() { return prompt_key(app, prompt, key); }
+    let Mode::WindowChooser { chooser } = &app.mode else { return Ok(()) };
+    let rows = rows(app, chooser);
+    let targets: Vec<String> = rows.iter().map(|r| r.item.target.clone()).collect();
+    let pos = targets.iter().position(|t| *t == chooser.selected).unwrap_or(0);
+    let row = rows.get(pos);
+    let last = targets.len().saturating_sub(1);
+    let page = areas(app.last_window_area, rows.len(), chooser.zoomed, chooser.preview).0.height.saturating_sub(3).max(1) as usize;
+    let searching = !chooser.search.is_empty();
+    let tagged = chooser.tagged.clone();
+    let select = |app: &mut AppState, i: usize| {
+        if let Some(t) = targets.get(i.min(last)).cloned() {
+            with_chooser(app, |c| { c.selected = t; c.preview_offset = 0; });
+        }
+    };
+    let toggle = |app: &mut AppState, target: &str| with_chooser(app, |c| {
+        match c.toggled.iter().position(|t| t == target) {
+            Some(i) => { c.toggled.remove(i); }
+            None => c.toggled.push(target.to_string()),
+        }
+    });
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    match key.code {
+        KeyCode::Char('c') if ctrl => app.mode = Mode::Passthrough,
+        KeyCode::Char('t') if ctrl => with_chooser(app, |c| c.tagged = targets.clone()),
+        KeyCode::Esc if searching => with_chooser(app, |c| c.search.clear()),
+        KeyCode::Esc | KeyCode::Char('q') => app.mode = Mode::Passthrough,
+        KeyCode::Up | KeyCode::Char('k') => select(app, pos.saturating_sub(1)),
+        KeyCode::Down | KeyCode::Char('j') => select(app, pos + 1),
+        KeyCode::PageUp => select(app, pos.saturating_sub(page)),
+        KeyCode::PageDown => select(app, pos + page),
+        KeyCode::Home | KeyCode::Char('g') => select(app, 0),
+        KeyCode::End | KeyCode::Char('G') => select(app, last),
+        KeyCode::Char('-') if alt => with_chooser(app, |c| { c.collapse = TreeKind::Session; c.toggled.clear(); }),
+        KeyCode::Char('+') if alt => with_chooser(app, |c| { c.collapse = TreeKind::Pane; c.toggled.clear(); }),
+        KeyCode::Left | KeyCode::Char('-') => {
+            let Some(row) = row else { return Ok(()) };
+            if row.children && row.expanded {
+                toggle(app, &row.item.target);
+            } else if let Some(p) = parent(&row.item) {
+                with_chooser(app, |c| c.selected = p);
+            }
+        }
+        KeyCode::Right | KeyCode::Char('+') => {
+            if let Some(row) = row.filter(|r| r.children && !r.expanded) { toggle(app, &row.item.target); }
+        }
+        KeyCode::Char('t') => {
+            let Some(row) = row else { return Ok(()) };
+            let target = row.item.target.clone();
+            with_chooser(app, |c| match c.tagged.iter().position(|t| *t == target) {
+                Some(i) => { c.tagged.remove(i); }
+                None => c.tagged.push(target),
+            });
+            select(app, pos + 1);
+        }
+        KeyCode::Char('T') => with_chooser(app, |c| c.tagged.clear()),
+        KeyCode::Char(':') => with_chooser(app, |c| c.prompt = Some(TreePrompt::Command(String::new()))),
+        KeyCode::Char('/') => with_chooser(app, |c| c.prompt = Some(TreePrompt::Search(c.search.clone()))),
+        KeyCode::Char('x') => {
+            if let Some(row) = row {
+                let target = row.item.target.clone();
+                with_chooser(app, |c| c.prompt = Some(TreePrompt::Kill(vec![target])));
+            }
+        }
+        KeyCode::Char('X') if !tagged.is_empty() => with_chooser(app, |c| c.prompt = Some(TreePrompt::Kill(tagged))),
+        KeyCode::Char('<') => with_chooser(app, |c| c.preview_offset = c.preview_offset.saturating_sub(1)),
+        KeyCode::Char('>') => {
+            // Stop once the last window is in view
+            let Some(row) = row.filter(|r| r.item.kind == TreeKind::Session) else { return Ok(()) };
+            let windows = if row.item.server.is_some() { 0 } else { app.windows.len() };
+            let Mode::WindowChooser { chooser } = &app.mode else { return Ok(()) };
+            let shown = preview_size(app, chooser, rows.len())
+                .map_or(1, |(w, _)| ((w + 1) / (MIN_COLUMN + 1)).max(1) as usize);
+            with_chooser(app, |c| c.preview_offset = (c.preview_offset + 1).min(windows.saturating_sub(shown)));
+        }
+        KeyCode::Char('O') => with_chooser(app, |c| {
+            c.sort_order = match c.sort_order.as_str() { "index" => "name", "name" => "time", _ => "index" }.to_string();
+        }),
+        KeyCode::Char('r') => with_chooser(app, |c| c.reverse = !c.reverse),
+        KeyCode::Char('v') => with_chooser(app, |c| c.preview = !c.preview),
+        KeyCode::Enter => {
+            let Mode::WindowChooser { chooser } = &app.mode else { return Ok(()) };
+            let items = chosen(chooser, &rows);
+            choose(app, &items);
+        }
+        KeyCode::Char(c) => {
+            // A row's shortcut chooses it
+            let name = if alt { format!("M-{}", c) } else { c.to_string() };
+            let Mode::WindowChooser { chooser } = &app.mode else { return Ok(()) };
+            if let Some(i) = (0..rows.len()).find(|&i| row_key(app, chooser, i) == name) {
+                choose(app, &[rows[i].item.clone()]);
+            }
+        }
+        _ => {}
+    }
+    fix_selection(app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(kind: TreeKind, target: &str, index: usize, activity: i64) -> TreeItem {
+        let session = target.split(':').next().unwrap_or_default().to_string();
+        TreeItem {
+            kind, target: target.to_string(), session, index, id: index, name: target.to_string(),
+            activity, text: target.to_string(), matches: true, current: false, server: None,
+        }
+    }
+
+    fn sample() -> Tree {
+        group(&[
+            item(TreeKind::Session, "work", 0, 10),
+            item(TreeKind::Window, "work:1", 1, 5),
+            item(TreeKind::Pane, "work:1.0", 0, 5),
+            item(TreeKind::Pane, "work:1.1", 1, 5),
+            item(TreeKind::Window, "work:2", 2, 10),
+            item(TreeKind::Pane, "work:2.0", 0, 10),
+            item(TreeKind::Session, "build", 0, 20),
+            item(TreeKind::Window, "build:1", 1, 20),
+            item(TreeKind::Pane, "build:1.0", 0, 20),
+        ])
+    }
+
+    fn targets(rows: &[Row]) -> Vec<&str> {
+        rows.iter().map(|r| r.item.target.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_grouped_flags_and_template() {
+        let c = parse_args(&["-Zw", "-O", "time", "-F", "#W", "join-pane", "-s", "'%%'"]);
+        assert!(c.zoomed);
+        assert!(c.collapse == TreeKind::Window);
+        assert_eq!(c.sort_order, "time");
+        assert_eq!(c.format, "#W");
+        assert_eq!(c.template, "join-pane -s '%%'");
+        assert_eq!(parse_args(&["-s"]).template, DEFAULT_TEMPLATE);
+    }
+
+    #[test]
+    fn sorts_sessions_and_windows() {
+        let mut tree = sample();
+        sort_tree(&mut tree, "name", false);
+        assert_eq!(tree[0].0.target, "build");
+        let mut tree = sample();
+        sort_tree(&mut tree, "time", false);
+        assert_eq!(tree[0].0.target, "build");
+        assert_eq!(tree[1].1[0].0.target, "work:2");
+        let mut tree = sample();
+        sort_tree(&mut tree, "index", true);
+        assert_eq!(tree[0].0.target, "work");
+        assert_eq!(tree[0].1[0].0.target, "work:2");
+        assert_eq!(tree[0].1[1].1[0].target, "work:1.0");
+    }
+
+    #[test]
+    fn collapses_and_toggles() {
+        let mut c = parse_args(&["-w"]);
+        let rows = visible(sample(), &c);
+        assert_eq!(targets(&rows), ["work", "work:1", "work:2", "build", "build:1"]);
+        assert_eq!(rows[1].lead, "├─> + ");
+        c.toggled.push("work:2".to_string());
+        c.toggled.push("build".to_string());
+        let rows = visible(sample(), &c);
+        assert_eq!(targets(&rows), ["work", "work:1", "work:2", "work:2.0", "build"]);
+        assert_eq!(rows[3].lead, "    └─> ");
+    }
+
+    #[test]
+    fn search_keeps_ancestors_of_matches() {
+        let mut c = parse_args(&["-s"]);
+        c.search = "1.1".to_string();
+        assert_eq!(targets(&visible(sample(), &c)), ["work", "work:1", "work:1.1"]);
+        assert_eq!(parent(&item(TreeKind::Pane, "work:1.1", 1, 0)).as_deref(), Some("work:1"));
+    }
+
+    #[test]
+    fn local_commands_run_in_this_session() {
+        let mut app = AppState::new("t".to_string());
+        for _ in 0..3 { crate::pane::test_window(&mut app, 5, 20, b""); }
+        let base = app.window_base_index;
+        let first = app.windows[0].id;
+        // Give the last window a second pane to kill
+        let extra = crate::pane::test_pane(&mut app, 5, 20, b"");
+        let last_pane = extra.id;
+        let win = &mut app.windows[2];
+        let Node::Leaf(kept) = std::mem::replace(&mut win.root, Node::Split {
+            kind: crate::types::LayoutKind::Horizontal, sizes: vec![50, 50], children: Vec::new(),
+        }) else { unreachable!() };
+        win.root = Node::Split {
+            kind: crate::types::LayoutKind::Horizontal, sizes: vec![50, 50],
+            children: vec![Node::Leaf(kept), Node::Leaf(extra)],
+        };
+        let mut pane = item(TreeKind::Pane, "t:3.1", 1, 0);
+        pane.id = last_pane;
+        kill(&mut app, vec![item(TreeKind::Window, &format!("t:{}", base + 1), 1, 0), pane]);
+        // The pane goes by id, the window by index, and nothing else dies
+        let ids: Vec<usize> = app.windows.iter().map(|w| w.id).collect();
+        assert_eq!(ids, [first, first + 2]);
+        assert_eq!(crate::tree::collect_pane_ids(&app.windows[1].root).len(), 1);
+        assert!(crate::tree::find_path_by_id(&app.windows[1].root, last_pane).is_none());
+
+        run(&mut app, DEFAULT_TEMPLATE, &[item(TreeKind::Window, &format!("t:{}", base), 0, 0)]);
+        assert_eq!(app.active_idx, 0);
+    }
+}
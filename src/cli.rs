@@ -83,6 +83,13 @@ PANE COMMANDS:
     list-panes, lsp         List panes in current window
     capture-pane, capturep  Capture pane content to buffer
         -p                  Print to stdout
+    record-pane             Record pane output to an asciicast file
+        -f <file>           Recording file (default ~/.psmux/recordings)
+        -x                  Stop recording
+    play <file.cast>        Replay a recording in a new window
+        -s <speed>          Playback speed (2 = twice as fast)
+        -i <secs>           Cap pauses at this many seconds
+        -P                  Play in this terminal
 
 COPY & PASTE COMMANDS:
     copy-mode               Enter copy/scroll mode
@@ -131,6 +138,9 @@ DISPLAY COMMANDS:
     display-message, display  Display a message or format variable
     display-menu, menu      Display an interactive menu
     display-popup, popup    Display a popup window
+    hide-popup              Hide a popup, keeping its command running
+    move-popup              Move a popup
+    resize-popup            Resize a popup
     confirm-before, confirm Run command after y/n confirmation
     clock-mode              Display a big clock
     run-shell, run          Run a shell command
@@ -217,9 +227,13 @@ SET OPTIONS (use with: set -g <option> <value>):
     display-panes-time  Int  Pane number display time in ms (default: 1000)
     status-interval     Int  Status refresh interval in sec (default: 15)
     mouse               Bool Mouse support (default: on)
+    wheel-scroll-lines  Int  Lines scrolled per wheel notch (default: 3)
     status              Bool Show status bar (default: on)
     status-position     Str  "top" or "bottom" (default: bottom)
     focus-events        Bool Pass focus events to apps (default: off)
+    ambiguous-width     Int  Width of East Asian ambiguous chars, 1 or 2 (default: 1)
+    variation-selector-always-wide
+                        Bool Chars followed by VS16 are wide (default: on)
     mode-keys           Str  "vi" or "emacs" (default: emacs)
     renumber-windows    Bool Auto-renumber on close (default: off)
     automatic-rename    Bool Auto-rename from foreground process (default: on)
@@ -227,6 +241,8 @@ SET OPTIONS (use with: set -g <option> <value>):
     monitor-silence     Int  Seconds before silence flag (default: 0)
     synchronize-panes   Bool Send input to all panes (default: off)
     remain-on-exit      Bool Keep panes after process exits (default: off)
+    remain-on-exit-format
+                        Str  Line drawn into a dead pane (default: "Pane is dead (...)")
     aggressive-resize   Bool Resize to smallest client (default: off)
     set-titles          Bool Update terminal title (default: off)
     set-titles-string   Str  Terminal title format
@@ -238,6 +254,12 @@ SET OPTIONS (use with: set -g <option> <value>):
     cursor-blink        Bool Cursor blinking (default: off)
     bell-action         Str  Bell handling: any, none, current, other
     visual-bell         Bool Visual bell indicator (default: off)
+    set-clipboard       Str  Clipboard handling: on, external, off (default: on)
+    allow-clipboard-read Bool Answer OSC 52 reads from panes (default: off)
+    buffer-limit        Int  Automatic paste buffers kept (default: 50)
+    hint-alphabet       Str  Letters for hint-mode labels
+    hint-patterns       Str  name=regex patterns scanned by hint-mode
+    hint-style          Str  Style of hint-mode labels (default: fg=black,bg=yellow,bold)
 
     STATUS / STYLE OPTIONS:
     status-left         Str  Left status content (default: "[#S] ")
@@ -251,6 +273,18 @@ SET OPTIONS (use with: set -g <option> <value>):
     message-style       Str  Message bar style
     message-command-style Str Command prompt style
     mode-style          Str  Copy-mode highlight style
+    copy-mode-match-style Str Copy-mode search match style
+    copy-mode-current-match-style Str Copy-mode current search match style
+    copy-mode-position-format Str Copy-mode position indicator format
+    pane-scrollbars     Str  Scrollbars: off, modal, on (default: off)
+    pane-scrollbars-style Str Scrollbar style (default: bg=black,fg=white)
+    popup-style         Str  Popup contents style (default: default)
+    popup-border-style  Str  Popup border style (default: fg=yellow)
+    popup-border-lines  Str  Popup border lines (default: single)
+    menu-style          Str  Menu item style (default: default)
+    menu-selected-style Str  Selected menu item style (default: bg=yellow,fg=black)
+    menu-border-style   Str  Menu border style (default: default)
+    menu-border-lines   Str  Menu border lines (default: single)
     pane-border-style   Str  Inactive pane border style
     pane-active-border-style Str Active pane border style
     window-status-format        Str  Inactive window tab format
@@ -395,6 +429,7 @@ pub fn print_commands() {
   display-popup (popup)     - Display a popup window
   find-window (findw)       - Search for a window by name
   has-session               - Check if a session exists
+  hide-popup                - Hide a popup, keeping its command running
   if-shell (if)             - Conditional command execution
   join-pane                 - Join a pane to a window
   kill-pane                 - Kill a pane
@@ -414,6 +449,7 @@ pub fn print_commands() {
   load-buffer (loadb)       - Load buffer from file
   lock-client (lockc)       - Lock the client
   move-pane (movep)         - Move a pane to another window
+  move-popup                - Move a popup
   move-window (movew)       - Move a window to a different index
   new-session (new)         - Create a new session
   new-window (neww)         - Create a new window
@@ -421,11 +457,14 @@ pub fn print_commands() {
   next-window (next)        - Move to the next window
   paste-buffer              - Paste from a buffer
   pipe-pane (pipep)         - Pipe pane output to a command
+  play                      - Replay a pane recording
   previous-window (prev)    - Move to the previous window
   refresh-client (refresh)  - Refresh client display
+  record-pane               - Record pane output to an asciicast file
   rename-session            - Rename a session
   rename-window (renamew)   - Rename a window
   resize-pane (resizep)     - Resize a pane
+  resize-popup              - Resize a popup
   respawn-pane              - Respawn a pane
   rotate-window (rotatew)   - Rotate panes in a window
   run-shell (run)           - Run a shell command
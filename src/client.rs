@@ -7,9 +7,9 @@ use crossterm::event::{Event, KeyCode, KeyModifiers, KeyEventKind};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
-use crate::layout::LayoutJson;
+use crate::layout::{LayoutJson, collect_leaf_rects_json};
 use crate::help;
-use crate::util::{WinTree, base64_encode};
+use crate::util::base64_encode;
 use crate::session::read_session_key;
 use crate::rendering::{dim_predictions_enabled, map_color, dim_color, centered_rect, fix_border_intersections};
 use crate::style::parse_tmux_style_components;
@@ -300,14 +300,8 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
     let mut rename_buf = String::new();
     let mut pane_renaming = false;
     let mut pane_title_buf = String::new();
-    let mut command_input = false;
-    let mut command_buf = String::new();
     let mut chooser = false;
     let mut choices: Vec<(usize, usize)> = Vec::new();
-    let mut tree_chooser = false;
-    let mut tree_entries: Vec<(bool, usize, usize, String, String)> = Vec::new();  // (is_win, id, sub_id, label, session_name)
-    let mut tree_selected: usize = 0;
-    let mut tree_scroll: usize = 0;
     let mut session_chooser = false;
     let mut session_entries: Vec<(String, String)> = Vec::new();
     let mut session_selected: usize = 0;
@@ -317,7 +311,6 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
     let mut last_status_lines: u16 = 1; // track server's status_lines for correct client-size height
     let mut last_dump_time = Instant::now() - Duration::from_millis(250);
     let mut force_dump = true;
-    let mut last_tree: Vec<WinTree> = Vec::new();
     // Default prefix is Ctrl+B, updated dynamically from server config
     let mut prefix_key: (KeyCode, KeyModifiers) = (KeyCode::Char('b'), KeyModifiers::CONTROL);
     // Precompute the raw control character for the default prefix
@@ -339,6 +332,8 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
     let mut win_status_style: Option<(Option<Color>, Option<Color>, bool)> = None;
     let mut win_status_current_style: Option<(Option<Color>, Option<Color>, bool)> = None;
     let mut mode_style_str: String = "bg=yellow,fg=black".to_string();
+    let mut match_style_str: String = "bg=cyan,fg=black".to_string();
+    let mut current_match_style_str: String = "bg=magenta,fg=black".to_string();
     let mut status_position_str: String = "bottom".to_string();
     let mut status_justify_str: String = "left".to_string();
     // Synced bindings from server (updated each frame from DumpState)
@@ -389,11 +384,13 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
     #[allow(unused_assignments)]
     let mut srv_menu_active = false;
     #[allow(unused_assignments)]
-    let mut srv_menu_title = String::new();
+    let mut srv_menus: Vec<crate::menu::MenuView> = Vec::new();
     #[allow(unused_assignments)]
-    let mut srv_menu_selected: usize = 0;
+    let mut srv_buffer_chooser: Option<crate::buffers::ChooserView> = None;
+    let mut srv_tree_chooser: Option<crate::choose_tree::TreeView> = None;
+    let mut srv_command_prompt: Option<crate::prompt::PromptView> = None;
     #[allow(unused_assignments)]
-    let mut srv_menu_items: Vec<ServerMenuItem> = Vec::new();
+    let mut srv_hints: Option<crate::hints::HintView> = None;
     #[allow(unused_assignments)]
     let mut srv_display_panes = false;
     #[allow(unused_assignments)]
@@ -424,15 +421,20 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
         r: bool,
     }
 
-    /// A menu item from server-side MenuMode
-    #[derive(serde::Deserialize, Clone, Debug, Default)]
-    struct ServerMenuItem {
-        #[serde(default)]
-        name: Option<String>,
-        #[serde(default)]
-        key: Option<String>,
-        #[serde(default)]
-        sep: bool,
+    /// A floating popup drawn above the panes (not focused)
+    #[derive(serde::Deserialize, Default)]
+    #[serde(default)]
+    struct FloatState {
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        command: String,
+        title: String,
+        style: String,
+        border_style: String,
+        border_lines: String,
+        lines: Vec<String>,
     }
 
     #[derive(serde::Deserialize)]
@@ -443,8 +445,6 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
         prefix: Option<String>,
         #[serde(default)]
         prefix2: Option<String>,
-        #[serde(default)]
-        tree: Vec<WinTree>,
         #[serde(default = "default_base_index")]
         base_index: usize,
         #[serde(default = "default_prediction_dimming")]
@@ -525,11 +525,26 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
         #[serde(default)]
         popup_command: Option<String>,
         #[serde(default)]
+        popup_x: Option<u16>,
+        #[serde(default)]
+        popup_y: Option<u16>,
+        #[serde(default)]
         popup_width: Option<u16>,
         #[serde(default)]
         popup_height: Option<u16>,
         #[serde(default)]
         popup_lines: Vec<String>,
+        #[serde(default)]
+        popup_title: Option<String>,
+        #[serde(default)]
+        popup_style: String,
+        #[serde(default)]
+        popup_border_style: Option<String>,
+        #[serde(default)]
+        popup_border_lines: String,
+        /// Visible floating popups of the active window, bottom to top
+        #[serde(default)]
+        floats: Vec<FloatState>,
         /// Confirm overlay active
         #[serde(default)]
         confirm_active: bool,
@@ -538,18 +553,46 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
         /// Menu overlay active
         #[serde(default)]
         menu_active: bool,
+        /// The open menu and the menus it was opened from, outermost first
+        #[serde(default)]
+        menus: Vec<crate::menu::MenuView>,
+        /// choose-buffer overlay (list, tags and preview)
+        #[serde(default)]
+        buffer_chooser: Option<crate::buffers::ChooserView>,
+        /// choose-tree overlay (tree, tags, prompt and preview)
         #[serde(default)]
-        menu_title: Option<String>,
+        tree_chooser: Option<crate::choose_tree::TreeView>,
+        /// command-prompt line, cursor and Tab completions
         #[serde(default)]
-        menu_selected: usize,
+        command_prompt: Option<crate::prompt::PromptView>,
+        /// Session switch-client asked this client to move to
         #[serde(default)]
-        menu_items: Vec<ServerMenuItem>,
+        switch_to: Option<String>,
+        /// hint-mode labels for the active pane
+        #[serde(default)]
+        hints: Option<crate::hints::HintView>,
         /// Display-panes overlay active
         #[serde(default)]
         display_panes: bool,
         /// Status bar message from display-message (without -p)
         #[serde(default)]
         status_message: Option<String>,
+        /// Copy-mode search prompt ("/query" or "?query") while typing a search
+        #[serde(default)]
+        copy_search_prompt: Option<String>,
+        /// copy-mode-match-style / copy-mode-current-match-style (copy mode only)
+        #[serde(default)]
+        copy_mode_match_style: Option<String>,
+        #[serde(default)]
+        copy_mode_current_match_style: Option<String>,
+        /// Expanded copy-mode-position-format (absent with copy-mode -H)
+        #[serde(default)]
+        copy_position: Option<String>,
+        /// pane-scrollbars ("modal" or "on"; absent when off)
+        #[serde(default)]
+        pane_scrollbars: Option<String>,
+        #[serde(default)]
+        pane_scrollbars_style: Option<String>,
     }
 
     let mut cmd_batch: Vec<String> = Vec::new();
@@ -862,7 +905,20 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                         // ── Server-side overlay key handling ─────────────────
                         // When a server overlay is active, intercept ALL keys and
                         // forward them to the server via overlay-specific commands.
-                        if srv_popup_active {
+                        // Root-table popup commands still fire while a popup
+                        // has the keys, so one key can toggle a scratch popup
+                        let popup_binding = if srv_popup_active {
+                            let key_tuple = normalize_key_for_binding((key.code, key.modifiers));
+                            synced_bindings.iter().find(|b| {
+                                b.t == "root"
+                                    && matches!(b.c.split_whitespace().next(), Some("display-popup" | "popup" | "hide-popup" | "move-popup" | "resize-popup"))
+                                    && parse_key_string(&b.k).map_or(false, |k| normalize_key_for_binding(k) == key_tuple)
+                            })
+                        } else { None };
+                        if let Some(entry) = popup_binding {
+                            cmd_batch.push(format!("{}\n", entry.c));
+                        }
+                        else if srv_popup_active {
                             match key.code {
                                 KeyCode::Esc => { cmd_batch.push("overlay-close\n".into()); }
                                 KeyCode::Char(c) => {
@@ -939,22 +995,20 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                             }
                         }
                         else if srv_menu_active {
+                            // The server resolves shortcuts, navigation and submenus
                             match key.code {
-                                KeyCode::Up | KeyCode::Char('k') => { cmd_batch.push("menu-navigate -1\n".into()); }
-                                KeyCode::Down | KeyCode::Char('j') => { cmd_batch.push("menu-navigate 1\n".into()); }
-                                KeyCode::Enter => {
-                                    cmd_batch.push(format!("menu-select {}\n", srv_menu_selected));
+                                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) && c != ' ' => {
+                                    let escaped = match c {
+                                        '"' => "\\\"".to_string(),
+                                        '\\' => "\\\\".to_string(),
+                                        _ => c.to_string(),
+                                    };
+                                    cmd_batch.push(format!("send-text \"{}\"\n", escaped));
                                 }
-                                KeyCode::Esc | KeyCode::Char('q') => { cmd_batch.push("overlay-close\n".into()); }
-                                KeyCode::Char(c) => {
-                                    // Shortcut key: find menu item with matching key
-                                    if let Some(idx) = srv_menu_items.iter().position(|item| {
-                                        item.key.as_ref().map(|k| k.len() == 1 && k.chars().next() == Some(c)).unwrap_or(false)
-                                    }) {
-                                        cmd_batch.push(format!("menu-select {}\n", idx));
-                                    }
+                                code => {
+                                    let name = crate::config::format_key_binding(&(code, key.modifiers - KeyModifiers::SHIFT));
+                                    cmd_batch.push(format!("send-key {}\n", name));
                                 }
-                                _ => {}
                             }
                         }
                         else if srv_display_panes {
@@ -968,12 +1022,27 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                 _ => {}
                             }
                         }
-                        else if matches!(key.code, KeyCode::Esc) && (command_input || renaming || pane_renaming || chooser || tree_chooser || session_chooser || confirm_cmd.is_some() || keys_viewer) {
-                            command_input = false;
+                        else if srv_command_prompt.is_some() {
+                            // The server edits, completes and runs the prompt
+                            match key.code {
+                                KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) && c != ' ' => {
+                                    let escaped = match c {
+                                        '"' => "\\\"".to_string(),
+                                        '\\' => "\\\\".to_string(),
+                                        _ => c.to_string(),
+                                    };
+                                    cmd_batch.push(format!("send-text \"{}\"\n", escaped));
+                                }
+                                code => {
+                                    let name = crate::config::format_key_binding(&(code, key.modifiers - KeyModifiers::SHIFT));
+                                    cmd_batch.push(format!("send-key {}\n", name));
+                                }
+                            }
+                        }
+                        else if matches!(key.code, KeyCode::Esc) && (renaming || pane_renaming || chooser || session_chooser || confirm_cmd.is_some() || keys_viewer) {
                             renaming = false;
                             pane_renaming = false;
                             chooser = false;
-                            tree_chooser = false;
                             session_chooser = false;
                             keys_viewer = false;
                             confirm_cmd = None;
@@ -991,7 +1060,7 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                         else if is_prefix { prefix_armed = true; prefix_armed_at = Instant::now(); prefix_repeating = false; }
                         // Check root-table bindings (bind-key -n / bind-key -T root)
                         // These fire without prefix, before keys are forwarded to PTY
-                        else if !command_input && !renaming && !pane_renaming && !chooser && !tree_chooser && !session_chooser && !keys_viewer && confirm_cmd.is_none() && {
+                        else if !renaming && !pane_renaming && !chooser && !session_chooser && !keys_viewer && confirm_cmd.is_none() && {
                             let key_tuple = normalize_key_for_binding((key.code, key.modifiers));
                             synced_bindings.iter().any(|b| b.t == "root" && parse_key_string(&b.k).map_or(false, |k| normalize_key_for_binding(k) == key_tuple))
                         } {
@@ -1083,107 +1152,9 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                 }
                                 KeyCode::Char('t') => { cmd_batch.push("clock-mode\n".into()); }
                                 KeyCode::Char('=') => { cmd_batch.push("choose-buffer\n".into()); }
-                                KeyCode::Char(':') => { command_input = true; command_buf.clear(); }
-                                KeyCode::Char('w') => {
-                                    tree_chooser = true;
-                                    tree_entries.clear();
-                                    tree_selected = 0;
-                                    tree_scroll = 0;
-                                    // Query ALL sessions (like tmux choose-tree)
-                                    let dir = format!("{}\\.psmux", home);
-                                    if let Ok(entries) = std::fs::read_dir(&dir) {
-                                        let mut sessions: Vec<(String, Vec<(usize, String, Vec<(usize, String)>)>)> = Vec::new();
-                                        for e in entries.flatten() {
-                                            if let Some(fname) = e.file_name().to_str().map(|s| s.to_string()) {
-                                                if let Some((base, ext)) = fname.rsplit_once('.') {
-                                                    if ext == "port" {
-                                                        // Hide warm (standby) sessions from user
-                                                        if crate::session::is_warm_session(base) { continue; }
-                                                        if let Ok(port_str) = std::fs::read_to_string(e.path()) {
-                                                            if let Ok(p) = port_str.trim().parse::<u16>() {
-                                                                let sess_addr = format!("127.0.0.1:{}", p);
-                                                                let sess_key = read_session_key(base).unwrap_or_default();
-                                                                if let Ok(mut ss) = std::net::TcpStream::connect_timeout(
-                                                                    &sess_addr.parse().unwrap(), Duration::from_millis(50)
-                                                                ) {
-                                                                    let _ = ss.set_read_timeout(Some(Duration::from_millis(100)));
-                                                                    let _ = write!(ss, "AUTH {}\n", sess_key);
-                                                                    let _ = ss.write_all(b"list-tree\n");
-                                                                    let _ = ss.flush();
-                                                                    let mut br = BufReader::new(ss);
-                                                                    let mut al = String::new();
-                                                                    let _ = br.read_line(&mut al); // AUTH OK
-                                                                    let mut tree_line = String::new();
-                                                                    if br.read_line(&mut tree_line).is_ok() {
-                                                                        // Parse JSON array of WinTree
-                                                                        if let Ok(wins) = serde_json::from_str::<Vec<WinTree>>(&tree_line.trim()) {
-                                                                            let mut win_data = Vec::new();
-                                                                            for w in &wins {
-                                                                                let panes: Vec<(usize, String)> = w.panes.iter().map(|p| (p.id, p.title.clone())).collect();
-                                                                                win_data.push((w.id, w.name.clone(), panes));
-                                                                            }
-                                                                            sessions.push((base.to_string(), win_data));
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        // Sort sessions: current session first, then alphabetical
-                                        sessions.sort_by(|a, b| {
-                                            if a.0 == current_session { std::cmp::Ordering::Less }
-                                            else if b.0 == current_session { std::cmp::Ordering::Greater }
-                                            else { a.0.cmp(&b.0) }
-                                        });
-                                        // Build tree entries: session > window > pane
-                                        // tree_entries format: (is_win, id, sub_id, label, session_name)
-                                        // For session headers: is_win=true with id=usize::MAX as sentinel
-                                        // For windows: is_win=true
-                                        // For panes: is_win=false
-                                        for (sess_name, wins) in &sessions {
-                                            let is_current = sess_name == &current_session;
-                                            let attached = if is_current { " (attached)" } else { "" };
-                                            let nw = wins.len();
-                                            // Session header line
-                                            tree_entries.push((true, usize::MAX, 0,
-                                                format!("{}: {} windows{}", sess_name, nw, attached),
-                                                sess_name.clone()));
-                                            if is_current {
-                                                // Show windows and panes for current session
-                                                for (wi, (wid, wname, panes)) in wins.iter().enumerate() {
-                                                    let flag = if panes.len() > 0 { "" } else { "" };
-                                                    tree_entries.push((true, *wid, 0,
-                                                        format!("  {}: {}{} ({} panes)", wi, wname, flag, panes.len()),
-                                                        sess_name.clone()));
-                                                    for (pid, ptitle) in panes {
-                                                        tree_entries.push((false, *wid, *pid,
-                                                            format!("    {}", ptitle),
-                                                            sess_name.clone()));
-                                                    }
-                                                }
-                                            } else {
-                                                // Show windows for other sessions (collapsed)
-                                                for (wi, (wid, wname, panes)) in wins.iter().enumerate() {
-                                                    tree_entries.push((true, *wid, 0,
-                                                        format!("  {}: {} ({} panes)", wi, wname, panes.len()),
-                                                        sess_name.clone()));
-                                                }
-                                            }
-                                        }
-                                    }
-                                    // Fallback: if no sessions found, use current session data
-                                    if tree_entries.is_empty() {
-                                        for wi in &last_tree {
-                                            tree_entries.push((true, wi.id, 0, wi.name.clone(), current_session.clone()));
-                                            for pi in &wi.panes {
-                                                tree_entries.push((false, wi.id, pi.id, pi.title.clone(), current_session.clone()));
-                                            }
-                                        }
-                                    }
-                                }
+                                KeyCode::Char('F') => { cmd_batch.push("hint-mode\n".into()); }
+                                KeyCode::Char(':') => { cmd_batch.push("command-prompt\n".into()); }
+                                KeyCode::Char('w') => { cmd_batch.push("choose-tree -Zw\n".into()); }
                                 KeyCode::Char('s') => {
                                     session_chooser = true;
                                     session_entries.clear();
@@ -1355,34 +1326,6 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                         }
                                     }
                                 }
-                                KeyCode::Up if tree_chooser => { if tree_selected > 0 { tree_selected -= 1; } }
-                                KeyCode::Down if tree_chooser => { if tree_selected + 1 < tree_entries.len() { tree_selected += 1; } }
-                                KeyCode::Enter if tree_chooser => {
-                                    if let Some((is_win, wid, pid, _label, sess_name)) = tree_entries.get(tree_selected) {
-                                        if *wid == usize::MAX {
-                                            // Session header — switch to that session
-                                            if *sess_name != current_session {
-                                                cmd_batch.push("client-detach\n".into());
-                                                env::set_var("PSMUX_SWITCH_TO", sess_name);
-                                                quit = true;
-                                            }
-                                            tree_chooser = false;
-                                        } else if *sess_name != current_session {
-                                            // Window/pane in another session — switch to that session
-                                            cmd_batch.push("client-detach\n".into());
-                                            env::set_var("PSMUX_SWITCH_TO", sess_name);
-                                            quit = true;
-                                            tree_chooser = false;
-                                        } else if *is_win {
-                                            cmd_batch.push(format!("focus-window {}\n", wid));
-                                            tree_chooser = false;
-                                        } else {
-                                            cmd_batch.push(format!("focus-pane {}\n", pid));
-                                            tree_chooser = false;
-                                        }
-                                    }
-                                }
-                                KeyCode::Esc if tree_chooser => { tree_chooser = false; }
                                 // --- list-keys viewer (C-b ?) ---
                                 KeyCode::Up if keys_viewer => { if keys_viewer_scroll > 0 { keys_viewer_scroll -= 1; } }
                                 KeyCode::Down if keys_viewer => { keys_viewer_scroll += 1; }
@@ -1405,10 +1348,8 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                 }
                                 KeyCode::Char(c) if renaming && !key.modifiers.contains(KeyModifiers::CONTROL) => { rename_buf.push(c); }
                                 KeyCode::Char(c) if pane_renaming && !key.modifiers.contains(KeyModifiers::CONTROL) => { pane_title_buf.push(c); }
-                                KeyCode::Char(c) if command_input && !key.modifiers.contains(KeyModifiers::CONTROL) => { command_buf.push(c); }
                                 KeyCode::Backspace if renaming => { let _ = rename_buf.pop(); }
                                 KeyCode::Backspace if pane_renaming => { let _ = pane_title_buf.pop(); }
-                                KeyCode::Backspace if command_input => { let _ = command_buf.pop(); }
                                 KeyCode::Enter if renaming => {
                                     if session_renaming {
                                         cmd_batch.push(format!("rename-session {}\n", rename_buf));
@@ -1419,16 +1360,8 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                     renaming = false;
                                 }
                                 KeyCode::Enter if pane_renaming => { cmd_batch.push(format!("set-pane-title {}\n", pane_title_buf)); pane_renaming = false; }
-                                KeyCode::Enter if command_input => {
-                                    let trimmed = command_buf.trim().to_string();
-                                    if !trimmed.is_empty() {
-                                        cmd_batch.push(format!("{}\n", trimmed));
-                                    }
-                                    command_input = false;
-                                }
                                 KeyCode::Esc if renaming => { renaming = false; session_renaming = false; }
                                 KeyCode::Esc if pane_renaming => { pane_renaming = false; }
-                                KeyCode::Esc if command_input => { command_input = false; }
                                 KeyCode::Char(d) if chooser && d.is_ascii_digit() => {
                                     let raw = d.to_digit(10).unwrap() as usize;
                                     let choice = if raw == 0 { 10 } else { raw };
@@ -1574,6 +1507,18 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                     } else { false }
                                 } else { false };
 
+                                // Clicks on a popup go to the server, which focuses,
+                                // moves or resizes it (the drag is forwarded too)
+                                let on_popup = serde_json::from_str::<DumpState>(&prev_dump_buf).map(|state| {
+                                    let top = u16::from(status_position_str == "top");
+                                    let hit = |x: u16, y: u16, w: u16, h: u16| {
+                                        me.column >= x && me.column < x + w && me.row >= y + top && me.row < y + top + h
+                                    };
+                                    (state.popup_active && hit(state.popup_x.unwrap_or(0), state.popup_y.unwrap_or(0), state.popup_width.unwrap_or(0), state.popup_height.unwrap_or(0)))
+                                        || state.floats.iter().any(|p| hit(p.x, p.y, p.width, p.height))
+                                        || state.menu_active
+                                }).unwrap_or(false);
+
                                 // Always forward to server for pane focus, tab clicks, border resize, copy-mode cursor positioning
                                 cmd_batch.push(format!("mouse-down {} {}\n", me.column, me.row));
 
@@ -1582,7 +1527,7 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                     rsel_start = None;
                                     rsel_end = None;
                                     selection_changed = true;
-                                } else if on_sep {
+                                } else if on_sep || on_popup {
                                     // Border resize mode — server handles drag
                                     border_drag = true;
                                     rsel_start = None;
@@ -1789,7 +1734,16 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
             if new_size != last_sent_size {
                 last_sent_size = new_size;
                 size_changed = true;
-                if writer.write_all(format!("client-size {} {}\n", new_size.0, new_size.1).as_bytes()).is_err() {
+                // Append the cell pixel size when the terminal reports it
+                // (used for SGR-pixel mouse reports, DECSET 1016).
+                let cell_px = crossterm::terminal::window_size().ok()
+                    .filter(|ws| ws.width > 0 && ws.height > 0 && ws.columns > 0 && ws.rows > 0)
+                    .map(|ws| (ws.width / ws.columns, ws.height / ws.rows));
+                let msg = match cell_px {
+                    Some((cw, ch)) => format!("client-size {} {} {} {}\n", new_size.0, new_size.1, cw, ch),
+                    None => format!("client-size {} {}\n", new_size.0, new_size.1),
+                };
+                if writer.write_all(msg.as_bytes()).is_err() {
                     break; // Connection lost
                 }
                 // SSH: re-send mouse-enable on resize — terminal may reset
@@ -1827,7 +1781,7 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
         // Rate-limit dump-state requests to avoid flooding the server.
         // dump_in_flight prevents >1 concurrent request; the interval check
         // ensures we don't re-request faster than ~100fps when typing.
-        let overlays_active = command_input || renaming || pane_renaming || chooser || tree_chooser || session_chooser || keys_viewer || confirm_cmd.is_some() || srv_popup_active || srv_confirm_active || srv_menu_active || srv_display_panes || clock_active;
+        let overlays_active = srv_command_prompt.is_some() || renaming || pane_renaming || chooser || session_chooser || keys_viewer || confirm_cmd.is_some() || srv_popup_active || srv_confirm_active || srv_menu_active || srv_buffer_chooser.is_some() || srv_tree_chooser.is_some() || srv_hints.is_some() || srv_display_panes || clock_active;
         let should_dump = if force_dump || size_changed {
             true
         } else if typing_active {
@@ -1879,7 +1833,6 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
 
         let root = state.layout;
         let windows = state.windows;
-        last_tree = state.tree;
         let base_index = state.base_index;
         let dim_preds = state.prediction_dimming;
         clock_active = state.clock_mode;
@@ -1893,9 +1846,16 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
         srv_confirm_active = state.confirm_active;
         srv_confirm_prompt = state.confirm_prompt.unwrap_or_default();
         srv_menu_active = state.menu_active;
-        srv_menu_title = state.menu_title.unwrap_or_default();
-        srv_menu_selected = state.menu_selected;
-        srv_menu_items = state.menu_items;
+        srv_menus = state.menus;
+        srv_buffer_chooser = state.buffer_chooser;
+        srv_tree_chooser = state.tree_chooser;
+        srv_command_prompt = state.command_prompt;
+        // switch-client to another session: reattach to it
+        if let Some(name) = state.switch_to.as_ref().filter(|n| **n != current_session) {
+            env::set_var("PSMUX_SWITCH_TO", name);
+            break;
+        }
+        srv_hints = state.hints;
         srv_display_panes = state.display_panes;
 
         // ── Extract active pane's cursor state ──────────────────────
@@ -1934,8 +1894,10 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
         // ratatui's output.
         if let Some(ref clip_b64) = state.clipboard_osc52 {
             if let Some(clip_text) = crate::util::base64_decode(clip_b64) {
-                // Also set the local Win32 clipboard for non-SSH scenarios
-                copy_to_system_clipboard(&clip_text);
+                // Also set the local Win32 clipboard for non-SSH scenarios.
+                // Elsewhere the server has already run the clipboard tool
+                // (or copy-command) itself.
+                if cfg!(windows) { copy_to_system_clipboard(&clip_text); }
                 pending_osc52 = Some(clip_text);
             }
         }
@@ -2046,6 +2008,8 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
         if let Some(ref ms) = state.mode_style {
             if !ms.is_empty() { mode_style_str = ms.clone(); }
         }
+        if let Some(ref ms) = state.copy_mode_match_style { match_style_str = ms.clone(); }
+        if let Some(ref ms) = state.copy_mode_current_match_style { current_match_style_str = ms.clone(); }
         if let Some(ref sp) = state.status_position {
             if !sp.is_empty() { status_position_str = sp.clone(); }
         }
@@ -2122,7 +2086,7 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                 }
             }
 
-            fn render_json(f: &mut Frame, node: &LayoutJson, area: Rect, dim_preds: bool, border_fg: Color, active_border_fg: Color, clock_mode: bool, active_rect: Option<Rect>, mode_style_str: &str, zoomed: bool) {
+            fn render_json(f: &mut Frame, node: &LayoutJson, area: Rect, dim_preds: bool, border_fg: Color, active_border_fg: Color, clock_mode: bool, active_rect: Option<Rect>, mode_style_str: &str, match_styles: (&str, &str), zoomed: bool) {
                 match node {
                     LayoutJson::Leaf {
                         id: _,
@@ -2135,7 +2099,8 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                         cursor_shape: _,
                         active,
                         copy_mode,
-                        scroll_offset,
+                        scroll_offset: _,
+                        history_size: _,
                         sel_start_row,
                         sel_start_col,
                         sel_end_row,
@@ -2143,12 +2108,17 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                         sel_mode,
                         copy_cursor_row,
                         copy_cursor_col,
+                        search_hl,
                         content,
                         rows_v2,
                     } => {
                         // No borders — content fills entire area (tmux-style)
                         let inner = area;
                         let mut lines: Vec<Line> = Vec::new();
+                        // Clusters whose server-assigned width differs from what
+                        // ratatui would measure; drawn into the buffer after the
+                        // Paragraph so the rest of the line stays aligned.
+                        let mut clusters: Vec<(u16, u16, &str, Style, u16)> = Vec::new();
                         let use_full_cells = *copy_mode && *active && !content.is_empty();
                         if use_full_cells || rows_v2.is_empty() {
                             for r in 0..inner.height.min(content.len() as u16) {
@@ -2187,6 +2157,14 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                         fg = dim_color(fg);
                                     }
                                     let mut style = Style::default().fg(fg).bg(bg);
+                                    if *copy_mode && *active {
+                                        if let Some(&(_, _, _, current)) = search_hl.iter()
+                                            .find(|&&(hr, hs, he, _)| hr == r && c >= hs && c < he)
+                                        {
+                                            let ms = if current { match_styles.1 } else { match_styles.0 };
+                                            style = crate::rendering::parse_tmux_style(ms);
+                                        }
+                                    }
                                     if in_selection {
                                         // Apply mode-style from theme/config instead of hardcoded colors
                                         let ms = crate::rendering::parse_tmux_style(&mode_style_str);
@@ -2198,8 +2176,13 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                     if cell.italic { style = style.add_modifier(Modifier::ITALIC); }
                                     if cell.underline { style = style.add_modifier(Modifier::UNDERLINED); }
                                     let text: &str = if cell.text.is_empty() { " " } else { &cell.text };
-                                    let char_width = unicode_width::UnicodeWidthStr::width(text) as u16;
-                                    spans.push(Span::styled(text, style));
+                                    let char_width = if cell.width > 0 { cell.width } else { unicode_width::UnicodeWidthStr::width(text) as u16 };
+                                    if cell.width > 0 && crate::layout::width_mismatch(text, cell.width) {
+                                        spans.push(Span::styled(" ".repeat(cell.width as usize), style));
+                                        clusters.push((inner.x + c, inner.y + r, text, style, cell.width));
+                                    } else {
+                                        spans.push(Span::styled(text, style));
+                                    }
                                     if char_width >= 2 {
                                         c += 2;
                                     } else {
@@ -2244,7 +2227,12 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                                     if run.flags & 32 != 0 { style = style.add_modifier(Modifier::SLOW_BLINK); }
                                     if run.flags & 64 != 0 { style = style.add_modifier(Modifier::HIDDEN); }
                                     let text: &str = if run.text.is_empty() { " " } else { &run.text };
-                                    spans.push(Span::styled(text, style));
+                                    if run.width > 0 && crate::layout::width_mismatch(text, run.width) {
+                                        spans.push(Span::styled(" ".repeat(run.width as usize), style));
+                                        clusters.push((inner.x + c, inner.y + r, text, style, run.width));
+                                    } else {
+                                        spans.push(Span::styled(text, style));
+                                    }
                                     c = c.saturating_add(run.width.max(1));
                                 }
                                 // Pad remaining columns with the last run's bg
@@ -2259,28 +2247,9 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                         f.render_widget(Clear, inner);
                         let para = Paragraph::new(Text::from(lines));
                         f.render_widget(para, inner);
-
-                        // Copy mode indicator (replaces the old block title "[copy mode]")
-                        if *copy_mode && *active {
-                            let label = "[copy mode]";
-                            let lw = label.len() as u16;
-                            if area.width >= lw {
-                                let lx = area.x + area.width.saturating_sub(lw);
-                                let la = Rect::new(lx, area.y, lw, 1);
-                                let ls = Span::styled(label, Style::default().fg(Color::Black).bg(Color::Yellow));
-                                f.render_widget(Paragraph::new(Line::from(ls)), la);
-                            }
-                        }
-
-                        if *copy_mode && *active && *scroll_offset > 0 {
-                            let indicator = format!("[{}/{}]", scroll_offset, scroll_offset);
-                            let indicator_width = indicator.len() as u16;
-                            if area.width > indicator_width + 2 {
-                                let indicator_x = area.x + area.width - indicator_width - 1;
-                                let indicator_y = if *copy_mode { area.y + 1 } else { area.y };
-                                let indicator_area = Rect::new(indicator_x, indicator_y, indicator_width, 1);
-                                let indicator_span = Span::styled(indicator, Style::default().fg(Color::Black).bg(Color::Yellow));
-                                f.render_widget(Paragraph::new(Line::from(indicator_span)), indicator_area);
+                        for (x, y, text, style, width) in clusters {
+                            if x < inner.right() {
+                                crate::rendering::place_cluster(f.buffer_mut(), x, y, text, style, width);
                             }
                         }
 
@@ -2335,7 +2304,7 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
 
                         // Render children first
                         for (i, child) in children.iter().enumerate() {
-                            if i < rects.len() { render_json(f, child, rects[i], dim_preds, border_fg, active_border_fg, clock_mode, active_rect, mode_style_str, zoomed); }
+                            if i < rects.len() { render_json(f, child, rects[i], dim_preds, border_fg, active_border_fg, clock_mode, active_rect, mode_style_str, match_styles, zoomed); }
                         }
 
                         // Draw separator lines between children using direct buffer access.
@@ -2432,7 +2401,25 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
             }
 
             let active_rect = compute_active_rect_json(&root, content_chunk);
-            render_json(f, &root, content_chunk, dim_preds, pane_border_fg, pane_active_border_fg, clock_active, active_rect, &mode_style_str, state.zoomed);
+            render_json(f, &root, content_chunk, dim_preds, pane_border_fg, pane_active_border_fg, clock_active, active_rect, &mode_style_str, (&match_style_str, &current_match_style_str), state.zoomed);
+            if let (Some(view), Some(r)) = (&srv_hints, active_rect) {
+                crate::rendering::render_hints(f.buffer_mut(), r, view);
+            }
+            if let Some(ref mode) = state.pane_scrollbars {
+                let style = crate::rendering::parse_tmux_style(state.pane_scrollbars_style.as_deref().unwrap_or(""));
+                let mut leaves = Vec::new();
+                collect_leaf_rects_json(&root, content_chunk, &mut leaves);
+                for (rect, leaf) in leaves {
+                    if let LayoutJson::Leaf { active, copy_mode, scroll_offset, history_size, .. } = leaf {
+                        if mode == "on" || (*active && *copy_mode) {
+                            crate::rendering::render_scrollbar(f.buffer_mut(), rect, *history_size, *scroll_offset, style);
+                        }
+                    }
+                }
+            }
+            if let (Some(text), Some(r)) = (&state.copy_position, active_rect) {
+                crate::rendering::render_copy_position(f, r, text);
+            }
             fix_border_intersections(f.buffer_mut());
 
             // ── Left-click drag text selection overlay ────────────────
@@ -2484,41 +2471,6 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                 let para = Paragraph::new(Text::from(lines));
                 f.render_widget(para, overlay.inner(oa));
             }
-            if tree_chooser {
-                let sel_style = crate::rendering::parse_tmux_style(&mode_style_str);
-                let overlay = Block::default().borders(Borders::ALL).title("choose-tree").border_style(sel_style);
-                // Use dynamic height: content lines + 2 (borders), capped to
-                // available space so the overlay never exceeds the terminal.
-                let tree_h = ((tree_entries.len() as u16).saturating_add(2))
-                    .max(5)
-                    .min(content_chunk.height.saturating_sub(2));
-                let oa = centered_rect(60, tree_h, content_chunk);
-                f.render_widget(Clear, oa);
-                f.render_widget(&overlay, oa);
-                let inner = overlay.inner(oa);
-                let visible_h = inner.height as usize;
-                // Keep tree_selected in view
-                if tree_selected >= tree_scroll + visible_h {
-                    tree_scroll = tree_selected.saturating_sub(visible_h - 1);
-                }
-                if tree_selected < tree_scroll {
-                    tree_scroll = tree_selected;
-                }
-                let mut lines: Vec<Line> = Vec::new();
-                for (i, (is_win, wid, _pid, label, _sess)) in tree_entries.iter().enumerate().skip(tree_scroll).take(visible_h) {
-                    let line = if i == tree_selected {
-                        Line::from(Span::styled(label.clone(), sel_style))
-                    } else if *is_win && *wid == usize::MAX {
-                        // Session header — bold
-                        Line::from(Span::styled(label.clone(), Style::default().add_modifier(Modifier::BOLD)))
-                    } else {
-                        Line::from(label.clone())
-                    };
-                    lines.push(line);
-                }
-                let para = Paragraph::new(Text::from(lines));
-                f.render_widget(para, inner);
-            }
             if keys_viewer {
                 // Proportional overlay: 90% width, up to 80% height
                 let avail_h = content_chunk.height;
@@ -2792,14 +2744,6 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                 let para = Paragraph::new(format!("title: {}", pane_title_buf));
                 f.render_widget(para, overlay.inner(oa));
             }
-            if command_input {
-                let overlay = Block::default().borders(Borders::ALL).title("command");
-                let oa = centered_rect(60, 3, content_chunk);
-                f.render_widget(Clear, oa);
-                f.render_widget(&overlay, oa);
-                let para = Paragraph::new(format!(": {}", command_buf));
-                f.render_widget(para, overlay.inner(oa));
-            }
             if let Some(ref cmd) = confirm_cmd {
                 let overlay = Block::default().borders(Borders::ALL).title("confirm");
                 let oa = centered_rect(50, 3, content_chunk);
@@ -2810,28 +2754,52 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
             }
 
             // ── Server-side overlay rendering ────────────────────────
-            if srv_popup_active {
-                let w = srv_popup_width.min(content_chunk.width.saturating_sub(2));
-                let h = srv_popup_height.min(content_chunk.height.saturating_sub(2));
+            // Floating popups sit above the panes, the focused popup on top
+            let focused = srv_popup_active.then(|| {
+                let (w, h) = (srv_popup_width.min(content_chunk.width), srv_popup_height.min(content_chunk.height));
+                FloatState {
+                    x: state.popup_x.unwrap_or(content_chunk.width.saturating_sub(w) / 2),
+                    y: state.popup_y.unwrap_or(content_chunk.height.saturating_sub(h) / 2),
+                    width: srv_popup_width,
+                    height: srv_popup_height,
+                    command: srv_popup_command.clone(),
+                    title: state.popup_title.clone().unwrap_or_default(),
+                    style: state.popup_style.clone(),
+                    border_style: state.popup_border_style.clone().unwrap_or_else(|| "fg=yellow".to_string()),
+                    border_lines: state.popup_border_lines.clone(),
+                    lines: srv_popup_lines.clone(),
+                }
+            });
+            for popup in state.floats.iter().chain(focused.as_ref()) {
+                let FloatState { x, y, width, height, ref command, ref title, ref style, ref border_style, ref border_lines, lines: ref popup_lines } = *popup;
+                let title = if title.is_empty() { command } else { title };
+                let w = width.min(content_chunk.width);
+                let h = height.min(content_chunk.height);
                 let popup_area = Rect {
-                    x: content_chunk.x + (content_chunk.width.saturating_sub(w)) / 2,
-                    y: content_chunk.y + (content_chunk.height.saturating_sub(h)) / 2,
+                    x: content_chunk.x + x.min(content_chunk.width.saturating_sub(w)),
+                    y: content_chunk.y + y.min(content_chunk.height.saturating_sub(h)),
                     width: w,
                     height: h,
                 };
-                let title = if srv_popup_command.is_empty() { "Popup".to_string() } else { let max_title = (w as usize).saturating_sub(4); if srv_popup_command.len() > max_title { format!("{}...", &srv_popup_command[..max_title.saturating_sub(3)]) } else { srv_popup_command.clone() } };
-                let block = Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Yellow))
-                    .title(title);
+                let title = if title.is_empty() { "Popup".to_string() } else { let max_title = (w as usize).saturating_sub(4); if title.chars().count() > max_title { format!("{}...", title.chars().take(max_title.saturating_sub(3)).collect::<String>()) } else { title.clone() } };
+                let block = crate::style::popup_block(title, style, border_style, border_lines);
                 let mut lines: Vec<Line<'static>> = Vec::new();
-                for line_str in &srv_popup_lines {
+                for line_str in popup_lines {
                     lines.push(Line::from(line_str.clone()));
                 }
                 let para = Paragraph::new(Text::from(lines)).block(block);
                 f.render_widget(Clear, popup_area);
                 f.render_widget(para, popup_area);
             }
+            if let Some(ref prompt) = state.copy_search_prompt {
+                let pane = active_rect.unwrap_or(content_chunk);
+                let width = (prompt.chars().count() as u16 + 10).clamp(30, 80).min(pane.width);
+                let search_area = Rect::new(pane.x, pane.y + pane.height.saturating_sub(1), width, 1);
+                let para = Paragraph::new(prompt.clone())
+                    .style(Style::default().fg(Color::Yellow).bg(Color::Black));
+                f.render_widget(Clear, search_area);
+                f.render_widget(para, search_area);
+            }
             if srv_confirm_active {
                 let overlay = Block::default().borders(Borders::ALL).title("confirm");
                 let oa = centered_rect(60, 3, content_chunk);
@@ -2840,33 +2808,19 @@ pub fn run_remote(terminal: &mut Terminal<CrosstermBackend<crate::platform::Psmu
                 let para = Paragraph::new(srv_confirm_prompt.clone());
                 f.render_widget(para, overlay.inner(oa));
             }
-            if srv_menu_active {
+            for view in &srv_menus {
+                crate::rendering::render_menu(f, content_chunk, view);
+            }
+            if let Some(ref view) = srv_buffer_chooser {
                 let sel_style = crate::rendering::parse_tmux_style(&mode_style_str);
-                let title_str = if srv_menu_title.is_empty() { "Menu".to_string() } else { srv_menu_title.clone() };
-                let overlay = Block::default().borders(Borders::ALL).title(title_str).border_style(sel_style);
-                let item_count = srv_menu_items.len();
-                let menu_h = ((item_count as u16).saturating_add(2)).max(3).min(content_chunk.height.saturating_sub(2));
-                let oa = centered_rect(50, menu_h, content_chunk);
-                f.render_widget(Clear, oa);
-                f.render_widget(&overlay, oa);
-                let inner = overlay.inner(oa);
-                let mut lines: Vec<Line<'static>> = Vec::new();
-                for (i, item) in srv_menu_items.iter().enumerate() {
-                    if item.sep {
-                        lines.push(Line::from("─".repeat(inner.width as usize)));
-                    } else {
-                        let name = item.name.clone().unwrap_or_default();
-                        let key_str = item.key.clone().unwrap_or_default();
-                        let label = if key_str.is_empty() { name } else { format!("{} ({})", name, key_str) };
-                        if i == srv_menu_selected {
-                            lines.push(Line::from(Span::styled(label, sel_style)));
-                        } else {
-                            lines.push(Line::from(label));
-                        }
-                    }
-                }
-                let para = Paragraph::new(Text::from(lines));
-                f.render_widget(para, inner);
+                crate::rendering::render_buffer_chooser(f, content_chunk, view, sel_style);
+            }
+            if let Some(ref view) = srv_tree_chooser {
+                let sel_style = crate::rendering::parse_tmux_style(&mode_style_str);
+                crate::rendering::render_tree_chooser(f, content_chunk, view, sel_style);
+            }
+            if let Some(ref view) = srv_command_prompt {
+                crate::rendering::render_command_prompt(f, content_chunk, status_chunk, view);
             }
             if srv_display_panes {
                 // Render pane numbers overlay (like tmux display-panes)
@@ -1,24 +1,14 @@
 use std::io;
 use std::time::Instant;
 
-use crate::types::{AppState, Mode, Action, FocusDir, LayoutKind, MenuItem, Menu, PopupPty, Node};
+use crate::types::{AppState, Mode, Action, FocusDir, LayoutKind, Node};
 use crate::tree::{compute_rects, kill_all_children};
-use crate::pane::{create_window, split_active, kill_active_pane};
-use crate::copy_mode::{enter_copy_mode, switch_with_copy_save, paste_latest,
+use crate::pane::{create_window, split_active, kill_active_pane, kill_pane_by_id};
+use crate::copy_mode::{enter_copy_mode, switch_with_copy_save, paste_latest, scroll_copy_up,
     capture_active_pane, save_latest_buffer};
-use crate::session::{send_control_to_port, list_all_sessions_tree};
+use crate::session::send_control_to_port;
 use crate::window_ops::toggle_zoom;
 
-/// Build the choose-tree data for the WindowChooser mode.
-pub fn build_choose_tree(app: &AppState) -> Vec<crate::session::TreeEntry> {
-    let current_windows: Vec<(String, usize, String, bool)> = app.windows.iter().enumerate().map(|(i, w)| {
-        let panes = crate::tree::count_panes(&w.root);
-        let size = format!("{}x{}", app.last_window_area.width, app.last_window_area.height);
-        (w.name.clone(), panes, size, i == app.active_idx)
-    }).collect();
-    list_all_sessions_tree(&app.session_name, &current_windows)
-}
-
 /// Extract a window index from a tmux-style target string.
 /// Handles formats like "0", ":0", ":=0", "=0", stripping leading ':'/'=' chars.
 fn parse_window_target(target: &str) -> Option<usize> {
@@ -50,11 +40,11 @@ pub fn parse_command_to_action(cmd: &str) -> Option<Action> {
         "kill-pane" | "killp" => Some(Action::KillPane),
         "next-window" | "next" => Some(Action::NextWindow),
         "previous-window" | "prev" => Some(Action::PrevWindow),
-        "copy-mode" => Some(Action::CopyMode),
+        "copy-mode" if parts.len() == 1 => Some(Action::CopyMode),
         "paste-buffer" | "pasteb" => Some(Action::Paste),
         "detach-client" | "detach" => Some(Action::Detach),
         "rename-window" | "renamew" => Some(Action::RenameWindow),
-        "choose-window" | "choose-tree" | "choose-session" => Some(Action::WindowChooser),
+        "choose-tree" if parts.len() == 1 => Some(Action::WindowChooser),
         "resize-pane" | "resizep" if parts.iter().any(|p| *p == "-Z") => Some(Action::ZoomPane),
         "zoom-pane" => Some(Action::ZoomPane),
         "select-pane" | "selectp" => {
@@ -93,7 +83,7 @@ pub fn parse_command_to_action(cmd: &str) -> Option<Action> {
         "rename-session" | "rename" => Some(Action::Command(cmd.to_string())),
         "clear-history" => Some(Action::Command("clear-history".to_string())),
         "set-buffer" | "setb" => Some(Action::Command(cmd.to_string())),
-        "delete-buffer" | "deleteb" => Some(Action::Command("delete-buffer".to_string())),
+        "delete-buffer" | "deleteb" => Some(Action::Command(cmd.to_string())),
         "display-message" | "display" => Some(Action::Command(cmd.to_string())),
         "switch-client" | "switchc" => {
             // Check for -T flag to switch key table
@@ -125,7 +115,7 @@ pub fn format_action(action: &Action) -> String {
         Action::Paste => "paste-buffer".to_string(),
         Action::Detach => "detach-client".to_string(),
         Action::RenameWindow => "rename-window".to_string(),
-        Action::WindowChooser => "choose-window".to_string(),
+        Action::WindowChooser => "choose-tree".to_string(),
         Action::ZoomPane => "resize-pane -Z".to_string(),
         Action::MoveFocus(dir) => {
             let flag = match dir {
@@ -161,13 +151,13 @@ pub fn parse_command_line(line: &str) -> Vec<String> {
                 current.push(c);
             }
         } else if c == '\\' && in_double_quotes {
-            // Inside double quotes, only treat \" as an escape (produces a
-            // literal double-quote).  All other backslashes are kept literal
-            // because psmux is a Windows-native tool where backslash is the
-            // normal path separator (e.g. "C:\Program Files\Git\bin\bash.exe").
-            if i + 1 < chars.len() && chars[i + 1] == '"' {
-                current.push('"');
-                i += 1; // skip the quote
+            // Inside double quotes, only \" and \\ are escapes (a literal
+            // double-quote or backslash).  All other backslashes are kept
+            // literal because psmux is a Windows-native tool where backslash
+            // is the normal path separator (e.g. "C:\Program Files\Git\bin\bash.exe").
+            if i + 1 < chars.len() && (chars[i + 1] == '"' || chars[i + 1] == '\\') {
+                current.push(chars[i + 1]);
+                i += 1; // skip the escaped character
             } else {
                 current.push(c); // literal backslash
             }
@@ -193,70 +183,6 @@ pub fn parse_command_line(line: &str) -> Vec<String> {
     args
 }
 
-/// Parse a menu definition string into a Menu structure
-pub fn parse_menu_definition(def: &str, x: Option<i16>, y: Option<i16>) -> Menu {
-    let mut menu = Menu {
-        title: String::new(),
-        items: Vec::new(),
-        selected: 0,
-        x,
-        y,
-    };
-    
-    let parts: Vec<&str> = def.split_whitespace().collect();
-    if parts.is_empty() {
-        return menu;
-    }
-    
-    let mut i = 0;
-    while i < parts.len() {
-        if parts[i] == "-T" {
-            if let Some(title) = parts.get(i + 1) {
-                menu.title = title.trim_matches('"').to_string();
-                i += 2;
-                continue;
-            }
-        }
-        
-        if let Some(name) = parts.get(i) {
-            let name = name.trim_matches('"').to_string();
-            if name.is_empty() || name == "-" {
-                menu.items.push(MenuItem {
-                    name: String::new(),
-                    key: None,
-                    command: String::new(),
-                    is_separator: true,
-                });
-                i += 1;
-            } else {
-                let key = parts.get(i + 1).map(|k| k.trim_matches('"').chars().next()).flatten();
-                let command = parts.get(i + 2).map(|c| c.trim_matches('"').to_string()).unwrap_or_default();
-                menu.items.push(MenuItem {
-                    name,
-                    key,
-                    command,
-                    is_separator: false,
-                });
-                i += 3;
-            }
-        } else {
-            break;
-        }
-    }
-    
-    if menu.items.is_empty() && !def.is_empty() {
-        menu.title = "Menu".to_string();
-        menu.items.push(MenuItem {
-            name: def.to_string(),
-            key: Some('1'),
-            command: def.to_string(),
-            is_separator: false,
-        });
-    }
-    
-    menu
-}
-
 /// Fire hooks for a given event
 pub fn fire_hooks(app: &mut AppState, event: &str) {
     if let Some(commands) = app.hooks.get(event).cloned() {
@@ -266,6 +192,45 @@ pub fn fire_hooks(app: &mut AppState, event: &str) {
     }
 }
 
+/// `switch-client -t target`: focus a window or pane of this session, or
+/// select it in another session and ask the attached client to move there.
+pub fn switch_client(app: &mut AppState, target: &str) {
+    if target.is_empty() { return; }
+    let t = crate::cli::parse_target(target);
+    match t.session.as_deref() {
+        Some(name) if name != app.session_name => {
+            let base = match app.socket_name {
+                Some(ref sn) => format!("{}__{}", sn, name),
+                None => name.to_string(),
+            };
+            let home = std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")).unwrap_or_default();
+            let port = std::fs::read_to_string(format!("{}\\.psmux\\{}.port", home, base)).ok()
+                .and_then(|s| s.trim().parse::<u16>().ok());
+            let Some(port) = port else { return };
+            let select = crate::session_view::select_commands(&t);
+            if !select.is_empty() {
+                let key = crate::session::read_session_key(&base).unwrap_or_default();
+                let _ = send_control_to_port(port, &format!("{}\n", select.join("\n")), &key);
+            }
+            app.switch_client = Some(name.to_string());
+        }
+        _ => {
+            if t.window.is_none() && t.pane.is_none() { return; }
+            let Some((win_idx, path)) = crate::tree::resolve_pane_target(app, target) else { return };
+            let pane_id = crate::tree::get_active_pane_id(&app.windows[win_idx].root, &path);
+            switch_with_copy_save(app, |app| {
+                if win_idx != app.active_idx {
+                    app.last_window_idx = app.active_idx;
+                    app.active_idx = win_idx;
+                }
+                if let Some(pid) = pane_id.filter(|_| t.pane.is_some()) {
+                    crate::tree::focus_pane_by_id(app, pid);
+                }
+            });
+        }
+    }
+}
+
 /// Execute an Action (from key bindings)
 pub fn execute_action(app: &mut AppState, action: &Action) -> io::Result<bool> {
     match action {
@@ -327,9 +292,7 @@ pub fn execute_action(app: &mut AppState, action: &Action) -> io::Result<bool> {
             app.mode = Mode::RenamePrompt { input: String::new() };
         }
         Action::WindowChooser => {
-            let tree = build_choose_tree(app);
-            let selected = tree.iter().position(|e| e.is_current_session && e.is_active_window && !e.is_session_header).unwrap_or(0);
-            app.mode = Mode::WindowChooser { selected, tree };
+            crate::choose_tree::open(app, &[]);
         }
         Action::ZoomPane => {
             toggle_zoom(app);
@@ -349,9 +312,7 @@ pub fn execute_action(app: &mut AppState, action: &Action) -> io::Result<bool> {
     Ok(false)
 }
 
-pub fn execute_command_prompt(app: &mut AppState) -> io::Result<()> {
-    let cmdline = match &app.mode { Mode::CommandPrompt { input, .. } => input.clone(), _ => String::new() };
-    app.mode = Mode::Passthrough;
+pub fn execute_command_prompt(app: &mut AppState, cmdline: &str) -> io::Result<()> {
     let parts: Vec<&str> = cmdline.split_whitespace().collect();
     if parts.is_empty() { return Ok(()); }
     match parts[0] {
@@ -392,7 +353,7 @@ pub fn execute_command_prompt(app: &mut AppState) -> io::Result<()> {
         _ => {
             // Apply config change locally (client-side state) and also
             // forward to the server so it takes effect for pane spawning.
-            crate::config::parse_config_line(app, &cmdline);
+            crate::config::parse_config_line(app, cmdline);
             if let Some(port) = app.control_port {
                 let _ = send_control_to_port(port, &format!("{}\n", cmdline), &app.session_key);
             }
@@ -418,13 +379,25 @@ pub fn execute_command_string(app: &mut AppState, cmd: &str) -> io::Result<()> {
                 let _ = send_control_to_port(port, &format!("{}\n", cmd), &app.session_key);
             }
         }
-        "kill-pane" => {
-            let _ = kill_active_pane(app);
+        "kill-pane" | "killp" => {
+            match parts.iter().position(|p| *p == "-t").and_then(|i| parts.get(i + 1)) {
+                Some(t) => {
+                    let id = crate::tree::resolve_pane_target(app, t)
+                        .and_then(|(wi, path)| crate::tree::get_active_pane_id(&app.windows[wi].root, &path));
+                    if let Some(id) = id { let _ = kill_pane_by_id(app, id); }
+                }
+                None => { let _ = kill_active_pane(app); }
+            }
         }
         "kill-window" | "killw" => {
-            if app.windows.len() > 1 {
-                let mut win = app.windows.remove(app.active_idx);
+            let idx = match parts.iter().position(|p| *p == "-t").and_then(|i| parts.get(i + 1)) {
+                Some(t) => crate::tree::resolve_pane_target(app, t).map(|(wi, _)| wi),
+                None => Some(app.active_idx),
+            };
+            if let Some(idx) = idx.filter(|_| app.windows.len() > 1) {
+                let mut win = app.windows.remove(idx);
                 kill_all_children(&mut win.root);
+                if idx < app.active_idx { app.active_idx -= 1; }
                 if app.active_idx >= app.windows.len() {
                     app.active_idx = app.windows.len() - 1;
                 }
@@ -561,6 +534,13 @@ pub fn execute_command_string(app: &mut AppState, cmd: &str) -> io::Result<()> {
         }
         "copy-mode" => {
             enter_copy_mode(app);
+            if parts.contains(&"-u") {
+                let rows = app.windows.get(app.active_idx)
+                    .and_then(|w| crate::tree::active_pane(&w.root, &w.active_path))
+                    .map(|p| p.last_rows as usize).unwrap_or(20);
+                scroll_copy_up(app, rows);
+            }
+            if parts.contains(&"-H") { app.copy_hide_position = true; }
         }
         "display-panes" | "displayp" => {
             app.mode = Mode::PaneChooser { opened_at: Instant::now() };
@@ -574,71 +554,17 @@ pub fn execute_command_string(app: &mut AppState, cmd: &str) -> io::Result<()> {
             };
         }
         "display-menu" | "menu" => {
-            let rest = parts[1..].join(" ");
-            let menu = parse_menu_definition(&rest, None, None);
-            if !menu.items.is_empty() {
-                app.mode = Mode::MenuMode { menu };
-            }
+            let argv = parse_command_line(cmd);
+            let argv: Vec<&str> = argv[1..].iter().map(String::as_str).collect();
+            crate::menu::open(app, crate::menu::parse_args(&argv));
         }
         "display-popup" | "popup" => {
-            // Parse -w width, -h height, -E close-on-exit flags
-            let mut width: u16 = 80;
-            let mut height: u16 = 24;
-            let close_on_exit = parts.iter().any(|p| *p == "-E");
-            if let Some(pos) = parts.iter().position(|p| *p == "-w") {
-                if let Some(v) = parts.get(pos + 1) { width = v.parse().unwrap_or(80); }
-            }
-            if let Some(pos) = parts.iter().position(|p| *p == "-h") {
-                if let Some(v) = parts.get(pos + 1) { height = v.parse().unwrap_or(24); }
-            }
-            // Collect command (non-flag args)
-            let cmd_parts: Vec<&str> = parts[1..].iter()
-                .filter(|a| !a.starts_with('-'))
-                .copied()
-                .collect();
-            // Skip width/height values
-            let rest = cmd_parts.join(" ");
-            
-            // Try PTY-based popup for interactive commands
-            let pty_result = if !rest.is_empty() {
-                Some(portable_pty::native_pty_system())
-                    .and_then(|pty_sys| {
-                        let pty_size = portable_pty::PtySize { rows: height.saturating_sub(2), cols: width.saturating_sub(2), pixel_width: 0, pixel_height: 0 };
-                        let pair = pty_sys.openpty(pty_size).ok()?;
-                        let mut cmd_builder = portable_pty::CommandBuilder::new(if cfg!(windows) { "pwsh" } else { "sh" });
-                        if let Ok(dir) = std::env::current_dir() { cmd_builder.cwd(dir); }
-                        if cfg!(windows) { cmd_builder.args(["-NoProfile", "-Command", &rest]); } else { cmd_builder.args(["-c", &rest]); }
-                        let child = pair.slave.spawn_command(cmd_builder).ok()?;
-                        // Close the slave handle immediately – required for ConPTY.
-                        drop(pair.slave);
-                        let term = std::sync::Arc::new(std::sync::Mutex::new(vt100::Parser::new(pty_size.rows, pty_size.cols, 0)));
-                        let term_reader = term.clone();
-                        if let Ok(mut reader) = pair.master.try_clone_reader() {
-                            std::thread::spawn(move || {
-                                let mut buf = [0u8; 8192];
-                                loop {
-                                    match std::io::Read::read(&mut reader, &mut buf) {
-                                        Ok(n) if n > 0 => { if let Ok(mut p) = term_reader.lock() { p.process(&buf[..n]); } }
-                                        _ => break,
-                                    }
-                                }
-                            });
-                        }
-                        let mut pty_writer = pair.master.take_writer().ok()?;
-                        crate::pane::conpty_preemptive_dsr_response(&mut *pty_writer);
-                        Some(PopupPty { master: pair.master, writer: pty_writer, child, term })
-                    })
-            } else { None };
-            
-            app.mode = Mode::PopupMode {
-                command: rest,
-                output: String::new(),
-                process: None,
-                width,
-                height,
-                close_on_exit,
-                popup_pty: pty_result,
-            };
+            crate::popup::open(app, crate::popup::parse_args(&parts[1..]));
+        }
+        "hide-popup" | "move-popup" | "resize-popup" => {
+            if let Err(e) = crate::popup::run(app, &parts) {
+                app.status_message = Some((e, Instant::now()));
+            }
         }
         "resize-pane" | "resizep" => {
             if parts.iter().any(|p| *p == "-Z") {
@@ -664,7 +590,8 @@ pub fn execute_command_string(app: &mut AppState, cmd: &str) -> io::Result<()> {
         }
         "break-pane" | "breakp" => {
             if let Some(port) = app.control_port {
-                let _ = send_control_to_port(port, "break-pane\n", &app.session_key);
+                // Forward the flags too: -f floats the pane instead
+                let _ = send_control_to_port(port, &format!("{}\n", cmd), &app.session_key);
             }
         }
         "respawn-pane" | "respawnp" => {
@@ -724,33 +651,65 @@ pub fn execute_command_string(app: &mut AppState, cmd: &str) -> io::Result<()> {
                 let _ = send_control_to_port(port, &format!("{}\n", cmd), &app.session_key);
             }
         }
-        "choose-tree" | "choose-window" => {
-            let tree = build_choose_tree(app);
-            let selected = tree.iter().position(|e| e.is_current_session && e.is_active_window && !e.is_session_header).unwrap_or(0);
-            app.mode = Mode::WindowChooser { selected, tree };
+        "choose-tree" | "choose-window" | "choose-session" => {
+            let args = parse_command_line(cmd);
+            let mut args: Vec<&str> = args[1..].iter().map(|s| s.as_str()).collect();
+            match parts[0] {
+                "choose-session" => args.insert(0, "-s"),
+                "choose-window" => args.insert(0, "-w"),
+                _ => {}
+            }
+            crate::choose_tree::open(app, &args);
         }
         "command-prompt" => {
-            // Support -I initial_text, -p prompt (ignored), -1 (ignored)
-            let initial = parts.windows(2).find(|w| w[0] == "-I").map(|w| w[1].to_string()).unwrap_or_default();
-            app.mode = Mode::CommandPrompt { input: initial.clone(), cursor: initial.len() };
+            let args = parse_command_line(cmd);
+            let args: Vec<&str> = args[1..].iter().map(|s| s.as_str()).collect();
+            crate::prompt::open(app, &args);
         }
         "paste-buffer" | "pasteb" => {
             paste_latest(app)?;
         }
-        "set-buffer" => {
-            if let Some(text) = parts.get(1) {
-                app.paste_buffers.insert(0, text.to_string());
-                if app.paste_buffers.len() > 10 { app.paste_buffers.pop(); }
+        "choose-buffer" | "chooseb" => {
+            crate::buffers::open_chooser(app, &parts[1..]);
+        }
+        "hint-mode" => {
+            crate::hints::open(app, &parts[1..]);
+        }
+        "capture-pane" | "capturep" => {
+            // Nothing to print to from a binding, so -p also saves a buffer
+            let opts = crate::capture::parse_args(&parts[1..]);
+            match crate::capture::capture_pane(app, &opts) {
+                Ok(text) => { crate::buffers::set_buffer(app, opts.buffer.as_deref(), &text, false); }
+                Err(e) => { app.status_message = Some((e, Instant::now())); }
             }
         }
-        "delete-buffer" => {
-            if !app.paste_buffers.is_empty() { app.paste_buffers.remove(0); }
+        "paste-from-clipboard" => {
+            crate::input::paste_clipboard_to_active(app)?;
+        }
+        "set-buffer" | "setb" | "delete-buffer" | "deleteb" => {
+            if let Some(port) = app.control_port {
+                // Forward the full command so -b/-n/-a/-w are parsed once
+                let _ = send_control_to_port(port, &format!("{}\n", cmd), &app.session_key);
+            }
         }
         "clear-history" => {
             if let Some(port) = app.control_port {
                 let _ = send_control_to_port(port, "clear-history\n", &app.session_key);
             }
         }
+        "switch-client" | "switchc" => {
+            let args = parse_command_line(cmd);
+            let flag = |f: &str| args.windows(2).find(|w| w[0] == f).map(|w| w[1].clone());
+            if let Some(table) = flag("-T") {
+                app.current_key_table = Some(table);
+            } else if let Some(target) = flag("-t") {
+                switch_client(app, &target);
+                crate::tree::resize_all_panes(app);
+            } else if let Some(port) = app.control_port {
+                // -n/-p/-l pick a session relative to the client's
+                let _ = send_control_to_port(port, &format!("{}\n", cmd), &app.session_key);
+            }
+        }
         "kill-session" => {
             if let Some(port) = app.control_port {
                 let _ = send_control_to_port(port, "kill-session\n", &app.session_key);
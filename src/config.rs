@@ -511,6 +511,21 @@ pub fn parse_option_value(app: &mut AppState, rest: &str, _is_global: bool) {
         "focus-events" => {
             app.focus_events = matches!(value, "on" | "true" | "1");
         }
+        "ambiguous-width" => {
+            if let Ok(n @ 1..=2) = value.parse::<u8>() {
+                app.ambiguous_width = n;
+                crate::pane::refresh_width_options(app);
+            }
+        }
+        "variation-selector-always-wide" => {
+            app.variation_selector_always_wide = matches!(value, "on" | "true" | "1");
+            crate::pane::refresh_width_options(app);
+        }
+        "wheel-scroll-lines" => {
+            if let Ok(n) = value.parse::<u16>() {
+                app.wheel_scroll_lines = n.max(1);
+            }
+        }
         "monitor-activity" => {
             app.monitor_activity = matches!(value, "on" | "true" | "1");
         }
@@ -520,6 +535,9 @@ pub fn parse_option_value(app: &mut AppState, rest: &str, _is_global: bool) {
         "remain-on-exit" => {
             app.remain_on_exit = matches!(value, "on" | "true" | "1");
         }
+        "remain-on-exit-format" => {
+            app.remain_on_exit_format = value.to_string();
+        }
         "destroy-unattached" => {
             app.destroy_unattached = matches!(value, "on" | "true" | "1");
         }
@@ -561,6 +579,16 @@ pub fn parse_option_value(app: &mut AppState, rest: &str, _is_global: bool) {
         "message-style" => { app.message_style = value.to_string(); }
         "message-command-style" => { app.message_command_style = value.to_string(); }
         "mode-style" => { app.mode_style = value.to_string(); }
+        "copy-mode-match-style" => { app.copy_mode_match_style = value.to_string(); }
+        "copy-mode-current-match-style" => { app.copy_mode_current_match_style = value.to_string(); }
+        "copy-mode-position-format" => { app.copy_mode_position_format = value.to_string(); }
+        "pane-scrollbars" => {
+            if matches!(value, "off" | "modal" | "on") {
+                app.pane_scrollbars = value.to_string();
+                crate::tree::resize_all_panes(app);
+            }
+        }
+        "pane-scrollbars-style" => { app.pane_scrollbars_style = value.to_string(); }
         "window-status-style" => { app.window_status_style = value.to_string(); }
         "window-status-current-style" => { app.window_status_current_style = value.to_string(); }
         "window-status-activity-style" => { app.window_status_activity_style = value.to_string(); }
@@ -570,7 +598,13 @@ pub fn parse_option_value(app: &mut AppState, rest: &str, _is_global: bool) {
         "status-right-style" => { app.status_right_style = value.to_string(); }
         "clock-mode-colour" | "clock-mode-style" => { app.environment.insert(key.to_string(), value.to_string()); }
         "pane-border-format" | "pane-border-status" => { app.environment.insert(key.to_string(), value.to_string()); }
-        "popup-style" | "popup-border-style" | "popup-border-lines" => { app.environment.insert(key.to_string(), value.to_string()); }
+        "popup-style" => { app.popup_style = value.to_string(); }
+        "popup-border-style" => { app.popup_border_style = value.to_string(); }
+        "popup-border-lines" => { app.popup_border_lines = value.to_string(); }
+        "menu-style" => { app.menu_style = value.to_string(); }
+        "menu-selected-style" => { app.menu_selected_style = value.to_string(); }
+        "menu-border-style" => { app.menu_border_style = value.to_string(); }
+        "menu-border-lines" => { app.menu_border_lines = value.to_string(); }
         "window-style" | "window-active-style" => { app.environment.insert(key.to_string(), value.to_string()); }
         "wrap-search" => { app.environment.insert(key.to_string(), value.to_string()); }
         "lock-after-time" | "lock-command" => { app.environment.insert(key.to_string(), value.to_string()); }
@@ -590,6 +624,20 @@ pub fn parse_option_value(app: &mut AppState, rest: &str, _is_global: bool) {
         "allow-passthrough" => { app.allow_passthrough = value.to_string(); }
         "copy-command" => { app.copy_command = value.to_string(); }
         "set-clipboard" => { app.set_clipboard = value.to_string(); }
+        "buffer-limit" => {
+            if let Ok(n) = value.parse::<usize>() { app.buffer_limit = n.max(1); }
+        }
+        "history-file" => {
+            app.history_file = value.to_string();
+            crate::prompt::load_history(app);
+        }
+        "prompt-history-limit" => {
+            if let Ok(n) = value.parse::<usize>() { app.prompt_history_limit = n; }
+        }
+        "allow-clipboard-read" => { app.allow_clipboard_read = matches!(value, "on" | "true" | "1"); }
+        "hint-alphabet" => { app.hint_alphabet = value.to_string(); }
+        "hint-patterns" => { crate::hints::set_pattern(app, value); }
+        "hint-style" => { app.hint_style = value.to_string(); }
         "env-shim" => {
             app.env_shim = matches!(value, "on" | "true" | "1");
         }
@@ -990,6 +1038,15 @@ pub fn parse_key_string(key: &str) -> Option<(KeyCode, KeyModifiers)> {
         }
     }
     
+    // A single printable character keeps its case ("N" is not "n"); with
+    // Ctrl, letters are case-insensitive like tmux.
+    let mut chars = key_part.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if !mods.contains(KeyModifiers::CONTROL) {
+            return Some((KeyCode::Char(c), mods));
+        }
+    }
+
     let keycode = match key_part.to_lowercase().as_str() {
         "a" => KeyCode::Char('a'),
         "b" => KeyCode::Char('b'),
@@ -1449,3 +1506,52 @@ fn parse_if_shell(app: &mut AppState, line: &str) {
         parse_config_line(app, cmd);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The binding a key event resolves to, the way the client matches
+    /// synced bindings: key name -> parse_key_string -> normalized tuple.
+    fn lookup(app: &AppState, table: &str, code: KeyCode, mods: KeyModifiers) -> Option<String> {
+        let want = normalize_key_for_binding((code, mods));
+        app.key_tables.get(table)?.iter()
+            .find(|b| parse_key_string(&format_key_binding(&b.key)).map(normalize_key_for_binding) == Some(want))
+            .map(|b| crate::commands::format_action(&b.action))
+    }
+
+    #[test]
+    fn single_character_keys_keep_their_case() {
+        assert_ne!(parse_key_string("V"), parse_key_string("v"));
+        assert_eq!(parse_key_string("N"), Some((KeyCode::Char('N'), KeyModifiers::NONE)));
+        // Ctrl letters stay case-insensitive
+        assert_eq!(parse_key_string("C-A"), parse_key_string("C-a"));
+
+        let mut app = AppState::new("t".to_string());
+        parse_config_line(&mut app, "bind -T copy-mode-vi V send -X select-line");
+        parse_config_line(&mut app, "bind -T copy-mode-vi v send -X begin-selection");
+        let vi = &app.key_tables["copy-mode-vi"];
+        assert_eq!(vi.len(), 2);
+        let upper = lookup(&app, "copy-mode-vi", KeyCode::Char('V'), KeyModifiers::SHIFT).unwrap();
+        let lower = lookup(&app, "copy-mode-vi", KeyCode::Char('v'), KeyModifiers::NONE).unwrap();
+        assert!(upper.contains("select-line"), "{upper}");
+        assert!(lower.contains("begin-selection"), "{lower}");
+    }
+
+    #[test]
+    fn root_and_prefix_bindings_still_resolve() {
+        let mut app = AppState::new("t".to_string());
+        parse_config_line(&mut app, "bind -n M-x kill-pane");
+        parse_config_line(&mut app, "bind -n F5 next-window");
+        parse_config_line(&mut app, "bind C-a last-window");
+        parse_config_line(&mut app, "bind | split-window -h");
+        parse_config_line(&mut app, "bind Space next-layout");
+
+        assert!(lookup(&app, "root", KeyCode::Char('x'), KeyModifiers::ALT).is_some());
+        assert!(lookup(&app, "root", KeyCode::F(5), KeyModifiers::NONE).is_some());
+        assert!(lookup(&app, "prefix", KeyCode::Char('a'), KeyModifiers::CONTROL).is_some());
+        assert!(lookup(&app, "prefix", KeyCode::Char('|'), KeyModifiers::SHIFT).is_some());
+        assert!(lookup(&app, "prefix", KeyCode::Char(' '), KeyModifiers::NONE).is_some());
+        assert!(lookup(&app, "root", KeyCode::Char('X'), KeyModifiers::ALT).is_none());
+    }
+}
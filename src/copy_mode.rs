@@ -38,6 +38,9 @@ pub fn enter_copy_mode(app: &mut AppState) {
     app.copy_register_pending = false;
     app.copy_register = None;
     app.copy_count = None;
+    app.copy_search_matches.clear();
+    app.copy_search_idx = 0;
+    app.copy_hide_position = false;
     // Mark the active pane as being in copy mode (pane-local state).
     save_copy_state_to_pane(app);
 }
@@ -65,7 +68,7 @@ pub fn exit_copy_mode(app: &mut AppState) {
 /// Called whenever we are about to switch away from a pane that is in copy mode.
 pub fn save_copy_state_to_pane(app: &mut AppState) {
     let (in_search, search_input, search_input_forward) = match &app.mode {
-        Mode::CopySearch { input, forward } => (true, input.clone(), *forward),
+        Mode::CopySearch { input, forward, .. } => (true, input.clone(), *forward),
         _ => (false, String::new(), true),
     };
     let state = CopyModeState {
@@ -79,6 +82,9 @@ pub fn save_copy_state_to_pane(app: &mut AppState) {
         search_matches: app.copy_search_matches.clone(),
         search_idx: app.copy_search_idx,
         search_forward: app.copy_search_forward,
+        search_regex: app.copy_search_regex,
+        search_incremental: app.copy_search_incremental,
+        search_origin: app.copy_search_origin,
         find_char_pending: app.copy_find_char_pending,
         text_object_pending: app.copy_text_object_pending,
         register_pending: app.copy_register_pending,
@@ -111,12 +117,15 @@ pub fn restore_copy_state_from_pane(app: &mut AppState) {
         app.copy_search_matches = s.search_matches;
         app.copy_search_idx = s.search_idx;
         app.copy_search_forward = s.search_forward;
+        app.copy_search_regex = s.search_regex;
+        app.copy_search_incremental = s.search_incremental;
+        app.copy_search_origin = s.search_origin;
         app.copy_find_char_pending = s.find_char_pending;
         app.copy_text_object_pending = s.text_object_pending;
         app.copy_register_pending = s.register_pending;
         app.copy_register = s.register;
         if s.in_search {
-            app.mode = Mode::CopySearch { input: s.search_input, forward: s.search_input_forward };
+            app.mode = Mode::CopySearch { input: s.search_input, forward: s.search_input_forward, search: None };
         } else {
             app.mode = Mode::CopyMode;
         }
@@ -190,8 +199,84 @@ pub fn copy_to_system_clipboard(text: &str) {
     }
 }
 
+/// A clipboard tool found on `PATH`: the argv that reads text from stdin into
+/// the clipboard and the argv that writes the clipboard to stdout.
+#[cfg(not(windows))]
+struct ClipboardBackend {
+    copy: Vec<String>,
+    paste: Vec<String>,
+}
+
+/// The clipboard tool for this session, picked once from the environment.
 #[cfg(not(windows))]
-pub fn copy_to_system_clipboard(_text: &str) {}
+fn clipboard_backend() -> Option<&'static ClipboardBackend> {
+    static BACKEND: std::sync::OnceLock<Option<ClipboardBackend>> = std::sync::OnceLock::new();
+    BACKEND.get_or_init(|| {
+        let has_env = |var: &str| std::env::var_os(var).map_or(false, |v| !v.is_empty());
+        choose_clipboard_backend(has_env, find_in_path)
+    }).as_ref()
+}
+
+/// `wl-copy`/`wl-paste` under Wayland, then `xclip` or `xsel` under X11.
+#[cfg(not(windows))]
+fn choose_clipboard_backend(has_env: impl Fn(&str) -> bool, found: impl Fn(&str) -> bool) -> Option<ClipboardBackend> {
+    let argv = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    if has_env("WAYLAND_DISPLAY") && found("wl-copy") && found("wl-paste") {
+        return Some(ClipboardBackend { copy: argv(&["wl-copy"]), paste: argv(&["wl-paste", "--no-newline"]) });
+    }
+    if has_env("DISPLAY") {
+        if found("xclip") {
+            return Some(ClipboardBackend {
+                copy: argv(&["xclip", "-selection", "clipboard", "-in"]),
+                paste: argv(&["xclip", "-selection", "clipboard", "-out"]),
+            });
+        }
+        if found("xsel") {
+            return Some(ClipboardBackend {
+                copy: argv(&["xsel", "--clipboard", "--input"]),
+                paste: argv(&["xsel", "--clipboard", "--output"]),
+            });
+        }
+    }
+    None
+}
+
+#[cfg(not(windows))]
+fn find_in_path(program: &str) -> bool {
+    std::env::var_os("PATH").map_or(false, |paths| find_in_paths(&paths, program))
+}
+
+#[cfg(not(windows))]
+fn find_in_paths(paths: &std::ffi::OsStr, program: &str) -> bool {
+    std::env::split_paths(paths).any(|dir| dir.join(program).is_file())
+}
+
+/// Copy text to the desktop clipboard through the detected tool.  Callers
+/// that honour `copy-command` skip this when it is set.
+#[cfg(not(windows))]
+pub fn copy_to_system_clipboard(text: &str) {
+    if let Some(backend) = clipboard_backend() {
+        copy_with_backend(backend, text);
+    }
+}
+
+#[cfg(not(windows))]
+fn copy_with_backend(backend: &ClipboardBackend, text: &str) {
+    if let Ok(mut child) = std::process::Command::new(&backend.copy[0])
+        .args(&backend.copy[1..])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+    {
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(text.as_bytes());
+        }
+        // wl-copy and xclip fork a daemon to own the selection; the parent
+        // exits as soon as stdin is closed.
+        let _ = child.wait();
+    }
+}
 
 /// Read text from the Windows system clipboard.
 #[cfg(windows)]
@@ -231,8 +316,23 @@ pub fn read_from_system_clipboard() -> Option<String> {
     None
 }
 
+/// Read text from the desktop clipboard through the detected tool.
 #[cfg(not(windows))]
-pub fn read_from_system_clipboard() -> Option<String> { None }
+pub fn read_from_system_clipboard() -> Option<String> {
+    read_with_backend(clipboard_backend()?)
+}
+
+#[cfg(not(windows))]
+fn read_with_backend(backend: &ClipboardBackend) -> Option<String> {
+    let out = std::process::Command::new(&backend.paste[0])
+        .args(&backend.paste[1..])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !out.status.success() { return None; }
+    Some(String::from_utf8_lossy(&out.stdout).into_owned())
+}
 
 pub fn current_prompt_pos(app: &mut AppState) -> Option<(u16,u16)> {
     let win = &mut app.windows[app.active_idx];
@@ -487,14 +587,15 @@ pub fn scroll_to_bottom(app: &mut AppState) {
     app.copy_scroll_offset = 0;
 }
 
-pub fn yank_selection(app: &mut AppState) -> io::Result<()> {
-    let (anchor, pos) = match (app.copy_anchor, app.copy_pos) { (Some(a), Some(p)) => (a,p), _ => return Ok(()) };
+/// Read the text of the current selection without storing it anywhere.
+pub fn selection_text(app: &mut AppState) -> Option<String> {
+    let (anchor, pos) = match (app.copy_anchor, app.copy_pos) { (Some(a), Some(p)) => (a,p), _ => return None };
     let sel_mode = app.copy_selection_mode;
     let anchor_scroll = app.copy_anchor_scroll_offset;
     let current_scroll = app.copy_scroll_offset;
     let win = &mut app.windows[app.active_idx];
-    let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return Ok(()) };
-    let mut parser = match p.term.lock() { Ok(g) => g, Err(_) => return Ok(()) };
+    let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return None };
+    let mut parser = match p.term.lock() { Ok(g) => g, Err(_) => return None };
     let rows = p.last_rows;
     let cols = p.last_cols;
 
@@ -577,27 +678,121 @@ pub fn yank_selection(app: &mut AppState) -> io::Result<()> {
     }
     // Restore original scrollback
     parser.screen_mut().set_scrollback(current_scroll);
+    Some(text)
+}
+
+/// Copy the selection into a new paste buffer (and the system clipboard,
+/// OSC 52 and copy-command).
+pub fn yank_selection(app: &mut AppState) -> io::Result<()> {
+    let text = match selection_text(app) { Some(t) => t, None => return Ok(()) };
+    store_yank(app, text);
+    Ok(())
+}
+
+/// Store yanked text: named register, paste buffer, system clipboard,
+/// OSC 52 and copy-command.
+pub fn store_yank(app: &mut AppState, text: String) {
     // Store in named register if one was selected
     if let Some(reg) = app.copy_register.take() {
         app.named_registers.insert(reg, text.clone());
     }
-    app.paste_buffers.insert(0, text.clone());
-    if app.paste_buffers.len() > 10 { app.paste_buffers.pop(); }
-    copy_to_system_clipboard(&text);
+    crate::buffers::add_automatic(app, text.clone());
+    export_yank(app, text);
+}
+
+/// `append-selection`: extend the most recent buffer with the yanked text.
+pub fn append_yank(app: &mut AppState, text: &str) {
+    let full = crate::buffers::set_buffer(app, None, text, true);
+    export_yank(app, full);
+}
+
+/// Hand yanked text to the client (OSC 52) and the system clipboard.
+fn export_yank(app: &mut AppState, text: String) {
+    send_to_clipboard(app, &text);
     // Stage text for OSC 52 delivery to the client (works over SSH)
     if app.set_clipboard != "off" {
-        app.clipboard_osc52 = Some(text.clone());
+        app.clipboard_osc52 = Some(text);
+    }
+}
+
+/// Put text on the system clipboard.  A configured `copy-command` replaces
+/// the detected clipboard tool on Linux; on Windows it runs in addition to
+/// the Win32 clipboard.
+pub fn send_to_clipboard(app: &AppState, text: &str) {
+    if cfg!(windows) || app.copy_command.is_empty() {
+        copy_to_system_clipboard(text);
     }
-    // Pipe to copy-command if configured
     if !app.copy_command.is_empty() {
-        let cmd = app.copy_command.clone();
-        pipe_text_to_command(&text, &cmd);
+        pipe_text_to_command(text, &app.copy_command);
     }
-    Ok(())
+}
+
+/// Drain OSC 52 requests made by applications in panes and popups, following
+/// tmux's `set-clipboard`: with `on` a copy becomes a paste buffer, with
+/// `external` it goes to the system clipboard the same way a copy-mode yank
+/// does, and either way it is forwarded to the client terminal.  Read
+/// requests (`?`) are answered from the top buffer only when
+/// `allow-clipboard-read` is on.  Returns true when a copy was taken.
+pub fn drain_pane_clipboard(app: &mut AppState) -> bool {
+    fn drain(term: &std::sync::Mutex<crate::pane::PaneParser>, writer: &mut dyn Write, reply: Option<&str>, out: &mut Vec<String>) {
+        let reads = match term.lock() {
+            Ok(mut parser) => {
+                let cb = parser.callbacks_mut();
+                out.append(&mut cb.clipboard_copies);
+                std::mem::take(&mut cb.clipboard_reads)
+            }
+            Err(_) => return,
+        };
+        if let Some(text) = reply {
+            for ty in reads {
+                let _ = write!(writer, "\x1b]52;{};{}\x07",
+                    String::from_utf8_lossy(&ty), crate::util::base64_encode(text));
+            }
+            let _ = writer.flush();
+        }
+    }
+    fn walk(node: &mut crate::types::Node, reply: Option<&str>, out: &mut Vec<String>) {
+        match node {
+            crate::types::Node::Leaf(p) => drain(&p.term, &mut p.writer, reply, out),
+            crate::types::Node::Split { children, .. } => {
+                for c in children { walk(c, reply, out); }
+            }
+        }
+    }
+    fn drain_popup(popup: &mut crate::popup::Popup, reply: Option<&str>, out: &mut Vec<String>) {
+        if let Some(pty) = popup.popup_pty.as_mut() {
+            drain(&pty.term, &mut pty.writer, reply, out);
+        }
+    }
+    let reply = if app.allow_clipboard_read && app.set_clipboard != "off" {
+        crate::buffers::get_data(app, None)
+    } else {
+        None
+    };
+    let mut copies = Vec::new();
+    for win in app.windows.iter_mut() {
+        walk(&mut win.root, reply.as_deref(), &mut copies);
+        for popup in win.floating.iter_mut() {
+            drain_popup(popup, reply.as_deref(), &mut copies);
+        }
+    }
+    if let Mode::PopupMode { ref mut popup } = app.mode {
+        drain_popup(popup, reply.as_deref(), &mut copies);
+    }
+    let copied = !copies.is_empty() && app.set_clipboard != "off";
+    for text in copies {
+        match app.set_clipboard.as_str() {
+            "on" => crate::buffers::add_automatic(app, text.clone()),
+            "external" => send_to_clipboard(app, &text),
+            _ => continue,
+        }
+        app.clipboard_osc52 = Some(text);
+    }
+    copied
 }
 
 /// Pipe text to a shell command's stdin.
-fn pipe_text_to_command(text: &str, cmd: &str) {
+pub fn pipe_text_to_command(text: &str, cmd: &str) {
     let shell = if cfg!(windows) { "pwsh" } else { "sh" };
     let args: Vec<&str> = if cfg!(windows) {
         vec!["-NoProfile", "-Command", cmd]
@@ -629,97 +824,312 @@ pub fn paste_latest(app: &mut AppState) -> io::Result<()> {
     }
     if let Some(buf) = app.paste_buffers.first() {
         let win = &mut app.windows[app.active_idx];
-        if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) { let _ = write!(p.writer, "{}", buf); }
+        if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) { let _ = write!(p.writer, "{}", buf.data); }
     }
     Ok(())
 }
 
 pub fn capture_active_pane(app: &mut AppState) -> io::Result<()> {
-    let win = &mut app.windows[app.active_idx];
-    let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return Ok(()) };
-    let parser = match p.term.lock() { Ok(g) => g, Err(_) => return Ok(()) };
-    let screen = parser.screen();
-    let mut text = String::new();
-    for r in 0..p.last_rows {
-        let mut row = String::new();
-        for c in 0..p.last_cols { if let Some(cell) = screen.cell(r, c) { row.push_str(&cell.contents().to_string()); } else { row.push(' '); } }
-        text.push_str(row.trim_end());
-        text.push('\n');
-    }
-    app.paste_buffers.insert(0, text);
-    if app.paste_buffers.len() > 10 { app.paste_buffers.pop(); }
+    if let Ok(text) = crate::capture::capture_pane(app, &crate::capture::CaptureOptions::default()) {
+        crate::buffers::add_automatic(app, text);
+    }
     Ok(())
 }
 
-pub fn capture_active_pane_text(app: &mut AppState) -> io::Result<Option<String>> {
-    let win = &mut app.windows[app.active_idx];
-    let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return Ok(None) };
-    let parser = match p.term.lock() { Ok(g) => g, Err(_) => return Ok(None) };
-    let screen = parser.screen();
-    let mut text = String::new();
-    for r in 0..p.last_rows {
-        let mut row = String::new();
-        for c in 0..p.last_cols { if let Some(cell) = screen.cell(r, c) { row.push_str(&cell.contents().to_string()); } else { row.push(' '); } }
-        text.push_str(row.trim_end());
-        text.push('\n');
+pub fn save_latest_buffer(app: &mut AppState, file: &str) -> io::Result<()> {
+    if let Some(buf) = app.paste_buffers.first() { std::fs::write(file, &buf.data)?; }
+    Ok(())
+}
+
+/// Compile a copy-mode search query.  Regex queries that fail to parse fall
+/// back to a literal match.  Smart-case (tmux parity): the search ignores
+/// case unless the query contains an uppercase letter.
+fn build_search_regex(query: &str, regex: bool) -> Option<regex::Regex> {
+    let ignore_case = !query.chars().any(|c| c.is_uppercase());
+    let build = |pat: &str| regex::RegexBuilder::new(pat).case_insensitive(ignore_case).build().ok();
+    if regex {
+        build(query).or_else(|| build(&regex::escape(query)))
+    } else {
+        build(&regex::escape(query))
     }
-    Ok(Some(text))
 }
 
-pub fn save_latest_buffer(app: &mut AppState, file: &str) -> io::Result<()> {
-    if let Some(buf) = app.paste_buffers.first() { std::fs::write(file, buf)?; }
-    Ok(())
+/// Read history line `line` (0 = oldest scrollback line) into `row_text`,
+/// filling `byte_col` with the starting column of the cell each byte
+/// belongs to, plus one trailing entry for the end of the row.  Blank cells
+/// read as spaces so regex matches map straight back to columns.
+pub fn history_row_text(screen: &vt100::Screen, line: usize, cols: u16, row_text: &mut String, byte_col: &mut Vec<u16>) {
+    row_text.clear();
+    byte_col.clear();
+    let mut c = 0u16;
+    while c < cols {
+        let (text, width) = match screen.history_cell(line, c) {
+            Some(cell) if cell.is_wide_continuation() => { c += 1; continue; }
+            Some(cell) => (cell.contents(), if cell.is_wide() { 2 } else { 1 }),
+            None => ("", 1),
+        };
+        let text = if text.is_empty() { " " } else { text };
+        for _ in 0..text.len() { byte_col.push(c); }
+        row_text.push_str(text);
+        c += width;
+    }
+    byte_col.push(c);
 }
 
-/// Search the active pane's screen content for a query string.
-/// Populates `app.copy_search_matches` with (row, col_start, col_end) tuples.
-/// If forward is true, sorts matches top-to-bottom; otherwise bottom-to-top.
+/// Search the active pane's whole history (scrollback plus screen).
+/// Populates `app.copy_search_matches` with (absolute line, col_start,
+/// col_end) tuples in document order and selects the first match after
+/// (forward) or before (backward) the copy cursor.
 pub fn search_copy_mode(app: &mut AppState, query: &str, forward: bool) {
     app.copy_search_matches.clear();
     app.copy_search_idx = 0;
     if query.is_empty() { return; }
+    let re = match build_search_regex(query, app.copy_search_regex) { Some(r) => r, None => return };
+    let cursor = get_copy_pos(app);
 
     let win = &mut app.windows[app.active_idx];
     let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return };
     let parser = match p.term.lock() { Ok(g) => g, Err(_) => return };
     let screen = parser.screen();
-    let query_lower = query.to_lowercase();
-    let qlen = query_lower.len() as u16;
-
-    // Scan all visible rows
-    for r in 0..p.last_rows {
-        // Build the row text
-        let mut row_text = String::with_capacity(p.last_cols as usize);
-        for c in 0..p.last_cols {
-            if let Some(cell) = screen.cell(r, c) {
-                let t = cell.contents();
-                if t.is_empty() { row_text.push(' '); } else { row_text.push_str(t); }
-            } else {
-                row_text.push(' ');
+    let hist = screen.history_len();
+    let dropped = screen.history_dropped();
+    let cols = p.last_cols;
+
+    let mut row_text = String::with_capacity(cols as usize);
+    let mut byte_col: Vec<u16> = Vec::with_capacity(cols as usize + 1);
+    for line in 0..hist + p.last_rows as usize {
+        history_row_text(screen, line, cols, &mut row_text, &mut byte_col);
+        for m in re.find_iter(&row_text) {
+            if m.start() == m.end() { continue; }
+            app.copy_search_matches.push((line + dropped, byte_col[m.start()], byte_col[m.end()]));
+        }
+    }
+
+    // Pick the starting match relative to the cursor's history position.
+    let scroll = screen.scrollback();
+    let cur = cursor.map(|(r, c)| ((hist + r as usize).saturating_sub(scroll) + dropped, c));
+    let n = app.copy_search_matches.len();
+    if n == 0 { return; }
+    app.copy_search_idx = match cur {
+        Some(cur) if forward => app.copy_search_matches.iter()
+            .position(|&(l, c, _)| (l, c) > cur)
+            .unwrap_or(0),
+        Some(cur) => app.copy_search_matches.iter()
+            .rposition(|&(l, c, _)| (l, c) < cur)
+            .unwrap_or(n - 1),
+        None => if forward { 0 } else { n - 1 },
+    };
+}
+
+/// Scroll the active pane so the current search match is visible and move
+/// the copy cursor onto it.
+pub fn jump_to_search_match(app: &mut AppState) {
+    let (abs, col, _) = match app.copy_search_matches.get(app.copy_search_idx) { Some(m) => *m, None => return };
+    let Some(line) = abs.checked_sub(active_history_dropped(app)) else { return };
+    jump_to_history_pos(app, line, col);
+}
+
+/// Lines the active pane has dropped from the top of its history.
+fn active_history_dropped(app: &AppState) -> usize {
+    let Some(win) = app.windows.get(app.active_idx) else { return 0 };
+    let Some(p) = active_pane(&win.root, &win.active_path) else { return 0 };
+    p.term.lock().map(|g| g.screen().history_dropped()).unwrap_or(0)
+}
+
+/// Forget matches on lines that have been trimmed from the history.
+fn forget_dropped_matches(app: &mut AppState) {
+    let dropped = active_history_dropped(app);
+    let gone = app.copy_search_matches.partition_point(|m| m.0 < dropped);
+    if gone == 0 { return; }
+    app.copy_search_matches.drain(..gone);
+    app.copy_search_idx = app.copy_search_idx.saturating_sub(gone);
+}
+
+/// Move the copy cursor to a history position (line 0 = oldest scrollback
+/// line), centring it vertically if it is off-screen.
+pub fn jump_to_history_pos(app: &mut AppState, line: usize, col: u16) {
+    let win = &mut app.windows[app.active_idx];
+    let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return };
+    let mut parser = match p.term.lock() { Ok(g) => g, Err(_) => return };
+    let rows = p.last_rows as usize;
+    let hist = parser.screen().history_len();
+    let mut scroll = parser.screen().scrollback();
+    let top = hist.saturating_sub(scroll);
+    if line < top || line >= top + rows {
+        // Centre the match vertically when it is off-screen
+        scroll = (hist + rows / 2).saturating_sub(line).min(hist);
+        parser.screen_mut().set_scrollback(scroll);
+        scroll = parser.screen().scrollback();
+    }
+    let row = (line + scroll).saturating_sub(hist).min(rows.saturating_sub(1));
+    app.copy_scroll_offset = scroll;
+    app.copy_pos = Some((row as u16, col));
+}
+
+/// Run a copy-mode search for `query` and jump to the first match.
+pub fn run_copy_search(app: &mut AppState, query: &str, forward: bool) {
+    app.copy_search_query = query.to_string();
+    app.copy_search_forward = forward;
+    search_copy_mode(app, query, forward);
+    jump_to_search_match(app);
+}
+
+/// Open the copy-mode search prompt.  The cursor and scroll position are
+/// remembered so Escape can return to them.
+pub fn begin_copy_search(app: &mut AppState, forward: bool, regex: bool, incremental: bool) {
+    app.copy_search_regex = regex;
+    app.copy_search_incremental = incremental;
+    app.copy_search_origin = Some((app.copy_scroll_offset, app.copy_pos));
+    app.prompt_history_idx = app.prompt_history.get("search").map_or(0, |h| h.len());
+    app.mode = Mode::CopySearch { input: String::new(), forward, search: None };
+}
+
+/// Scroll back to the position saved by `begin_copy_search`.
+fn restore_search_origin(app: &mut AppState) {
+    let (scroll, pos) = match app.copy_search_origin { Some(o) => o, None => return };
+    {
+        let win = &mut app.windows[app.active_idx];
+        if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) {
+            if let Ok(mut parser) = p.term.lock() {
+                parser.screen_mut().set_scrollback(scroll);
             }
         }
-        // Case-insensitive search
-        let row_lower = row_text.to_lowercase();
-        let mut start = 0;
-        while let Some(pos) = row_lower[start..].find(&query_lower) {
-            let col_start = (start + pos) as u16;
-            let col_end = col_start + qlen;
-            app.copy_search_matches.push((r, col_start, col_end));
-            start += pos + 1;
+    }
+    app.copy_scroll_offset = scroll;
+    app.copy_pos = pos;
+}
+
+/// Re-run an incremental search after the prompt text changed.  Each
+/// keystroke searches again from the original position, so deleting
+/// characters walks back to earlier matches like tmux.
+pub fn update_incremental_search(app: &mut AppState) {
+    if !app.copy_search_incremental { return; }
+    let (query, forward) = match &app.mode {
+        Mode::CopySearch { input, forward, .. } => (input.clone(), *forward),
+        _ => return,
+    };
+    restore_search_origin(app);
+    if query.is_empty() {
+        app.copy_search_matches.clear();
+        app.copy_search_idx = 0;
+        return;
+    }
+    run_copy_search(app, &query, forward);
+}
+
+/// Submit the search prompt and return to copy mode.
+pub fn submit_copy_search(app: &mut AppState) {
+    if let Mode::CopySearch { input, forward, .. } = &app.mode {
+        let (query, forward) = (input.clone(), *forward);
+        crate::prompt::add_history(app, "search", &query);
+        app.prompt_history_idx = app.prompt_history.get("search").map_or(0, |h| h.len());
+        // Incremental searches have already jumped to the match
+        if !app.copy_search_incremental {
+            run_copy_search(app, &query, forward);
+        }
+    }
+    app.copy_search_origin = None;
+    app.mode = Mode::CopyMode;
+}
+
+/// Cancel the search prompt, returning to where the search started.
+pub fn cancel_copy_search(app: &mut AppState) {
+    if app.copy_search_incremental {
+        restore_search_origin(app);
+        app.copy_search_matches.clear();
+        app.copy_search_idx = 0;
+    }
+    app.copy_search_origin = None;
+    app.mode = Mode::CopyMode;
+}
+
+/// Handle a key in the search prompt.  Up and Down step through the
+/// search history and C-r searches it, like the command prompt.
+pub fn search_prompt_key(app: &mut AppState, key: crossterm::event::KeyEvent) {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let Mode::CopySearch { input, search, .. } = &mut app.mode else { return };
+    let history = app.prompt_history.get("search").map_or(&[][..], |h| h.as_slice());
+    if crate::prompt::search_key(history, search, input, key) {
+        update_incremental_search(app);
+        return;
+    }
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => cancel_copy_search(app),
+        KeyCode::Char('c' | 'g') if ctrl => cancel_copy_search(app),
+        KeyCode::Enter => submit_copy_search(app),
+        KeyCode::Char('r') if ctrl => *search = Some(crate::prompt::start_search(input)),
+        KeyCode::Up | KeyCode::Down => {
+            let Some(text) = crate::prompt::step_history(app, "search", key.code == KeyCode::Up) else { return };
+            if let Mode::CopySearch { input, .. } = &mut app.mode { *input = text; }
+            update_incremental_search(app);
+        }
+        KeyCode::Backspace => {
+            input.pop();
+            update_incremental_search(app);
         }
+        KeyCode::Char(c) if !ctrl => {
+            input.push(c);
+            update_incremental_search(app);
+        }
+        _ => {}
+    }
+}
+
+/// The search prompt as shown: `/` or `?` and the input, or the C-r label
+/// while searching the history.
+pub fn search_prompt_text(input: &str, forward: bool, search: Option<&crate::types::HistorySearch>) -> String {
+    match search {
+        Some(s) => format!("{}{}", crate::prompt::search_label(s, input), input),
+        None => format!("{}{}", if forward { "/" } else { "?" }, input),
+    }
+}
+
+/// Search matches visible in the active pane as (row, col_start, col_end,
+/// current), clipped to the screen.  Used to highlight matches.
+pub fn visible_search_matches(app: &AppState) -> Vec<(u16, u16, u16, bool)> {
+    let mut out = Vec::new();
+    if app.copy_search_matches.is_empty() { return out; }
+    let win = match app.windows.get(app.active_idx) { Some(w) => w, None => return out };
+    let p = match active_pane(&win.root, &win.active_path) { Some(p) => p, None => return out };
+    let (hist, scroll, dropped) = match p.term.lock() {
+        Ok(parser) => (parser.screen().history_len(), parser.screen().scrollback(), parser.screen().history_dropped()),
+        Err(_) => return out,
+    };
+    let top = hist.saturating_sub(scroll) + dropped;
+    let rows = p.last_rows as usize;
+    let first = app.copy_search_matches.partition_point(|m| m.0 < top);
+    for (i, &(line, start, end)) in app.copy_search_matches.iter().enumerate().skip(first) {
+        if line >= top + rows { break; }
+        out.push(((line - top) as u16, start, end, i == app.copy_search_idx));
     }
+    out
+}
 
-    if !forward {
-        app.copy_search_matches.reverse();
+/// Step to the next match in the search direction, wrapping around.
+fn step_search_match(app: &mut AppState, forward: bool) {
+    forget_dropped_matches(app);
+    if app.copy_search_matches.is_empty() && !app.copy_search_query.is_empty() {
+        // Matches are dropped when copy mode is re-entered; search again
+        let query = app.copy_search_query.clone();
+        search_copy_mode(app, &query, forward);
+        jump_to_search_match(app);
+        return;
     }
+    let n = app.copy_search_matches.len();
+    if n == 0 { return; }
+    app.copy_search_idx = if forward {
+        (app.copy_search_idx + 1) % n
+    } else {
+        (app.copy_search_idx + n - 1) % n
+    };
+    jump_to_search_match(app);
 }
 
-/// Jump to the next search match in copy mode.
+/// Jump to the next search match in copy mode (n: same direction as the search).
 pub fn search_next(app: &mut AppState) {
-    if app.copy_search_matches.is_empty() { return; }
-    app.copy_search_idx = (app.copy_search_idx + 1) % app.copy_search_matches.len();
-    let (r, c, _) = app.copy_search_matches[app.copy_search_idx];
-    app.copy_pos = Some((r, c));
+    let fwd = app.copy_search_forward;
+    step_search_match(app, fwd);
 }
 
 /// Move by WORD (whitespace-delimited) forward — W key
@@ -822,6 +1232,28 @@ pub fn move_to_screen_bottom(app: &mut AppState) {
     app.copy_pos = Some((rows.saturating_sub(1), 0));
 }
 
+/// Run a find-char motion (0 = f, 1 = F, 2 = t, 3 = T) and remember it for
+/// jump-again / jump-reverse.
+pub fn find_char(app: &mut AppState, kind: u8, ch: char) {
+    app.copy_last_find = Some((kind, ch));
+    match kind {
+        0 => find_char_forward(app, ch),
+        1 => find_char_backward(app, ch),
+        2 => find_char_to_forward(app, ch),
+        3 => find_char_to_backward(app, ch),
+        _ => {}
+    }
+}
+
+/// Repeat the last find-char motion, optionally in the opposite direction.
+fn repeat_find_char(app: &mut AppState, reverse: bool) {
+    if let Some((kind, ch)) = app.copy_last_find {
+        find_char(app, if reverse { kind ^ 1 } else { kind }, ch);
+        // Reversing is a one-off; keep the original direction
+        app.copy_last_find = Some((kind, ch));
+    }
+}
+
 /// Find character forward on current line — f key
 pub fn find_char_forward(app: &mut AppState, ch: char) {
     let (r, c) = match get_copy_pos(app) { Some(p) => p, None => return };
@@ -869,191 +1301,26 @@ pub fn find_char_to_backward(app: &mut AppState, ch: char) {
 /// Yank from cursor to end of line — D key
 pub fn copy_end_of_line(app: &mut AppState) -> io::Result<()> {
     let (r, c) = match get_copy_pos(app) { Some(p) => p, None => return Ok(()) };
-    let win = &mut app.windows[app.active_idx];
-    let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return Ok(()) };
-    let parser = match p.term.lock() { Ok(g) => g, Err(_) => return Ok(()) };
-    let screen = parser.screen();
-    let cols = p.last_cols;
-    let mut text = String::new();
-    for col in c..cols {
-        if let Some(cell) = screen.cell(r, col) { text.push_str(&cell.contents().to_string()); } else { text.push(' '); }
-    }
-    let text = text.trim_end().to_string();
-    app.paste_buffers.insert(0, text.clone());
-    if app.paste_buffers.len() > 10 { app.paste_buffers.pop(); }
-    copy_to_system_clipboard(&text);
+    let text = {
+        let win = &mut app.windows[app.active_idx];
+        let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return Ok(()) };
+        let parser = match p.term.lock() { Ok(g) => g, Err(_) => return Ok(()) };
+        let screen = parser.screen();
+        let cols = p.last_cols;
+        let mut text = String::new();
+        for col in c..cols {
+            if let Some(cell) = screen.cell(r, col) { text.push_str(&cell.contents().to_string()); } else { text.push(' '); }
+        }
+        text.trim_end().to_string()
+    };
+    store_yank(app, text);
     Ok(())
 }
 
-/// Jump to the previous search match in copy mode.
+/// Jump to the previous search match in copy mode (N: opposite direction).
 pub fn search_prev(app: &mut AppState) {
-    if app.copy_search_matches.is_empty() { return; }
-    if app.copy_search_idx == 0 {
-        app.copy_search_idx = app.copy_search_matches.len() - 1;
-    } else {
-        app.copy_search_idx -= 1;
-    }
-    let (r, c, _) = app.copy_search_matches[app.copy_search_idx];
-    app.copy_pos = Some((r, c));
-}
-
-pub fn capture_active_pane_range(app: &mut AppState, s: Option<i32>, e: Option<i32>) -> io::Result<Option<String>> {
-    let win = &mut app.windows[app.active_idx];
-    let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return Ok(None) };
-    let parser = match p.term.lock() { Ok(g) => g, Err(_) => return Ok(None) };
-    let screen = parser.screen();
-    // Negative values are relative to the bottom of the visible area (tmux behavior):
-    // -S -3 means "start 3 lines from the bottom", -E -1 means "1 line from bottom"
-    let bottom = p.last_rows.saturating_sub(1) as i32;
-    let start = match s {
-        Some(v) if v < 0 => (bottom + v + 1).max(0) as u16,
-        Some(v) => (v as u16).min(p.last_rows.saturating_sub(1)),
-        None => 0,
-    };
-    let end = match e {
-        Some(v) if v < 0 => (bottom + v + 1).max(0) as u16,
-        Some(v) => (v as u16).min(p.last_rows.saturating_sub(1)),
-        None => p.last_rows.saturating_sub(1),
-    };
-    let mut text = String::new();
-    for r in start..=end {
-        let mut row = String::new();
-        for c in 0..p.last_cols { if let Some(cell) = screen.cell(r, c) { row.push_str(&cell.contents().to_string()); } else { row.push(' '); } }
-        text.push_str(row.trim_end());
-        text.push('\n');
-    }
-    Ok(Some(text))
-}
-
-/// Capture the active pane's screen content with ANSI escape sequences preserved.
-/// This is the `-e` flag for capture-pane.  Supports optional start/end range.
-pub fn capture_active_pane_styled(app: &mut AppState, s: Option<i32>, e: Option<i32>) -> io::Result<Option<String>> {
-    let win = &mut app.windows[app.active_idx];
-    let p = match active_pane_mut(&mut win.root, &win.active_path) { Some(p) => p, None => return Ok(None) };
-    let parser = match p.term.lock() { Ok(g) => g, Err(_) => return Ok(None) };
-    let screen = parser.screen();
-    let bottom = p.last_rows.saturating_sub(1) as i32;
-    let start_row = match s {
-        Some(v) if v < 0 => (bottom + v + 1).max(0) as u16,
-        Some(v) => (v as u16).min(p.last_rows.saturating_sub(1)),
-        None => 0,
-    };
-    let end_row = match e {
-        Some(v) if v < 0 => (bottom + v + 1).max(0) as u16,
-        Some(v) => (v as u16).min(p.last_rows.saturating_sub(1)),
-        None => p.last_rows.saturating_sub(1),
-    };
-    let mut text = String::new();
-    let mut prev_fg: Option<vt100::Color> = None;
-    let mut prev_bg: Option<vt100::Color> = None;
-    let mut prev_bold = false;
-    let mut prev_dim = false;
-    let mut prev_italic = false;
-    let mut prev_underline = false;
-    let mut prev_blink = false;
-    let mut prev_inverse = false;
-    let mut prev_hidden = false;
-
-    for r in start_row..=end_row {
-        // Build the row content, then trim trailing whitespace
-        let mut row_chars: Vec<String> = Vec::new();
-        let mut row_sgr: Vec<Option<String>> = Vec::new();
-        let mut any_style_active = false;
-        for c in 0..p.last_cols {
-            if let Some(cell) = screen.cell(r, c) {
-                let fg = cell.fgcolor();
-                let bg = cell.bgcolor();
-                let bold = cell.bold();
-                let dim = cell.dim();
-                let italic = cell.italic();
-                let underline = cell.underline();
-                let blink = cell.blink();
-                let inverse = cell.inverse();
-                let hidden = cell.hidden();
-
-                // Emit SGR if attributes changed
-                let style_changed = Some(fg) != prev_fg || Some(bg) != prev_bg
-                    || bold != prev_bold || dim != prev_dim
-                    || italic != prev_italic
-                    || underline != prev_underline || blink != prev_blink
-                    || inverse != prev_inverse || hidden != prev_hidden;
-
-                let sgr = if style_changed {
-                    let mut params = Vec::new();
-                    params.push("0".to_string()); // reset first
-                    if bold { params.push("1".to_string()); }
-                    if dim { params.push("2".to_string()); }
-                    if italic { params.push("3".to_string()); }
-                    if underline { params.push("4".to_string()); }
-                    if blink { params.push("5".to_string()); }
-                    if inverse { params.push("7".to_string()); }
-                    if hidden { params.push("8".to_string()); }
-                    // Foreground
-                    match fg {
-                        vt100::Color::Default => {}
-                        vt100::Color::Idx(n) => {
-                            if n < 8 { params.push(format!("{}", 30 + n)); }
-                            else if n < 16 { params.push(format!("{}", 90 + n - 8)); }
-                            else { params.push(format!("38;5;{}", n)); }
-                        }
-                        vt100::Color::Rgb(r, g, b) => { params.push(format!("38;2;{};{};{}", r, g, b)); }
-                    }
-                    // Background
-                    match bg {
-                        vt100::Color::Default => {}
-                        vt100::Color::Idx(n) => {
-                            if n < 8 { params.push(format!("{}", 40 + n)); }
-                            else if n < 16 { params.push(format!("{}", 100 + n - 8)); }
-                            else { params.push(format!("48;5;{}", n)); }
-                        }
-                        vt100::Color::Rgb(r, g, b) => { params.push(format!("48;2;{};{};{}", r, g, b)); }
-                    }
-                    prev_fg = Some(fg);
-                    prev_bg = Some(bg);
-                    prev_bold = bold;
-                    prev_dim = dim;
-                    prev_italic = italic;
-                    prev_underline = underline;
-                    prev_blink = blink;
-                    prev_inverse = inverse;
-                    prev_hidden = hidden;
-                    any_style_active = true;
-                    Some(format!("\x1b[{}m", params.join(";")))
-                } else {
-                    None
-                };
-                row_sgr.push(sgr);
-                row_chars.push(cell.contents().to_string());
-            } else {
-                row_sgr.push(None);
-                row_chars.push(" ".to_string());
-            }
-        }
-        // Find last non-whitespace cell to trim trailing spaces
-        let last_non_ws = row_chars.iter().rposition(|s| !s.is_empty() && s.trim() != "");
-        let trim_end = match last_non_ws {
-            Some(pos) => pos + 1,
-            None => 0,  // entirely empty row
-        };
-        for c in 0..trim_end {
-            if let Some(ref sgr) = row_sgr[c] { text.push_str(sgr); }
-            text.push_str(&row_chars[c]);
-        }
-        if any_style_active {
-            text.push_str("\x1b[0m");
-            prev_fg = None;
-            prev_bg = None;
-            prev_bold = false;
-            prev_dim = false;
-            prev_italic = false;
-            prev_underline = false;
-            prev_blink = false;
-            prev_inverse = false;
-            prev_hidden = false;
-        }
-        text.push('\n');
-    }
-    Ok(Some(text))
+    let fwd = app.copy_search_forward;
+    step_search_match(app, !fwd);
 }
 
 /// Move to next empty line (paragraph boundary) — } key
@@ -1105,32 +1372,44 @@ pub fn move_prev_paragraph(app: &mut AppState) {
     app.copy_pos = Some((row, 0));
 }
 
-/// Move to matching bracket — % key
-pub fn move_matching_bracket(app: &mut AppState) {
+/// Move to matching bracket — % key.  Off a bracket, first look along the
+/// line for one: forwards for next-matching-bracket, backwards for
+/// previous-matching-bracket, as tmux does.
+pub fn move_matching_bracket(app: &mut AppState, search_forward: bool) {
     let (r, c) = match get_copy_pos(app) { Some(p) => p, None => return };
     let win = match app.windows.get(app.active_idx) { Some(w) => w, None => return };
     let p = match active_pane(&win.root, &win.active_path) { Some(p) => p, None => return };
     let parser = match p.term.lock() { Ok(g) => g, Err(_) => return };
     let screen = parser.screen();
     
-    // Get char at cursor
-    let ch = screen.cell(r, c).map(|cell| {
-        let t = cell.contents();
-        t.chars().next().unwrap_or(' ')
-    }).unwrap_or(' ');
-    
-    let (open, close, forward) = match ch {
-        '(' => ('(', ')', true),
-        ')' => ('(', ')', false),
-        '[' => ('[', ']', true),
-        ']' => ('[', ']', false),
-        '{' => ('{', '}', true),
-        '}' => ('{', '}', false),
-        '<' => ('<', '>', true),
-        '>' => ('<', '>', false),
-        _ => return,
+    let bracket_at = |col: u16| {
+        let ch = screen.cell(r, col).map(|cell| {
+            let t = cell.contents();
+            t.chars().next().unwrap_or(' ')
+        }).unwrap_or(' ');
+        match ch {
+            '(' => Some(('(', ')', true)),
+            ')' => Some(('(', ')', false)),
+            '[' => Some(('[', ']', true)),
+            ']' => Some(('[', ']', false)),
+            '{' => Some(('{', '}', true)),
+            '}' => Some(('{', '}', false)),
+            '<' => Some(('<', '>', true)),
+            '>' => Some(('<', '>', false)),
+            _ => None,
+        }
     };
     
+    // Bracket at the cursor, else the nearest one on the line
+    let found = bracket_at(c).map(|b| (c, b)).or_else(|| {
+        if search_forward {
+            (c + 1..p.last_cols).find_map(|col| bracket_at(col).map(|b| (col, b)))
+        } else {
+            (0..c).rev().find_map(|col| bracket_at(col).map(|b| (col, b)))
+        }
+    });
+    let (c, (open, close, forward)) = match found { Some(f) => f, None => return };
+    
     let rows = p.last_rows;
     let cols = p.last_cols;
     let mut depth = 1i32;
@@ -1269,3 +1548,689 @@ pub fn select_a_word_big(app: &mut AppState) {
     }
     app.copy_selection_mode = crate::types::SelectionMode::Char;
 }
+
+/// Rows in the active pane (24 if there is none).
+fn active_rows(app: &AppState) -> u16 {
+    app.windows.get(app.active_idx)
+        .and_then(|w| active_pane(&w.root, &w.active_path))
+        .map(|p| p.last_rows).unwrap_or(24)
+}
+
+/// History position (line 0 = oldest scrollback line) of the copy cursor.
+fn cursor_history_pos(app: &mut AppState) -> Option<(usize, u16)> {
+    let (r, c) = get_copy_pos(app)?;
+    let win = &app.windows[app.active_idx];
+    let p = active_pane(&win.root, &win.active_path)?;
+    let parser = p.term.lock().ok()?;
+    let screen = parser.screen();
+    Some(((screen.history_len() + r as usize).saturating_sub(screen.scrollback()), c))
+}
+
+/// set-mark: remember the cursor position in the active pane.
+fn set_mark(app: &mut AppState) {
+    let Some((line, col)) = cursor_history_pos(app) else { return };
+    let win = &mut app.windows[app.active_idx];
+    if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) {
+        let dropped = p.term.lock().map(|g| g.screen().history_dropped()).unwrap_or(0);
+        p.copy_mark = Some((line + dropped, col));
+    }
+}
+
+/// jump-to-mark: swap the cursor and the mark, like tmux.  Nothing happens
+/// once the marked line has fallen out of the history.
+fn jump_to_mark(app: &mut AppState) {
+    let win = &app.windows[app.active_idx];
+    let Some(p) = active_pane(&win.root, &win.active_path) else { return };
+    let Some((abs, col)) = p.copy_mark else { return };
+    let dropped = p.term.lock().map(|g| g.screen().history_dropped()).unwrap_or(0);
+    let Some(line) = abs.checked_sub(dropped) else { return };
+    set_mark(app);
+    jump_to_history_pos(app, line, col);
+}
+
+/// Scroll so the cursor's line lands on screen row `target`, keeping the
+/// cursor on the same text (scroll-top / scroll-middle / scroll-bottom).
+fn scroll_cursor_to_row(app: &mut AppState, target: u16) {
+    let (r, c) = match get_copy_pos(app) { Some(p) => p, None => return };
+    let before = app.copy_scroll_offset;
+    if r > target {
+        scroll_copy_down(app, (r - target) as usize);
+        let moved = before - app.copy_scroll_offset;
+        app.copy_pos = Some((r - moved as u16, c));
+    } else {
+        scroll_copy_up(app, (target - r) as usize);
+        let moved = app.copy_scroll_offset - before;
+        app.copy_pos = Some((r + moved as u16, c));
+    }
+}
+
+/// Start a selection at the cursor in the given mode.
+fn begin_selection(app: &mut AppState, mode: crate::types::SelectionMode) {
+    if let Some((r, c)) = get_copy_pos(app) {
+        app.copy_anchor = Some((r, c));
+        app.copy_anchor_scroll_offset = app.copy_scroll_offset;
+        app.copy_pos = Some((r, c));
+        app.copy_selection_mode = mode;
+    }
+}
+
+/// Select the whole cursor line.
+fn select_cursor_line(app: &mut AppState) {
+    if let Some((r, _)) = get_copy_pos(app) {
+        let cols = app.windows.get(app.active_idx)
+            .and_then(|w| active_pane(&w.root, &w.active_path))
+            .map(|p| p.last_cols).unwrap_or(80);
+        app.copy_anchor = Some((r, 0));
+        app.copy_anchor_scroll_offset = app.copy_scroll_offset;
+        app.copy_selection_mode = crate::types::SelectionMode::Line;
+        app.copy_pos = Some((r, cols.saturating_sub(1)));
+    }
+}
+
+/// Select from the cursor to the end of its line.
+fn select_to_end_of_line(app: &mut AppState) {
+    begin_selection(app, crate::types::SelectionMode::Char);
+    move_to_line_end(app);
+}
+
+/// Single-step motions and scrolls, repeated for `send-keys -N`.  Returns
+/// false if `name` is not one of them.
+fn copy_motion(app: &mut AppState, name: &str) -> bool {
+    let rows = active_rows(app) as usize;
+    match name {
+        "cursor-up" => move_copy_cursor(app, 0, -1),
+        "cursor-down" => move_copy_cursor(app, 0, 1),
+        "cursor-left" => move_copy_cursor(app, -1, 0),
+        "cursor-right" => move_copy_cursor(app, 1, 0),
+        "next-word" => move_word_forward(app),
+        "previous-word" => move_word_backward(app),
+        "next-word-end" => move_word_end(app),
+        "next-space" => move_word_forward_big(app),
+        "previous-space" => move_word_backward_big(app),
+        "next-space-end" => move_word_end_big(app),
+        "next-paragraph" => move_next_paragraph(app),
+        "previous-paragraph" => move_prev_paragraph(app),
+        "next-matching-bracket" => move_matching_bracket(app, true),
+        "previous-matching-bracket" => move_matching_bracket(app, false),
+        "halfpage-up" => scroll_copy_up(app, (rows / 2).max(1)),
+        "halfpage-down" => scroll_copy_down(app, (rows / 2).max(1)),
+        "page-up" => scroll_copy_up(app, rows.max(1)),
+        "page-down" => scroll_copy_down(app, rows.max(1)),
+        "scroll-up" => scroll_copy_up(app, 1),
+        "scroll-down" => scroll_copy_down(app, 1),
+        "search-again" => search_next(app),
+        "search-reverse" => search_prev(app),
+        "jump-again" => repeat_find_char(app, false),
+        "jump-reverse" => repeat_find_char(app, true),
+        _ => return false,
+    }
+    true
+}
+
+/// Extract `command [argument]` from `send-keys -X` arguments, skipping
+/// flags and the values of -N and -t.
+pub fn send_keys_x_command(args: &[&str]) -> String {
+    let mut out: Vec<&str> = Vec::new();
+    let mut skip_next = false;
+    for a in args {
+        if skip_next { skip_next = false; continue; }
+        match *a {
+            "-N" | "-t" => skip_next = true,
+            s if s.starts_with('-') && out.is_empty() => {}
+            s => out.push(s),
+        }
+    }
+    out.join(" ")
+}
+
+/// Run a copy-mode command by name (`send-keys -X <command> [argument]`),
+/// entering copy mode first if needed.  Motions are repeated `count` times
+/// (`send-keys -N`).  This is the primary mechanism used by key tables and
+/// by plugins like tmux-yank.
+pub fn run_copy_mode_command(app: &mut AppState, cmd: &str, count: usize) {
+    let in_copy = matches!(app.mode, Mode::CopyMode | Mode::CopySearch { .. });
+    if !in_copy {
+        // Auto-enter copy mode for commands that require it
+        enter_copy_mode(app);
+    }
+    let (name, arg) = match cmd.split_once(' ') {
+        Some((n, a)) => (n, a.trim().trim_matches('"')),
+        None => (cmd, ""),
+    };
+    let count = count.max(1);
+
+    // page-down-and-cancel etc.: move, then leave copy mode at the bottom
+    if let Some(motion) = name.strip_suffix("-and-cancel") {
+        if matches!(motion, "page-down" | "halfpage-down" | "scroll-down" | "cursor-down") {
+            let at_bottom_row = get_copy_pos(app).map_or(false, |(r, _)| r + 1 >= active_rows(app));
+            for _ in 0..count { copy_motion(app, motion); }
+            if app.copy_scroll_offset == 0 && (motion != "cursor-down" || at_bottom_row) {
+                exit_copy_mode(app);
+            }
+            return;
+        }
+    }
+    if copy_motion(app, name) {
+        for _ in 1..count { copy_motion(app, name); }
+        return;
+    }
+
+    match name {
+        "cancel" => exit_copy_mode(app),
+        // ── Selection ──
+        "begin-selection" => begin_selection(app, crate::types::SelectionMode::Char),
+        "select-line" => begin_selection(app, crate::types::SelectionMode::Line),
+        "select-word" => {
+            move_word_backward(app);
+            begin_selection(app, crate::types::SelectionMode::Char);
+            move_word_end(app);
+        }
+        "stop-selection" | "clear-selection" => {
+            app.copy_anchor = None;
+            if name == "clear-selection" {
+                app.copy_selection_mode = crate::types::SelectionMode::Char;
+            }
+        }
+        "other-end" => {
+            if let (Some(a), Some(p)) = (app.copy_anchor, app.copy_pos) {
+                app.copy_anchor = Some(p);
+                app.copy_anchor_scroll_offset = app.copy_scroll_offset;
+                app.copy_pos = Some(a);
+            }
+        }
+        "rectangle-toggle" => {
+            app.copy_selection_mode = match app.copy_selection_mode {
+                crate::types::SelectionMode::Rect => crate::types::SelectionMode::Char,
+                _ => crate::types::SelectionMode::Rect,
+            };
+        }
+        "rectangle-on" => { app.copy_selection_mode = crate::types::SelectionMode::Rect; }
+        "rectangle-off" => { app.copy_selection_mode = crate::types::SelectionMode::Char; }
+        // ── Copying ──
+        // copy-{selection,line,end-of-line}, copy-pipe-{…} and pipe-{…},
+        // each with optional -no-clear / -and-cancel suffixes.
+        _ if name.starts_with("copy-") || name.starts_with("pipe") || name.starts_with("append-selection") => {
+            let (base, cancel, no_clear) = if let Some(b) = name.strip_suffix("-and-cancel") {
+                (b, true, false)
+            } else if let Some(b) = name.strip_suffix("-no-clear") {
+                (b, false, true)
+            } else {
+                (name, false, false)
+            };
+            let (base, piped) = match base.strip_prefix("copy-pipe") {
+                Some("") => ("copy-selection", true),
+                Some(rest) => (rest.trim_start_matches('-'), true),
+                None => (base, false),
+            };
+            match base {
+                "line" | "copy-line" => select_cursor_line(app),
+                "end-of-line" | "copy-end-of-line" => select_to_end_of_line(app),
+                _ => {}
+            }
+            let Some(text) = selection_text(app) else {
+                if cancel { exit_copy_mode(app); }
+                return;
+            };
+            if base == "pipe" {
+                // pipe: send to the command without copying
+                if !arg.is_empty() { pipe_text_to_command(&text, arg); }
+            } else if base.starts_with("append-selection") {
+                append_yank(app, &text);
+            } else {
+                store_yank(app, text.clone());
+                if piped && !arg.is_empty() { pipe_text_to_command(&text, arg); }
+            }
+            if cancel {
+                exit_copy_mode(app);
+            } else if !no_clear {
+                app.copy_anchor = None;
+            }
+        }
+        // ── Line / screen positions ──
+        "start-of-line" => move_to_line_start(app),
+        "end-of-line" => move_to_line_end(app),
+        "back-to-indentation" => move_to_first_nonblank(app),
+        "top-line" => move_to_screen_top(app),
+        "middle-line" => move_to_screen_middle(app),
+        "bottom-line" => move_to_screen_bottom(app),
+        "history-top" => { scroll_to_top(app); app.copy_pos = Some((0, 0)); }
+        "history-bottom" => {
+            scroll_to_bottom(app);
+            app.copy_pos = current_prompt_pos(app);
+        }
+        "scroll-top" => scroll_cursor_to_row(app, 0),
+        "scroll-middle" | "cursor-centre-vertical" => {
+            let mid = active_rows(app) / 2;
+            scroll_cursor_to_row(app, mid);
+        }
+        "scroll-bottom" => {
+            let last = active_rows(app).saturating_sub(1);
+            scroll_cursor_to_row(app, last);
+        }
+        "cursor-centre-horizontal" => {
+            let cols = app.windows.get(app.active_idx)
+                .and_then(|w| active_pane(&w.root, &w.active_path))
+                .map(|p| p.last_cols).unwrap_or(80);
+            if let Some((r, _)) = get_copy_pos(app) { app.copy_pos = Some((r, cols / 2)); }
+        }
+        "goto-line" => {
+            // tmux: the line number counts up from the bottom of the history
+            let n = arg.parse::<usize>().unwrap_or(0);
+            let win = &mut app.windows[app.active_idx];
+            if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) {
+                if let Ok(mut parser) = p.term.lock() {
+                    parser.screen_mut().set_scrollback(n);
+                    app.copy_scroll_offset = parser.screen().scrollback();
+                }
+            }
+        }
+        // ── Marks ──
+        "set-mark" => set_mark(app),
+        "jump-to-mark" => jump_to_mark(app),
+        // ── Searching ──
+        _ if name.starts_with("search-forward") || name.starts_with("search-backward") => {
+            // search-{forward,backward}[-text|-incremental] [query]
+            let forward = name.starts_with("search-forward");
+            // tmux: the plain commands take a regex, -text and
+            // -incremental take literal text.
+            let incremental = name.ends_with("-incremental");
+            let regex = !name.ends_with("-text") && !incremental;
+            if arg.is_empty() {
+                begin_copy_search(app, forward, regex, incremental);
+            } else {
+                app.copy_search_regex = regex;
+                run_copy_search(app, arg, forward);
+                for _ in 1..count { search_next(app); }
+            }
+        }
+        "jump-forward" | "jump-backward" | "jump-to-forward" | "jump-to-backward" => {
+            let kind = match name {
+                "jump-forward" => 0,
+                "jump-backward" => 1,
+                "jump-to-forward" => 2,
+                _ => 3,
+            };
+            match arg.chars().next() {
+                Some(ch) => for _ in 0..count { find_char(app, kind, ch); },
+                None => app.copy_find_char_pending = Some(kind),
+            }
+        }
+        "refresh-from-pane" => {} // copy mode always reads the live screen
+        _ => {} // ignore unknown copy-mode commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An app in copy mode on a single 3x10 pane showing `lines`, with a
+    /// five-line history.
+    fn copy_app(lines: &[&str]) -> AppState {
+        let mut app = AppState::new("t".to_string());
+        app.history_limit = 5;
+        crate::pane::test_window(&mut app, 3, 10, lines.join("\r\n").as_bytes());
+        app.mode = Mode::CopyMode;
+        app.copy_pos = Some((0, 0));
+        app
+    }
+
+    fn feed(app: &mut AppState, bytes: &[u8]) {
+        let win = &app.windows[app.active_idx];
+        let p = active_pane(&win.root, &win.active_path).unwrap();
+        p.term.lock().unwrap().process(bytes);
+    }
+
+    #[test]
+    fn search_steps_forward_and_backward_with_wrapping() {
+        let mut app = copy_app(&["foo 1", "bar", "foo 2", "bar", "foo 3"]);
+        run_copy_search(&mut app, "foo", true);
+        assert_eq!(app.copy_search_matches, [(0, 0, 3), (2, 0, 3), (4, 0, 3)]);
+        assert_eq!(app.copy_search_idx, 2);
+        assert_eq!(app.copy_pos, Some((2, 0)));
+        search_next(&mut app);
+        assert_eq!(app.copy_search_idx, 0);
+        assert_eq!((app.copy_scroll_offset, app.copy_pos), (2, Some((0, 0))));
+        search_prev(&mut app);
+        assert_eq!(app.copy_search_idx, 2);
+        assert_eq!((app.copy_scroll_offset, app.copy_pos), (0, Some((2, 0))));
+
+        app.copy_pos = Some((1, 0));
+        run_copy_search(&mut app, "foo", false);
+        assert_eq!(app.copy_search_idx, 1);
+        assert_eq!(app.copy_pos, Some((0, 0)));
+        search_next(&mut app);
+        assert_eq!(app.copy_search_idx, 0);
+        search_prev(&mut app);
+        assert_eq!(app.copy_search_idx, 1);
+        assert_eq!(visible_search_matches(&app), [(0, 0, 3, false), (2, 0, 3, true)]);
+    }
+
+    #[test]
+    fn search_matches_follow_trimmed_history() {
+        let mut app = copy_app(&["x0", "foo", "x2", "x3", "x4", "x5", "x6", "x7"]);
+        run_copy_search(&mut app, "foo", true);
+        assert_eq!(app.copy_search_matches, [(1, 0, 3)]);
+        assert_eq!(app.copy_pos, Some((1, 0)));
+
+        // one more line pushes x0 out of the five-line history
+        feed(&mut app, b"\r\ny8");
+        search_next(&mut app);
+        assert_eq!(app.copy_search_matches, [(1, 0, 3)]);
+        assert_eq!(app.copy_pos, Some((0, 0)));
+        assert_eq!(visible_search_matches(&app), [(0, 0, 3, true)]);
+
+        // once the match itself is trimmed n finds nothing
+        feed(&mut app, b"\r\ny9\r\ny10");
+        search_next(&mut app);
+        assert!(app.copy_search_matches.is_empty());
+    }
+
+    #[test]
+    fn search_prompt_has_history_and_reverse_search() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = copy_app(&["foo", "bar"]);
+        app.prompt_history.insert("search".to_string(), vec!["foo".to_string(), "bar".to_string()]);
+        begin_copy_search(&mut app, true, false, false);
+        let prompt = |app: &AppState| match &app.mode {
+            Mode::CopySearch { input, forward, search } => search_prompt_text(input, *forward, search.as_ref()),
+            _ => String::new(),
+        };
+        search_prompt_key(&mut app, key(KeyCode::Up));
+        assert_eq!(prompt(&app), "/bar");
+        search_prompt_key(&mut app, key(KeyCode::Up));
+        assert_eq!(prompt(&app), "/foo");
+        search_prompt_key(&mut app, key(KeyCode::Down));
+        search_prompt_key(&mut app, key(KeyCode::Down));
+        assert_eq!(prompt(&app), "/");
+
+        search_prompt_key(&mut app, KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        search_prompt_key(&mut app, key(KeyCode::Char('f')));
+        assert_eq!(prompt(&app), "(reverse-i-search)`f': foo");
+        search_prompt_key(&mut app, key(KeyCode::Char('x')));
+        assert_eq!(prompt(&app), "(failed reverse-i-search)`fx': foo");
+        search_prompt_key(&mut app, key(KeyCode::Backspace));
+        // Enter ends the history search and runs the search at once
+        search_prompt_key(&mut app, key(KeyCode::Enter));
+        assert!(matches!(app.mode, Mode::CopyMode));
+        assert_eq!(app.copy_search_query, "foo");
+        assert_eq!(app.prompt_history["search"], ["bar", "foo"]);
+    }
+
+    #[test]
+    fn incremental_search_moves_from_the_origin_and_cancel_returns() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = copy_app(&["abd", "x", "ab", "x", "abc"]);
+        app.copy_pos = Some((1, 0));
+        begin_copy_search(&mut app, false, false, true);
+
+        // each keystroke searches up from the origin again
+        search_prompt_key(&mut app, key(KeyCode::Char('a')));
+        search_prompt_key(&mut app, key(KeyCode::Char('b')));
+        assert_eq!((app.copy_scroll_offset, app.copy_pos), (0, Some((0, 0))));
+        search_prompt_key(&mut app, key(KeyCode::Char('d')));
+        assert_eq!((app.copy_scroll_offset, app.copy_pos), (2, Some((0, 0))));
+        search_prompt_key(&mut app, key(KeyCode::Backspace));
+        assert_eq!((app.copy_scroll_offset, app.copy_pos), (0, Some((0, 0))));
+        search_prompt_key(&mut app, key(KeyCode::Backspace));
+        search_prompt_key(&mut app, key(KeyCode::Backspace));
+        assert_eq!((app.copy_scroll_offset, app.copy_pos), (0, Some((1, 0))));
+        assert!(app.copy_search_matches.is_empty());
+
+        search_prompt_key(&mut app, key(KeyCode::Char('c')));
+        assert_eq!((app.copy_scroll_offset, app.copy_pos), (0, Some((2, 2))));
+        search_prompt_key(&mut app, key(KeyCode::Esc));
+        assert!(matches!(app.mode, Mode::CopyMode));
+        assert_eq!((app.copy_scroll_offset, app.copy_pos), (0, Some((1, 0))));
+        assert!(app.copy_search_matches.is_empty());
+        assert!(app.copy_search_origin.is_none());
+    }
+
+    #[test]
+    fn send_keys_x_takes_the_command_and_its_argument() {
+        assert_eq!(send_keys_x_command(&["-X", "-N", "3", "cursor-down"]), "cursor-down");
+        assert_eq!(send_keys_x_command(&["-t", "%1", "-X", "search-forward", "foo"]), "search-forward foo");
+        assert_eq!(send_keys_x_command(&["-X", "copy-pipe-and-cancel", "sort", "-u"]), "copy-pipe-and-cancel sort -u");
+    }
+
+    #[test]
+    fn motions_run_by_name_and_repeat_with_count() {
+        let mut app = copy_app(&["ab cd ef", "gh", "ij"]);
+        run_copy_mode_command(&mut app, "cursor-down", 2);
+        assert_eq!(app.copy_pos, Some((2, 0)));
+        run_copy_mode_command(&mut app, "cursor-up", 1);
+        assert_eq!(app.copy_pos, Some((1, 0)));
+        run_copy_mode_command(&mut app, "top-line", 1);
+        run_copy_mode_command(&mut app, "next-word", 2);
+        assert_eq!(app.copy_pos, Some((0, 6)));
+        run_copy_mode_command(&mut app, "previous-word", 1);
+        assert_eq!(app.copy_pos, Some((0, 3)));
+        run_copy_mode_command(&mut app, "end-of-line", 1);
+        assert_eq!(app.copy_pos, Some((0, 9)));
+        run_copy_mode_command(&mut app, "start-of-line", 1);
+        assert_eq!(app.copy_pos, Some((0, 0)));
+        run_copy_mode_command(&mut app, "cursor-right", 4);
+        assert_eq!(app.copy_pos, Some((0, 4)));
+        run_copy_mode_command(&mut app, "jump-forward f", 1);
+        assert_eq!(app.copy_pos, Some((0, 7)));
+        run_copy_mode_command(&mut app, "bottom-line", 1);
+        assert_eq!(app.copy_pos.map(|p| p.0), Some(2));
+        run_copy_mode_command(&mut app, "cancel", 1);
+        assert!(matches!(app.mode, Mode::Passthrough));
+
+        // Outside copy mode the command enters it first
+        run_copy_mode_command(&mut app, "cursor-up", 1);
+        assert!(matches!(app.mode, Mode::CopyMode));
+
+        // Off a bracket each direction looks its own way along the line
+        let mut app = copy_app(&["x (ab) [c]"]);
+        run_copy_mode_command(&mut app, "next-matching-bracket", 1);
+        assert_eq!(app.copy_pos, Some((0, 5)));
+        run_copy_mode_command(&mut app, "next-matching-bracket", 1);
+        assert_eq!(app.copy_pos, Some((0, 2)));
+        app.copy_pos = Some((0, 6));
+        run_copy_mode_command(&mut app, "previous-matching-bracket", 1);
+        assert_eq!(app.copy_pos, Some((0, 2)));
+        app.copy_pos = Some((0, 6));
+        run_copy_mode_command(&mut app, "next-matching-bracket", 1);
+        assert_eq!(app.copy_pos, Some((0, 9)));
+        app.copy_pos = Some((0, 0));
+        run_copy_mode_command(&mut app, "previous-matching-bracket", 1);
+        assert_eq!(app.copy_pos, Some((0, 0)));
+    }
+
+    #[test]
+    fn copy_commands_honour_their_suffixes() {
+        let mut app = copy_app(&["ab cd ef", "gh", "ij"]);
+        // Keep the system clipboard out of the test
+        app.copy_command = "cat >/dev/null".to_string();
+        run_copy_mode_command(&mut app, "begin-selection", 1);
+        run_copy_mode_command(&mut app, "cursor-right", 1);
+        run_copy_mode_command(&mut app, "copy-selection-no-clear", 1);
+        assert_eq!(crate::buffers::get_data(&app, None).as_deref(), Some("ab"));
+        assert!(app.copy_anchor.is_some());
+        run_copy_mode_command(&mut app, "copy-selection", 1);
+        assert!(app.copy_anchor.is_none());
+        assert!(matches!(app.mode, Mode::CopyMode));
+
+        run_copy_mode_command(&mut app, "cursor-down", 1);
+        run_copy_mode_command(&mut app, "copy-line", 1);
+        assert_eq!(crate::buffers::get_data(&app, None).as_deref(), Some("gh\n"));
+
+        let out = std::env::temp_dir().join(format!("psmux-copy-pipe-{}", std::process::id()));
+        run_copy_mode_command(&mut app, "start-of-line", 1);
+        run_copy_mode_command(&mut app, "begin-selection", 1);
+        run_copy_mode_command(&mut app, "cursor-down", 1);
+        run_copy_mode_command(&mut app, &format!("copy-pipe-and-cancel \"cat > '{}'\"", out.display()), 1);
+        let piped = std::fs::read_to_string(&out).unwrap_or_default();
+        let _ = std::fs::remove_file(&out);
+        assert_eq!(crate::buffers::get_data(&app, None).as_deref(), Some(piped.as_str()));
+        assert_eq!(piped, "gh\ni");
+        assert!(matches!(app.mode, Mode::Passthrough));
+
+        // page-down-and-cancel leaves copy mode once at the bottom
+        let mut app = copy_app(&["ab cd ef", "gh", "ij"]);
+        run_copy_mode_command(&mut app, "page-down-and-cancel", 1);
+        assert!(matches!(app.mode, Mode::Passthrough));
+    }
+
+    /// A pane writer that keeps what was written to it.
+    #[derive(Clone, Default)]
+    struct Capture(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    fn osc52_copy(text: &str) -> Vec<u8> {
+        format!("\x1b]52;c;{}\x07", crate::util::base64_encode(text)).into_bytes()
+    }
+
+    #[test]
+    fn clipboard_drain_follows_set_clipboard() {
+        let mut app = copy_app(&["x"]);
+        app.set_clipboard = "on".to_string();
+        feed(&mut app, &osc52_copy("hello"));
+        assert!(drain_pane_clipboard(&mut app));
+        assert_eq!(crate::buffers::get_data(&app, None).as_deref(), Some("hello"));
+        assert_eq!(app.clipboard_osc52.take().as_deref(), Some("hello"));
+        assert!(!drain_pane_clipboard(&mut app));
+
+        // external: to the system clipboard (here copy-command), not a buffer
+        let out = std::env::temp_dir().join(format!("psmux-osc52-{}", std::process::id()));
+        app.set_clipboard = "external".to_string();
+        app.copy_command = format!("cat > '{}'", out.display());
+        feed(&mut app, &osc52_copy("world"));
+        assert!(drain_pane_clipboard(&mut app));
+        let copied = std::fs::read_to_string(&out).unwrap_or_default();
+        let _ = std::fs::remove_file(&out);
+        assert_eq!(copied, "world");
+        assert_eq!(crate::buffers::get_data(&app, None).as_deref(), Some("hello"));
+        assert_eq!(app.clipboard_osc52.take().as_deref(), Some("world"));
+
+        // off: the copy is dropped, not left queued for later
+        app.set_clipboard = "off".to_string();
+        feed(&mut app, &osc52_copy("dropped"));
+        assert!(!drain_pane_clipboard(&mut app));
+        app.set_clipboard = "on".to_string();
+        assert!(!drain_pane_clipboard(&mut app));
+        assert_eq!(crate::buffers::get_data(&app, None).as_deref(), Some("hello"));
+        assert!(app.clipboard_osc52.is_none());
+    }
+
+    #[test]
+    fn clipboard_drain_covers_popups_and_floats() {
+        fn popup(pane: crate::types::Pane) -> crate::popup::Popup {
+            crate::popup::Popup {
+                name: None, command: String::new(), output: String::new(), process: None,
+                x: 0, y: 0, width: 10, height: 3, close: crate::popup::PopupClose::Keep,
+                title: String::new(), style: String::new(), border_style: String::new(),
+                border_lines: "single".to_string(),
+                popup_pty: Some(crate::types::PopupPty {
+                    master: pane.master, writer: pane.writer, child: pane.child,
+                    term: pane.term, data_version: pane.data_version,
+                }),
+                pane_id: None, hidden: true, view: None,
+            }
+        }
+        let mut app = copy_app(&["x"]);
+        app.set_clipboard = "on".to_string();
+        let float = crate::pane::test_pane(&mut app, 3, 10, &osc52_copy("from float"));
+        app.windows[0].floating.push(popup(float));
+        assert!(drain_pane_clipboard(&mut app));
+        assert_eq!(crate::buffers::get_data(&app, None).as_deref(), Some("from float"));
+
+        let shown = crate::pane::test_pane(&mut app, 3, 10, &osc52_copy("from popup"));
+        app.mode = Mode::PopupMode { popup: popup(shown) };
+        assert!(drain_pane_clipboard(&mut app));
+        assert_eq!(crate::buffers::get_data(&app, None).as_deref(), Some("from popup"));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn clipboard_backend_prefers_wayland_then_xclip_then_xsel() {
+        let pick = |env: &[&str], tools: &[&str]| {
+            choose_clipboard_backend(|v| env.contains(&v), |t| tools.contains(&t)).map(|b| b.copy[0].clone())
+        };
+        let all = ["wl-copy", "wl-paste", "xclip", "xsel"];
+        assert_eq!(pick(&["WAYLAND_DISPLAY", "DISPLAY"], &all).as_deref(), Some("wl-copy"));
+        // wl-copy alone is not enough: reads need wl-paste
+        assert_eq!(pick(&["WAYLAND_DISPLAY", "DISPLAY"], &["wl-copy", "xsel"]).as_deref(), Some("xsel"));
+        assert_eq!(pick(&["DISPLAY"], &all).as_deref(), Some("xclip"));
+        assert_eq!(pick(&["DISPLAY"], &["xsel"]).as_deref(), Some("xsel"));
+        assert_eq!(pick(&[], &all), None);
+        assert_eq!(pick(&["WAYLAND_DISPLAY"], &["xclip"]), None);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn clipboard_tools_are_found_on_path() {
+        let dir = std::env::temp_dir().join(format!("psmux-path-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("xsel")).unwrap();
+        std::fs::write(dir.join("xclip"), b"").unwrap();
+        let paths = std::env::join_paths([std::path::Path::new("/nonexistent"), &dir]).unwrap();
+        assert!(find_in_paths(&paths, "xclip"));
+        // A directory is not a program
+        assert!(!find_in_paths(&paths, "xsel"));
+        assert!(!find_in_paths(&paths, "wl-copy"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn clipboard_backend_copies_and_reads_through_its_tool() {
+        let file = std::env::temp_dir().join(format!("psmux-clip-{}", std::process::id()));
+        let path = file.display().to_string();
+        let backend = ClipboardBackend {
+            copy: vec!["sh".into(), "-c".into(), format!("cat > '{}'", path)],
+            paste: vec!["cat".into(), path.clone()],
+        };
+        copy_with_backend(&backend, "clip text");
+        assert_eq!(read_with_backend(&backend).as_deref(), Some("clip text"));
+        let _ = std::fs::remove_file(&file);
+        // A failing tool reads as nothing
+        assert_eq!(read_with_backend(&backend), None);
+
+        // Without a tool, copying is a no-op and reading gives nothing
+        if clipboard_backend().is_none() {
+            copy_to_system_clipboard("ignored");
+            assert_eq!(read_from_system_clipboard(), None);
+        }
+    }
+
+    #[test]
+    fn clipboard_reads_are_answered_only_when_allowed() {
+        let mut app = copy_app(&["x"]);
+        let written = Capture::default();
+        {
+            let win = &mut app.windows[app.active_idx];
+            active_pane_mut(&mut win.root, &win.active_path).unwrap().writer = Box::new(written.clone());
+        }
+        crate::buffers::add_automatic(&mut app, "secret".to_string());
+        app.set_clipboard = "on".to_string();
+
+        app.allow_clipboard_read = false;
+        feed(&mut app, b"\x1b]52;c;?\x07");
+        drain_pane_clipboard(&mut app);
+        assert!(written.0.lock().unwrap().is_empty());
+
+        app.allow_clipboard_read = true;
+        feed(&mut app, b"\x1b]52;p;?\x07");
+        drain_pane_clipboard(&mut app);
+        let reply = format!("\x1b]52;p;{}\x07", crate::util::base64_encode("secret"));
+        assert_eq!(String::from_utf8_lossy(&written.0.lock().unwrap()), reply);
+
+        // set-clipboard off answers nothing even when reads are allowed
+        written.0.lock().unwrap().clear();
+        app.set_clipboard = "off".to_string();
+        feed(&mut app, b"\x1b]52;c;?\x07");
+        drain_pane_clipboard(&mut app);
+        assert!(written.0.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! `capture-pane -F html|json`: pane contents as a self-contained HTML page
+//! for bug reports, or as a JSON cell dump with the cursor position for
+//! test assertions.  HTML is built from the same runs as `rows_v2`.
+
+use serde::Serialize;
+
+use crate::capture::Lines;
+use crate::layout::{cell_flags, cell_width, CellJson, CellRunJson, RowRunsJson, RunBuilder,
+    FLAG_BOLD, FLAG_DIM, FLAG_HIDDEN, FLAG_INVERSE, FLAG_ITALIC, FLAG_UNDERLINE};
+
+/// Page colours used for the terminal's default foreground and background.
+const DEFAULT_FG: &str = "#c0c0c0";
+const DEFAULT_BG: &str = "#000000";
+
+/// The runs of one captured line, merged as for `rows_v2`.
+pub fn line_runs(lines: &Lines, line: usize) -> RowRunsJson {
+    let mut runs = RunBuilder::default();
+    let mut c = 0;
+    while c < lines.cols() {
+        let width = match lines.cell(line, c) {
+            Some(cell) => {
+                let t = cell.contents();
+                let t = if t.is_empty() { " " } else { t };
+                let w = cell_width(cell);
+                runs.push(t, cell.fgcolor(), cell.bgcolor(), cell_flags(cell), w);
+                w
+            }
+            None => { runs.push_blank(); 1 }
+        };
+        c = c.saturating_add(width.max(1));
+    }
+    runs.finish()
+}
+
+/// An HTML page showing the captured lines with their colours and
+/// attributes.  With `join`, soft-wrapped lines are joined.
+pub fn html(lines: &Lines, join: bool) -> String {
+    let mut body = String::new();
+    for line in lines.top..=lines.bottom {
+        let mut row = line_runs(lines, line);
+        // Drop the blank padding at the end of the line
+        while let Some(last) = row.runs.last_mut() {
+            if last.bg != "default" || last.flags & FLAG_INVERSE != 0 { break; }
+            let len = last.text.trim_end_matches(' ').len();
+            last.text.truncate(len);
+            if !last.text.is_empty() { break; }
+            row.runs.pop();
+        }
+        for run in &row.runs {
+            let style = run_style(run);
+            if style.is_empty() {
+                push_escaped(&mut body, &run.text);
+            } else {
+                body.push_str(&format!("<span style=\"{}\">", style));
+                push_escaped(&mut body, &run.text);
+                body.push_str("</span>");
+            }
+        }
+        if !(join && lines.wrapped(line)) { body.push('\n'); }
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>psmux capture</title>\n<style>\n\
+         body {{ margin: 0; background: {bg}; color: {fg}; }}\n\
+         pre {{ margin: 0; padding: 8px; font: 14px/1.2 Consolas, Menlo, \"DejaVu Sans Mono\", monospace; }}\n\
+         </style>\n</head>\n<body><pre>\n{body}</pre></body>\n</html>\n",
+        bg = DEFAULT_BG, fg = DEFAULT_FG, body = body,
+    )
+}
+
+fn push_escaped(out: &mut String, text: &str) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+}
+
+/// Inline CSS for a run; empty for default text.
+fn run_style(run: &CellRunJson) -> String {
+    let mut fg = css_color(&run.fg);
+    let mut bg = css_color(&run.bg);
+    if run.flags & FLAG_INVERSE != 0 {
+        let new_fg = bg.unwrap_or_else(|| DEFAULT_BG.to_string());
+        bg = Some(fg.unwrap_or_else(|| DEFAULT_FG.to_string()));
+        fg = Some(new_fg);
+    }
+    let mut style = Vec::new();
+    if let Some(c) = fg { style.push(format!("color:{}", c)); }
+    if let Some(c) = bg { style.push(format!("background-color:{}", c)); }
+    if run.flags & FLAG_BOLD != 0 { style.push("font-weight:bold".to_string()); }
+    if run.flags & FLAG_DIM != 0 { style.push("opacity:0.5".to_string()); }
+    if run.flags & FLAG_ITALIC != 0 { style.push("font-style:italic".to_string()); }
+    if run.flags & FLAG_UNDERLINE != 0 { style.push("text-decoration:underline".to_string()); }
+    if run.flags & FLAG_HIDDEN != 0 { style.push("visibility:hidden".to_string()); }
+    style.join(";")
+}
+
+/// CSS for a colour name from `color_to_name`; `None` for the default.
+fn css_color(name: &str) -> Option<String> {
+    let (r, g, b) = if let Some(i) = name.strip_prefix("idx:") {
+        indexed_rgb(i.parse().ok()?)
+    } else if let Some(rgb) = name.strip_prefix("rgb:") {
+        let mut parts = rgb.split(',').map(|p| p.parse::<u8>().ok());
+        (parts.next()??, parts.next()??, parts.next()??)
+    } else {
+        return None;
+    };
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+/// xterm's default 256-colour palette.
+fn indexed_rgb(i: u8) -> (u8, u8, u8) {
+    const BASE: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00), (0xcd, 0x00, 0x00), (0x00, 0xcd, 0x00), (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee), (0xcd, 0x00, 0xcd), (0x00, 0xcd, 0xcd), (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f), (0xff, 0x00, 0x00), (0x00, 0xff, 0x00), (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff), (0xff, 0x00, 0xff), (0x00, 0xff, 0xff), (0xff, 0xff, 0xff),
+    ];
+    let level = |v: u8| if v == 0 { 0 } else { 55 + 40 * v };
+    match i {
+        0..=15 => BASE[i as usize],
+        16..=231 => {
+            let n = i - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let v = 8 + 10 * (i - 232);
+            (v, v, v)
+        }
+    }
+}
+
+/// The JSON dump: one entry per cell, with a wide character followed by a
+/// zero-width continuation cell, as in the layout's `content`.
+#[derive(Serialize)]
+pub struct ScreenJson {
+    pub cols: u16,
+    pub rows: u16,
+    pub cursor_row: u16,
+    pub cursor_col: u16,
+    pub cursor_visible: bool,
+    pub alternate_screen: bool,
+    pub history_size: usize,
+    /// tmux line number of `lines[0]`: 0 is the top of the screen.
+    pub first_line: i64,
+    pub lines: Vec<Vec<CellJson>>,
+}
+
+pub fn json(lines: &Lines) -> String {
+    let screen = lines.screen;
+    let (rows, cols) = screen.size();
+    let (cursor_row, cursor_col) = screen.cursor_position();
+    let blank = || CellJson {
+        text: " ".to_string(), fg: "default".to_string(), bg: "default".to_string(),
+        bold: false, italic: false, underline: false, inverse: false, dim: false,
+        blink: false, hidden: false, width: 1,
+    };
+    let dump = (lines.top..=lines.bottom).map(|line| {
+        (0..cols).map(|c| match lines.cell(line, c) {
+            Some(cell) => {
+                let (text, width) = if cell.is_wide_continuation() {
+                    (String::new(), 0)
+                } else if cell.has_contents() {
+                    (cell.contents().to_string(), cell_width(cell))
+                } else {
+                    (" ".to_string(), 1)
+                };
+                CellJson {
+                    text,
+                    fg: crate::util::color_to_name(cell.fgcolor()).into_owned(),
+                    bg: crate::util::color_to_name(cell.bgcolor()).into_owned(),
+                    bold: cell.bold(), italic: cell.italic(), underline: cell.underline(),
+                    inverse: cell.inverse(), dim: cell.dim(), blink: cell.blink(),
+                    hidden: cell.hidden(), width,
+                }
+            }
+            None => blank(),
+        }).collect()
+    }).collect();
+    let out = ScreenJson {
+        cols,
+        rows,
+        cursor_row,
+        cursor_col,
+        cursor_visible: !screen.hide_cursor(),
+        alternate_screen: screen.alternate_screen(),
+        history_size: screen.history_len(),
+        first_line: lines.number(lines.top),
+        lines: dump,
+    };
+    let mut text = serde_json::to_string(&out).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{capture_screen, parse_args};
+
+    #[test]
+    fn html_keeps_colours_and_escapes_text() {
+        let mut parser = vt100::Parser::new(2, 20, 0);
+        parser.process(b"\x1b[1;31mfail\x1b[0m <a&b>\r\n\x1b[7mrev\x1b[0m");
+        let page = capture_screen(parser.screen(), &parse_args(&["-F", "html"])).unwrap();
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<span style=\"color:#cd0000;font-weight:bold\">fail</span> &lt;a&amp;b&gt;\n"));
+        assert!(page.contains("<span style=\"color:#000000;background-color:#c0c0c0\">rev</span>\n"));
+    }
+
+    #[test]
+    fn palette_matches_xterm() {
+        assert_eq!(css_color("idx:196").as_deref(), Some("#ff0000"));
+        assert_eq!(css_color("idx:244").as_deref(), Some("#808080"));
+        assert_eq!(css_color("rgb:1,2,255").as_deref(), Some("#0102ff"));
+        assert_eq!(css_color("default"), None);
+    }
+
+    #[test]
+    fn json_dump_has_cursor_and_cells() {
+        let mut parser = vt100::Parser::new(2, 4, 10);
+        parser.process(b"one\r\n\xe4\xb8\xad\x1b[32mx");
+        let text = capture_screen(parser.screen(), &parse_args(&["-F", "json"])).unwrap();
+        let v: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(v["cursor_row"], 1);
+        assert_eq!(v["cursor_col"], 3);
+        assert_eq!(v["first_line"], 0);
+        assert_eq!(v["lines"][0][0]["text"], "o");
+        assert_eq!(v["lines"][1][0]["width"], 2);
+        assert_eq!(v["lines"][1][1]["width"], 0);
+        assert_eq!(v["lines"][1][2]["fg"], "idx:2");
+    }
+}
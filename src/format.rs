@@ -10,7 +10,7 @@
 use std::env;
 use std::cell::Cell;
 
-use crate::types::{AppState, Node, LayoutKind, Pane, Mode, VERSION};
+use crate::types::{AppState, Node, LayoutKind, Pane, Mode, TreeKind, Window, VERSION};
 use crate::tree::{split_with_gaps, get_active_pane_id, active_pane, count_panes};
 use crate::config::format_key_binding;
 
@@ -20,6 +20,13 @@ use crate::config::format_key_binding;
 thread_local! {
     static PANE_POS_OVERRIDE: Cell<Option<usize>> = const { Cell::new(None) };
     static BUFFER_IDX_OVERRIDE: Cell<Option<usize>> = const { Cell::new(None) };
+    static FORMAT_TYPE_OVERRIDE: Cell<Option<TreeKind>> = const { Cell::new(None) };
+}
+
+/// Set what a format is being expanded for, so `session_format`,
+/// `window_format` and `pane_format` tell them apart (choose-tree, list-*).
+pub fn set_format_type_override(kind: Option<TreeKind>) {
+    FORMAT_TYPE_OVERRIDE.set(kind);
 }
 
 /// Set the buffer index for per-buffer format expansion in list-buffers -F.
@@ -785,6 +792,7 @@ fn lookup_option(name: &str, app: &AppState) -> Option<String> {
         "automatic-rename" => Some(if app.automatic_rename { "on".into() } else { "off".into() }),
         "monitor-activity" => Some(if app.monitor_activity { "on".into() } else { "off".into() }),
         "remain-on-exit" => Some(if app.remain_on_exit { "on".into() } else { "off".into() }),
+        "remain-on-exit-format" => Some(app.remain_on_exit_format.clone()),
         "destroy-unattached" => Some(if app.destroy_unattached { "on".into() } else { "off".into() }),
         "exit-empty" => Some(if app.exit_empty { "on".into() } else { "off".into() }),
         "set-titles" => Some(if app.set_titles { "on".into() } else { "off".into() }),
@@ -802,6 +810,18 @@ fn lookup_option(name: &str, app: &AppState) -> Option<String> {
         "message-style" => Some(app.message_style.clone()),
         "message-command-style" => Some(app.message_command_style.clone()),
         "mode-style" => Some(app.mode_style.clone()),
+        "copy-mode-match-style" => Some(app.copy_mode_match_style.clone()),
+        "copy-mode-current-match-style" => Some(app.copy_mode_current_match_style.clone()),
+        "copy-mode-position-format" => Some(app.copy_mode_position_format.clone()),
+        "pane-scrollbars" => Some(app.pane_scrollbars.clone()),
+        "pane-scrollbars-style" => Some(app.pane_scrollbars_style.clone()),
+        "popup-style" => Some(app.popup_style.clone()),
+        "popup-border-style" => Some(app.popup_border_style.clone()),
+        "popup-border-lines" => Some(app.popup_border_lines.clone()),
+        "menu-style" => Some(app.menu_style.clone()),
+        "menu-selected-style" => Some(app.menu_selected_style.clone()),
+        "menu-border-style" => Some(app.menu_border_style.clone()),
+        "menu-border-lines" => Some(app.menu_border_lines.clone()),
         "status-left-style" => Some(app.status_left_style.clone()),
         "status-right-style" => Some(app.status_right_style.clone()),
         "status-interval" => Some(app.status_interval.to_string()),
@@ -809,11 +829,21 @@ fn lookup_option(name: &str, app: &AppState) -> Option<String> {
         "display-time" => Some(app.display_time_ms.to_string()),
         "display-panes-time" => Some(app.display_panes_time_ms.to_string()),
         "focus-events" => Some(if app.focus_events { "on".into() } else { "off".into() }),
+        "ambiguous-width" => Some(app.ambiguous_width.to_string()),
+        "variation-selector-always-wide" => Some(if app.variation_selector_always_wide { "on".into() } else { "off".into() }),
+        "wheel-scroll-lines" => Some(app.wheel_scroll_lines.to_string()),
         "aggressive-resize" => Some(if app.aggressive_resize { "on".into() } else { "off".into() }),
         "synchronize-panes" => Some(if app.sync_input { "on".into() } else { "off".into() }),
         "monitor-silence" => Some(app.monitor_silence.to_string()),
         "bell-action" => Some(app.bell_action.clone()),
         "visual-bell" => Some(if app.visual_bell { "on".into() } else { "off".into() }),
+        "set-clipboard" => Some(app.set_clipboard.clone()),
+        "buffer-limit" => Some(app.buffer_limit.to_string()),
+        "history-file" => Some(app.history_file.clone()),
+        "prompt-history-limit" => Some(app.prompt_history_limit.to_string()),
+        "allow-clipboard-read" => Some(if app.allow_clipboard_read { "on".into() } else { "off".into() }),
+        "hint-alphabet" => Some(app.hint_alphabet.clone()),
+        "hint-style" => Some(app.hint_style.clone()),
         "claude-code-fix-tty" => Some(if app.claude_code_fix_tty { "on".into() } else { "off".into() }),
         "claude-code-force-interactive" => Some(if app.claude_code_force_interactive { "on".into() } else { "off".into() }),
         _ => {
@@ -877,7 +907,7 @@ fn expand_boolean_and(body: &str, app: &AppState, win_idx: usize) -> String {
 }
 
 #[inline]
-fn is_truthy(s: &str) -> bool {
+pub(crate) fn is_truthy(s: &str) -> bool {
     !s.is_empty() && s != "0" && s != "off" && s != "no"
 }
 
@@ -998,12 +1028,14 @@ pub fn expand_var(var: &str, app: &AppState, win_idx: usize) -> String {
         "session_id" => format!("${}", app.session_id),
         "session_created" => app.created_at.timestamp().to_string(),
         "session_created_string" => app.created_at.format("%a %b %e %H:%M:%S %Y").to_string(),
-        "session_activity" | "session_last_attached" => app.created_at.timestamp().to_string(),
-        "session_activity_string" => app.created_at.format("%a %b %e %H:%M:%S %Y").to_string(),
+        "session_activity" => session_activity(app).timestamp().to_string(),
+        "session_last_attached" => app.created_at.timestamp().to_string(),
+        "session_activity_string" => session_activity(app).format("%a %b %e %H:%M:%S %Y").to_string(),
         "session_group" | "session_group_list" | "session_alerts" | "session_stack" => String::new(),
         "session_group_attached" | "session_group_size" => "0".into(),
         "session_grouped" => "0".into(),
-        "session_format" | "session_many_attached" => if app.attached_clients > 1 { "1".into() } else { "0".into() },
+        "session_format" => if FORMAT_TYPE_OVERRIDE.get() == Some(TreeKind::Session) { "1".into() } else { "0".into() },
+        "session_many_attached" => if app.attached_clients > 1 { "1".into() } else { "0".into() },
         "session_path" => env::var("HOME").or_else(|_| env::var("USERPROFILE")).unwrap_or_default(),
 
         // ── Window ──
@@ -1024,8 +1056,11 @@ pub fn expand_var(var: &str, app: &AppState, win_idx: usize) -> String {
         "window_layout" | "window_visible_layout" => generate_window_layout(&win.root, app.last_window_area),
         "window_width" => app.last_window_area.width.to_string(),
         "window_height" => app.last_window_area.height.to_string(),
-        "window_format" => "1".into(),
-        "window_activity" => app.created_at.timestamp().to_string(),
+        "window_format" => match FORMAT_TYPE_OVERRIDE.get() {
+            Some(kind) if kind != TreeKind::Window => "0".into(),
+            _ => "1".into(),
+        },
+        "window_activity" => window_activity(win).timestamp().to_string(),
         "window_silence_flag" => if win.silence_flag { "1".into() } else { "0".into() },
         "window_bell_flag" => if win.bell_flag { "1".into() } else { "0".into() },
         "window_linked" => "0".into(),
@@ -1108,12 +1143,13 @@ pub fn expand_var(var: &str, app: &AppState, win_idx: usize) -> String {
             else { String::new() }
         }
         "pane_in_mode" => match app.mode {
-            Mode::CopyMode | Mode::CopySearch { .. } | Mode::ClockMode => "1".into(),
+            Mode::CopyMode | Mode::CopySearch { .. } | Mode::ClockMode | Mode::HintMode { .. } => "1".into(),
             _ => "0".into(),
         },
         "pane_mode" => match app.mode {
             Mode::CopyMode | Mode::CopySearch { .. } => "copy-mode".into(),
             Mode::ClockMode => "clock-mode".into(),
+            Mode::HintMode { .. } => "hint-mode".into(),
             _ => String::new(),
         },
         "pane_synchronized" => if app.sync_input { "1".into() } else { "0".into() },
@@ -1122,8 +1158,23 @@ pub fn expand_var(var: &str, app: &AppState, win_idx: usize) -> String {
                 if p.dead { "1".into() } else { "0".into() }
             } else { "0".into() }
         }
-        "pane_dead_signal" | "pane_dead_status" | "pane_dead_time" => "0".into(),
-        "pane_format" => "1".into(),
+        "pane_dead_status" => target_pane()
+            .and_then(|p| p.exit_status.as_ref())
+            .filter(|st| st.signal().is_none())
+            .map(|st| st.exit_code().to_string())
+            .unwrap_or_default(),
+        "pane_dead_signal" => target_pane()
+            .and_then(|p| p.exit_status.as_ref())
+            .and_then(|st| st.signal().map(|s| s.to_string()))
+            .unwrap_or_default(),
+        "pane_dead_time" => target_pane()
+            .and_then(|p| p.dead_time)
+            .map(|t| t.timestamp().to_string())
+            .unwrap_or_default(),
+        "pane_format" => match FORMAT_TYPE_OVERRIDE.get() {
+            Some(kind) if kind != TreeKind::Pane => "0".into(),
+            _ => "1".into(),
+        },
         "pane_input_off"
         | "pane_pipe" | "pane_unseen_changes" => "0".into(),
         "pane_last" => {
@@ -1330,6 +1381,10 @@ pub fn expand_var(var: &str, app: &AppState, win_idx: usize) -> String {
         "selection_end_x" => app.copy_pos.map(|(_, c)| c.to_string()).unwrap_or("0".into()),
         "selection_end_y" => app.copy_pos.map(|(r, _)| r.to_string()).unwrap_or("0".into()),
         "search_present" => if !app.copy_search_query.is_empty() { "1".into() } else { "0".into() },
+        "search_count" => app.copy_search_matches.len().to_string(),
+        "search_index" => {
+            if app.copy_search_matches.is_empty() { "0".into() } else { (app.copy_search_idx + 1).to_string() }
+        }
         "search_match" => {
             if !app.copy_search_matches.is_empty() {
                 app.copy_search_matches.get(app.copy_search_idx)
@@ -1349,17 +1404,28 @@ pub fn expand_var(var: &str, app: &AppState, win_idx: usize) -> String {
         // ── Buffer ──
         "buffer_size" => {
             let idx = BUFFER_IDX_OVERRIDE.get().unwrap_or(0);
-            app.paste_buffers.get(idx).map(|b| b.len().to_string()).unwrap_or("0".into())
+            app.paste_buffers.get(idx).map(|b| b.data.len().to_string()).unwrap_or("0".into())
         }
         "buffer_sample" => {
             let idx = BUFFER_IDX_OVERRIDE.get().unwrap_or(0);
-            app.paste_buffers.get(idx).map(|b| b.chars().take(50).collect::<String>()).unwrap_or_default()
+            app.paste_buffers.get(idx).map(|b| b.data.chars().take(50).collect::<String>()).unwrap_or_default()
         }
         "buffer_name" => {
             let idx = BUFFER_IDX_OVERRIDE.get().unwrap_or(0);
-            if idx < app.paste_buffers.len() { format!("buffer{:04}", idx) } else { String::new() }
+            app.paste_buffers.get(idx).map(|b| b.name.clone()).unwrap_or_default()
+        }
+        "buffer_created" => {
+            let idx = BUFFER_IDX_OVERRIDE.get().unwrap_or(0);
+            app.paste_buffers.get(idx).map(|b| b.created.timestamp().to_string()).unwrap_or_default()
+        }
+        "buffer_created_string" => {
+            let idx = BUFFER_IDX_OVERRIDE.get().unwrap_or(0);
+            app.paste_buffers.get(idx).map(|b| b.created.format("%a %b %e %H:%M:%S %Y").to_string()).unwrap_or_default()
+        }
+        "buffer_full" => {
+            let idx = BUFFER_IDX_OVERRIDE.get().unwrap_or(0);
+            app.paste_buffers.get(idx).map(|b| b.data.clone()).unwrap_or_default()
         }
-        "buffer_created" => app.created_at.timestamp().to_string(),
 
         // ── Client ──
         "client_width" => app.last_window_area.width.to_string(),
@@ -1403,7 +1469,15 @@ pub fn expand_var(var: &str, app: &AppState, win_idx: usize) -> String {
         "status" => if app.status_visible { "on".into() } else { "off".into() },
         "mode_keys" => app.mode_keys.clone(),
         "history_limit" => app.history_limit.to_string(),
-        "history_size" => app.history_limit.to_string(),
+        "history_size" => {
+            // tmux: lines currently in the history, not the limit
+            if let Some(p) = active_pane(&win.root, &win.active_path) {
+                if let Ok(parser) = p.term.lock() {
+                    return parser.screen().history_len().to_string();
+                }
+            }
+            "0".into()
+        }
         "alternate_on" => {
             if let Some(p) = active_pane(&win.root, &win.active_path) {
                 if let Ok(parser) = p.term.lock() {
@@ -1562,9 +1636,11 @@ pub fn default_list_buffers_format() -> &'static str {
 /// Format a list of windows using a format string.
 pub fn format_list_windows(app: &AppState, fmt: &str) -> String {
     let mut lines = Vec::with_capacity(app.windows.len());
+    FORMAT_TYPE_OVERRIDE.set(Some(TreeKind::Window));
     for (i, _win) in app.windows.iter().enumerate() {
         lines.push(expand_format_for_window(fmt, app, i));
     }
+    FORMAT_TYPE_OVERRIDE.set(None);
     lines.join("\n")
 }
 
@@ -1576,12 +1652,33 @@ pub fn format_list_panes(app: &AppState, fmt: &str, win_idx: usize) -> String {
     };
     let mut ids = Vec::new();
     collect_pane_ids(&win.root, &mut ids);
-    ids.iter().enumerate().map(|(pos, _pid)| {
+    FORMAT_TYPE_OVERRIDE.set(Some(TreeKind::Pane));
+    let lines = ids.iter().enumerate().map(|(pos, _pid)| {
         PANE_POS_OVERRIDE.set(Some(pos));
         let line = expand_format_for_window(fmt, app, win_idx);
         PANE_POS_OVERRIDE.set(None);
         line
-    }).collect::<Vec<_>>().join("\n")
+    }).collect::<Vec<_>>().join("\n");
+    FORMAT_TYPE_OVERRIDE.set(None);
+    lines
+}
+
+/// Format the session line of list-sessions using a format string.
+pub fn format_list_session(app: &AppState, fmt: &str) -> String {
+    FORMAT_TYPE_OVERRIDE.set(Some(TreeKind::Session));
+    let line = expand_format(fmt, app);
+    FORMAT_TYPE_OVERRIDE.set(None);
+    line
+}
+
+/// When a window last had output.
+pub fn window_activity(win: &Window) -> chrono::DateTime<chrono::Local> {
+    chrono::Local::now() - chrono::Duration::from_std(win.last_output_time.elapsed()).unwrap_or_default()
+}
+
+/// When any window of the session last had output.
+pub fn session_activity(app: &AppState) -> chrono::DateTime<chrono::Local> {
+    app.windows.iter().map(window_activity).max().unwrap_or(app.created_at)
 }
 
 fn collect_pane_ids(node: &Node, ids: &mut Vec<usize>) {
@@ -1709,4 +1806,25 @@ mod tests {
         let val = apply_modifier(&Modifier::Quote, "(hello)", &app, 0);
         assert_eq!(val, "\\(hello\\)");
     }
+
+    #[test]
+    fn test_remain_on_exit_format() {
+        let mut app = mock_app();
+        crate::pane::test_window(&mut app, 5, 20, b"");
+        let died = chrono::Local::now();
+        let when = died.format("%a %b %e %H:%M:%S %Y").to_string();
+        let set_status = |app: &mut AppState, st: portable_pty::ExitStatus| {
+            let win = &mut app.windows[0];
+            let p = crate::tree::active_pane_mut(&mut win.root, &win.active_path).unwrap();
+            p.dead = true;
+            p.exit_status = Some(st);
+            p.dead_time = Some(died);
+        };
+        set_status(&mut app, portable_pty::ExitStatus::with_exit_code(3));
+        assert_eq!(expand_format(crate::types::DEFAULT_REMAIN_ON_EXIT_FORMAT, &app),
+            format!("Pane is dead (status 3, {})", when));
+        set_status(&mut app, portable_pty::ExitStatus::with_signal("9"));
+        assert_eq!(expand_format(crate::types::DEFAULT_REMAIN_ON_EXIT_FORMAT, &app),
+            format!("Pane is dead (signal 9, {})", when));
+    }
 }
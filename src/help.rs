@@ -76,6 +76,7 @@ pub const PREFIX_DEFAULTS: &[(&str, &str)] = &[
     ("[",       "copy-mode"),
     ("]",       "paste-buffer"),
     ("=",       "choose-buffer"),
+    ("F",       "hint-mode"),
 
     // ── Misc ──
     (":",       "command-prompt"),
@@ -85,6 +86,62 @@ pub const PREFIX_DEFAULTS: &[(&str, &str)] = &[
     ("s",       "choose-session"),
 ];
 
+/// Default `copy-mode` (emacs) key table, used when `mode-keys` is
+/// `emacs`.  Unlike the prefix table this one also drives dispatch:
+/// input.rs looks keys up here after user `bind -T copy-mode` bindings.
+pub const COPY_MODE_DEFAULTS: &[(&str, &str)] = &[
+    ("C-Space",  "send-keys -X begin-selection"),
+    ("C-a",      "send-keys -X start-of-line"),
+    ("C-b",      "send-keys -X cursor-left"),
+    ("C-c",      "send-keys -X cancel"),
+    ("C-e",      "send-keys -X end-of-line"),
+    ("C-f",      "send-keys -X cursor-right"),
+    ("C-g",      "send-keys -X clear-selection"),
+    ("C-k",      "send-keys -X copy-pipe-end-of-line-and-cancel"),
+    ("C-n",      "send-keys -X cursor-down"),
+    ("C-p",      "send-keys -X cursor-up"),
+    ("C-r",      "send-keys -X search-backward-incremental"),
+    ("C-s",      "send-keys -X search-forward-incremental"),
+    ("C-v",      "send-keys -X page-down"),
+    ("C-w",      "send-keys -X copy-pipe-and-cancel"),
+    ("Escape",   "send-keys -X cancel"),
+    ("Space",    "send-keys -X page-down"),
+    (",",        "send-keys -X jump-reverse"),
+    (";",        "send-keys -X jump-again"),
+    ("F",        "send-keys -X jump-backward"),
+    ("N",        "send-keys -X search-reverse"),
+    ("R",        "send-keys -X rectangle-toggle"),
+    ("T",        "send-keys -X jump-to-backward"),
+    ("f",        "send-keys -X jump-forward"),
+    ("n",        "send-keys -X search-again"),
+    ("q",        "send-keys -X cancel"),
+    ("t",        "send-keys -X jump-to-forward"),
+    ("Home",     "send-keys -X start-of-line"),
+    ("End",      "send-keys -X end-of-line"),
+    ("NPage",    "send-keys -X page-down"),
+    ("PPage",    "send-keys -X page-up"),
+    ("Up",       "send-keys -X cursor-up"),
+    ("Down",     "send-keys -X cursor-down"),
+    ("Left",     "send-keys -X cursor-left"),
+    ("Right",    "send-keys -X cursor-right"),
+    ("M-<",      "send-keys -X history-top"),
+    ("M->",      "send-keys -X history-bottom"),
+    ("M-R",      "send-keys -X top-line"),
+    ("M-b",      "send-keys -X previous-word"),
+    ("C-M-f",    "send-keys -X next-matching-bracket"),
+    ("M-f",      "send-keys -X next-word-end"),
+    ("M-m",      "send-keys -X back-to-indentation"),
+    ("M-r",      "send-keys -X middle-line"),
+    ("M-v",      "send-keys -X page-up"),
+    ("M-w",      "send-keys -X copy-pipe-and-cancel"),
+    ("M-{",      "send-keys -X previous-paragraph"),
+    ("M-}",      "send-keys -X next-paragraph"),
+    ("M-Up",     "send-keys -X halfpage-up"),
+    ("M-Down",   "send-keys -X halfpage-down"),
+    ("C-Up",     "send-keys -X scroll-up"),
+    ("C-Down",   "send-keys -X scroll-down"),
+];
+
 // ─────────────────────────────────────────────────────────────────────
 // Sections below are used *only* by the overlay — they don't affect
 // key dispatching at all (that lives in input.rs).
@@ -175,6 +232,20 @@ const COPY_MODE_VI: &[(&str, &str)] = &[
     ("1-9",       "numeric prefix for motions"),
 ];
 
+/// Section for the default copy-mode (emacs) table, minus keys the user
+/// has rebound.
+pub fn copy_mode_lines(overridden: &std::collections::HashSet<&str>) -> Vec<String> {
+    let mut v = Vec::new();
+    v.push(String::new());
+    v.push("── copy-mode ─────────────────────────────────────────────".into());
+    for (k, cmd) in COPY_MODE_DEFAULTS {
+        if !overridden.contains(k) {
+            v.push(format!("bind-key -T copy-mode {} {}", k, cmd));
+        }
+    }
+    v
+}
+
 /// Section for copy-mode search bindings.
 pub fn copy_search_lines() -> Vec<String> {
     let mut v = Vec::new();
@@ -207,6 +278,7 @@ pub fn command_prompt_lines() -> Vec<String> {
 const COMMAND_PROMPT: &[(&str, &str)] = &[
     ("Escape",    "cancel"),
     ("Enter",     "execute command (saved to history)"),
+    ("Tab",       "complete command, flag, option, target or path"),
     ("Backspace", "delete char before cursor"),
     ("Delete",    "delete char at cursor"),
     ("Left",      "move cursor left"),
@@ -215,6 +287,7 @@ const COMMAND_PROMPT: &[(&str, &str)] = &[
     ("End",       "move cursor to end"),
     ("Up",        "history: older command"),
     ("Down",      "history: newer command"),
+    ("C-r",       "history: reverse incremental search"),
     ("C-a",       "move cursor to start"),
     ("C-e",       "move cursor to end"),
     ("C-u",       "kill line (clear to start)"),
@@ -251,7 +324,7 @@ const CLI_COMMANDS: &[(&str, &str, &str)] = &[
     ("rename-session",    "rename",   "Rename the current session"),
     ("switch-client",     "switchc",  "Switch to another session"),
     // Window
-    ("choose-tree",       "",         "Interactive session/window chooser"),
+    ("choose-tree",       "",         "Interactive session, window and pane tree"),
     ("find-window",       "findw",    "Search for a window by name"),
     ("kill-window",       "killw",    "Destroy the current window"),
     ("last-window",       "last",     "Select the previous window"),
@@ -275,6 +348,7 @@ const CLI_COMMANDS: &[(&str, &str, &str)] = &[
     ("last-pane",         "lastp",    "Select the previously active pane"),
     ("move-pane",         "movep",    "Move a pane to another window"),
     ("pipe-pane",         "pipep",    "Pipe pane output to a command"),
+    ("record-pane",       "",         "Record pane output to an asciicast file"),
     ("resize-pane",       "resizep",  "Resize a pane (-Z to zoom)"),
     ("respawn-pane",      "respawnp", "Restart the process in a pane"),
     ("select-pane",       "selectp",  "Select/focus a pane"),
@@ -286,12 +360,14 @@ const CLI_COMMANDS: &[(&str, &str, &str)] = &[
     ("select-layout",     "selectl",  "Apply a layout preset"),
     // Copy / Paste
     ("choose-buffer",     "chooseb",  "Interactive buffer chooser"),
+    ("hint-mode",         "",         "Label and copy URLs, paths, hashes on screen"),
     ("clear-history",     "clearhist","Clear pane scrollback"),
     ("copy-mode",         "",         "Enter copy mode"),
     ("delete-buffer",     "deleteb",  "Delete a paste buffer"),
     ("list-buffers",      "lsb",      "List paste buffers"),
     ("load-buffer",       "loadb",    "Load buffer from file"),
     ("paste-buffer",      "pasteb",   "Paste buffer into pane"),
+    ("paste-from-clipboard", "",      "Paste the system clipboard into pane"),
     ("save-buffer",       "saveb",    "Save buffer to file"),
     ("set-buffer",        "setb",     "Set a buffer's contents"),
     ("show-buffer",       "showb",    "Show buffer contents"),
@@ -310,6 +386,9 @@ const CLI_COMMANDS: &[(&str, &str, &str)] = &[
     ("display-menu",      "menu",     "Display an interactive menu"),
     ("display-message",   "display",  "Display a message / pane info"),
     ("display-popup",     "popup",    "Display a popup window"),
+    ("hide-popup",        "",         "Hide a popup, keeping its command running"),
+    ("move-popup",        "",         "Move a popup"),
+    ("resize-popup",      "",         "Resize a popup"),
     ("list-commands",     "lscm",     "List available commands"),
     ("server-info",       "info",     "Show server information"),
     // Misc
@@ -339,19 +418,25 @@ pub fn options_lines() -> Vec<String> {
     v
 }
 
-const OPTIONS_REF: &[(&str, &str)] = &[
+pub(crate) const OPTIONS_REF: &[(&str, &str)] = &[
     // Key
     ("prefix",                     "C-b"),
     ("prefix2",                    "none"),
     // Behaviour
     ("escape-time",                "500"),
+    ("history-file",               "\"\""),
+    ("prompt-history-limit",       "100"),
     ("base-index",                 "0"),
     ("pane-base-index",            "0"),
     ("history-limit",              "2000"),
     ("mouse",                      "on"),
+    ("wheel-scroll-lines",         "3"),
     ("mode-keys",                  "emacs"),
     ("focus-events",               "off"),
+    ("ambiguous-width",            "1"),
+    ("variation-selector-always-wide", "on"),
     ("remain-on-exit",             "off"),
+    ("remain-on-exit-format",      "\"Pane is dead (...)\""),
     ("renumber-windows",           "off"),
     ("aggressive-resize",          "off"),
     ("automatic-rename",           "on"),
@@ -391,6 +476,18 @@ const OPTIONS_REF: &[(&str, &str)] = &[
     ("message-style",              "bg=yellow,fg=black"),
     ("message-command-style",      "bg=black,fg=yellow"),
     ("mode-style",                 "bg=yellow,fg=black"),
+    ("copy-mode-match-style",      "bg=cyan,fg=black"),
+    ("copy-mode-current-match-style", "bg=magenta,fg=black"),
+    ("copy-mode-position-format",  "[#{scroll_position}/#{history_size}]..."),
+    ("pane-scrollbars",            "off"),
+    ("pane-scrollbars-style",      "bg=black,fg=white"),
+    ("popup-style",                "default"),
+    ("popup-border-style",         "fg=yellow"),
+    ("popup-border-lines",         "single"),
+    ("menu-style",                 "default"),
+    ("menu-selected-style",        "bg=yellow,fg=black"),
+    ("menu-border-style",          "default"),
+    ("menu-border-lines",          "single"),
     // Monitoring
     ("monitor-activity",           "off"),
     ("monitor-silence",            "0"),
@@ -403,6 +500,11 @@ const OPTIONS_REF: &[(&str, &str)] = &[
     // Copy / Clipboard
     ("copy-command",               "\"\""),
     ("set-clipboard",              "on"),
+    ("allow-clipboard-read",       "off"),
+    ("buffer-limit",               "50"),
+    ("hint-alphabet",              "asdfjklghqwertyuiopzxcvbnm"),
+    ("hint-patterns",              "url uuid ip path sha hex number"),
+    ("hint-style",                 "fg=black,bg=yellow,bold"),
     ("set-titles-string",          "\"\""),
     // psmux extensions
     ("cursor-style",               "\"\""),
@@ -453,7 +555,7 @@ pub fn hooks_lines() -> Vec<String> {
     v.push("  after-select-layout   after-copy-mode        after-set-option".into());
     v.push("  after-bind-key        after-unbind-key       after-source".into());
     v.push("  after-swap-pane       after-swap-window      client-attached".into());
-    v.push("  client-detached".into());
+    v.push("  client-detached       pane-died              pane-exited".into());
     v
 }
 
@@ -466,7 +568,7 @@ pub fn mouse_lines() -> Vec<String> {
     v.push("  Left click pane          focus pane (+ forward to child)".into());
     v.push("  Left click border        begin drag-resize".into());
     v.push("  Left drag border         resize split interactively".into());
-    v.push("  Scroll up/down           forward wheel to child (or copy mode scroll)".into());
+    v.push("  Scroll up/down           forward wheel to child, arrow keys in alt-screen apps (or copy mode scroll)".into());
     v
 }
 
@@ -506,6 +608,12 @@ pub fn build_overlay_lines(
     }
 
     // ── 3-8. Reference sections ──
+    let copy_overridden: std::collections::HashSet<&str> = user_bindings
+        .iter()
+        .filter(|(_, t, _, _)| t == "copy-mode")
+        .map(|(_, _, k, _)| k.as_str())
+        .collect();
+    lines.extend(copy_mode_lines(&copy_overridden));
     lines.extend(copy_mode_vi_lines());
     lines.extend(copy_search_lines());
     lines.extend(command_prompt_lines());
@@ -521,24 +629,28 @@ pub fn build_overlay_lines(
 /// Build the output for the CLI `list-keys` command (server-side).
 ///
 /// `user_tables` — iterator of `(table_name, key_str, action_str, repeat)`.
+/// `only_table` — restrict output to one key table (`list-keys -T`).
 pub fn build_list_keys_output<'a>(
     user_tables: impl Iterator<Item = (&'a str, String, String, bool)>,
+    only_table: Option<&str>,
 ) -> String {
     let mut output = String::new();
-    let mut overridden: std::collections::HashSet<String> = std::collections::HashSet::new();
-
-    // Peek at prefix-table user bindings first to know what's overridden
-    let user_entries: Vec<(&str, String, String, bool)> = user_tables.collect();
-    for (table, key, _, _) in &user_entries {
-        if *table == "prefix" {
-            overridden.insert(key.clone());
-        }
-    }
+    let user_entries: Vec<(&str, String, String, bool)> = user_tables
+        .filter(|(table, _, _, _)| only_table.map_or(true, |t| t == *table))
+        .collect();
+    // User bindings replace the defaults for the same key
+    let overridden = |table: &str, key: &str| {
+        user_entries.iter().any(|(t, k, _, _)| *t == table && k == key)
+    };
 
     // Defaults
-    for (k, cmd) in PREFIX_DEFAULTS {
-        if !overridden.contains(*k) {
-            output.push_str(&format!("bind-key -T prefix {} {}\n", k, cmd));
+    let defaults: [(&str, &[(&str, &str)]); 2] = [("prefix", PREFIX_DEFAULTS), ("copy-mode", COPY_MODE_DEFAULTS)];
+    for (table, entries) in defaults {
+        if only_table.map_or(false, |t| t != table) { continue; }
+        for (k, cmd) in entries {
+            if !overridden(table, k) {
+                output.push_str(&format!("bind-key -T {} {} {}\n", table, k, cmd));
+            }
         }
     }
 
@@ -0,0 +1,221 @@
+//! Hint mode (`hint-mode`, `Prefix F`), in the spirit of tmux-thumbs and
+//! tmux-fingers.
+//!
+//! The visible part of the active pane is scanned with the `hint-patterns`
+//! regexes and every match is labelled with letters from `hint-alphabet`.
+//! Typing a label copies the match like a copy-mode yank; typing it in
+//! upper case pastes the match into the pane instead, or pipes it to the
+//! command given with `-c`.
+
+use std::io;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::tree::active_pane;
+use crate::types::{AppState, HintState, Mode};
+
+/// Default for the `hint-alphabet` option: home row first.
+pub const DEFAULT_ALPHABET: &str = "asdfjklghqwertyuiopzxcvbnm";
+
+/// Built-in `hint-patterns` as (name, regex), highest priority first.
+/// Where matches overlap the earlier pattern wins, so a UUID is not also
+/// offered as a SHA.
+pub const DEFAULT_PATTERNS: &[(&str, &str)] = &[
+    ("url", r#"(?:https?|ftp|file)://[^\s<>"'`]+[^\s<>"'`.,;:)\]]"#),
+    ("uuid", r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b"),
+    ("ip", r"\b\d{1,3}(?:\.\d{1,3}){3}(?::\d+)?\b"),
+    ("path", r"(?:[A-Za-z]:)?(?:[~.\w-]*[/\\])+[\w.-]+(?::\d+){0,2}|\b[\w.-]+\.\w+:\d+(?::\d+)?"),
+    ("sha", r"\b[0-9a-f]{7,40}\b"),
+    ("hex", r"\b0x[0-9a-fA-F]+\b"),
+    ("number", r"\b\d{4,}\b"),
+];
+
+pub fn default_patterns() -> Vec<(String, String)> {
+    DEFAULT_PATTERNS.iter().map(|(n, r)| (n.to_string(), r.to_string())).collect()
+}
+
+/// Apply a `hint-patterns` value.  `name=regex` adds a pattern or replaces
+/// the one with that name, `name=` removes it, and a bare regex is added
+/// under a generated name.
+pub fn set_pattern(app: &mut AppState, value: &str) {
+    let (name, regex) = match value.split_once('=') {
+        Some((n, r)) if !n.is_empty() && n.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') => (n.to_string(), r.to_string()),
+        _ => (format!("pattern{}", app.hint_patterns.len()), value.to_string()),
+    };
+    match app.hint_patterns.iter().position(|(n, _)| *n == name) {
+        Some(i) if regex.is_empty() => { app.hint_patterns.remove(i); }
+        Some(i) => app.hint_patterns[i].1 = regex,
+        None if regex.is_empty() => {}
+        None => app.hint_patterns.push((name, regex)),
+    }
+}
+
+/// Enter hint mode.  `-c command` sets what upper-case labels do.
+pub fn open(app: &mut AppState, args: &[&str]) {
+    let mut command = None;
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "-c" {
+            command = args.get(i + 1).map(|s| s.to_string());
+            i += 1;
+        }
+        i += 1;
+    }
+    let from_copy = matches!(app.mode, Mode::CopyMode);
+    let (matches, labels) = scan(app);
+    if matches.is_empty() {
+        app.status_message = Some(("No hints".to_string(), std::time::Instant::now()));
+        return;
+    }
+    app.mode = Mode::HintMode { hints: HintState { matches, labels, input: String::new(), command, from_copy } };
+}
+
+/// Find pattern matches on the visible rows of the active pane.  Returns
+/// the matches as (row, col_start, col_end, index into labels) and the
+/// distinct match texts with their labels.
+fn scan(app: &AppState) -> (Vec<(u16, u16, u16, usize)>, Vec<(String, String)>) {
+    let regexes: Vec<regex::Regex> = app.hint_patterns.iter()
+        .filter_map(|(_, r)| regex::Regex::new(r).ok())
+        .collect();
+    let Some(win) = app.windows.get(app.active_idx) else { return (Vec::new(), Vec::new()) };
+    let Some(p) = active_pane(&win.root, &win.active_path) else { return (Vec::new(), Vec::new()) };
+    let Ok(parser) = p.term.lock() else { return (Vec::new(), Vec::new()) };
+    let screen = parser.screen();
+    let top = screen.history_len().saturating_sub(screen.scrollback());
+
+    // (row, col_start, col_end, text)
+    let mut found: Vec<(u16, u16, u16, String)> = Vec::new();
+    let mut row_text = String::new();
+    let mut byte_col = Vec::new();
+    for row in 0..p.last_rows {
+        crate::copy_mode::history_row_text(screen, top + row as usize, p.last_cols, &mut row_text, &mut byte_col);
+        // Byte ranges already claimed on this row by a higher-priority pattern
+        let mut taken: Vec<(usize, usize)> = Vec::new();
+        for re in &regexes {
+            for m in re.find_iter(&row_text) {
+                if m.start() == m.end() || taken.iter().any(|&(s, e)| m.start() < e && s < m.end()) { continue; }
+                taken.push((m.start(), m.end()));
+                found.push((row, byte_col[m.start()], byte_col[m.end()], m.as_str().to_string()));
+            }
+        }
+    }
+    found.sort_by_key(|&(r, s, _, _)| (r, s));
+
+    // Equal texts share a label; the most recent output gets the first labels.
+    let mut texts: Vec<String> = Vec::new();
+    for (_, _, _, t) in found.iter().rev() {
+        if !texts.contains(t) { texts.push(t.clone()); }
+    }
+    let labels = make_labels(&app.hint_alphabet, texts.len());
+    let labels: Vec<(String, String)> = labels.into_iter().zip(texts).collect();
+    let matches = found.into_iter()
+        .filter_map(|(r, s, e, t)| labels.iter().position(|(_, lt)| *lt == t).map(|i| (r, s, e, i)))
+        .collect();
+    (matches, labels)
+}
+
+/// `n` labels of equal length over `alphabet`, so no label is a prefix of
+/// another and every label takes the same number of keystrokes.
+pub fn make_labels(alphabet: &str, n: usize) -> Vec<String> {
+    let mut letters: Vec<char> = Vec::new();
+    for c in alphabet.chars().map(|c| c.to_ascii_lowercase()) {
+        if c.is_ascii_alphanumeric() && !letters.contains(&c) { letters.push(c); }
+    }
+    if letters.len() < 2 { letters = DEFAULT_ALPHABET.chars().collect(); }
+    let k = letters.len();
+    let mut len = 1;
+    while k.pow(len) < n { len += 1; }
+    (0..n).map(|mut i| {
+        let mut label = vec![' '; len as usize];
+        for slot in label.iter_mut().rev() {
+            *slot = letters[i % k];
+            i /= k;
+        }
+        label.into_iter().collect()
+    }).collect()
+}
+
+/// Handle a key in hint mode.  Escape, C-c and C-g cancel; Backspace undoes
+/// a typed letter; letters that do not continue any label are ignored.
+pub fn hint_key(app: &mut AppState, key: KeyEvent) -> io::Result<()> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let (c, upper) = match key.code {
+        KeyCode::Esc => { close(app); return Ok(()); }
+        KeyCode::Char('c') | KeyCode::Char('g') if ctrl => { close(app); return Ok(()); }
+        KeyCode::Char(c) if !ctrl => (c.to_ascii_lowercase(), c.is_uppercase() || key.modifiers.contains(KeyModifiers::SHIFT)),
+        KeyCode::Backspace => {
+            if let Mode::HintMode { hints } = &mut app.mode { hints.input.pop(); }
+            return Ok(());
+        }
+        _ => return Ok(()),
+    };
+    let Mode::HintMode { hints } = &mut app.mode else { return Ok(()) };
+    let typed = format!("{}{}", hints.input, c);
+    let hit = hints.labels.iter().find(|(l, _)| *l == typed).map(|(_, t)| (t.clone(), hints.command.clone()));
+    let Some((text, command)) = hit else {
+        if hints.labels.iter().any(|(l, _)| l.starts_with(&typed)) { hints.input = typed; }
+        return Ok(());
+    };
+    close(app);
+    if !upper {
+        crate::copy_mode::store_yank(app, text);
+    } else if let Some(cmd) = command {
+        crate::copy_mode::pipe_text_to_command(&text, &cmd);
+    } else {
+        crate::input::send_paste_to_active(app, &text)?;
+    }
+    Ok(())
+}
+
+fn close(app: &mut AppState) {
+    let from_copy = matches!(&app.mode, Mode::HintMode { hints } if hints.from_copy);
+    app.mode = if from_copy { Mode::CopyMode } else { Mode::Passthrough };
+}
+
+/// What the client draws for hint mode: matches still reachable with the
+/// typed letters, as (row, col_start, col_end, remaining label), relative
+/// to the active pane.
+#[derive(Serialize, Deserialize, Default)]
+pub struct HintView {
+    pub items: Vec<(u16, u16, u16, String)>,
+    pub style: String,
+    pub match_style: String,
+}
+
+pub fn hint_view(app: &AppState, hints: &HintState) -> HintView {
+    let items = hints.matches.iter()
+        .filter_map(|&(r, s, e, i)| {
+            let label = &hints.labels[i].0;
+            label.strip_prefix(hints.input.as_str()).map(|rest| (r, s, e, rest.to_string()))
+        })
+        .collect();
+    HintView { items, style: app.hint_style.clone(), match_style: app.copy_mode_match_style.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_are_prefix_free() {
+        assert_eq!(make_labels("abc", 3), vec!["a", "b", "c"]);
+        let labels = make_labels("abc", 4);
+        assert_eq!(labels.len(), 4);
+        assert!(labels.iter().all(|l| l.len() == 2));
+        assert_eq!(labels[0], "aa");
+        assert_eq!(labels[3], "ba");
+    }
+
+    #[test]
+    fn default_patterns_compile_and_match() {
+        let find = |name: &str, text: &str| {
+            let (_, re) = DEFAULT_PATTERNS.iter().find(|(n, _)| *n == name).unwrap();
+            regex::Regex::new(re).unwrap().find(text).map(|m| m.as_str().to_string())
+        };
+        assert_eq!(find("url", "see https://example.com/a?b=1)."), Some("https://example.com/a?b=1".into()));
+        assert_eq!(find("path", "error at src/main.rs:12:5 here"), Some("src/main.rs:12:5".into()));
+        assert_eq!(find("sha", "commit 903e340 done"), Some("903e340".into()));
+        assert_eq!(find("ip", "host 10.0.0.1:22"), Some("10.0.0.1:22".into()));
+    }
+}
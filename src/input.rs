@@ -9,11 +9,11 @@ use crate::types::{AppState, Mode, FocusDir, LayoutKind, DragState, Node, Pane};
 use crate::tree::{active_pane, active_pane_mut, compute_rects, compute_split_borders,
     split_sizes_at, adjust_split_sizes, path_exists, resize_all_panes};
 use crate::pane::{create_window, split_active};
-use crate::commands::{execute_action, execute_command_prompt, execute_command_string};
-use crate::config::normalize_key_for_binding;
+use crate::commands::{execute_action, execute_command_string};
+use crate::config::{normalize_key_for_binding, parse_key_string};
 use crate::copy_mode::{enter_copy_mode, exit_copy_mode, switch_with_copy_save, move_copy_cursor,
     scroll_copy_up, scroll_copy_down, paste_latest, yank_selection,
-    search_copy_mode, search_next, search_prev, scroll_to_top, scroll_to_bottom};
+    search_next, search_prev, scroll_to_top, scroll_to_bottom};
 use crate::layout::{cycle_top_layout, apply_layout};
 use crate::window_ops::{toggle_zoom, swap_pane, break_pane_to_window};
 
@@ -181,9 +181,7 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                     return Ok(true);
                 }
                 KeyCode::Char('w') => {
-                    let tree = crate::commands::build_choose_tree(app);
-                    let selected = tree.iter().position(|e| e.is_current_session && e.is_active_window && !e.is_session_header).unwrap_or(0);
-                    app.mode = Mode::WindowChooser { selected, tree };
+                    crate::choose_tree::open(app, &["-Zw"]);
                     true
                 }
                 KeyCode::Char(',') => { app.mode = Mode::RenamePrompt { input: String::new() }; true }
@@ -191,7 +189,7 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                 KeyCode::Char('[') => { enter_copy_mode(app); true }
                 KeyCode::Char(']') => { paste_latest(app)?; app.mode = Mode::Passthrough; true }
                 KeyCode::Char(':') => {
-                    app.mode = Mode::CommandPrompt { input: String::new(), cursor: 0 };
+                    crate::prompt::open(app, &[]);
                     true
                 }
                 KeyCode::Char('q') => {
@@ -312,7 +310,12 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                 }
                 // --- buffer chooser (=) ---
                 KeyCode::Char('=') => {
-                    app.mode = Mode::BufferChooser { selected: 0 };
+                    crate::buffers::open_chooser(app, &[]);
+                    true
+                }
+                // --- hint mode (F) ---
+                KeyCode::Char('F') => {
+                    crate::hints::open(app, &[]);
                     true
                 }
                 _ => false,
@@ -337,162 +340,11 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
             Ok(false)
         }
         Mode::CommandPrompt { .. } => {
-            match key.code {
-                KeyCode::Esc => { app.mode = Mode::Passthrough; }
-                KeyCode::Enter => {
-                    // Save to history before executing
-                    if let Mode::CommandPrompt { input, .. } = &app.mode {
-                        if !input.is_empty() {
-                            let cmd = input.clone();
-                            app.command_history.push(cmd);
-                            app.command_history_idx = app.command_history.len();
-                        }
-                    }
-                    execute_command_prompt(app)?;
-                }
-                KeyCode::Backspace => {
-                    if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                        if *cursor > 0 {
-                            input.remove(*cursor - 1);
-                            *cursor -= 1;
-                        }
-                    }
-                }
-                KeyCode::Delete => {
-                    if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                        if *cursor < input.len() {
-                            input.remove(*cursor);
-                        }
-                    }
-                }
-                KeyCode::Left => {
-                    if let Mode::CommandPrompt { cursor, .. } = &mut app.mode {
-                        if *cursor > 0 { *cursor -= 1; }
-                    }
-                }
-                KeyCode::Right => {
-                    if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                        if *cursor < input.len() { *cursor += 1; }
-                    }
-                }
-                KeyCode::Home => {
-                    if let Mode::CommandPrompt { cursor, .. } = &mut app.mode {
-                        *cursor = 0;
-                    }
-                }
-                KeyCode::End => {
-                    if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                        *cursor = input.len();
-                    }
-                }
-                KeyCode::Up => {
-                    // Cycle through command history (older)
-                    if app.command_history_idx > 0 {
-                        app.command_history_idx -= 1;
-                        let cmd = app.command_history[app.command_history_idx].clone();
-                        let len = cmd.len();
-                        if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                            *input = cmd;
-                            *cursor = len;
-                        }
-                    }
-                }
-                KeyCode::Down => {
-                    // Cycle through command history (newer)
-                    if app.command_history_idx < app.command_history.len() {
-                        app.command_history_idx += 1;
-                        let cmd = if app.command_history_idx < app.command_history.len() {
-                            app.command_history[app.command_history_idx].clone()
-                        } else {
-                            String::new()
-                        };
-                        let len = cmd.len();
-                        if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                            *input = cmd;
-                            *cursor = len;
-                        }
-                    }
-                }
-                KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    // Ctrl+A: move to beginning
-                    if let Mode::CommandPrompt { cursor, .. } = &mut app.mode {
-                        *cursor = 0;
-                    }
-                }
-                KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    // Ctrl+E: move to end
-                    if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                        *cursor = input.len();
-                    }
-                }
-                KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    // Ctrl+U: kill line (clear from cursor to start)
-                    if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                        input.drain(..*cursor);
-                        *cursor = 0;
-                    }
-                }
-                KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    // Ctrl+K: kill to end of line
-                    if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                        input.truncate(*cursor);
-                    }
-                }
-                KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    // Ctrl+W: delete word backwards
-                    if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                        let mut pos = *cursor;
-                        while pos > 0 && input.as_bytes().get(pos - 1) == Some(&b' ') { pos -= 1; }
-                        while pos > 0 && input.as_bytes().get(pos - 1) != Some(&b' ') { pos -= 1; }
-                        input.drain(pos..*cursor);
-                        *cursor = pos;
-                    }
-                }
-                KeyCode::Char(c) => {
-                    if let Mode::CommandPrompt { input, cursor } = &mut app.mode {
-                        input.insert(*cursor, c);
-                        *cursor += 1;
-                    }
-                }
-                _ => {}
-            }
+            crate::prompt::key(app, key)?;
             Ok(false)
         }
-        Mode::WindowChooser { selected, ref tree } => {
-            let tree_len = tree.len();
-            match key.code {
-                KeyCode::Esc | KeyCode::Char('q') => { app.mode = Mode::Passthrough; }
-                KeyCode::Up | KeyCode::Char('k') => {
-                    if selected > 0 { if let Mode::WindowChooser { selected: s, .. } = &mut app.mode { *s -= 1; } }
-                }
-                KeyCode::Down | KeyCode::Char('j') => {
-                    if selected + 1 < tree_len { if let Mode::WindowChooser { selected: s, .. } = &mut app.mode { *s += 1; } }
-                }
-                KeyCode::Enter => {
-                    if let Mode::WindowChooser { selected: s, ref tree } = &app.mode {
-                        let entry = &tree[*s];
-                        if entry.is_current_session {
-                            // Same session: switch window directly
-                            if let Some(wi) = entry.window_index {
-                                app.last_window_idx = app.active_idx;
-                                app.active_idx = wi;
-                            }
-                        } else {
-                            // Different session: set env and trigger switch
-                            std::env::set_var("PSMUX_SWITCH_TO", &entry.session_name);
-                        }
-                    }
-                    app.mode = Mode::Passthrough;
-                }
-                KeyCode::Char(c) if c.is_ascii_digit() => {
-                    // Quick-select by window number
-                    let n = c.to_digit(10).unwrap_or(0) as usize;
-                    if let Some(idx) = tree.iter().position(|e| !e.is_session_header && e.window_index == Some(n) && e.is_current_session) {
-                        if let Mode::WindowChooser { selected: s, .. } = &mut app.mode { *s = idx; }
-                    }
-                }
-                _ => {}
-            }
+        Mode::WindowChooser { .. } => {
+            crate::choose_tree::key(app, key)?;
             Ok(false)
         }
         Mode::RenamePrompt { .. } => {
@@ -521,15 +373,6 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
             Ok(false)
         }
         Mode::CopyMode => {
-            // Check copy-mode key table for user bindings first (used by plugins like tmux-yank)
-            let table_name = if app.mode_keys == "vi" { "copy-mode-vi" } else { "copy-mode" };
-            let key_tuple = normalize_key_for_binding((key.code, key.modifiers));
-            if let Some(bind) = app.key_tables.get(table_name)
-                .and_then(|t| t.iter().find(|b| b.key == key_tuple))
-                .cloned()
-            {
-                return execute_action(app, &bind.action);
-            }
             // Handle register pending state (waiting for a-z after ")
             if app.copy_register_pending {
                 app.copy_register_pending = false;
@@ -557,16 +400,14 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
             if let Some(pending) = app.copy_find_char_pending.take() {
                 let n = app.copy_count.take().unwrap_or(1);
                 if let KeyCode::Char(ch) = key.code {
-                    match pending {
-                        0 => { for _ in 0..n { crate::copy_mode::find_char_forward(app, ch); } }
-                        1 => { for _ in 0..n { crate::copy_mode::find_char_backward(app, ch); } }
-                        2 => { for _ in 0..n { crate::copy_mode::find_char_to_forward(app, ch); } }
-                        3 => { for _ in 0..n { crate::copy_mode::find_char_to_backward(app, ch); } }
-                        _ => {}
-                    }
+                    for _ in 0..n { crate::copy_mode::find_char(app, pending, ch); }
                 }
                 return Ok(false);
             }
+            // Key table: user bindings (tmux-yank etc.), then the emacs defaults
+            if let Some(quit) = dispatch_copy_mode_table(app, (key.code, key.modifiers))? {
+                return Ok(quit);
+            }
             // Handle numeric prefix accumulation for copy-mode motions (vi-style)
             if let KeyCode::Char(d) = key.code {
                 if d.is_ascii_digit() && !key.modifiers.contains(KeyModifiers::CONTROL) && !key.modifiers.contains(KeyModifiers::ALT) {
@@ -599,14 +440,8 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                 // Page scroll: C-b / PageUp = page up, C-f / PageDown = page down
                 KeyCode::PageUp => { scroll_copy_up(app, 10); }
                 KeyCode::PageDown => { scroll_copy_down(app, 10); }
-                KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    if app.mode_keys == "emacs" { move_copy_cursor(app, -1, 0); }
-                    else { scroll_copy_up(app, 10); }
-                }
-                KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    if app.mode_keys == "emacs" { move_copy_cursor(app, 1, 0); }
-                    else { scroll_copy_down(app, 10); }
-                }
+                KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => { scroll_copy_up(app, 10); }
+                KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => { scroll_copy_down(app, 10); }
                 // Half-page scroll: C-u / C-d
                 KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                     let half = app.windows.get(app.active_idx)
@@ -630,10 +465,10 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                 KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::ALT) => { crate::copy_mode::move_word_backward(app); }
                 KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::ALT) => { yank_selection(app)?; exit_copy_mode(app); }
                 KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    app.mode = Mode::CopySearch { input: String::new(), forward: true };
+                    crate::copy_mode::begin_copy_search(app, true, false, true);
                 }
                 KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    app.mode = Mode::CopySearch { input: String::new(), forward: false };
+                    crate::copy_mode::begin_copy_search(app, false, false, true);
                 }
                 KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                     exit_copy_mode(app);
@@ -659,8 +494,13 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                 KeyCode::Char('T') => { app.copy_find_char_pending = Some(3); app.copy_count = Some(copy_repeat); }
                 // D = copy from cursor to end of line
                 KeyCode::Char('D') => { crate::copy_mode::copy_end_of_line(app)?; exit_copy_mode(app); }
+                // Marks: X = set-mark, M-x = jump-to-mark (swaps with the cursor)
+                KeyCode::Char('X') => { crate::copy_mode::run_copy_mode_command(app, "set-mark", 1); }
+                KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::ALT) => {
+                    crate::copy_mode::run_copy_mode_command(app, "jump-to-mark", 1);
+                }
                 // Bracket matching: % = jump to matching bracket/paren/brace
-                KeyCode::Char('%') => { crate::copy_mode::move_matching_bracket(app); }
+                KeyCode::Char('%') => { crate::copy_mode::move_matching_bracket(app, true); }
                 // Paragraph jump: { = previous paragraph, } = next paragraph
                 KeyCode::Char('{') => { for _ in 0..copy_repeat { crate::copy_mode::move_prev_paragraph(app); } }
                 KeyCode::Char('}') => { for _ in 0..copy_repeat { crate::copy_mode::move_next_paragraph(app); } }
@@ -671,12 +511,8 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                 KeyCode::Home => { crate::copy_mode::move_to_line_start(app); }
                 KeyCode::End => { crate::copy_mode::move_to_line_end(app); }
                 KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    // vi: toggle rectangle selection, emacs: page down
-                    if app.mode_keys == "emacs" {
-                        scroll_copy_down(app, 10);
-                    } else {
-                        app.copy_selection_mode = crate::types::SelectionMode::Rect;
-                    }
+                    // vi: toggle rectangle selection (emacs C-v comes from the key table)
+                    app.copy_selection_mode = crate::types::SelectionMode::Rect;
                 }
                 KeyCode::Char('v') => {
                     // tmux parity #62: rectangle-toggle (not begin-selection)
@@ -705,13 +541,8 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                 KeyCode::Char('A') => {
                     // Append to buffer (yank + append to buffer 0)
                     if let (Some(_), Some(_)) = (app.copy_anchor, app.copy_pos) {
-                        // Save current buffer 0
-                        let prev = app.paste_buffers.first().cloned().unwrap_or_default();
-                        yank_selection(app)?;
-                        // buffer 0 is now the new yank; prepend old text
-                        if let Some(buf) = app.paste_buffers.first_mut() {
-                            let new_text = buf.clone();
-                            *buf = format!("{}{}", prev, new_text);
+                        if let Some(text) = crate::copy_mode::selection_text(app) {
+                            crate::copy_mode::append_yank(app, &text);
                         }
                         exit_copy_mode(app);
                     }
@@ -735,10 +566,10 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                 KeyCode::Char('y') => { yank_selection(app)?; exit_copy_mode(app); }
                 // --- copy-mode search ---
                 KeyCode::Char('/') => {
-                    app.mode = Mode::CopySearch { input: String::new(), forward: true };
+                    crate::copy_mode::begin_copy_search(app, true, true, false);
                 }
                 KeyCode::Char('?') => {
-                    app.mode = Mode::CopySearch { input: String::new(), forward: false };
+                    crate::copy_mode::begin_copy_search(app, false, true, false);
                 }
                 KeyCode::Char('n') => { search_next(app); }
                 KeyCode::Char('N') => { search_prev(app); }
@@ -764,35 +595,7 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
             Ok(false)
         }
         Mode::CopySearch { .. } => {
-            match key.code {
-                KeyCode::Esc => {
-                    // Cancel search, return to copy mode
-                    app.mode = Mode::CopyMode;
-                }
-                KeyCode::Enter => {
-                    // Execute search
-                    if let Mode::CopySearch { ref input, forward } = app.mode {
-                        let query = input.clone();
-                        let fwd = forward;
-                        app.copy_search_query = query.clone();
-                        app.copy_search_forward = fwd;
-                        search_copy_mode(app, &query, fwd);
-                        // Jump to first match
-                        if !app.copy_search_matches.is_empty() {
-                            let (r, c, _) = app.copy_search_matches[0];
-                            app.copy_pos = Some((r, c));
-                        }
-                    }
-                    app.mode = Mode::CopyMode;
-                }
-                KeyCode::Backspace => {
-                    if let Mode::CopySearch { ref mut input, .. } = app.mode { let _ = input.pop(); }
-                }
-                KeyCode::Char(c) => {
-                    if let Mode::CopySearch { ref mut input, .. } = app.mode { input.push(c); }
-                }
-                _ => {}
-            }
+            crate::copy_mode::search_prompt_key(app, key);
             Ok(false)
         }
         Mode::PaneChooser { .. } => {
@@ -810,56 +613,13 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
             }
             Ok(false)
         }
-        Mode::MenuMode { ref mut menu } => {
-            match key.code {
-                KeyCode::Esc | KeyCode::Char('q') => { 
-                    app.mode = Mode::Passthrough; 
-                }
-                KeyCode::Up | KeyCode::Char('k') => {
-                    if menu.selected > 0 {
-                        menu.selected -= 1;
-                        while menu.selected > 0 && menu.items.get(menu.selected).map(|i| i.is_separator).unwrap_or(false) {
-                            menu.selected -= 1;
-                        }
-                    }
-                }
-                KeyCode::Down | KeyCode::Char('j') => {
-                    if menu.selected + 1 < menu.items.len() {
-                        menu.selected += 1;
-                        while menu.selected + 1 < menu.items.len() && menu.items.get(menu.selected).map(|i| i.is_separator).unwrap_or(false) {
-                            menu.selected += 1;
-                        }
-                    }
-                }
-                KeyCode::Enter => {
-                    if let Some(item) = menu.items.get(menu.selected) {
-                        if !item.is_separator && !item.command.is_empty() {
-                            let cmd = item.command.clone();
-                            app.mode = Mode::Passthrough;
-                            let _ = execute_command_string(app, &cmd);
-                        } else {
-                            app.mode = Mode::Passthrough;
-                        }
-                    } else {
-                        app.mode = Mode::Passthrough;
-                    }
-                }
-                KeyCode::Char(c) => {
-                    if let Some((_idx, item)) = menu.items.iter().enumerate().find(|(_, i)| i.key == Some(c)) {
-                        if !item.is_separator && !item.command.is_empty() {
-                            let cmd = item.command.clone();
-                            app.mode = Mode::Passthrough;
-                            let _ = execute_command_string(app, &cmd);
-                        } else {
-                            app.mode = Mode::Passthrough;
-                        }
-                    }
-                }
-                _ => {}
-            }
+        Mode::MenuMode { .. } => {
+            let mods = if matches!(key.code, KeyCode::Char(_)) { key.modifiers - KeyModifiers::SHIFT } else { key.modifiers };
+            crate::menu::key(app, &crate::config::format_key_binding(&(key.code, mods)));
             Ok(false)
         }
-        Mode::PopupMode { ref mut output, ref mut process, close_on_exit, ref mut popup_pty, .. } => {
+        Mode::PopupMode { ref mut popup } => {
+            let crate::popup::Popup { ref mut output, ref mut process, close, ref mut popup_pty, .. } = *popup;
             let mut should_close = false;
             let mut exit_status: Option<std::process::ExitStatus> = None;
             
@@ -901,8 +661,8 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                     _ => {}
                 }
                 // Check if child exited
-                if let Ok(Some(_status)) = pty.child.try_wait() {
-                    if close_on_exit {
+                if let Ok(Some(status)) = pty.child.try_wait() {
+                    if close.closes(status.success()) {
                         should_close = true;
                     }
                 }
@@ -927,14 +687,14 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
                 if let Some(ref mut proc) = process {
                     if let Ok(Some(status)) = proc.try_wait() {
                         exit_status = Some(status);
-                        if close_on_exit {
+                        if close.closes(status.success()) {
                             should_close = true;
                         }
                     }
                 }
                 
                 if let Some(status) = exit_status {
-                    if !close_on_exit {
+                    if !should_close {
                         output.push_str(&format!("\n[Process exited with status: {}]", status));
                     }
                 }
@@ -971,45 +731,12 @@ pub fn handle_key(app: &mut AppState, key: KeyEvent) -> io::Result<bool> {
             app.mode = Mode::Passthrough;
             Ok(false)
         }
-        Mode::BufferChooser { selected } => {
-            match key.code {
-                KeyCode::Esc | KeyCode::Char('q') => { app.mode = Mode::Passthrough; }
-                KeyCode::Up | KeyCode::Char('k') => {
-                    if selected > 0 {
-                        if let Mode::BufferChooser { selected: s } = &mut app.mode { *s -= 1; }
-                    }
-                }
-                KeyCode::Down | KeyCode::Char('j') => {
-                    let max = app.paste_buffers.len().saturating_sub(1);
-                    if selected < max {
-                        if let Mode::BufferChooser { selected: s } = &mut app.mode { *s += 1; }
-                    }
-                }
-                KeyCode::Enter => {
-                    // Paste selected buffer
-                    if selected < app.paste_buffers.len() {
-                        let text = app.paste_buffers[selected].clone();
-                        app.mode = Mode::Passthrough;
-                        let win = &mut app.windows[app.active_idx];
-                        if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) {
-                            let _ = write!(p.writer, "{}", text);
-                        }
-                    } else {
-                        app.mode = Mode::Passthrough;
-                    }
-                }
-                KeyCode::Char('d') | KeyCode::Delete => {
-                    // Delete selected buffer
-                    if selected < app.paste_buffers.len() {
-                        app.paste_buffers.remove(selected);
-                        if let Mode::BufferChooser { selected: s } = &mut app.mode {
-                            if *s >= app.paste_buffers.len() && *s > 0 { *s -= 1; }
-                        }
-                        if app.paste_buffers.is_empty() { app.mode = Mode::Passthrough; }
-                    }
-                }
-                _ => {}
-            }
+        Mode::BufferChooser { .. } => {
+            crate::buffers::chooser_key(app, key)?;
+            Ok(false)
+        }
+        Mode::HintMode { .. } => {
+            crate::hints::hint_key(app, key)?;
             Ok(false)
         }
     }
@@ -1475,7 +1202,7 @@ fn wheel_cell_for_area(area: Rect, x: u16, y: u16) -> (u16, u16) {
 
 /// Paste the system clipboard content into the active pane.
 /// This is the Windows Terminal right-click-to-paste behavior.
-fn paste_clipboard_to_active(app: &mut AppState) -> io::Result<()> {
+pub fn paste_clipboard_to_active(app: &mut AppState) -> io::Result<()> {
     if let Some(text) = crate::copy_mode::read_from_system_clipboard() {
         if !text.is_empty() {
             send_paste_to_active(app, &text)?;
@@ -1495,7 +1222,7 @@ fn paste_clipboard_to_active(app: &mut AppState) -> io::Result<()> {
 /// When mouse protocol is NOT enabled (shell prompt), use Win32 MOUSE_EVENT
 /// injection as a harmless fallback (most programs ignore it).
 fn forward_mouse_to_pane(pane: &mut Pane, area: Rect, abs_x: u16, abs_y: u16, button_state: u32, event_flags: u32) {
-    forward_mouse_to_pane_ex(pane, area, abs_x, abs_y, button_state, event_flags, 0xff, false);
+    forward_mouse_to_pane_ex(pane, area, abs_x, abs_y, button_state, event_flags, 0xff, false, None);
 }
 
 /// Forward a mouse event to a child pane by writing SGR mouse sequences
@@ -1506,69 +1233,26 @@ fn forward_mouse_to_pane(pane: &mut Pane, area: Rect, abs_x: u16, abs_y: u16, bu
 /// and passes VT through for nvim/vim apps.  (fixes #60)
 fn forward_mouse_to_pane_ex(pane: &mut Pane, area: Rect, abs_x: u16, abs_y: u16,
                              _button_state: u32, _event_flags: u32,
-                             vt_button: u8, press: bool) {
+                             vt_button: u8, press: bool, cell_px: Option<(u16, u16)>) {
     let col = abs_x as i16 - area.x as i16;
     let row = abs_y as i16 - area.y as i16;
     crate::window_ops::inject_mouse_combined(
-        pane, col, row, vt_button, press, 0, 0, "client");
+        pane, col, row, vt_button, press, 0, 0, "client", cell_px);
 }
 
 pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io::Result<()> {
     use crossterm::event::{MouseEventKind, MouseButton};
+    let cell_px = app.client_cell_size;
 
-    // --- MenuMode: handle mouse clicks on menu items ---
+    // --- MenuMode: hover, click and scroll over the menus ---
     if let Mode::MenuMode { ref mut menu } = app.mode {
-        if matches!(me.kind, MouseEventKind::Down(MouseButton::Left)) {
-            // Recompute menu_area the same way as the renderer (app.rs).
-            let full_area = Rect {
-                x: 0, y: 0,
-                width: window_area.width,
-                height: window_area.height + app.status_lines as u16,
-            };
-            let item_count = menu.items.len();
-            let height = (item_count as u16 + 2).min(20);
-            let width = menu.items.iter().map(|i| i.name.len()).max().unwrap_or(10).max(menu.title.len()) as u16 + 8;
-            let menu_area = if let (Some(x), Some(y)) = (menu.x, menu.y) {
-                let x = if x < 0 { (full_area.width as i16 + x).max(0) as u16 } else { x as u16 };
-                let y = if y < 0 { (full_area.height as i16 + y).max(0) as u16 } else { y as u16 };
-                Rect { x: x.min(full_area.width.saturating_sub(width)), y: y.min(full_area.height.saturating_sub(height)), width, height }
-            } else {
-                crate::rendering::centered_rect((width * 100 / full_area.width.max(1)).max(30), height, full_area)
-            };
-            let pos = ratatui::layout::Position { x: me.column, y: me.row };
-            if menu_area.contains(pos) {
-                // Block border is 1 row top
-                let inner_y = me.row.saturating_sub(menu_area.y + 1);
-                let idx = inner_y as usize;
-                if idx < menu.items.len() && !menu.items[idx].is_separator && !menu.items[idx].command.is_empty() {
-                    let cmd = menu.items[idx].command.clone();
-                    app.mode = Mode::Passthrough;
-                    let _ = execute_command_string(app, &cmd);
-                } else {
-                    app.mode = Mode::Passthrough;
-                }
-            } else {
-                app.mode = Mode::Passthrough;
-            }
-            return Ok(());
-        }
-        if matches!(me.kind, MouseEventKind::ScrollUp) {
-            if menu.selected > 0 {
-                menu.selected -= 1;
-                while menu.selected > 0 && menu.items.get(menu.selected).map(|i| i.is_separator).unwrap_or(false) {
-                    menu.selected -= 1;
-                }
-            }
-            return Ok(());
-        }
-        if matches!(me.kind, MouseEventKind::ScrollDown) {
-            if menu.selected + 1 < menu.items.len() {
-                menu.selected += 1;
-                while menu.selected + 1 < menu.items.len() && menu.items.get(menu.selected).map(|i| i.is_separator).unwrap_or(false) {
-                    menu.selected += 1;
-                }
-            }
-            return Ok(());
+        match me.kind {
+            MouseEventKind::Down(MouseButton::Left) => { crate::menu::mouse_down(app, me.column, me.row); }
+            MouseEventKind::Up(MouseButton::Left) => { crate::menu::mouse_up(app, me.column, me.row); }
+            MouseEventKind::Moved | MouseEventKind::Drag(_) => { crate::menu::mouse_move(app, me.column, me.row); }
+            MouseEventKind::ScrollUp => crate::menu::navigate(menu, -1),
+            MouseEventKind::ScrollDown => crate::menu::navigate(menu, 1),
+            _ => {}
         }
         return Ok(());
     }
@@ -1700,7 +1384,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                         if crate::window_ops::pane_wants_mouse(active) {
                             forward_mouse_to_pane_ex(active, area, me.column, me.row,
                                 crate::platform::mouse_inject::FROM_LEFT_1ST_BUTTON_PRESSED, 0,
-                                0, true); // SGR button 0 = left, press
+                                0, true, cell_px); // SGR button 0 = left, press
                         }
                     }
                 }
@@ -1725,7 +1409,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                         if crate::window_ops::pane_wants_mouse(active) {
                             forward_mouse_to_pane_ex(active, area, me.column, me.row,
                                 crate::platform::mouse_inject::RIGHTMOST_BUTTON_PRESSED, 0,
-                                2, true); // SGR button 2 = right, press
+                                2, true, cell_px); // SGR button 2 = right, press
                         }
                     }
                 }
@@ -1743,7 +1427,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                     if crate::window_ops::pane_wants_mouse(active) {
                         forward_mouse_to_pane_ex(active, area, me.column, me.row,
                             crate::platform::mouse_inject::FROM_LEFT_2ND_BUTTON_PRESSED, 0,
-                            1, true); // SGR button 1 = middle, press
+                            1, true, cell_px); // SGR button 1 = middle, press
                     }
                 }
             }
@@ -1774,7 +1458,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                 if let Some(active) = active_pane_mut(&mut win.root, &win.active_path) {
                     if crate::window_ops::pane_wants_mouse(active) {
                         forward_mouse_to_pane_ex(active, area, me.column, me.row, 0, 0,
-                            0, false); // SGR button 0 = left, release
+                            0, false, cell_px); // SGR button 0 = left, release
                     }
                 }
             }
@@ -1789,7 +1473,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                     if let Some(active) = active_pane_mut(&mut win.root, &win.active_path) {
                         if crate::window_ops::pane_wants_mouse(active) {
                             forward_mouse_to_pane_ex(active, area, me.column, me.row, 0, 0,
-                                2, false); // SGR button 2 = right, release
+                                2, false, cell_px); // SGR button 2 = right, release
                         }
                     }
                 }
@@ -1801,7 +1485,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                 if let Some(active) = active_pane_mut(&mut win.root, &win.active_path) {
                     if crate::window_ops::pane_wants_mouse(active) {
                         forward_mouse_to_pane_ex(active, area, me.column, me.row, 0, 0,
-                            1, false); // SGR button 1 = middle, release
+                            1, false, cell_px); // SGR button 1 = middle, release
                     }
                 }
             }
@@ -1844,7 +1528,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                             forward_mouse_to_pane_ex(active, area, me.column, me.row,
                                 crate::platform::mouse_inject::FROM_LEFT_1ST_BUTTON_PRESSED,
                                 crate::platform::mouse_inject::MOUSE_MOVED,
-                                32, true); // SGR button 32 = left-drag
+                                32, true, cell_px); // SGR button 32 = left-drag
                         }
                     }
                 } else {
@@ -1874,7 +1558,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                     if crate::window_ops::pane_wants_mouse(active) {
                         forward_mouse_to_pane_ex(active, area, me.column, me.row,
                             0, crate::platform::mouse_inject::MOUSE_MOVED,
-                            35, true);
+                            35, true, cell_px);
                     }
                 }
             }
@@ -1917,7 +1601,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                         let button_state = ((wheel_delta as i32) << 16) as u32;
                         forward_mouse_to_pane_ex(active, area, me.column, me.row,
                             button_state, crate::platform::mouse_inject::MOUSE_WHEELED,
-                            64, true); // SGR button 64 = scroll-up
+                            64, true, cell_px); // SGR button 64 = scroll-up
                     }
                 }
             } else {
@@ -1964,7 +1648,7 @@ pub fn handle_mouse(app: &mut AppState, me: MouseEvent, window_area: Rect) -> io
                         let button_state = ((wheel_delta as i32) << 16) as u32;
                         forward_mouse_to_pane_ex(active, area, me.column, me.row,
                             button_state, crate::platform::mouse_inject::MOUSE_WHEELED,
-                            65, true); // SGR button 65 = scroll-down
+                            65, true, cell_px); // SGR button 65 = scroll-down
                     }
                 }
             }
@@ -2129,11 +1813,8 @@ pub fn send_text_to_active(app: &mut AppState, text: &str) -> io::Result<()> {
             app.mode = Mode::Passthrough;
             return Ok(());
         }
-        if let Mode::PopupMode { ref mut popup_pty, .. } = app.mode {
-            if let Some(ref mut pty) = popup_pty {
-                let _ = pty.writer.write_all(text.as_bytes());
-                let _ = pty.writer.flush();
-            }
+        if let Mode::PopupMode { ref mut popup } = app.mode {
+            popup.input(text.as_bytes());
         }
         return Ok(());
     }
@@ -2157,10 +1838,62 @@ pub fn send_text_to_active(app: &mut AppState, text: &str) -> io::Result<()> {
         }
         return Ok(());
     }
+    if matches!(app.mode, Mode::BufferChooser { .. }) {
+        for c in text.chars() {
+            let key = if c == '\x1b' {
+                KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)
+            } else {
+                KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+            };
+            crate::buffers::chooser_key(app, key)?;
+            if !matches!(app.mode, Mode::BufferChooser { .. }) { break; }
+        }
+        return Ok(());
+    }
+    if matches!(app.mode, Mode::CommandPrompt { .. }) {
+        for c in text.chars() {
+            let key = if c == '\x1b' {
+                KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)
+            } else {
+                KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+            };
+            crate::prompt::key(app, key)?;
+            if !matches!(app.mode, Mode::CommandPrompt { .. }) { break; }
+        }
+        return Ok(());
+    }
+    if matches!(app.mode, Mode::WindowChooser { .. }) {
+        for c in text.chars() {
+            let key = if c == '\x1b' {
+                KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)
+            } else {
+                KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+            };
+            crate::choose_tree::key(app, key)?;
+            if !matches!(app.mode, Mode::WindowChooser { .. }) { break; }
+        }
+        return Ok(());
+    }
+    if matches!(app.mode, Mode::HintMode { .. }) {
+        for c in text.chars() {
+            let key = if c == '\x1b' {
+                KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)
+            } else {
+                KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+            };
+            crate::hints::hint_key(app, key)?;
+            if !matches!(app.mode, Mode::HintMode { .. }) { break; }
+        }
+        return Ok(());
+    }
     if matches!(app.mode, Mode::MenuMode { .. }) {
-        // Escape closes menu; other text is ignored (menu is navigated via send-key)
         if text == "\x1b" {
-            app.mode = Mode::Passthrough;
+            crate::menu::key(app, "esc");
+        } else {
+            for c in text.chars() {
+                if !matches!(app.mode, Mode::MenuMode { .. }) { break; }
+                crate::menu::key(app, &c.to_string());
+            }
         }
         return Ok(());
     }
@@ -2193,12 +1926,11 @@ pub fn send_text_to_active(app: &mut AppState, text: &str) -> io::Result<()> {
         }
         return Ok(());
     }
-    // In copy-search mode, append characters to the search input
+    // In copy-search mode, characters edit the search input
     if matches!(app.mode, Mode::CopySearch { .. }) {
-        if let Mode::CopySearch { ref mut input, .. } = app.mode {
-            for c in text.chars() {
-                input.push(c);
-            }
+        for c in text.chars() {
+            crate::copy_mode::search_prompt_key(app, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+            if !matches!(app.mode, Mode::CopySearch { .. }) { break; }
         }
         return Ok(());
     }
@@ -2223,6 +1955,52 @@ pub fn send_text_to_active(app: &mut AppState, text: &str) -> io::Result<()> {
     Ok(())
 }
 
+/// Look a key up in the copy-mode key table for the current `mode-keys`.
+/// User bindings (`bind -T copy-mode[-vi]`) win; in emacs mode the built-in
+/// `copy-mode` table (`help::COPY_MODE_DEFAULTS`) follows.  Returns the
+/// action's quit flag when the key was bound, None otherwise.
+fn dispatch_copy_mode_table(app: &mut AppState, key: (KeyCode, KeyModifiers)) -> io::Result<Option<bool>> {
+    let vi = app.mode_keys == "vi";
+    let table_name = if vi { "copy-mode-vi" } else { "copy-mode" };
+    let key_tuple = normalize_key_for_binding(key);
+    if let Some(bind) = app.key_tables.get(table_name)
+        .and_then(|t| t.iter().find(|b| b.key == key_tuple))
+        .cloned()
+    {
+        return run_copy_binding(app, &bind.action).map(Some);
+    }
+    if vi { return Ok(None); }
+    let default = crate::help::COPY_MODE_DEFAULTS.iter()
+        .find(|(k, _)| parse_key_string(k).map(normalize_key_for_binding) == Some(key_tuple))
+        .map(|(_, cmd)| cmd.to_string());
+    match default {
+        Some(cmd) => run_copy_binding(app, &crate::types::Action::Command(cmd)).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Run a copy-mode binding.  `send-keys -X` commands are dispatched in
+/// place rather than round-tripping through the control port.
+fn run_copy_binding(app: &mut AppState, action: &crate::types::Action) -> io::Result<bool> {
+    if let crate::types::Action::Command(cmd) = action {
+        let args = crate::commands::parse_command_line(cmd);
+        let is_send_keys = matches!(args.first().map(|s| s.as_str()), Some("send-keys") | Some("send"));
+        if is_send_keys && args.iter().any(|a| a == "-X") {
+            let args: Vec<&str> = args[1..].iter().map(|a| a.as_str()).collect();
+            // -N wins; otherwise a typed numeric prefix repeats the command
+            let count = args.iter().position(|a| *a == "-N")
+                .and_then(|i| args.get(i + 1))
+                .and_then(|n| n.parse::<usize>().ok())
+                .or_else(|| app.copy_count.take())
+                .unwrap_or(1);
+            let copy_cmd = crate::copy_mode::send_keys_x_command(&args);
+            crate::copy_mode::run_copy_mode_command(app, &copy_cmd, count);
+            return Ok(false);
+        }
+    }
+    execute_action(app, action)
+}
+
 /// Dispatch a single character as a copy-mode action.
 fn handle_copy_mode_char(app: &mut AppState, c: char) -> io::Result<()> {
     // Handle text-object pending state (waiting for w/W after a/i)
@@ -2238,13 +2016,10 @@ fn handle_copy_mode_char(app: &mut AppState, c: char) -> io::Result<()> {
     }
     // Handle find-char pending state (waiting for char after f/F/t/T)
     if let Some(pending) = app.copy_find_char_pending.take() {
-        match pending {
-            0 => crate::copy_mode::find_char_forward(app, c),
-            1 => crate::copy_mode::find_char_backward(app, c),
-            2 => crate::copy_mode::find_char_to_forward(app, c),
-            3 => crate::copy_mode::find_char_to_backward(app, c),
-            _ => {}
-        }
+        crate::copy_mode::find_char(app, pending, c);
+        return Ok(());
+    }
+    if dispatch_copy_mode_table(app, (KeyCode::Char(c), KeyModifiers::NONE))?.is_some() {
         return Ok(());
     }
     match c {
@@ -2307,18 +2082,15 @@ fn handle_copy_mode_char(app: &mut AppState, c: char) -> io::Result<()> {
         }
         'A' => {
             if let (Some(_), Some(_)) = (app.copy_anchor, app.copy_pos) {
-                let prev = app.paste_buffers.first().cloned().unwrap_or_default();
-                yank_selection(app)?;
-                if let Some(buf) = app.paste_buffers.first_mut() {
-                    let new_text = buf.clone();
-                    *buf = format!("{}{}", prev, new_text);
+                if let Some(text) = crate::copy_mode::selection_text(app) {
+                    crate::copy_mode::append_yank(app, &text);
                 }
                 exit_copy_mode(app);
             }
         }
         'y' => { yank_selection(app)?; exit_copy_mode(app); }
-        '/' => { app.mode = Mode::CopySearch { input: String::new(), forward: true }; }
-        '?' => { app.mode = Mode::CopySearch { input: String::new(), forward: false }; }
+        '/' => { crate::copy_mode::begin_copy_search(app, true, true, false); }
+        '?' => { crate::copy_mode::begin_copy_search(app, false, true, false); }
         'n' => { search_next(app); }
         'N' => { search_prev(app); }
         'i' => { app.copy_text_object_pending = Some(1); }  // inner text object
@@ -2358,11 +2130,8 @@ pub fn send_key_to_active(app: &mut AppState, k: &str) -> io::Result<()> {
             _ => None,
         };
         if let Some(seq) = seq {
-            if let Mode::PopupMode { ref mut popup_pty, .. } = app.mode {
-                if let Some(ref mut pty) = popup_pty {
-                    let _ = pty.writer.write_all(seq.as_bytes());
-                    let _ = pty.writer.flush();
-                }
+            if let Mode::PopupMode { ref mut popup } = app.mode {
+                popup.input(seq.as_bytes());
             }
         }
         return Ok(());
@@ -2376,39 +2145,34 @@ pub fn send_key_to_active(app: &mut AppState, k: &str) -> io::Result<()> {
         }
         return Ok(());
     }
-    if matches!(app.mode, Mode::MenuMode { .. }) {
-        match k {
-            "up" => {
-                if let Mode::MenuMode { ref mut menu } = app.mode {
-                    if menu.selected > 0 { menu.selected -= 1; }
-                }
-            }
-            "down" => {
-                if let Mode::MenuMode { ref mut menu } = app.mode {
-                    let len = menu.items.len();
-                    if menu.selected + 1 < len { menu.selected += 1; }
-                }
-            }
-            "enter" => {
-                if let Mode::MenuMode { ref menu } = app.mode {
-                    if let Some(item) = menu.items.get(menu.selected) {
-                        if !item.is_separator && !item.command.is_empty() {
-                            let cmd = item.command.clone();
-                            app.mode = Mode::Passthrough;
-                            crate::config::parse_config_line(app, &cmd);
-                            return Ok(());
-                        }
-                    }
-                }
-                app.mode = Mode::Passthrough;
-            }
-            "esc" | "escape" | "q" => {
-                app.mode = Mode::Passthrough;
-            }
-            _ => {}
+    if matches!(app.mode, Mode::BufferChooser { .. }) {
+        if let Some((code, mods)) = parse_key_string(k) {
+            crate::buffers::chooser_key(app, KeyEvent::new(code, mods))?;
         }
         return Ok(());
     }
+    if matches!(app.mode, Mode::CommandPrompt { .. }) {
+        if let Some((code, mods)) = parse_key_string(k) {
+            crate::prompt::key(app, KeyEvent::new(code, mods))?;
+        }
+        return Ok(());
+    }
+    if matches!(app.mode, Mode::WindowChooser { .. }) {
+        if let Some((code, mods)) = parse_key_string(k) {
+            crate::choose_tree::key(app, KeyEvent::new(code, mods))?;
+        }
+        return Ok(());
+    }
+    if matches!(app.mode, Mode::HintMode { .. }) {
+        if let Some((code, mods)) = parse_key_string(k) {
+            crate::hints::hint_key(app, KeyEvent::new(code, mods))?;
+        }
+        return Ok(());
+    }
+    if matches!(app.mode, Mode::MenuMode { .. }) {
+        crate::menu::key(app, k);
+        return Ok(());
+    }
     if matches!(app.mode, Mode::PaneChooser { .. }) {
         match k {
             "esc" | "escape" => {
@@ -2418,34 +2182,21 @@ pub fn send_key_to_active(app: &mut AppState, k: &str) -> io::Result<()> {
         }
         return Ok(());
     }
-    // --- Copy-search mode: handle esc/enter/backspace ---
+    // --- Copy-search mode: editing, history and C-r ---
     if matches!(app.mode, Mode::CopySearch { .. }) {
-        match k {
-            "esc" => { app.mode = Mode::CopyMode; }
-            "enter" => {
-                if let Mode::CopySearch { ref input, forward } = app.mode {
-                    let query = input.clone();
-                    let fwd = forward;
-                    app.copy_search_query = query.clone();
-                    app.copy_search_forward = fwd;
-                    search_copy_mode(app, &query, fwd);
-                    if !app.copy_search_matches.is_empty() {
-                        let (r, c, _) = app.copy_search_matches[0];
-                        app.copy_pos = Some((r, c));
-                    }
-                }
-                app.mode = Mode::CopyMode;
-            }
-            "backspace" => {
-                if let Mode::CopySearch { ref mut input, .. } = app.mode { input.pop(); }
-            }
-            _ => {}
+        if let Some((code, mods)) = parse_key_string(k) {
+            crate::copy_mode::search_prompt_key(app, KeyEvent::new(code, mods));
         }
         return Ok(());
     }
 
-    // --- Copy mode: full vi-style key table ---
+    // --- Copy mode: key table first, then the built-in vi-style keys ---
     if matches!(app.mode, Mode::CopyMode) {
+        if let Some(key) = parse_key_string(k) {
+            if dispatch_copy_mode_table(app, key)?.is_some() {
+                return Ok(());
+            }
+        }
         match k {
             "esc" | "q" => {
                 exit_copy_mode(app);
@@ -2474,14 +2225,8 @@ pub fn send_key_to_active(app: &mut AppState, k: &str) -> io::Result<()> {
             "right" => { move_copy_cursor(app, 1, 0); }
             "home" => { crate::copy_mode::move_to_line_start(app); }
             "end" => { crate::copy_mode::move_to_line_end(app); }
-            "C-b" | "c-b" => {
-                if app.mode_keys == "emacs" { move_copy_cursor(app, -1, 0); }
-                else { scroll_copy_up(app, 10); }
-            }
-            "C-f" | "c-f" => {
-                if app.mode_keys == "emacs" { move_copy_cursor(app, 1, 0); }
-                else { scroll_copy_down(app, 10); }
-            }
+            "C-b" | "c-b" => { scroll_copy_up(app, 10); }
+            "C-f" | "c-f" => { scroll_copy_down(app, 10); }
             "C-n" | "c-n" => { move_copy_cursor(app, 0, 1); }
             "C-p" | "c-p" => { move_copy_cursor(app, 0, -1); }
             "C-a" | "c-a" => { crate::copy_mode::move_to_line_start(app); }
@@ -2491,8 +2236,8 @@ pub fn send_key_to_active(app: &mut AppState, k: &str) -> io::Result<()> {
             "M-f" | "m-f" => { crate::copy_mode::move_word_forward(app); }
             "M-b" | "m-b" => { crate::copy_mode::move_word_backward(app); }
             "M-w" | "m-w" => { yank_selection(app)?; exit_copy_mode(app); }
-            "C-s" | "c-s" => { app.mode = Mode::CopySearch { input: String::new(), forward: true }; }
-            "C-r" | "c-r" => { app.mode = Mode::CopySearch { input: String::new(), forward: false }; }
+            "C-s" | "c-s" => { crate::copy_mode::begin_copy_search(app, true, false, true); }
+            "C-r" | "c-r" => { crate::copy_mode::begin_copy_search(app, false, false, true); }
             "C-c" | "c-c" => {
                 exit_copy_mode(app);
             }
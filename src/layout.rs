@@ -2,9 +2,10 @@ use std::io;
 
 use serde::{Serialize, Deserialize};
 use unicode_width::UnicodeWidthStr;
+use ratatui::layout::Rect;
 
 use crate::types::{AppState, Node, LayoutKind, Mode};
-use crate::tree::get_split_mut;
+use crate::tree::{get_split_mut, split_with_gaps};
 use crate::util::infer_title_from_prompt;
 
 pub fn cycle_top_layout(app: &mut AppState) {
@@ -22,7 +23,22 @@ pub fn cycle_top_layout(app: &mut AppState) {
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct CellJson { pub text: String, pub fg: String, pub bg: String, pub bold: bool, pub italic: bool, pub underline: bool, pub inverse: bool, pub dim: bool, pub blink: bool, pub hidden: bool }
+pub struct CellJson { pub text: String, pub fg: String, pub bg: String, pub bold: bool, pub italic: bool, pub underline: bool, pub inverse: bool, pub dim: bool, pub blink: bool, pub hidden: bool, #[serde(default)] pub width: u16 }
+
+/// Display width of a cell as decided by the pane's parser.  The parser has
+/// already applied grapheme clustering, `ambiguous-width` and
+/// `variation-selector-always-wide`, so the width must never be re-derived
+/// from the cell text (unicode-width disagrees for exactly those cases).
+pub fn cell_width(cell: &vt100::Cell) -> u16 {
+    if cell.is_wide() { 2 } else { 1 }
+}
+
+/// True when a renderer measuring `text` on its own would get a different
+/// width than the parser assigned.  Such clusters are sent as runs of their
+/// own so the client can place them at an explicit column.
+pub fn width_mismatch(text: &str, width: u16) -> bool {
+    (UnicodeWidthStr::width(text).max(1) as u16) != width
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct CellRunJson {
@@ -38,6 +54,69 @@ pub struct RowRunsJson {
     pub runs: Vec<CellRunJson>,
 }
 
+// `flags` bits of a run.
+pub const FLAG_DIM: u8 = 1;
+pub const FLAG_BOLD: u8 = 2;
+pub const FLAG_ITALIC: u8 = 4;
+pub const FLAG_UNDERLINE: u8 = 8;
+pub const FLAG_INVERSE: u8 = 16;
+pub const FLAG_BLINK: u8 = 32;
+pub const FLAG_HIDDEN: u8 = 64;
+
+pub fn cell_flags(cell: &vt100::Cell) -> u8 {
+    let mut fl = 0u8;
+    if cell.dim() { fl |= FLAG_DIM; }
+    if cell.bold() { fl |= FLAG_BOLD; }
+    if cell.italic() { fl |= FLAG_ITALIC; }
+    if cell.underline() { fl |= FLAG_UNDERLINE; }
+    if cell.inverse() { fl |= FLAG_INVERSE; }
+    if cell.blink() { fl |= FLAG_BLINK; }
+    if cell.hidden() { fl |= FLAG_HIDDEN; }
+    fl
+}
+
+/// Builds the `rows_v2` runs of one row: consecutive cells with the same
+/// colours and flags are merged, and cells whose text width disagrees with
+/// the parser get a run of their own.
+#[derive(Default)]
+pub struct RunBuilder {
+    runs: Vec<CellRunJson>,
+    prev: Option<(vt100::Color, vt100::Color, u8)>,
+    prev_lone: bool,
+}
+
+impl RunBuilder {
+    pub fn push(&mut self, text: &str, fg: vt100::Color, bg: vt100::Color, flags: u8, width: u16) {
+        let lone = width_mismatch(text, width);
+        let merge = !lone && !self.prev_lone && self.prev == Some((fg, bg, flags));
+        match self.runs.last_mut() {
+            // push_str(&str) avoids allocating for merged cells
+            Some(last) if merge => {
+                last.text.push_str(text);
+                last.width = last.width.saturating_add(width);
+            }
+            _ => self.runs.push(CellRunJson {
+                text: text.to_string(),
+                fg: crate::util::color_to_name(fg).into_owned(),
+                bg: crate::util::color_to_name(bg).into_owned(),
+                flags,
+                width,
+            }),
+        }
+        self.prev = Some((fg, bg, flags));
+        self.prev_lone = lone;
+    }
+
+    /// A default-coloured blank cell.
+    pub fn push_blank(&mut self) {
+        self.push(" ", vt100::Color::Default, vt100::Color::Default, 0, 1);
+    }
+
+    pub fn finish(self) -> RowRunsJson {
+        RowRunsJson { runs: self.runs }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum LayoutJson {
@@ -59,6 +138,9 @@ pub enum LayoutJson {
         active: bool,
         copy_mode: bool,
         scroll_offset: usize,
+        /// Lines of scrollback above the screen (for scrollbars)
+        #[serde(default)]
+        history_size: usize,
         sel_start_row: Option<u16>,
         sel_start_col: Option<u16>,
         sel_end_row: Option<u16>,
@@ -69,6 +151,9 @@ pub enum LayoutJson {
         copy_cursor_row: Option<u16>,
         #[serde(default)]
         copy_cursor_col: Option<u16>,
+        /// Visible search matches as (row, col_start, col_end, current)
+        #[serde(default)]
+        search_hl: Vec<(u16, u16, u16, bool)>,
         #[serde(default)]
         content: Vec<Vec<CellJson>>,
         #[serde(default)]
@@ -76,9 +161,28 @@ pub enum LayoutJson {
     },
 }
 
+/// Collect every leaf of a LayoutJson tree with its rectangle.
+pub fn collect_leaf_rects_json<'a>(node: &'a LayoutJson, area: Rect, out: &mut Vec<(Rect, &'a LayoutJson)>) {
+    match node {
+        LayoutJson::Leaf { .. } => out.push((area, node)),
+        LayoutJson::Split { kind, sizes, children } => {
+            let effective_sizes: Vec<u16> = if sizes.len() == children.len() {
+                sizes.clone()
+            } else {
+                vec![(100 / children.len().max(1)) as u16; children.len()]
+            };
+            let rects = split_with_gaps(kind == "Horizontal", &effective_sizes, area);
+            for (child, rect) in children.iter().zip(rects) {
+                collect_leaf_rects_json(child, rect, out);
+            }
+        }
+    }
+}
+
 pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
     let in_copy_mode = matches!(app.mode, Mode::CopyMode | Mode::CopySearch { .. });
     let scroll_offset = app.copy_scroll_offset;
+    let search_hl = if in_copy_mode { crate::copy_mode::visible_search_matches(app) } else { Vec::new() };
     
     fn build(node: &mut Node, cur_path: &mut Vec<usize>, active_path: &[usize], include_full_content: bool) -> LayoutJson {
         match node {
@@ -93,32 +197,26 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
                 LayoutJson::Split { kind: k, sizes: sizes.clone(), children: ch }
             }
             Node::Leaf(p) => {
-                const FLAG_DIM: u8 = 1;
-                const FLAG_BOLD: u8 = 2;
-                const FLAG_ITALIC: u8 = 4;
-                const FLAG_UNDERLINE: u8 = 8;
-                const FLAG_INVERSE: u8 = 16;
-                const FLAG_BLINK: u8 = 32;
-                const FLAG_HIDDEN: u8 = 64;
-
                 let Ok(parser) = p.term.lock() else {
                     return LayoutJson::Leaf {
                         id: p.id, rows: p.last_rows, cols: p.last_cols,
                         cursor_row: 0, cursor_col: 0, alternate_screen: false,
                         hide_cursor: false,
-                        cursor_shape: p.cursor_shape.load(std::sync::atomic::Ordering::Relaxed),
+                        cursor_shape: crate::pane::CURSOR_SHAPE_UNSET,
                         active: *cur_path == active_path, copy_mode: false,
-                        scroll_offset: 0,
+                        scroll_offset: 0, history_size: 0,
                         sel_start_row: None, sel_start_col: None,
                         sel_end_row: None, sel_end_col: None,
                         sel_mode: None,
                         copy_cursor_row: None, copy_cursor_col: None,
+                        search_hl: vec![],
                         content: vec![], rows_v2: vec![],
                     };
                 };
                 let screen = parser.screen();
                 let (cr, cc) = screen.cursor_position();
                 let hide_cursor_flag = screen.hide_cursor();
+                let cursor_shape = screen.cursor_shape().unwrap_or(crate::pane::CURSOR_SHAPE_UNSET);
                 // ConPTY never passes through ESC[?1049h, so alternate_screen()
                 // is always false.  Use a heuristic instead: if the last row of
                 // the screen has non-blank content, this is a fullscreen TUI app.
@@ -166,47 +264,18 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
                     } else {
                         Vec::new()
                     };
-                    let mut runs: Vec<CellRunJson> = Vec::new();
+                    let mut runs = RunBuilder::default();
                     let mut c = 0;
-                    // Track previous cell's raw color enums for run-merging
-                    // without allocating strings on every cell.
-                    let mut prev_fg_raw: Option<vt100::Color> = None;
-                    let mut prev_bg_raw: Option<vt100::Color> = None;
-                    let mut prev_flags: u8 = 0;
                     while c < p.last_cols {
-                        // Process each cell inline to avoid per-cell String allocation.
-                        // The &str from cell.contents() can only be used inside the
-                        // if-let block (borrows from parser), so run-merging happens
-                        // here too — push_str(&str) avoids allocation for merged cells.
-                        let (width, cell_fg_raw, cell_bg_raw, flags) = if let Some(cell) = screen.cell(r, c) {
+                        // Process each cell inline: the &str from cell.contents()
+                        // borrows from the parser, so run-merging happens here too.
+                        let width = if let Some(cell) = screen.cell(r, c) {
                             let t = cell.contents();
                             let t = if t.is_empty() { " " } else { t };
                             let cell_fg = cell.fgcolor();
                             let cell_bg = cell.bgcolor();
-                            let mut w = UnicodeWidthStr::width(t) as u16;
-                            if w == 0 { w = 1; }
-                            let mut fl = 0u8;
-                            if cell.dim() { fl |= FLAG_DIM; }
-                            if cell.bold() { fl |= FLAG_BOLD; }
-                            if cell.italic() { fl |= FLAG_ITALIC; }
-                            if cell.underline() { fl |= FLAG_UNDERLINE; }
-                            if cell.inverse() { fl |= FLAG_INVERSE; }
-                            if cell.blink() { fl |= FLAG_BLINK; }
-                            if cell.hidden() { fl |= FLAG_HIDDEN; }
-
-                            // Run merging — push &str directly, no String allocation
-                            let merged = if let Some(last) = runs.last_mut() {
-                                if prev_fg_raw == Some(cell_fg) && prev_bg_raw == Some(cell_bg) && prev_flags == fl {
-                                    last.text.push_str(t);
-                                    last.width = last.width.saturating_add(w);
-                                    true
-                                } else { false }
-                            } else { false };
-                            if !merged {
-                                let fg = crate::util::color_to_name(cell_fg);
-                                let bg = crate::util::color_to_name(cell_bg);
-                                runs.push(CellRunJson { text: t.to_string(), fg: fg.into_owned(), bg: bg.into_owned(), flags: fl, width: w });
-                            }
+                            let w = cell_width(cell);
+                            runs.push(t, cell_fg, cell_bg, cell_flags(cell), w);
 
                             if need_full_content {
                                 let fg_str = crate::util::color_to_name(cell_fg).into_owned();
@@ -215,43 +284,31 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
                                     text: t.to_string(), fg: fg_str.clone(), bg: bg_str.clone(),
                                     bold: cell.bold(), italic: cell.italic(),
                                     underline: cell.underline(), inverse: cell.inverse(), dim: cell.dim(),
-                                    blink: cell.blink(), hidden: cell.hidden(),
+                                    blink: cell.blink(), hidden: cell.hidden(), width: w,
                                 });
                                 for _ in 1..w {
                                     row.push(CellJson {
                                         text: String::new(), fg: fg_str.clone(), bg: bg_str.clone(),
                                         bold: cell.bold(), italic: cell.italic(),
                                         underline: cell.underline(), inverse: cell.inverse(), dim: cell.dim(),
-                                        blink: cell.blink(), hidden: cell.hidden(),
+                                        blink: cell.blink(), hidden: cell.hidden(), width: 0,
                                     });
                                 }
                             }
 
-                            (w, cell_fg, cell_bg, fl)
+                            w
                         } else {
                             // No cell — default space
-                            let merged = if let Some(last) = runs.last_mut() {
-                                if prev_fg_raw == Some(vt100::Color::Default) && prev_bg_raw == Some(vt100::Color::Default) && prev_flags == 0 {
-                                    last.text.push(' ');
-                                    last.width = last.width.saturating_add(1);
-                                    true
-                                } else { false }
-                            } else { false };
-                            if !merged {
-                                runs.push(CellRunJson { text: " ".to_string(), fg: "default".to_string(), bg: "default".to_string(), flags: 0, width: 1 });
-                            }
+                            runs.push_blank();
                             if need_full_content {
                                 row.push(CellJson {
                                     text: " ".to_string(), fg: "default".to_string(), bg: "default".to_string(),
                                     bold: false, italic: false, underline: false, inverse: false, dim: false,
-                                    blink: false, hidden: false,
+                                    blink: false, hidden: false, width: 1,
                                 });
                             }
-                            (1u16, vt100::Color::Default, vt100::Color::Default, 0u8)
+                            1u16
                         };
-                        prev_fg_raw = Some(cell_fg_raw);
-                        prev_bg_raw = Some(cell_bg_raw);
-                        prev_flags = flags;
                         c = c.saturating_add(width.max(1));
                     }
                     if need_full_content {
@@ -267,11 +324,12 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
                                 dim: false,
                                 blink: false,
                                 hidden: false,
+                                width: 1,
                             });
                         }
                         lines.push(row);
                     }
-                    rows_v2.push(RowRunsJson { runs });
+                    rows_v2.push(runs.finish());
                 }
                 LayoutJson::Leaf {
                     id: p.id,
@@ -281,10 +339,11 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
                     cursor_col: cc,
                     alternate_screen,
                     hide_cursor: hide_cursor_flag,
-                    cursor_shape: p.cursor_shape.load(std::sync::atomic::Ordering::Relaxed),
+                    cursor_shape,
                     active: false,
                     copy_mode: false,
                     scroll_offset: 0,
+                    history_size: screen.history_len(),
                     sel_start_row: None,
                     sel_start_col: None,
                     sel_end_row: None,
@@ -292,6 +351,7 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
                     sel_mode: None,
                     copy_cursor_row: None,
                     copy_cursor_col: None,
+                    search_hl: Vec::new(),
                     content: lines,
                     rows_v2,
                 }
@@ -310,6 +370,7 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
         scroll_offset: usize,
         copy_anchor: Option<(u16, u16)>,
         copy_pos: Option<(u16, u16)>,
+        search_hl: &[(u16, u16, u16, bool)],
     ) {
         match node {
             LayoutJson::Leaf {
@@ -322,6 +383,7 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
                 sel_end_col,
                 copy_cursor_row,
                 copy_cursor_col,
+                search_hl: hl,
                 ..
             } => {
                 let is_active = idx >= path.len();
@@ -330,6 +392,7 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
                     *copy_mode = in_copy_mode;
                     *so = scroll_offset;
                     if in_copy_mode {
+                        *hl = search_hl.to_vec();
                         if let Some((pr, pc)) = copy_pos {
                             *copy_cursor_row = Some(pr);
                             *copy_cursor_col = Some(pc);
@@ -361,7 +424,7 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
             LayoutJson::Split { children, .. } => {
                 if idx < path.len() {
                     if let Some(child) = children.get_mut(path[idx]) {
-                        mark_active(child, path, idx + 1, in_copy_mode, scroll_offset, copy_anchor, copy_pos);
+                        mark_active(child, path, idx + 1, in_copy_mode, scroll_offset, copy_anchor, copy_pos, search_hl);
                     }
                 }
             }
@@ -375,6 +438,7 @@ pub fn dump_layout_json(app: &mut AppState) -> io::Result<String> {
         scroll_offset,
         app.copy_anchor,
         app.copy_pos,
+        &search_hl,
     );
     let s = serde_json::to_string(&root).map_err(|e| io::Error::new(io::ErrorKind::Other, format!("json error: {e}")))?;
     Ok(s)
@@ -391,6 +455,7 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
     let anchor_scroll = app.copy_anchor_scroll_offset;
     let cpos = app.copy_pos;
     let sel_mode = app.copy_selection_mode;
+    let search_hl = if in_copy { crate::copy_mode::visible_search_matches(app) } else { Vec::new() };
 
     // ── tiny helpers (no captures needed, so plain `fn` items) ───────
 
@@ -447,6 +512,7 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
         anchor_scroll: usize,
         cpos: Option<(u16, u16)>,
         sel_mode: crate::types::SelectionMode,
+        search_hl: &[(u16, u16, u16, bool)],
         out: &mut String,
     ) {
         match node {
@@ -465,21 +531,13 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                 for (i, c) in children.iter_mut().enumerate() {
                     if i > 0 { out.push(','); }
                     cur_path.push(i);
-                    write_node(c, cur_path, active_path, in_copy, scroll_off, anchor, anchor_scroll, cpos, sel_mode, out);
+                    write_node(c, cur_path, active_path, in_copy, scroll_off, anchor, anchor_scroll, cpos, sel_mode, search_hl, out);
                     cur_path.pop();
                 }
                 out.push_str("]}");
             }
 
             Node::Leaf(p) => {
-                const FLAG_DIM: u8      = 1;
-                const FLAG_BOLD: u8     = 2;
-                const FLAG_ITALIC: u8   = 4;
-                const FLAG_UNDERLINE: u8 = 8;
-                const FLAG_INVERSE: u8  = 16;
-                const FLAG_BLINK: u8    = 32;
-                const FLAG_HIDDEN: u8   = 64;
-
                 let is_active    = cur_path.as_slice() == active_path;
                 let need_content = in_copy && is_active;
 
@@ -493,7 +551,9 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                 struct CopyCell { text: String, fg: vt100::Color, bg: vt100::Color, bold: bool, italic: bool, underline: bool, inverse: bool, dim: bool, blink: bool, hidden: bool, width: u16 }
                 struct LeafSnap {
                     cr: u16, cc: u16, alt: bool,
+                    hist: usize,
                     hide_cursor: bool,
+                    cursor_shape: u8,
                     rows_v2: Vec<RowSnap>,
                     content: Vec<Vec<CopyCell>>,
                 }
@@ -501,11 +561,12 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                 let snap = 'snap: {
                     let parser = match p.term.lock() {
                         Ok(g) => g,
-                        Err(_) => break 'snap LeafSnap { cr: 0, cc: 0, alt: false, hide_cursor: false, rows_v2: vec![], content: vec![] },
+                        Err(_) => break 'snap LeafSnap { cr: 0, cc: 0, alt: false, hist: 0, hide_cursor: false, cursor_shape: crate::pane::CURSOR_SHAPE_UNSET, rows_v2: vec![], content: vec![] },
                     };
                     let screen = parser.screen();
                     let (cr, cc) = screen.cursor_position();
                     let hide_cursor = screen.hide_cursor();
+                    let cursor_shape = screen.cursor_shape().unwrap_or(crate::pane::CURSOR_SHAPE_UNSET);
 
                     // Alternate-screen heuristic
                     let alt = screen.alternate_screen() || {
@@ -540,6 +601,7 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                         let mut prev_fg: Option<vt100::Color> = None;
                         let mut prev_bg: Option<vt100::Color> = None;
                         let mut prev_fl: u8 = 0;
+                        let mut prev_lone = false;
 
                         while c < p.last_cols {
                             if let Some(cell) = screen.cell(r, c) {
@@ -547,18 +609,11 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                                 let t = if t.is_empty() { " " } else { t };
                                 let cfg = cell.fgcolor();
                                 let cbg = cell.bgcolor();
-                                let mut w = UnicodeWidthStr::width(t) as u16;
-                                if w == 0 { w = 1; }
-                                let mut fl = 0u8;
-                                if cell.dim()   { fl |= FLAG_DIM; }
-                                if cell.bold()  { fl |= FLAG_BOLD; }
-                                if cell.italic(){ fl |= FLAG_ITALIC; }
-                                if cell.underline() { fl |= FLAG_UNDERLINE; }
-                                if cell.inverse()   { fl |= FLAG_INVERSE; }
-                                if cell.blink()     { fl |= FLAG_BLINK; }
-                                if cell.hidden()    { fl |= FLAG_HIDDEN; }
+                                let w = cell_width(cell);
+                                let lone = width_mismatch(t, w);
+                                let fl = cell_flags(cell);
 
-                                if prev_fg == Some(cfg) && prev_bg == Some(cbg) && prev_fl == fl {
+                                if !lone && !prev_lone && prev_fg == Some(cfg) && prev_bg == Some(cbg) && prev_fl == fl {
                                     if let Some(last) = runs.last_mut() {
                                         last.text.push_str(t);
                                         last.width += w;
@@ -569,12 +624,13 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                                 prev_fg = Some(cfg);
                                 prev_bg = Some(cbg);
                                 prev_fl = fl;
+                                prev_lone = lone;
                                 c += w.max(1);
                             } else {
                                 let cfg = vt100::Color::Default;
                                 let cbg = vt100::Color::Default;
                                 let fl  = 0u8;
-                                if prev_fg == Some(cfg) && prev_bg == Some(cbg) && prev_fl == fl {
+                                if !prev_lone && prev_fg == Some(cfg) && prev_bg == Some(cbg) && prev_fl == fl {
                                     if let Some(last) = runs.last_mut() {
                                         last.text.push(' ');
                                         last.width += 1;
@@ -585,6 +641,7 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                                 prev_fg = Some(cfg);
                                 prev_bg = Some(cbg);
                                 prev_fl = fl;
+                                prev_lone = false;
                                 c += 1;
                             }
                         }
@@ -601,7 +658,7 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                                 if let Some(cell) = screen.cell(r, c) {
                                     let t = cell.contents();
                                     let t = if t.is_empty() { " " } else { t };
-                                    let w = UnicodeWidthStr::width(t).max(1) as u16;
+                                    let w = cell_width(cell);
                                     row_cells.push(CopyCell {
                                         text: t.to_string(), fg: cell.fgcolor(), bg: cell.bgcolor(),
                                         bold: cell.bold(), italic: cell.italic(), underline: cell.underline(),
@@ -620,7 +677,7 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                         }
                     }
 
-                    LeafSnap { cr, cc, alt, hide_cursor, rows_v2: snap_rows, content: snap_content }
+                    LeafSnap { cr, cc, alt, hist: screen.history_len(), hide_cursor, cursor_shape, rows_v2: snap_rows, content: snap_content }
                 };
                 // ── Parser mutex is now RELEASED ──
                 // All JSON string building below happens without holding the lock,
@@ -628,7 +685,6 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
 
                 // ── leaf header ──────────────────────────────────────
                 let so = if is_active && in_copy { scroll_off } else { 0 };
-                let cs = p.cursor_shape.load(std::sync::atomic::Ordering::Relaxed);
                 let _ = std::fmt::Write::write_fmt(out, format_args!(
                     concat!(
                         "{{\"type\":\"leaf\",\"id\":{},",
@@ -638,11 +694,11 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                         "\"hide_cursor\":{},",
                         "\"cursor_shape\":{},",
                         "\"active\":{},\"copy_mode\":{},",
-                        "\"scroll_offset\":{},"),
+                        "\"scroll_offset\":{},\"history_size\":{},"),
                     p.id, p.last_rows, p.last_cols,
                     snap.cr, snap.cc, snap.alt, snap.hide_cursor,
-                    cs,
-                    is_active, need_content, so,
+                    snap.cursor_shape,
+                    is_active, need_content, so, snap.hist,
                 ));
 
                 // selection bounds + copy cursor position
@@ -697,6 +753,12 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                     } else {
                         out.push_str("\"copy_cursor_row\":null,\"copy_cursor_col\":null,");
                     }
+                    out.push_str("\"search_hl\":[");
+                    for (i, (r, s, e, cur)) in search_hl.iter().enumerate() {
+                        if i > 0 { out.push(','); }
+                        let _ = std::fmt::Write::write_fmt(out, format_args!("[{},{},{},{}]", r, s, e, cur));
+                    }
+                    out.push_str("],");
                 } else {
                     out.push_str("\"sel_start_row\":null,\"sel_start_col\":null,\"sel_end_row\":null,\"sel_end_col\":null,\"sel_mode\":null,");
                     out.push_str("\"copy_cursor_row\":null,\"copy_cursor_col\":null,");
@@ -717,8 +779,8 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                             out.push_str("\",\"bg\":\"");
                             push_color(cell.bg, out);
                             let _ = std::fmt::Write::write_fmt(out, format_args!(
-                                "\",\"bold\":{},\"italic\":{},\"underline\":{},\"inverse\":{},\"dim\":{},\"blink\":{},\"hidden\":{}}}",
-                                cell.bold, cell.italic, cell.underline, cell.inverse, cell.dim, cell.blink, cell.hidden,
+                                "\",\"bold\":{},\"italic\":{},\"underline\":{},\"inverse\":{},\"dim\":{},\"blink\":{},\"hidden\":{},\"width\":{}}}",
+                                cell.bold, cell.italic, cell.underline, cell.inverse, cell.dim, cell.blink, cell.hidden, cell.width,
                             ));
                             // Emit width-2 filler cells
                             for _ in 1..cell.width {
@@ -727,7 +789,7 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                                 out.push_str("\",\"bg\":\"");
                                 push_color(cell.bg, out);
                                 let _ = std::fmt::Write::write_fmt(out, format_args!(
-                                    "\",\"bold\":{},\"italic\":{},\"underline\":{},\"inverse\":{},\"dim\":{},\"blink\":{},\"hidden\":{}}}",
+                                    "\",\"bold\":{},\"italic\":{},\"underline\":{},\"inverse\":{},\"dim\":{},\"blink\":{},\"hidden\":{},\"width\":0}}",
                                     cell.bold, cell.italic, cell.underline, cell.inverse, cell.dim, cell.blink, cell.hidden,
                                 ));
                             }
@@ -735,7 +797,7 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
                         // pad to full column width
                         let total_w: u16 = row.iter().map(|c| c.width).sum();
                         for _ in total_w..p.last_cols {
-                            out.push_str(",{\"text\":\" \",\"fg\":\"default\",\"bg\":\"default\",\"bold\":false,\"italic\":false,\"underline\":false,\"inverse\":false,\"dim\":false,\"blink\":false,\"hidden\":false}");
+                            out.push_str(",{\"text\":\" \",\"fg\":\"default\",\"bg\":\"default\",\"bold\":false,\"italic\":false,\"underline\":false,\"inverse\":false,\"dim\":false,\"blink\":false,\"hidden\":false,\"width\":1}");
                         }
                         out.push(']');
                     }
@@ -768,7 +830,7 @@ pub fn dump_layout_json_fast(app: &mut AppState) -> io::Result<String> {
     let mut out = String::with_capacity(32768);
     write_node(
         &mut win.root, &mut path, &active_path,
-        in_copy, scroll_off, anchor, anchor_scroll, cpos, sel_mode, &mut out,
+        in_copy, scroll_off, anchor, anchor_scroll, cpos, sel_mode, &search_hl, &mut out,
     );
     Ok(out)
 }
@@ -13,6 +13,16 @@ mod config;
 mod commands;
 mod pane;
 mod copy_mode;
+mod buffers;
+mod hints;
+mod popup;
+mod menu;
+mod session_view;
+mod choose_tree;
+mod prompt;
+mod capture;
+mod export;
+mod record;
 mod input;
 mod layout;
 mod window_ops;
@@ -68,6 +78,7 @@ fn run_main() -> io::Result<()> {
     // IMPORTANT: Only recognize -L as a global flag when it appears BEFORE the subcommand.
     // This avoids conflict with subcommand flags (e.g. select-pane -L, resize-pane -L).
     let mut l_socket_name: Option<String> = None;
+    let mut subcommand_idx = args.len();
     {
         let mut i = 1; // skip binary name
         while i < args.len() {
@@ -80,6 +91,7 @@ fn run_main() -> io::Result<()> {
             } else if arg.starts_with('-') {
                 i += 1; // skip single global flags (e.g. -v, -V)
             } else {
+                subcommand_idx = i;
                 break; // hit the subcommand name — stop scanning for global flags
             }
         }
@@ -89,10 +101,20 @@ fn run_main() -> io::Result<()> {
     // Supports session:window.pane format (e.g., "dev:0.1")
     // PSMUX_TARGET_SESSION stores the port file base name (for port file lookup)
     // PSMUX_TARGET_FULL stores the full target (session:window.pane) for the server
-    if let Some(pos) = args.iter().position(|a| a == "-t") {
+    // display-popup's own -t names what the popup shows, and switch-client's
+    // where the client goes; either may be another session, but the command
+    // still goes to the current one.
+    let popup_target = matches!(args.get(subcommand_idx).map(String::as_str), Some("display-popup" | "popup" | "switch-client" | "switchc"))
+        .then(|| args.iter().skip(subcommand_idx).position(|a| a == "-t"))
+        .flatten()
+        .and_then(|pos| args.get(subcommand_idx + pos + 1));
+    if let Some(target) = popup_target {
+        env::set_var("PSMUX_TARGET_FULL", target);
+    }
+    if let Some(pos) = args.iter().position(|a| a == "-t").filter(|&pos| popup_target.is_none() || pos < subcommand_idx) {
         if let Some(target) = args.get(pos + 1) {
             // Store the full target for the server to parse
-            env::set_var("PSMUX_TARGET_FULL", target);
+            env::set_var("PSMUX_TARGET_FULL", popup_target.unwrap_or(target));
             // Extract just the session name for port file lookup
             let session = extract_session_from_target(target);
             // Apply -L namespace prefix for port file lookup
@@ -206,6 +228,47 @@ fn run_main() -> io::Result<()> {
     }
 
     match cmd {
+        // play works without a server: inside a session it opens a new
+        // window, otherwise (or with -P) it plays in this terminal
+        "play" => {
+            let mut speed = 1.0;
+            let mut idle: Option<f64> = None;
+            let mut here = false;
+            let mut wait = false;
+            let mut file: Option<String> = None;
+            let mut i = 1;
+            while i < cmd_args.len() {
+                match cmd_args[i].as_str() {
+                    "-s" => { i += 1; speed = cmd_args.get(i).and_then(|v| v.parse().ok()).unwrap_or(1.0); }
+                    "-i" => { i += 1; idle = cmd_args.get(i).and_then(|v| v.parse().ok()); }
+                    "-P" => { here = true; }
+                    "-w" => { wait = true; }
+                    a => { file = Some(a.to_string()); }
+                }
+                i += 1;
+            }
+            let Some(file) = file else {
+                eprintln!("usage: psmux play [-P] [-s speed] [-i idle-limit] file.cast");
+                std::process::exit(1);
+            };
+            let path = env::current_dir().map(|d| d.join(&file)).unwrap_or_else(|_| file.clone().into());
+            if !here && env::var("PSMUX_TARGET_SESSION").is_ok() {
+                let mut line = format!("play -s {}", speed);
+                if let Some(idle) = idle { line.push_str(&format!(" -i {}", idle)); }
+                line.push_str(&format!(" \"{}\"\n", path.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\"")));
+                if send_control(line).is_ok() { return Ok(()); }
+            }
+            if let Err(e) = crate::record::play(&path, speed, idle) {
+                eprintln!("psmux play: {}: {}", file, e);
+                std::process::exit(1);
+            }
+            if wait {
+                print!("\r\n[end of recording, press Enter to close]");
+                let _ = io::stdout().flush();
+                let _ = io::stdin().read_line(&mut String::new());
+            }
+            return Ok(());
+        }
         // kill-server MUST be handled early before any potential fall-through
         "kill-server" => {
             let home = env::var("USERPROFILE").or_else(|_| env::var("HOME")).unwrap_or_default();
@@ -680,16 +743,16 @@ fn run_main() -> io::Result<()> {
                 if detached { cmd_line.push_str(" -d"); }
                 if print_info { cmd_line.push_str(" -P"); }
                 if let Some(ref fmt) = format_str {
-                    cmd_line.push_str(&format!(" -F \"{}\"", fmt.replace("\"", "\\\"")));
+                    cmd_line.push_str(&format!(" -F \"{}\"", fmt.replace('\\', "\\\\").replace('"', "\\\"")));
                 }
                 if let Some(name) = &name_arg {
-                    cmd_line.push_str(&format!(" -n \"{}\"", name.replace("\"", "\\\"")));
+                    cmd_line.push_str(&format!(" -n \"{}\"", name.replace('\\', "\\\\").replace('"', "\\\"")));
                 }
                 if let Some(dir) = &start_dir {
-                    cmd_line.push_str(&format!(" -c \"{}\"", dir.replace("\"", "\\\"")));
+                    cmd_line.push_str(&format!(" -c \"{}\"", dir.replace('\\', "\\\\").replace('"', "\\\"")));
                 }
                 if !cmd_arg.is_empty() {
-                    cmd_line.push_str(&format!(" \"{}\"", cmd_arg.replace("\"", "\\\"")));
+                    cmd_line.push_str(&format!(" \"{}\"", cmd_arg.replace('\\', "\\\\").replace('"', "\\\"")));
                 }
                 cmd_line.push('\n');
                 if print_info {
@@ -737,16 +800,16 @@ fn run_main() -> io::Result<()> {
                 if detached { cmd_line.push_str(" -d"); }
                 if print_info { cmd_line.push_str(" -P"); }
                 if let Some(ref fmt) = format_str {
-                    cmd_line.push_str(&format!(" -F \"{}\"", fmt.replace("\"", "\\\"")));
+                    cmd_line.push_str(&format!(" -F \"{}\"", fmt.replace('\\', "\\\\").replace('"', "\\\"")));
                 }
                 if let Some(dir) = &start_dir {
-                    cmd_line.push_str(&format!(" -c \"{}\"", dir.replace("\"", "\\\"")));
+                    cmd_line.push_str(&format!(" -c \"{}\"", dir.replace('\\', "\\\\").replace('"', "\\\"")));
                 }
                 if let Some(pct) = &size_pct {
                     cmd_line.push_str(&format!(" -p {}", pct));
                 }
                 if !cmd_arg.is_empty() {
-                    cmd_line.push_str(&format!(" \"{}\"", cmd_arg.replace("\"", "\\\"")));
+                    cmd_line.push_str(&format!(" \"{}\"", cmd_arg.replace('\\', "\\\\").replace('"', "\\\"")));
                 }
                 cmd_line.push('\n');
                 if print_info {
@@ -763,49 +826,38 @@ fn run_main() -> io::Result<()> {
             }
             "kill-pane" | "killp" => { send_control("kill-pane\n".to_string())?; return Ok(()); }
             "capture-pane" | "capturep" => {
-                // Parse optional flags - cmd_args[0] is command, start from 1
                 let mut cmd = "capture-pane".to_string();
-                let mut print_stdout = false;
+                for a in &cmd_args[1..] {
+                    cmd.push_str(&format!(" \"{}\"", a.replace('\\', "\\\\").replace('"', "\\\"")));
+                }
+                cmd.push('\n');
+                let args: Vec<&str> = cmd_args[1..].iter().map(|s| s.as_str()).collect();
+                let resp = send_control_with_response(cmd)?;
+                if crate::capture::parse_args(&args).print {
+                    print!("{}", resp);
+                } else if !resp.is_empty() {
+                    eprint!("{}", resp);
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            "record-pane" => {
+                let mut cmd = "record-pane".to_string();
                 let mut i = 1;
                 while i < cmd_args.len() {
-                    match cmd_args[i].as_str() {
-                        "-t" => {
-                            if let Some(target) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -t {}", target));
-                                i += 1;
-                            }
-                        }
-                        "-S" => {
-                            if let Some(start) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -S {}", start));
-                                i += 1;
-                            }
-                        }
-                        "-E" => {
-                            if let Some(end) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -E {}", end));
-                                i += 1;
-                            }
-                        }
-                        "-p" => { cmd.push_str(" -p"); print_stdout = true; }
-                        "-e" => { cmd.push_str(" -e"); }
-                        "-J" => { cmd.push_str(" -J"); }
-                        "-b" => {
-                            if let Some(buf) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -b {}", buf));
-                                i += 1;
-                            }
-                        }
-                        _ => {}
+                    let mut a = cmd_args[i].to_string();
+                    // The server has its own cwd, so hand it an absolute path
+                    if cmd_args[i - 1] == "-f" && std::path::Path::new(&a).is_relative() {
+                        if let Ok(cwd) = env::current_dir() { a = cwd.join(&a).to_string_lossy().into_owned(); }
                     }
+                    cmd.push_str(&format!(" \"{}\"", a.replace('\\', "\\\\").replace('"', "\\\"")));
                     i += 1;
                 }
                 cmd.push('\n');
-                if print_stdout {
-                    let resp = send_control_with_response(cmd)?;
-                    print!("{}", resp);
-                } else {
-                    send_control(cmd)?;
+                let resp = send_control_with_response(cmd)?;
+                if !resp.is_empty() {
+                    eprint!("{}", resp);
+                    std::process::exit(1);
                 }
                 return Ok(());
             }
@@ -906,7 +958,7 @@ fn run_main() -> io::Result<()> {
                         }
                         "-F" => {
                             if let Some(f) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -F \"{}\"", f.trim_matches('"').replace("\"", "\\\"")));
+                                cmd.push_str(&format!(" -F \"{}\"", f.trim_matches('"').replace('\\', "\\\\").replace('"', "\\\"")));
                                 i += 1;
                             }
                         }
@@ -929,7 +981,7 @@ fn run_main() -> io::Result<()> {
                         "-J" => { cmd.push_str(" -J"); }
                         "-F" => {
                             if let Some(f) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -F \"{}\"", f.trim_matches('"').replace("\"", "\\\"")));
+                                cmd.push_str(&format!(" -F \"{}\"", f.trim_matches('"').replace('\\', "\\\\").replace('"', "\\\"")));
                                 i += 1;
                             }
                         }
@@ -1191,6 +1243,8 @@ fn run_main() -> io::Result<()> {
             // set-buffer - Set buffer contents
             "set-buffer" | "setb" => {
                 let mut buffer_name: Option<String> = None;
+                let mut new_name: Option<String> = None;
+                let mut flags = String::new();
                 let mut data: Option<String> = None;
                 let mut i = 1;
                 while i < cmd_args.len() {
@@ -1201,6 +1255,13 @@ fn run_main() -> io::Result<()> {
                                 i += 1;
                             }
                         }
+                        "-n" => {
+                            if let Some(n) = cmd_args.get(i + 1) {
+                                new_name = Some(n.to_string());
+                                i += 1;
+                            }
+                        }
+                        "-a" | "-w" => { flags.push_str(&format!(" {}", cmd_args[i])); }
                         s if !s.starts_with('-') => {
                             data = Some(s.to_string());
                         }
@@ -1209,8 +1270,10 @@ fn run_main() -> io::Result<()> {
                     i += 1;
                 }
                 let mut cmd = "set-buffer".to_string();
-                if let Some(b) = buffer_name { cmd.push_str(&format!(" -b {}", b)); }
-                if let Some(d) = data { cmd.push_str(&format!(" {}", d)); }
+                cmd.push_str(&flags);
+                if let Some(b) = buffer_name { cmd.push_str(&format!(" -b \"{}\"", b.replace('\\', "\\\\").replace('"', "\\\""))); }
+                if let Some(n) = new_name { cmd.push_str(&format!(" -n \"{}\"", n.replace('\\', "\\\\").replace('"', "\\\""))); }
+                if let Some(d) = data { cmd.push_str(&format!(" \"{}\"", d.replace('\\', "\\\\").replace('"', "\\\""))); }
                 cmd.push('\n');
                 send_control(cmd)?;
                 return Ok(());
@@ -1241,6 +1304,11 @@ fn run_main() -> io::Result<()> {
                 print!("{}", resp);
                 return Ok(());
             }
+            // paste-from-clipboard - Paste the system clipboard into pane
+            "paste-from-clipboard" => {
+                send_control("paste-from-clipboard\n".to_string())?;
+                return Ok(());
+            }
             // show-buffer - Show buffer contents
             "show-buffer" | "showb" => {
                 let mut buffer_name: Option<String> = None;
@@ -1428,9 +1496,10 @@ fn run_main() -> io::Result<()> {
                 while i < cmd_args.len() {
                     match cmd_args[i].as_str() {
                         "-d" => { cmd.push_str(" -d"); }
-                        "-t" => {
+                        "-f" => { cmd.push_str(" -f"); }
+                        "-t" | "-n" => {
                             if let Some(t) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -t {}", t));
+                                cmd.push_str(&format!(" {} {}", cmd_args[i], t));
                                 i += 1;
                             }
                         }
@@ -1523,7 +1592,12 @@ fn run_main() -> io::Result<()> {
             }
             // list-keys - List all key bindings
             "list-keys" | "lsk" => {
-                let resp = send_control_with_response("list-keys\n".to_string())?;
+                let table = cmd_args.iter().position(|a| a.as_str() == "-T").and_then(|i| cmd_args.get(i + 1));
+                let cmd = match table {
+                    Some(t) => format!("list-keys -T {}\n", t),
+                    None => "list-keys\n".to_string(),
+                };
+                let resp = send_control_with_response(cmd)?;
                 print!("{}", resp);
                 return Ok(());
             }
@@ -1556,7 +1630,7 @@ fn run_main() -> io::Result<()> {
                 let cmd_str: String = cmd_args.iter().map(|s| {
                     let s = s.as_str();
                     if s.contains(' ') {
-                        format!("\"{}\"", s.replace('"', "\\\""))
+                        format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
                     } else {
                         s.to_string()
                     }
@@ -1882,10 +1956,24 @@ fn run_main() -> io::Result<()> {
                 send_control("clock-mode\n".to_string())?;
                 return Ok(());
             }
+            // hint-mode - Label pattern matches on screen for quick copying
+            "hint-mode" => {
+                let mut cmd = "hint-mode".to_string();
+                for a in &cmd_args[1..] {
+                    cmd.push_str(&format!(" \"{}\"", a.replace('\\', "\\\\").replace('"', "\\\"")));
+                }
+                cmd.push('\n');
+                send_control(cmd)?;
+                return Ok(());
+            }
             // choose-buffer - List paste buffers interactively
             "choose-buffer" | "chooseb" => {
-                let resp = send_control_with_response("choose-buffer\n".to_string())?;
-                print!("{}", resp);
+                let mut cmd = "choose-buffer".to_string();
+                for a in &cmd_args[1..] {
+                    cmd.push_str(&format!(" \"{}\"", a.replace('\\', "\\\\").replace('"', "\\\"")));
+                }
+                cmd.push('\n');
+                send_control(cmd)?;
                 return Ok(());
             }
             // set-environment / setenv - Set environment variable
@@ -2107,40 +2195,10 @@ fn run_main() -> io::Result<()> {
             }
             // command-prompt - Open interactive command prompt
             "command-prompt" => {
+                // Quote every argument: prompts and the template usually have spaces
                 let mut cmd = "command-prompt".to_string();
-                let mut i = 1;
-                while i < cmd_args.len() {
-                    match cmd_args[i].as_str() {
-                        "-I" => {
-                            if let Some(t) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -I {}", t));
-                                i += 1;
-                            }
-                        }
-                        "-p" => {
-                            if let Some(t) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -p {}", t));
-                                i += 1;
-                            }
-                        }
-                        "-1" => { cmd.push_str(" -1"); }
-                        "-N" => { cmd.push_str(" -N"); }
-                        "-W" => { cmd.push_str(" -W"); }
-                        "-T" => {
-                            if let Some(t) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -T {}", t));
-                                i += 1;
-                            }
-                        }
-                        "-t" => {
-                            if let Some(t) = cmd_args.get(i + 1) {
-                                cmd.push_str(&format!(" -t {}", t));
-                                i += 1;
-                            }
-                        }
-                        _ => {}
-                    }
-                    i += 1;
+                for a in &cmd_args[1..] {
+                    cmd.push_str(&format!(" \"{}\"", a.replace('\\', "\\\\").replace('"', "\\\"")));
                 }
                 cmd.push('\n');
                 send_control(cmd)?;
@@ -2148,14 +2206,34 @@ fn run_main() -> io::Result<()> {
             }
             // display-menu - Display a menu
             "display-menu" | "menu" => {
-                let joined: String = cmd_args.iter().map(|s| s.as_str()).collect::<Vec<&str>>().join(" ");
-                send_control(format!("{}\n", joined))?;
+                // Quote every argument: item names and commands usually have spaces
+                let mut cmd = "display-menu".to_string();
+                for a in &cmd_args[1..] {
+                    cmd.push_str(&format!(" \"{}\"", a.replace('\\', "\\\\").replace('"', "\\\"")));
+                }
+                cmd.push('\n');
+                send_control(cmd)?;
                 return Ok(());
             }
             // display-popup - Display a popup window
             "display-popup" | "popup" => {
-                let joined: String = cmd_args.iter().map(|s| s.as_str()).collect::<Vec<&str>>().join(" ");
-                send_control(format!("{}\n", joined))?;
+                // Quote every argument so -T titles and -e values keep their spaces
+                let mut cmd = "display-popup".to_string();
+                for a in &cmd_args[1..] {
+                    cmd.push_str(&format!(" \"{}\"", a.replace('\\', "\\\\").replace('"', "\\\"")));
+                }
+                cmd.push('\n');
+                send_control(cmd)?;
+                return Ok(());
+            }
+            // hide-popup / move-popup / resize-popup - Manage floating popups
+            "hide-popup" | "move-popup" | "resize-popup" => {
+                let mut cmd = cmd_args[0].to_string();
+                for a in &cmd_args[1..] {
+                    cmd.push_str(&format!(" {}", a));
+                }
+                cmd.push('\n');
+                send_control(cmd)?;
                 return Ok(());
             }
             // server-info - Show server information
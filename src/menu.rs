@@ -0,0 +1,412 @@
+//! `display-menu`: argument parsing, placement, keys and the mouse.
+//!
+//! Items are `name key command` triples.  Names are formats, expanded when
+//! the menu opens; an empty name is a separator and a name starting with
+//! `-` (after expansion) is shown dimmed and cannot be chosen.  `-x`/`-y`
+//! take the same positions as `display-popup`.
+//!
+//! An item whose command is another `display-menu` opens it as a submenu
+//! beside the item.  Escape or Left goes back to the menu it came from;
+//! choosing an item closes them all.
+//!
+//! The mouse follows tmux: the item under the pointer is selected, and
+//! releasing the button over an item chooses it while releasing it
+//! elsewhere closes the menu.  With `-O` the menu stays open when the
+//! button is released, and a click chooses or closes instead.
+
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+use crate::commands::{execute_command_string, parse_command_line};
+use crate::format::expand_format;
+use crate::popup::{geometry, Anchors, PopupArgs};
+use crate::types::{AppState, Menu, MenuItem, Mode};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MenuArgs {
+    pub title: String,
+    pub x: Option<String>,
+    pub y: Option<String>,
+    pub style: Option<String>,
+    pub selected_style: Option<String>,
+    pub border_style: Option<String>,
+    pub border_lines: Option<String>,
+    /// `-C`: the item selected at first.
+    pub starting_choice: Option<usize>,
+    pub stay_open: bool,
+    /// `(name, key, command)` as given, before format expansion.
+    pub items: Vec<(String, String, String)>,
+}
+
+/// Parse `display-menu` arguments (`-t` already removed), getopt-style:
+/// flags come first, then the item triples.  An empty name (or `-`) is a
+/// separator and takes no key or command.
+pub fn parse_args(args: &[&str]) -> MenuArgs {
+    let mut out = MenuArgs::default();
+    let mut i = 0;
+    while i < args.len() {
+        let a = args[i];
+        if a == "--" { i += 1; break; }
+        if !a.starts_with('-') || a.len() < 2 { break; }
+        let flags: Vec<char> = a[1..].chars().collect();
+        for (j, &f) in flags.iter().enumerate() {
+            if "bcCHsStTxy".contains(f) {
+                let rest: String = flags[j + 1..].iter().collect();
+                let value = if !rest.is_empty() {
+                    Some(rest)
+                } else {
+                    i += 1;
+                    args.get(i).map(|v| v.to_string())
+                };
+                let Some(value) = value else { break };
+                match f {
+                    'b' => out.border_lines = Some(value),
+                    'C' => out.starting_choice = value.parse().ok(),
+                    'H' => out.selected_style = Some(value),
+                    's' => out.style = Some(value),
+                    'S' => out.border_style = Some(value),
+                    'T' => out.title = value,
+                    'x' => out.x = Some(value),
+                    'y' => out.y = Some(value),
+                    _ => {} // -c: menus show on every client
+                }
+                break;
+            }
+            if f == 'O' { out.stay_open = true; }
+        }
+        i += 1;
+    }
+    let rest = &args[i.min(args.len())..];
+    let mut i = 0;
+    while i < rest.len() {
+        let name = rest[i];
+        if name.is_empty() || name == "-" {
+            out.items.push((String::new(), String::new(), String::new()));
+            i += 1;
+        } else {
+            let field = |n: usize| rest.get(i + n).map(|s| s.to_string()).unwrap_or_default();
+            out.items.push((name.to_string(), field(1), field(2)));
+            i += 3;
+        }
+    }
+    out
+}
+
+/// Open the menu described by `args` as the current mode.  A menu without
+/// items is not shown.
+pub fn open(app: &mut AppState, args: MenuArgs) {
+    open_from(app, args, None);
+}
+
+fn open_from(app: &mut AppState, mut args: MenuArgs, parent: Option<Box<Menu>>) {
+    let items: Vec<MenuItem> = args.items.iter().map(|(name, key, command)| {
+        if name.is_empty() {
+            return MenuItem { name: String::new(), key: None, command: String::new(), is_separator: true, disabled: false };
+        }
+        let name = expand_format(name, app);
+        let (name, disabled) = match name.strip_prefix('-') {
+            Some(n) => (n.to_string(), true),
+            None => (name, false),
+        };
+        let mut chars = key.chars();
+        let key = chars.next().filter(|_| chars.next().is_none());
+        MenuItem { name, key, command: command.clone(), is_separator: false, disabled }
+    }).collect();
+    if items.is_empty() { return; }
+    let title = expand_format(&args.title, app);
+    let border_lines = args.border_lines.unwrap_or_else(|| app.menu_border_lines.clone());
+    let inset = if border_lines == "none" { 0 } else { 2 };
+    let inner = items.iter().map(|i| label_width(i) + 2).chain([title.width() + 2]).max().unwrap_or(0) as u16;
+    for pos in [&mut args.x, &mut args.y] {
+        if let Some(p) = pos.as_mut() { *p = expand_format(p, app); }
+    }
+    let size = PopupArgs {
+        width: Some((inner + inset).to_string()),
+        height: Some((items.len() as u16 + inset).to_string()),
+        x: args.x,
+        y: args.y,
+        ..Default::default()
+    };
+    let rect = geometry(&size, &Anchors::of(app));
+    let mut menu = Menu {
+        title,
+        selected: 0,
+        x: rect.x,
+        y: rect.y,
+        width: rect.width,
+        height: rect.height,
+        style: args.style.unwrap_or_else(|| app.menu_style.clone()),
+        selected_style: args.selected_style.unwrap_or_else(|| app.menu_selected_style.clone()),
+        border_style: args.border_style.unwrap_or_else(|| app.menu_border_style.clone()),
+        border_lines,
+        stay_open: args.stay_open,
+        pressed: false,
+        parent,
+        items,
+    };
+    menu.selected = match args.starting_choice {
+        Some(n) if menu.items.get(n).is_some_and(choosable) => n,
+        _ => menu.items.iter().position(choosable).unwrap_or(0),
+    };
+    app.mode = Mode::MenuMode { menu };
+}
+
+fn choosable(item: &MenuItem) -> bool {
+    !item.is_separator && !item.disabled
+}
+
+/// Width of an item's name and, if it has one, its `(key)`.
+fn label_width(item: &MenuItem) -> usize {
+    item.name.width() + item.key.map_or(0, |_| 4)
+}
+
+/// Move the selection by `delta` choosable items, wrapping around.
+pub fn navigate(menu: &mut Menu, delta: i32) {
+    let len = menu.items.len() as i32;
+    if !menu.items.iter().any(choosable) { return; }
+    let step = delta.signum();
+    let mut left = delta.abs();
+    let mut i = menu.selected as i32;
+    while left > 0 {
+        i = (i + step).rem_euclid(len);
+        if choosable(&menu.items[i as usize]) { left -= 1; }
+    }
+    menu.selected = i as usize;
+}
+
+/// Choose item `idx` of the open menu: run its command, or open it as a
+/// submenu if the command is another `display-menu`.
+pub fn choose(app: &mut AppState, idx: usize) {
+    let Mode::MenuMode { menu } = &app.mode else { return };
+    let Some(item) = menu.items.get(idx).filter(|i| choosable(i)) else { return };
+    let argv = parse_command_line(&item.command);
+    let Mode::MenuMode { menu } = std::mem::replace(&mut app.mode, Mode::Passthrough) else { return };
+    if matches!(argv.first().map(String::as_str), Some("display-menu" | "menu")) {
+        let argv: Vec<&str> = argv[1..].iter().map(String::as_str).collect();
+        let mut args = parse_args(&argv);
+        // Beside the item, its first entry level with it
+        args.x.get_or_insert_with(|| (menu.x + menu.width).to_string());
+        args.y.get_or_insert_with(|| (menu.y + idx as u16).to_string());
+        open_from(app, args, Some(Box::new(menu)));
+        return;
+    }
+    let command = menu.items[idx].command.clone();
+    if !command.is_empty() {
+        let _ = execute_command_string(app, &command);
+    }
+}
+
+/// Close the open menu, going back to the one it was opened from.
+fn back(app: &mut AppState) {
+    let Mode::MenuMode { menu } = std::mem::replace(&mut app.mode, Mode::Passthrough) else { return };
+    if let Some(parent) = menu.parent {
+        app.mode = Mode::MenuMode { menu: *parent };
+    }
+}
+
+/// Handle a key in the open menu, named as `send-key` or a key binding
+/// would name it (`up`, `Escape`, `C-c`, `x`).  An item's shortcut wins
+/// over the navigation keys.
+pub fn key(app: &mut AppState, name: &str) {
+    let Mode::MenuMode { menu } = &mut app.mode else { return };
+    let mut chars = name.chars();
+    let single = chars.next().filter(|_| chars.next().is_none());
+    if let Some(idx) = single.and_then(|c| menu.items.iter().position(|i| i.key == Some(c) && choosable(i))) {
+        choose(app, idx);
+        return;
+    }
+    let lower = name.to_ascii_lowercase();
+    match if single.is_some() { name } else { lower.as_str() } {
+        "up" | "k" | "btab" => navigate(menu, -1),
+        "down" | "j" | "tab" => navigate(menu, 1),
+        "pageup" | "ppage" => navigate(menu, -5),
+        "pagedown" | "npage" => navigate(menu, 5),
+        "home" | "g" => { if let Some(i) = menu.items.iter().position(choosable) { menu.selected = i; } }
+        "end" | "G" => { if let Some(i) = menu.items.iter().rposition(choosable) { menu.selected = i; } }
+        "enter" | "right" | "space" => { let idx = menu.selected; choose(app, idx); }
+        "esc" | "escape" | "left" => back(app),
+        "q" | "c-c" | "c-g" => { app.mode = Mode::Passthrough; }
+        _ => {}
+    }
+}
+
+/// Where a point in the window area falls: the depth of the menu (0 for
+/// the open one, 1 for its parent, ...) and the item under it, if any.
+fn locate(menu: &Menu, px: u16, py: u16) -> Option<(usize, Option<usize>)> {
+    let mut m = Some(menu);
+    let mut depth = 0;
+    while let Some(cur) = m {
+        let r = ratatui::layout::Rect { x: cur.x, y: cur.y, width: cur.width, height: cur.height };
+        if r.contains(ratatui::layout::Position { x: px, y: py }) {
+            let top = cur.y + u16::from(cur.border_lines != "none");
+            let item = py.checked_sub(top).map(usize::from).filter(|&i| i < cur.items.len());
+            return Some((depth, item));
+        }
+        m = cur.parent.as_deref();
+        depth += 1;
+    }
+    None
+}
+
+/// The open menu's view of a mouse event at (x, y): `None` when no menu is
+/// open, else where it fell, after closing submenus above the menu hit.
+fn pointer(app: &mut AppState, x: u16, y: u16) -> Option<Option<Option<usize>>> {
+    let area = app.last_window_area;
+    let Mode::MenuMode { menu } = &app.mode else { return None };
+    let hit = match (x.checked_sub(area.x), y.checked_sub(area.y)) {
+        (Some(px), Some(py)) => locate(menu, px, py),
+        _ => None,
+    };
+    let Some((depth, item)) = hit else { return Some(None) };
+    for _ in 0..depth { back(app); }
+    if let (Mode::MenuMode { menu }, Some(i)) = (&mut app.mode, item) {
+        if choosable(&menu.items[i]) { menu.selected = i; }
+    }
+    Some(Some(item))
+}
+
+/// Left button press; true if a menu took it.
+pub fn mouse_down(app: &mut AppState, x: u16, y: u16) -> bool {
+    let Some(hit) = pointer(app, x, y) else { return false };
+    let Mode::MenuMode { menu } = &mut app.mode else { return true };
+    menu.pressed = true;
+    if menu.stay_open {
+        match hit {
+            Some(Some(i)) => choose(app, i),
+            Some(None) => {}
+            None => { app.mode = Mode::Passthrough; }
+        }
+    }
+    true
+}
+
+/// Left button release; true if a menu took it.
+pub fn mouse_up(app: &mut AppState, x: u16, y: u16) -> bool {
+    let Mode::MenuMode { menu } = &app.mode else { return false };
+    // The release of the click that opened the menu, or one that -O ignores
+    if !menu.pressed || menu.stay_open { return true; }
+    match pointer(app, x, y) {
+        Some(Some(Some(i))) => choose(app, i),
+        Some(None) => { app.mode = Mode::Passthrough; }
+        _ => {}
+    }
+    true
+}
+
+/// Motion or a drag: select the item under the pointer.  True if a menu
+/// took it.
+pub fn mouse_move(app: &mut AppState, x: u16, y: u16) -> bool {
+    let area = app.last_window_area;
+    let Mode::MenuMode { menu } = &mut app.mode else { return false };
+    if let (Some(px), Some(py)) = (x.checked_sub(area.x), y.checked_sub(area.y)) {
+        if let Some((0, Some(i))) = locate(menu, px, py) {
+            if choosable(&menu.items[i]) { menu.selected = i; }
+        }
+    }
+    true
+}
+
+/// What a menu overlay draws.  The server sends one per open menu,
+/// outermost first, inside the state dump.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct MenuView {
+    pub title: String,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub style: String,
+    pub selected_style: String,
+    pub border_style: String,
+    pub border_lines: String,
+    /// `(name, key, disabled)` per item; an empty name is a separator.
+    pub items: Vec<(String, String, bool)>,
+    pub selected: usize,
+}
+
+/// Views of `menu` and the menus it was opened from, outermost first.
+pub fn views(menu: &Menu) -> Vec<MenuView> {
+    let mut out = Vec::new();
+    let mut m = Some(menu);
+    while let Some(cur) = m {
+        out.push(MenuView {
+            title: cur.title.clone(),
+            x: cur.x,
+            y: cur.y,
+            width: cur.width,
+            height: cur.height,
+            style: cur.style.clone(),
+            selected_style: cur.selected_style.clone(),
+            border_style: cur.border_style.clone(),
+            border_lines: cur.border_lines.clone(),
+            items: cur.items.iter().map(|i| (i.name.clone(), i.key.map(String::from).unwrap_or_default(), i.disabled)).collect(),
+            selected: cur.selected,
+        });
+        m = cur.parent.as_deref();
+    }
+    out.reverse();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, disabled: bool) -> MenuItem {
+        MenuItem { name: name.to_string(), key: None, command: String::new(), is_separator: name.is_empty(), disabled }
+    }
+
+    #[test]
+    fn parses_flags_and_items() {
+        let a = parse_args(&["-OT", "#S", "-x", "M", "-H", "bg=red", "--", "-Gone", "g", "", "New", "n", "new-window", "-", "Split", "s"]);
+        assert!(a.stay_open);
+        assert_eq!(a.title, "#S");
+        assert_eq!(a.x.as_deref(), Some("M"));
+        assert_eq!(a.selected_style.as_deref(), Some("bg=red"));
+        assert_eq!(a.items, vec![
+            ("-Gone".to_string(), "g".to_string(), String::new()),
+            ("New".to_string(), "n".to_string(), "new-window".to_string()),
+            (String::new(), String::new(), String::new()),
+            ("Split".to_string(), "s".to_string(), String::new()),
+        ]);
+    }
+
+    #[test]
+    fn navigation_skips_separators_and_disabled_items() {
+        let mut menu = Menu {
+            title: String::new(),
+            items: vec![item("a", false), item("", false), item("b", true), item("c", false)],
+            selected: 0,
+            x: 0, y: 0, width: 10, height: 6,
+            style: String::new(), selected_style: String::new(), border_style: String::new(), border_lines: String::new(),
+            stay_open: false, pressed: false, parent: None,
+        };
+        navigate(&mut menu, 1);
+        assert_eq!(menu.selected, 3);
+        navigate(&mut menu, 1);
+        assert_eq!(menu.selected, 0);
+        navigate(&mut menu, -1);
+        assert_eq!(menu.selected, 3);
+        assert_eq!(locate(&menu, 2, 4), Some((0, Some(3))));
+        assert_eq!(locate(&menu, 2, 0), Some((0, None)));
+        let sub = Menu { x: 10, y: 3, parent: Some(Box::new(menu.clone())), ..menu.clone() };
+        assert_eq!(locate(&sub, 2, 1), Some((1, Some(0))));
+        assert_eq!(locate(&sub, 30, 1), None);
+        assert_eq!(views(&sub).iter().map(|v| v.x).collect::<Vec<_>>(), vec![0, 10]);
+        // Home and End land on the first and last choosable items, even
+        // when those ends are a disabled header or a separator
+        let mut app = AppState::new("t".to_string());
+        let items = vec![item("Header", true), item("a", false), item("b", false), item("", false)];
+        app.mode = Mode::MenuMode { menu: Menu { items, selected: 1, ..sub } };
+        let selected = |app: &AppState| match &app.mode { Mode::MenuMode { menu } => menu.selected, _ => usize::MAX };
+        key(&mut app, "End");
+        assert_eq!(selected(&app), 2);
+        key(&mut app, "Home");
+        assert_eq!(selected(&app), 1);
+        key(&mut app, "G");
+        assert_eq!(selected(&app), 2);
+        key(&mut app, "g");
+        assert_eq!(selected(&app), 1);
+    }
+}
@@ -8,13 +8,77 @@ use portable_pty::{CommandBuilder, PtySize, native_pty_system};
 use crate::types::{AppState, Pane, Node, LayoutKind, Window};
 use crate::tree::{replace_leaf_with_split, active_pane_mut, kill_leaf};
 
-/// Sentinel value for cursor_shape: means "no DECSCUSR received from child yet".
+/// Sentinel value for the `cursor_shape` sent to clients: means "no DECSCUSR
+/// received from child yet".
 /// When ConPTY passthrough mode is unavailable, DECSCUSR sequences from child
 /// processes are consumed by ConPTY and never forwarded.  Using this sentinel
 /// lets the rendering code skip emitting any cursor-shape override, so the
 /// real terminal keeps its user-configured default cursor.
 pub const CURSOR_SHAPE_UNSET: u8 = 255;
 
+/// Terminal events a pane's parser reports outside the screen state.
+/// The server drains them from its main loop.
+#[derive(Default)]
+pub struct PaneCallbacks {
+    /// Text copied by the application with OSC 52, oldest first.
+    pub clipboard_copies: Vec<String>,
+    /// Selection parameters of OSC 52 `?` read requests not yet answered.
+    pub clipboard_reads: Vec<Vec<u8>>,
+    /// Active `record-pane` recording, fed by the reader thread.
+    pub recording: Option<crate::record::Recorder>,
+}
+
+impl vt100::Callbacks for PaneCallbacks {
+    fn copy_to_clipboard(&mut self, _: &mut vt100::Screen, _ty: &[u8], data: &[u8]) {
+        if let Some(text) = std::str::from_utf8(data).ok().and_then(crate::util::base64_decode) {
+            self.clipboard_copies.push(text);
+        }
+    }
+
+    fn paste_from_clipboard(&mut self, _: &mut vt100::Screen, ty: &[u8]) {
+        self.clipboard_reads.push(ty.to_vec());
+    }
+}
+
+/// The vt100 parser type every pane (and popup) uses.
+pub type PaneParser = vt100::Parser<PaneCallbacks>;
+
+/// Create the vt100 parser for a pane, with the character width options
+/// (`ambiguous-width`, `variation-selector-always-wide`) applied so the
+/// server measures text the same way the client will draw it.
+/// Takes the option values rather than `&AppState` so callers can hold a
+/// mutable borrow of a window while creating the parser.
+pub fn new_parser(rows: u16, cols: u16, scrollback: usize, ambiguous_width: u8, variation_selector_always_wide: bool) -> PaneParser {
+    let mut parser = PaneParser::new_with_callbacks(rows, cols, scrollback, PaneCallbacks::default());
+    apply_width_options(&mut parser, ambiguous_width, variation_selector_always_wide);
+    parser
+}
+
+fn apply_width_options(parser: &mut PaneParser, ambiguous_width: u8, variation_selector_always_wide: bool) {
+    let screen = parser.screen_mut();
+    screen.set_ambiguous_width_wide(ambiguous_width == 2);
+    screen.set_variation_selector_always_wide(variation_selector_always_wide);
+}
+
+/// Push the current width options into every existing pane (and the warm
+/// pane) after one of them changes.  Only text written afterwards is
+/// affected, like tmux.
+pub fn refresh_width_options(app: &mut AppState) {
+    fn walk(node: &Node, amb: u8, vs: bool) {
+        match node {
+            Node::Leaf(p) => {
+                if let Ok(mut parser) = p.term.lock() { apply_width_options(&mut parser, amb, vs); }
+            }
+            Node::Split { children, .. } => { for c in children { walk(c, amb, vs); } }
+        }
+    }
+    let (amb, vs) = (app.ambiguous_width, app.variation_selector_always_wide);
+    for win in &app.windows { walk(&win.root, amb, vs); }
+    if let Some(wp) = &app.warm_pane {
+        if let Ok(mut parser) = wp.term.lock() { apply_width_options(&mut parser, amb, vs); }
+    }
+}
+
 /// Send a preemptive cursor-position report (\x1b[1;1R) to the ConPTY input pipe.
 ///
 /// Windows ConPTY sends a Device Status Report (\x1b[6n]) during initialization
@@ -91,10 +155,10 @@ pub fn create_window(pty_system: &dyn portable_pty::PtySystem, app: &mut AppStat
         }
         let epoch = std::time::Instant::now() - Duration::from_secs(2);
         let configured_shell = if app.default_shell.is_empty() { None } else { Some(app.default_shell.as_str()) };
-        let pane = Pane { master: wp.master, writer: wp.writer, child: wp.child, term: wp.term, last_rows: rows, last_cols: cols, id: wp.pane_id, title: format!("pane %{}", wp.pane_id), child_pid: wp.child_pid, data_version: wp.data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, cursor_shape: wp.cursor_shape, copy_state: None, pane_style: None };
+        let pane = Pane { master: wp.master, writer: wp.writer, child: wp.child, term: wp.term, last_rows: rows, last_cols: cols, id: wp.pane_id, title: format!("pane %{}", wp.pane_id), child_pid: wp.child_pid, data_version: wp.data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, exit_status: None, dead_time: None, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, copy_state: None, copy_mark: None, pane_style: None };
         let win_name = default_shell_name(None, configured_shell);
         let initial_pane_id = wp.pane_id;
-        app.windows.push(Window { root: Node::Leaf(pane), active_path: vec![], name: win_name, id: app.next_win_id, activity_flag: false, bell_flag: false, silence_flag: false, last_output_time: std::time::Instant::now(), last_seen_version: 0, manual_rename: false, layout_index: 0, pane_mru: vec![initial_pane_id] });
+        app.windows.push(Window { root: Node::Leaf(pane), active_path: vec![], name: win_name, id: app.next_win_id, activity_flag: false, bell_flag: false, silence_flag: false, last_output_time: std::time::Instant::now(), last_seen_version: 0, manual_rename: false, layout_index: 0, pane_mru: vec![initial_pane_id], floating: Vec::new() });
         app.next_win_id += 1;
         app.active_idx = app.windows.len() - 1;
         return Ok(());
@@ -134,18 +198,16 @@ pub fn create_window(pty_system: &dyn portable_pty::PtySystem, app: &mut AppStat
     drop(pair.slave);
 
     let scrollback = app.history_limit as u32;
-    let term: Arc<Mutex<vt100::Parser>> = Arc::new(Mutex::new(vt100::Parser::new(size.rows, size.cols, scrollback as usize)));
+    let term: Arc<Mutex<PaneParser>> = Arc::new(Mutex::new(new_parser(size.rows, size.cols, scrollback as usize, app.ambiguous_width, app.variation_selector_always_wide)));
     let term_reader = term.clone();
     let data_version = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
     let dv_writer = data_version.clone();
-    let cursor_shape = std::sync::Arc::new(std::sync::atomic::AtomicU8::new(CURSOR_SHAPE_UNSET));
-    let cs_writer = cursor_shape.clone();
     let reader = pair
         .master
         .try_clone_reader()
         .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("clone reader error: {e}")))?;
 
-    spawn_reader_thread(reader, term_reader, dv_writer, cs_writer);
+    spawn_reader_thread(reader, term_reader, dv_writer);
 
     let configured_shell = if app.default_shell.is_empty() { None } else { Some(app.default_shell.as_str()) };
     let child_pid = crate::platform::mouse_inject::get_child_pid(&*child);
@@ -154,10 +216,10 @@ pub fn create_window(pty_system: &dyn portable_pty::PtySystem, app: &mut AppStat
     conpty_preemptive_dsr_response(&mut *pty_writer);
     let epoch = std::time::Instant::now() - Duration::from_secs(2);
     let pane_id = app.next_pane_id;
-    let pane = Pane { master: pair.master, writer: pty_writer, child, term, last_rows: size.rows, last_cols: size.cols, id: pane_id, title: format!("pane %{}", pane_id), child_pid, data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, cursor_shape, copy_state: None, pane_style: None };
+    let pane = Pane { master: pair.master, writer: pty_writer, child, term, last_rows: size.rows, last_cols: size.cols, id: pane_id, title: format!("pane %{}", pane_id), child_pid, data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, exit_status: None, dead_time: None, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, copy_state: None, copy_mark: None, pane_style: None };
     app.next_pane_id += 1;
     let win_name = command.map(|c| default_shell_name(Some(c), None)).unwrap_or_else(|| default_shell_name(None, configured_shell));
-    app.windows.push(Window { root: Node::Leaf(pane), active_path: vec![], name: win_name, id: app.next_win_id, activity_flag: false, bell_flag: false, silence_flag: false, last_output_time: std::time::Instant::now(), last_seen_version: 0, manual_rename: false, layout_index: 0, pane_mru: vec![pane_id] });
+    app.windows.push(Window { root: Node::Leaf(pane), active_path: vec![], name: win_name, id: app.next_win_id, activity_flag: false, bell_flag: false, silence_flag: false, last_output_time: std::time::Instant::now(), last_seen_version: 0, manual_rename: false, layout_index: 0, pane_mru: vec![pane_id], floating: Vec::new() });
     app.next_win_id += 1;
     app.active_idx = app.windows.len() - 1;
     Ok(())
@@ -190,21 +252,19 @@ pub fn spawn_warm_pane(pty_system: &dyn portable_pty::PtySystem, app: &mut AppSt
         .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("spawn shell error: {e}")))?;
     drop(pair.slave);
     let scrollback = app.history_limit as u32;
-    let term: Arc<Mutex<vt100::Parser>> = Arc::new(Mutex::new(vt100::Parser::new(rows, cols, scrollback as usize)));
+    let term: Arc<Mutex<PaneParser>> = Arc::new(Mutex::new(new_parser(rows, cols, scrollback as usize, app.ambiguous_width, app.variation_selector_always_wide)));
     let term_reader = term.clone();
     let data_version = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
     let dv_writer = data_version.clone();
-    let cursor_shape = std::sync::Arc::new(std::sync::atomic::AtomicU8::new(CURSOR_SHAPE_UNSET));
-    let cs_writer = cursor_shape.clone();
     let reader = pair.master
         .try_clone_reader()
         .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("clone reader error: {e}")))?;
-    spawn_reader_thread(reader, term_reader, dv_writer, cs_writer);
+    spawn_reader_thread(reader, term_reader, dv_writer);
     let child_pid = crate::platform::mouse_inject::get_child_pid(&*child);
     let mut pty_writer = pair.master.take_writer()
         .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("take writer error: {e}")))?;
     conpty_preemptive_dsr_response(&mut *pty_writer);
-    Ok(crate::types::WarmPane { master: pair.master, writer: pty_writer, child, term, data_version, cursor_shape, child_pid, pane_id, rows, cols })
+    Ok(crate::types::WarmPane { master: pair.master, writer: pty_writer, child, term, data_version, child_pid, pane_id, rows, cols })
 }
 
 pub fn split_active(app: &mut AppState, kind: LayoutKind) -> io::Result<()> {
@@ -232,18 +292,16 @@ pub fn create_window_raw(pty_system: &dyn portable_pty::PtySystem, app: &mut App
     drop(pair.slave);
 
     let scrollback = app.history_limit;
-    let term: Arc<Mutex<vt100::Parser>> = Arc::new(Mutex::new(vt100::Parser::new(size.rows, size.cols, scrollback)));
+    let term: Arc<Mutex<PaneParser>> = Arc::new(Mutex::new(new_parser(size.rows, size.cols, scrollback, app.ambiguous_width, app.variation_selector_always_wide)));
     let term_reader = term.clone();
     let data_version = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
     let dv_writer = data_version.clone();
-    let cursor_shape = std::sync::Arc::new(std::sync::atomic::AtomicU8::new(CURSOR_SHAPE_UNSET));
-    let cs_writer = cursor_shape.clone();
     let reader = pair
         .master
         .try_clone_reader()
         .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("clone reader error: {e}")))?;
 
-    spawn_reader_thread(reader, term_reader, dv_writer, cs_writer);
+    spawn_reader_thread(reader, term_reader, dv_writer);
 
     let child_pid = crate::platform::mouse_inject::get_child_pid(&*child);
     let mut pty_writer = pair.master.take_writer()
@@ -251,10 +309,10 @@ pub fn create_window_raw(pty_system: &dyn portable_pty::PtySystem, app: &mut App
     conpty_preemptive_dsr_response(&mut *pty_writer);
     let epoch = std::time::Instant::now() - Duration::from_secs(2);
     let raw_pane_id = app.next_pane_id;
-    let pane = Pane { master: pair.master, writer: pty_writer, child, term, last_rows: size.rows, last_cols: size.cols, id: raw_pane_id, title: format!("pane %{}", raw_pane_id), child_pid, data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, cursor_shape, copy_state: None, pane_style: None };
+    let pane = Pane { master: pair.master, writer: pty_writer, child, term, last_rows: size.rows, last_cols: size.cols, id: raw_pane_id, title: format!("pane %{}", raw_pane_id), child_pid, data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, exit_status: None, dead_time: None, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, copy_state: None, copy_mark: None, pane_style: None };
     app.next_pane_id += 1;
     let win_name = std::path::Path::new(&raw_args[0]).file_stem().and_then(|s| s.to_str()).unwrap_or(&raw_args[0]).to_string();
-    app.windows.push(Window { root: Node::Leaf(pane), active_path: vec![], name: win_name, id: app.next_win_id, activity_flag: false, bell_flag: false, silence_flag: false, last_output_time: std::time::Instant::now(), last_seen_version: 0, manual_rename: false, layout_index: 0, pane_mru: vec![raw_pane_id] });
+    app.windows.push(Window { root: Node::Leaf(pane), active_path: vec![], name: win_name, id: app.next_win_id, activity_flag: false, bell_flag: false, silence_flag: false, last_output_time: std::time::Instant::now(), last_seen_version: 0, manual_rename: false, layout_index: 0, pane_mru: vec![raw_pane_id], floating: Vec::new() });
     app.next_win_id += 1;
     app.active_idx = app.windows.len() - 1;
     Ok(())
@@ -349,7 +407,7 @@ pub fn split_active_with_command(app: &mut AppState, kind: LayoutKind, command:
         }
         let epoch = std::time::Instant::now() - Duration::from_secs(2);
         let new_pane_id = wp.pane_id;
-        let new_leaf = Node::Leaf(Pane { master: wp.master, writer: wp.writer, child: wp.child, term: wp.term, last_rows: rows, last_cols: cols, id: new_pane_id, title: format!("pane %{}", new_pane_id), child_pid: wp.child_pid, data_version: wp.data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, cursor_shape: wp.cursor_shape, copy_state: None, pane_style: None });
+        let new_leaf = Node::Leaf(Pane { master: wp.master, writer: wp.writer, child: wp.child, term: wp.term, last_rows: rows, last_cols: cols, id: new_pane_id, title: format!("pane %{}", new_pane_id), child_pid: wp.child_pid, data_version: wp.data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, exit_status: None, dead_time: None, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, copy_state: None, copy_mark: None, pane_style: None });
         let win = &mut app.windows[app.active_idx];
         replace_leaf_with_split(&mut win.root, &win.active_path, kind, new_leaf);
         let mut new_path = win.active_path.clone();
@@ -379,21 +437,19 @@ pub fn split_active_with_command(app: &mut AppState, kind: LayoutKind, command:
     let child = pair.slave.spawn_command(shell_cmd).map_err(|e| io::Error::new(io::ErrorKind::Other, format!("spawn shell error: {e}")))?;
     // Close the slave handle immediately – see create_window() comment.
     drop(pair.slave);
-    let term: Arc<Mutex<vt100::Parser>> = Arc::new(Mutex::new(vt100::Parser::new(size.rows, size.cols, app.history_limit)));
+    let term: Arc<Mutex<PaneParser>> = Arc::new(Mutex::new(new_parser(size.rows, size.cols, app.history_limit, app.ambiguous_width, app.variation_selector_always_wide)));
     let term_reader = term.clone();
     let reader = pair.master.try_clone_reader().map_err(|e| io::Error::new(io::ErrorKind::Other, format!("clone reader error: {e}")))?;
     let data_version = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
     let dv_writer = data_version.clone();
-    let cursor_shape = std::sync::Arc::new(std::sync::atomic::AtomicU8::new(CURSOR_SHAPE_UNSET));
-    let cs_writer = cursor_shape.clone();
-    spawn_reader_thread(reader, term_reader, dv_writer, cs_writer);
+    spawn_reader_thread(reader, term_reader, dv_writer);
     let child_pid = crate::platform::mouse_inject::get_child_pid(&*child);
     let mut pty_writer = pair.master.take_writer()
         .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("take writer error: {e}")))?;
     conpty_preemptive_dsr_response(&mut *pty_writer);
     let epoch = std::time::Instant::now() - Duration::from_secs(2);
     let split_pane_id = app.next_pane_id;
-    let new_leaf = Node::Leaf(Pane { master: pair.master, writer: pty_writer, child, term, last_rows: size.rows, last_cols: size.cols, id: split_pane_id, title: format!("pane %{}", split_pane_id), child_pid, data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, cursor_shape, copy_state: None, pane_style: None });
+    let new_leaf = Node::Leaf(Pane { master: pair.master, writer: pty_writer, child, term, last_rows: size.rows, last_cols: size.cols, id: split_pane_id, title: format!("pane %{}", split_pane_id), child_pid, data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, exit_status: None, dead_time: None, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, copy_state: None, copy_mark: None, pane_style: None });
     app.next_pane_id += 1;
     let win = &mut app.windows[app.active_idx];
     replace_leaf_with_split(&mut win.root, &win.active_path, kind, new_leaf);
@@ -844,47 +900,10 @@ pub fn build_raw_command(raw_args: &[String]) -> CommandBuilder {
 /// Uses an 8KB read buffer (down from 64KB) to reduce mutex hold time during
 /// `parser.process()`, which improves DumpState latency under heavy output.
 
-/// Scan raw ConPTY output for DECSCUSR cursor shape sequences (`\x1b[N q`).
-/// Returns the last cursor shape value found, or None.
-///
-/// We accept all DECSCUSR cursor shape values (0-6) from child processes.
-/// Value 0 resets to default, 1-2 = block, 3-4 = underline, 5-6 = bar.
-fn scan_cursor_shape(data: &[u8]) -> Option<u8> {
-    let mut last_shape: Option<u8> = None;
-    let mut i = 0;
-    while i < data.len() {
-        if data[i] == 0x1b && i + 1 < data.len() && data[i + 1] == b'[' {
-            let mut j = i + 2;
-            let mut param: u8 = 0;
-            while j < data.len() && data[j].is_ascii_digit() {
-                param = param.saturating_mul(10).saturating_add(data[j] - b'0');
-                j += 1;
-            }
-            // Check for SP q (space 0x20 + 'q') = DECSCUSR
-            if j + 1 < data.len() && data[j] == b' ' && data[j + 1] == b'q' {
-                if param <= 6 {
-                    last_shape = Some(param);
-                }
-                i = j + 2;
-                continue;
-            }
-        }
-        i += 1;
-    }
-    last_shape
-}
-
-/// Returns true if `data` contains the RMCUP sequence (ESC[?1049l).
-fn scan_rmcup(data: &[u8]) -> bool {
-    const RMCUP: &[u8] = b"\x1b[?1049l";
-    data.windows(RMCUP.len()).any(|w| w == RMCUP)
-}
-
 pub fn spawn_reader_thread(
     mut reader: Box<dyn std::io::Read + Send>,
-    term_reader: Arc<Mutex<vt100::Parser>>,
+    term_reader: Arc<Mutex<PaneParser>>,
     dv_writer: Arc<std::sync::atomic::AtomicU64>,
-    cursor_shape: Arc<std::sync::atomic::AtomicU8>,
 ) {
     thread::spawn(move || {
         // 64KB buffer: captures most full-screen TUI paints in a single
@@ -896,18 +915,11 @@ pub fn spawn_reader_thread(
             match reader.read(&mut local) {
                 Ok(n) if n > 0 => {
                     zero_reads = 0;
-                    // Scan for DECSCUSR cursor shape before vt100 parser consumes data.
-                    if let Some(shape) = scan_cursor_shape(&local[..n]) {
-                        cursor_shape.store(shape, std::sync::atomic::Ordering::Release);
-                    }
-                    let rmcup = scan_rmcup(&local[..n]);
                     if let Ok(mut parser) = term_reader.lock() {
                         parser.process(&local[..n]);
-                    }
-                    // When TUI sends RMCUP, reset cursor shape so it
-                    // doesn't persist from the exiting TUI app.
-                    if rmcup {
-                        cursor_shape.store(0, std::sync::atomic::Ordering::Release);
+                        if let Some(rec) = parser.callbacks_mut().recording.as_mut() {
+                            rec.output(&local[..n]);
+                        }
                     }
                     dv_writer.fetch_add(1, std::sync::atomic::Ordering::Release);
                     crate::types::PTY_DATA_READY.store(true, std::sync::atomic::Ordering::Release);
@@ -926,7 +938,6 @@ pub fn spawn_reader_thread(
         if let Ok(mut parser) = term_reader.lock() {
             if parser.screen().alternate_screen() {
                 parser.process(b"\x1b[?25h\x1b[?1049l");
-                cursor_shape.store(0, std::sync::atomic::Ordering::Release);
                 dv_writer.fetch_add(1, std::sync::atomic::Ordering::Release);
                 crate::types::PTY_DATA_READY.store(true, std::sync::atomic::Ordering::Release);
             }
@@ -935,3 +946,31 @@ pub fn spawn_reader_thread(
 }
 
 // reap_children is in tree.rs
+
+/// A pane for unit tests: a real PTY running a command that exits at once,
+/// with `output` already fed to its parser and writes discarded.
+#[cfg(test)]
+pub(crate) fn test_pane(app: &mut AppState, rows: u16, cols: u16, output: &[u8]) -> Pane {
+    let pair = native_pty_system()
+        .openpty(PtySize { rows, cols, pixel_width: 0, pixel_height: 0 })
+        .expect("openpty");
+    let mut cmd = if cfg!(windows) { CommandBuilder::new("cmd.exe") } else { CommandBuilder::new("true") };
+    if cfg!(windows) { cmd.args(["/c", "exit"]); }
+    let child = pair.slave.spawn_command(cmd).expect("spawn");
+    let mut parser = new_parser(rows, cols, app.history_limit, app.ambiguous_width, app.variation_selector_always_wide);
+    parser.process(output);
+    let epoch = std::time::Instant::now();
+    let id = app.next_pane_id;
+    app.next_pane_id += 1;
+    Pane { master: pair.master, writer: Box::new(io::sink()), child, term: Arc::new(Mutex::new(parser)), last_rows: rows, last_cols: cols, id, title: format!("pane %{}", id), child_pid: None, data_version: Arc::new(std::sync::atomic::AtomicU64::new(0)), last_title_check: epoch, last_infer_title: epoch, dead: false, exit_status: None, dead_time: None, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, copy_state: None, copy_mark: None, pane_style: None }
+}
+
+/// Add a window holding a single `test_pane` and make it active.
+#[cfg(test)]
+pub(crate) fn test_window(app: &mut AppState, rows: u16, cols: u16, output: &[u8]) {
+    let pane = test_pane(app, rows, cols, output);
+    let pane_id = pane.id;
+    app.windows.push(Window { root: Node::Leaf(pane), active_path: vec![], name: "test".to_string(), id: app.next_win_id, activity_flag: false, bell_flag: false, silence_flag: false, last_output_time: std::time::Instant::now(), last_seen_version: 0, manual_rename: false, layout_index: 0, pane_mru: vec![pane_id], floating: Vec::new() });
+    app.next_win_id += 1;
+    app.active_idx = app.windows.len() - 1;
+}
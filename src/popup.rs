@@ -0,0 +1,802 @@
+//! `display-popup`: argument parsing, placement and the popup's PTY.
+//!
+//! Sizes and positions follow tmux.  `-w`/`-h` take cells or a percentage
+//! of the window area; `-x`/`-y` take cells, percentages, formats, or one of
+//! the position tokens: `C` centre, `R` right edge (x only), `P` the active
+//! pane, `M` the last mouse click, `W` the window's status-line entry and `S`
+//! the line next to the status line (y only).
+//!
+//! The focused popup lives in `Mode::PopupMode`.  A popup given a name with
+//! `-n` can also be hidden or left in the background: it then moves to its
+//! window's `floating` list, where visible floats are drawn above the tiled
+//! layout and keep running until shown again, joined into the layout with
+//! `join-pane -s NAME` or closed.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+
+use portable_pty::{CommandBuilder, PtySize};
+use ratatui::layout::Rect;
+
+use crate::format::expand_format;
+use crate::pane::PaneParser;
+use crate::session_view::SessionView;
+use crate::tree::{compute_rects, resize_all_panes};
+use crate::types::{AppState, LayoutKind, Mode, Node, Pane, PopupPty};
+
+/// When a popup whose command has exited closes by itself (`-E`, `-EE`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PopupClose {
+    /// Stay open until `q` or Escape.
+    #[default]
+    Keep,
+    /// `-E`: close when the command exits.
+    OnExit,
+    /// `-EE`: close when the command exits successfully.
+    OnSuccess,
+}
+
+impl PopupClose {
+    pub fn closes(self, success: bool) -> bool {
+        match self {
+            PopupClose::Keep => false,
+            PopupClose::OnExit => true,
+            PopupClose::OnSuccess => success,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PopupArgs {
+    pub command: String,
+    pub width: Option<String>,
+    pub height: Option<String>,
+    pub x: Option<String>,
+    pub y: Option<String>,
+    pub start_dir: Option<String>,
+    pub env: Vec<(String, String)>,
+    pub border_lines: Option<String>,
+    pub style: Option<String>,
+    pub border_style: Option<String>,
+    pub title: Option<String>,
+    pub no_border: bool,
+    pub close: PopupClose,
+    pub target_client: Option<String>,
+    /// `-n`: a named popup toggles: shown if hidden, hidden if focused.
+    pub name: Option<String>,
+    /// `-t`: the pane the popup belongs to, or another session to show.
+    pub target: Option<String>,
+}
+
+/// A popup, focused (in `Mode::PopupMode`) or floating in a window.
+pub struct Popup {
+    pub name: Option<String>,
+    pub command: String,
+    pub output: String,
+    pub process: Option<std::process::Child>,
+    /// Position and size within the window area
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub close: PopupClose,
+    pub title: String,
+    pub style: String,
+    pub border_style: String,
+    pub border_lines: String,
+    /// Optional: interactive PTY for the popup (fzf, etc.)
+    pub popup_pty: Option<PopupPty>,
+    /// The pane id of a float made with `break-pane -f`, reused when it is
+    /// joined back into the layout.
+    pub pane_id: Option<usize>,
+    /// Hidden floats keep running but are not drawn.
+    pub hidden: bool,
+    /// `-t` naming another session: the popup shows that session instead
+    /// of running a command.
+    pub view: Option<SessionView>,
+}
+
+impl Popup {
+    pub fn rect(&self) -> Rect {
+        Rect { x: self.x, y: self.y, width: self.width, height: self.height }
+    }
+
+    /// Cells taken by the border on each axis.
+    pub fn inset(&self) -> u16 {
+        if self.border_lines == "none" { 0 } else { 2 }
+    }
+
+    /// Move and resize to `r`, kept inside an `area` of (cols, rows), and
+    /// resize the PTY to the new inner size.
+    pub fn place(&mut self, r: Rect, area: (u16, u16)) {
+        let min = self.inset() + 1;
+        self.width = r.width.clamp(min, area.0.max(min));
+        self.height = r.height.clamp(min, area.1.max(min));
+        self.x = r.x.min(area.0.saturating_sub(self.width));
+        self.y = r.y.min(area.1.saturating_sub(self.height));
+        let (rows, cols) = (self.height - self.inset(), self.width - self.inset());
+        if let Some(view) = self.view.as_ref() { view.resize(rows, cols); }
+        if let Some(pty) = self.popup_pty.as_mut() {
+            let _ = pty.master.resize(PtySize { rows, cols, pixel_width: 0, pixel_height: 0 });
+            if let Ok(mut parser) = pty.term.lock() {
+                if parser.screen().size() != (rows, cols) { parser.screen_mut().set_size(rows, cols); }
+            }
+        }
+    }
+
+    /// `Some(success)` once the command has exited, or the session shown
+    /// has ended.
+    pub fn exit_status(&mut self) -> Option<bool> {
+        if let Some(view) = self.view.as_ref() {
+            return view.ended().then_some(true);
+        }
+        let pty = self.popup_pty.as_mut()?;
+        match pty.child.try_wait() {
+            Ok(Some(status)) => Some(status.success()),
+            _ => None,
+        }
+    }
+
+    /// The screen shown inside the border, if the popup has one.
+    pub fn term(&self) -> Option<&Arc<Mutex<PaneParser>>> {
+        match (&self.popup_pty, &self.view) {
+            (Some(pty), _) => Some(&pty.term),
+            (None, Some(view)) => Some(&view.term),
+            (None, None) => None,
+        }
+    }
+
+    /// Bumped whenever the screen inside changes.
+    pub fn data_version(&self) -> Option<&AtomicU64> {
+        match (&self.popup_pty, &self.view) {
+            (Some(pty), _) => Some(&pty.data_version),
+            (None, Some(view)) => Some(&view.data_version),
+            (None, None) => None,
+        }
+    }
+
+    /// Send typed bytes to the command or the session shown.
+    pub fn input(&mut self, bytes: &[u8]) {
+        if let Some(pty) = self.popup_pty.as_mut() {
+            let _ = pty.writer.write_all(bytes);
+            let _ = pty.writer.flush();
+        } else if let Some(view) = self.view.as_ref() {
+            view.input(bytes);
+        }
+    }
+}
+
+/// A popup being moved (dragged by its top border) or resized (by its
+/// right or bottom border) with the mouse.
+#[derive(Clone, Copy, Debug)]
+pub struct PopupDrag {
+    resize: bool,
+    from: (u16, u16),
+    orig: Rect,
+}
+
+/// Parse `display-popup` arguments getopt-style:
+/// flags may be combined (`-EE`, `-BE`) and the first non-flag argument
+/// starts the shell command.
+pub fn parse_args(args: &[&str]) -> PopupArgs {
+    let mut out = PopupArgs::default();
+    let mut exits = 0;
+    let mut i = 0;
+    while i < args.len() {
+        let a = args[i];
+        if a == "--" { i += 1; break; }
+        if !a.starts_with('-') || a.len() < 2 { break; }
+        let flags: Vec<char> = a[1..].chars().collect();
+        for (j, &f) in flags.iter().enumerate() {
+            if "bcdehnsStTwxy".contains(f) {
+                // The value is the rest of this word or the next argument
+                let rest: String = flags[j + 1..].iter().collect();
+                let value = if !rest.is_empty() {
+                    Some(rest)
+                } else {
+                    i += 1;
+                    args.get(i).map(|v| v.to_string())
+                };
+                let Some(value) = value else { break };
+                match f {
+                    'b' => out.border_lines = Some(value),
+                    'c' => out.target_client = Some(value),
+                    'd' => out.start_dir = Some(value),
+                    'e' => {
+                        if let Some((k, v)) = value.split_once('=') {
+                            out.env.push((k.to_string(), v.to_string()));
+                        }
+                    }
+                    'h' => out.height = Some(value),
+                    'n' => out.name = Some(value),
+                    's' => out.style = Some(value),
+                    'S' => out.border_style = Some(value),
+                    'T' => out.title = Some(value),
+                    'w' => out.width = Some(value),
+                    'x' => out.x = Some(value),
+                    'y' => out.y = Some(value),
+                    't' => out.target = Some(value),
+                    _ => {}
+                }
+                break;
+            }
+            match f {
+                'E' => exits += 1,
+                'B' => out.no_border = true,
+                // psmux's old spelling of "keep the popup open"
+                'K' => exits = 0,
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+    out.command = args[i.min(args.len())..].join(" ");
+    out.close = match exits {
+        0 => PopupClose::Keep,
+        1 => PopupClose::OnExit,
+        _ => PopupClose::OnSuccess,
+    };
+    out
+}
+
+/// A size in cells: `N` or `N%` of `total`, at most `total`.
+fn size(spec: Option<&str>, total: u16, default: u16) -> u16 {
+    let n = match spec {
+        Some(s) => match s.strip_suffix('%') {
+            Some(p) => p.parse::<u32>().map(|p| (total as u32 * p.min(100) / 100) as u16).unwrap_or(default),
+            None => s.parse().unwrap_or(default),
+        },
+        None => default,
+    };
+    n.clamp(1, total.max(1))
+}
+
+/// What the position tokens refer to, in window-area cells.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Anchors {
+    pub area: (u16, u16),
+    pub pane: Rect,
+    pub mouse: Option<(u16, u16)>,
+    /// Column of the active window's entry on the status line.
+    pub window_x: u16,
+    pub status_top: bool,
+}
+
+impl Anchors {
+    pub fn of(app: &AppState) -> Self {
+        let area = app.last_window_area;
+        let mut rects = Vec::new();
+        let mut pane = area;
+        if let Some(win) = app.windows.get(app.active_idx) {
+            compute_rects(&win.root, area, &mut rects);
+            if let Some((_, r)) = rects.into_iter().find(|(p, _)| *p == win.active_path) { pane = r; }
+        }
+        let window_x = app.tab_positions.iter().find(|t| t.0 == app.active_idx).map(|t| t.1).unwrap_or(0);
+        Anchors {
+            area: (area.width, area.height),
+            pane: Rect { x: pane.x.saturating_sub(area.x), y: pane.y.saturating_sub(area.y), ..pane },
+            mouse: app.last_mouse_down.map(|(x, y)| (x.saturating_sub(area.x), y.saturating_sub(area.y))),
+            window_x,
+            status_top: app.status_position == "top",
+        }
+    }
+}
+
+/// Resolve `-w`/`-h`/`-x`/`-y` to a rectangle inside the window area.
+/// `-x`/`-y` have already been format-expanded.
+pub fn geometry(args: &PopupArgs, at: &Anchors) -> Rect {
+    let (aw, ah) = at.area;
+    let w = size(args.width.as_deref(), aw, 80);
+    let h = size(args.height.as_deref(), ah, 24);
+    let number = |s: &str, total: u16| match s.strip_suffix('%') {
+        Some(p) => p.parse::<u32>().ok().map(|p| (total as u32 * p.min(100) / 100) as u16),
+        None => s.parse::<u16>().ok(),
+    };
+    let x = match args.x.as_deref().unwrap_or("C") {
+        "C" => aw.saturating_sub(w) / 2,
+        "R" => aw.saturating_sub(w),
+        "P" => at.pane.x,
+        "M" => at.mouse.map(|m| m.0).unwrap_or(aw.saturating_sub(w) / 2),
+        "W" => at.window_x,
+        s => number(s, aw).unwrap_or(aw.saturating_sub(w) / 2),
+    };
+    let status_line = if at.status_top { 0 } else { ah.saturating_sub(h) };
+    let y = match args.y.as_deref().unwrap_or("C") {
+        "C" => ah.saturating_sub(h) / 2,
+        // Bottom-left corner of the pane
+        "P" => (at.pane.y + at.pane.height).saturating_sub(h),
+        // Just below the click
+        "M" => at.mouse.map(|m| m.1 + 1).unwrap_or(ah.saturating_sub(h) / 2),
+        "S" | "W" => status_line,
+        s => number(s, ah).unwrap_or(ah.saturating_sub(h) / 2),
+    };
+    Rect { x: x.min(aw.saturating_sub(w)), y: y.min(ah.saturating_sub(h)), width: w, height: h }
+}
+
+/// Open the popup described by `args` as the current mode.  Without a
+/// command the popup is an empty box closed with `q`.  With a name (`-n`)
+/// the focused popup of that name is hidden instead, and a floating one in
+/// the current window is brought back rather than started again.
+///
+/// `-t` picks the pane the popup is opened for (its formats, its directory
+/// and `-x P`).  Without a command, a `-t` naming another session shows
+/// that session in the popup instead, starting it if it is not running.
+pub fn open(app: &mut AppState, mut args: PopupArgs) {
+    if let Some(name) = args.name.as_deref() {
+        if focused_name(app) == Some(name) {
+            let _ = hide(app, None);
+            return;
+        }
+        if let Some(popup) = take_float(app, name) {
+            focus(app, popup);
+            return;
+        }
+    }
+    let target = args.target.as_deref().map(crate::cli::parse_target);
+    let elsewhere = target.as_ref()
+        .and_then(|t| t.session.as_deref())
+        .is_some_and(|s| s != app.session_name && args.command.is_empty());
+    let restore = match args.target.as_deref() {
+        Some(t) if !elsewhere => focus_target(app, t),
+        _ => None,
+    };
+    for pos in [&mut args.x, &mut args.y] {
+        if let Some(p) = pos.as_mut() { *p = expand_format(p, app); }
+    }
+    let rect = geometry(&args, &Anchors::of(app));
+    let border_lines = if args.no_border {
+        "none".to_string()
+    } else {
+        args.border_lines.clone().unwrap_or_else(|| app.popup_border_lines.clone())
+    };
+    let inset = if border_lines == "none" { 0 } else { 2 };
+    let title = match args.title.as_deref() {
+        Some(t) => expand_format(t, app),
+        None if elsewhere => args.target.clone().unwrap_or_default(),
+        None => args.command.clone(),
+    };
+    let size = PtySize { rows: rect.height.saturating_sub(inset).max(1), cols: rect.width.saturating_sub(inset).max(1), pixel_width: 0, pixel_height: 0 };
+    let popup_pty = if args.command.is_empty() { None } else { spawn(app, &args, size) };
+    let view = match target {
+        Some(t) if elsewhere => Some(SessionView::start(app, &t, size.rows, size.cols)),
+        _ => None,
+    };
+    if let Some((idx, win, path)) = restore {
+        app.windows[win].active_path = path;
+        app.active_idx = idx;
+    }
+    let popup = Popup {
+        name: args.name,
+        output: if args.command.is_empty() && view.is_none() { "Press 'q' or Escape to close\n".to_string() } else { String::new() },
+        command: args.command,
+        process: None,
+        x: rect.x,
+        y: rect.y,
+        width: rect.width,
+        height: rect.height,
+        close: if popup_pty.is_some() || view.is_some() { args.close } else { PopupClose::Keep },
+        title,
+        style: args.style.unwrap_or_else(|| app.popup_style.clone()),
+        border_style: args.border_style.unwrap_or_else(|| app.popup_border_style.clone()),
+        border_lines,
+        popup_pty,
+        pane_id: None,
+        hidden: false,
+        view,
+    };
+    focus(app, popup);
+}
+
+/// Make the pane `target` names active for the moment.  Returns what to
+/// put back: the active window, and the window and active path changed.
+fn focus_target(app: &mut AppState, target: &str) -> Option<(usize, usize, Vec<usize>)> {
+    let (win, path) = crate::tree::resolve_pane_target(app, target)?;
+    let old = std::mem::replace(&mut app.windows[win].active_path, path);
+    let idx = std::mem::replace(&mut app.active_idx, win);
+    Some((idx, win, old))
+}
+
+fn focused_name(app: &AppState) -> Option<&str> {
+    match &app.mode {
+        Mode::PopupMode { popup } => popup.name.as_deref(),
+        _ => None,
+    }
+}
+
+/// Make `popup` the focused popup, moving the one focused now (if any) to
+/// the floats.
+pub fn focus(app: &mut AppState, mut popup: Popup) {
+    unfocus(app, false);
+    popup.hidden = false;
+    app.mode = Mode::PopupMode { popup };
+}
+
+/// Move the focused popup to the current window's floats, visible or
+/// hidden.  A popup showing neither a command nor a session has nothing to
+/// keep and is closed.
+/// Returns false when no popup is focused.
+pub fn unfocus(app: &mut AppState, hidden: bool) -> bool {
+    if !matches!(app.mode, Mode::PopupMode { .. }) { return false; }
+    let Mode::PopupMode { mut popup } = std::mem::replace(&mut app.mode, Mode::Passthrough) else { return false };
+    if popup.popup_pty.is_none() && popup.view.is_none() { return true; }
+    if popup.name.is_none() { popup.name = Some(unused_name(app)); }
+    popup.hidden = hidden;
+    if let Some(win) = app.windows.get_mut(app.active_idx) { win.floating.push(popup); }
+    true
+}
+
+/// `popupN` with the lowest N no popup is using.
+fn unused_name(app: &AppState) -> String {
+    let taken: Vec<&str> = app.windows.iter()
+        .flat_map(|w| w.floating.iter())
+        .chain(match &app.mode { Mode::PopupMode { popup } => Some(popup), _ => None })
+        .filter_map(|p| p.name.as_deref())
+        .collect();
+    (0..).map(|n| format!("popup{}", n)).find(|n| !taken.contains(&n.as_str())).unwrap_or_default()
+}
+
+/// Remove the float called `name` from the current window.
+fn take_float(app: &mut AppState, name: &str) -> Option<Popup> {
+    let win = app.windows.get_mut(app.active_idx)?;
+    let i = win.floating.iter().position(|p| p.name.as_deref() == Some(name))?;
+    Some(win.floating.remove(i))
+}
+
+/// Remove the popup called `name`, focused or floating in the current window.
+fn take(app: &mut AppState, name: &str) -> Option<Popup> {
+    if focused_name(app) == Some(name) {
+        if let Mode::PopupMode { popup } = std::mem::replace(&mut app.mode, Mode::Passthrough) {
+            return Some(popup);
+        }
+    }
+    take_float(app, name)
+}
+
+/// The popup a command applies to: `name`, or without one the focused popup.
+fn target<'a>(app: &'a mut AppState, name: Option<&str>) -> Result<&'a mut Popup, String> {
+    if let Mode::PopupMode { popup } = &mut app.mode {
+        if name.is_none() || popup.name.as_deref() == name {
+            return Ok(popup);
+        }
+    }
+    let Some(name) = name else { return Err("no popup is focused".to_string()) };
+    app.windows.get_mut(app.active_idx)
+        .and_then(|w| w.floating.iter_mut().find(|p| p.name.as_deref() == Some(name)))
+        .ok_or_else(|| format!("can't find popup: {}", name))
+}
+
+/// Run `hide-popup`, `move-popup` or `resize-popup` (`argv[0]`).
+pub fn run(app: &mut AppState, argv: &[&str]) -> Result<(), String> {
+    let args = argv.get(1..).unwrap_or_default();
+    match argv.first().copied() {
+        Some("hide-popup") => {
+            let name = args.iter().position(|a| *a == "-n").and_then(|i| args.get(i + 1)).copied();
+            hide(app, name)
+        }
+        Some("move-popup") => adjust(app, false, args),
+        Some("resize-popup") => adjust(app, true, args),
+        other => Err(format!("unknown command: {}", other.unwrap_or(""))),
+    }
+}
+
+/// `hide-popup [-n name]`: hide a popup without stopping its command.
+pub fn hide(app: &mut AppState, name: Option<&str>) -> Result<(), String> {
+    if name.is_none() || focused_name(app) == name {
+        return if unfocus(app, true) { Ok(()) } else { Err("no popup is focused".to_string()) };
+    }
+    target(app, name)?.hidden = true;
+    Ok(())
+}
+
+/// `move-popup` and `resize-popup`: `-U`/`-D`/`-L`/`-R` with an optional
+/// count (default 1), or absolute `-x`/`-y` (move) and `-w`/`-h` (resize).
+pub fn adjust(app: &mut AppState, resize: bool, args: &[&str]) -> Result<(), String> {
+    let (mut name, mut dir, mut count) = (None, None, 1u16);
+    let (mut abs_a, mut abs_b) = (None, None);
+    let mut i = 0;
+    while i < args.len() {
+        match args[i] {
+            "-n" => { i += 1; name = args.get(i).copied(); }
+            "-U" | "-D" | "-L" | "-R" => dir = Some(args[i]),
+            "-x" | "-w" => { i += 1; abs_a = args.get(i).copied(); }
+            "-y" | "-h" => { i += 1; abs_b = args.get(i).copied(); }
+            a => count = a.parse().map_err(|_| format!("invalid count: {}", a))?,
+        }
+        i += 1;
+    }
+    let area = (app.last_window_area.width, app.last_window_area.height);
+    let popup = target(app, name)?;
+    let mut r = popup.rect();
+    {
+        let (across, down) = if resize { (&mut r.width, &mut r.height) } else { (&mut r.x, &mut r.y) };
+        match dir {
+            Some("-U") => *down = down.saturating_sub(count),
+            Some("-D") => *down = down.saturating_add(count),
+            Some("-L") => *across = across.saturating_sub(count),
+            Some("-R") => *across = across.saturating_add(count),
+            _ => {}
+        }
+        if let Some(a) = abs_a { *across = size(Some(a), area.0, *across); }
+        if let Some(b) = abs_b { *down = size(Some(b), area.1, *down); }
+    }
+    popup.place(r, area);
+    Ok(())
+}
+
+/// Left button press at (x, y).  Returns true when a popup takes the click:
+/// a press on a popup focuses it, and on its top border starts a move or on
+/// its right or bottom border a resize.  A press elsewhere sends a named
+/// popup to the background; an unnamed popup is modal and swallows it.
+pub fn mouse_down(app: &mut AppState, x: u16, y: u16) -> bool {
+    let area = app.last_window_area;
+    let (Some(px), Some(py)) = (x.checked_sub(area.x), y.checked_sub(area.y)) else { return false };
+    let hit = |p: &Popup| !p.hidden && p.rect().contains(ratatui::layout::Position { x: px, y: py });
+    if let Mode::PopupMode { popup } = &app.mode {
+        if hit(popup) {
+            app.popup_drag = border_drag(popup, px, py);
+            return true;
+        }
+        if popup.name.is_none() { return true; }
+        unfocus(app, false);
+    }
+    let Some(win) = app.windows.get_mut(app.active_idx) else { return false };
+    let Some(i) = win.floating.iter().rposition(|p| hit(p)) else { return false };
+    let popup = win.floating.remove(i);
+    app.popup_drag = border_drag(&popup, px, py);
+    focus(app, popup);
+    true
+}
+
+fn border_drag(p: &Popup, px: u16, py: u16) -> Option<PopupDrag> {
+    if p.inset() == 0 { return None; }
+    let r = p.rect();
+    let resize = px + 1 == r.x + r.width || py + 1 == r.y + r.height;
+    (resize || py == r.y).then_some(PopupDrag { resize, from: (px, py), orig: r })
+}
+
+/// Mouse motion with the left button held; true while dragging a popup.
+pub fn mouse_drag(app: &mut AppState, x: u16, y: u16) -> bool {
+    let Some(d) = app.popup_drag else { return false };
+    let area = app.last_window_area;
+    let (px, py) = (x.saturating_sub(area.x) as i32, y.saturating_sub(area.y) as i32);
+    let (dx, dy) = (px - d.from.0 as i32, py - d.from.1 as i32);
+    let shift = |v: u16, by: i32| (v as i32 + by).max(0) as u16;
+    let r = if d.resize {
+        Rect { width: shift(d.orig.width, dx), height: shift(d.orig.height, dy), ..d.orig }
+    } else {
+        Rect { x: shift(d.orig.x, dx), y: shift(d.orig.y, dy), ..d.orig }
+    };
+    if let Mode::PopupMode { popup } = &mut app.mode {
+        popup.place(r, (area.width, area.height));
+    }
+    true
+}
+
+/// Left button release; true if it ended a popup drag.
+pub fn mouse_up(app: &mut AppState) -> bool {
+    app.popup_drag.take().is_some()
+}
+
+/// Close floats whose command has exited and whose `-E`/`-EE` says so.
+/// Returns true if any were closed.
+pub fn reap_floats(app: &mut AppState) -> bool {
+    let mut any = false;
+    for win in app.windows.iter_mut() {
+        let before = win.floating.len();
+        win.floating.retain_mut(|p| !p.exit_status().is_some_and(|ok| p.close.closes(ok)));
+        any |= win.floating.len() != before;
+    }
+    any
+}
+
+/// `break-pane -f`: take the active pane out of the layout and focus it as
+/// a popup named `name`.
+pub fn float_active_pane(app: &mut AppState, name: Option<String>) -> Result<(), String> {
+    let idx = app.active_idx;
+    let win = app.windows.get_mut(idx).ok_or("no window")?;
+    if matches!(win.root, Node::Leaf(_)) {
+        return Err("can't break the only pane in a window into a popup".to_string());
+    }
+    let root = std::mem::replace(&mut win.root, Node::Split { kind: LayoutKind::Horizontal, sizes: vec![], children: vec![] });
+    let (remaining, extracted) = crate::tree::extract_node(root, &win.active_path);
+    if let Some(rem) = remaining { win.root = rem; }
+    let Some(Node::Leaf(pane)) = extracted else { return Err("no active pane".to_string()) };
+    win.active_path = crate::tree::first_leaf_path(&win.root);
+    win.pane_mru.retain(|id| *id != pane.id);
+    let mut popup = Popup {
+        name: Some(name.unwrap_or_else(|| unused_name(app))),
+        command: String::new(),
+        output: String::new(),
+        process: None,
+        x: 0,
+        y: 0,
+        width: 0,
+        height: 0,
+        close: PopupClose::OnExit,
+        title: pane.title,
+        style: app.popup_style.clone(),
+        border_style: app.popup_border_style.clone(),
+        border_lines: app.popup_border_lines.clone(),
+        popup_pty: Some(PopupPty { master: pane.master, writer: pane.writer, child: pane.child, term: pane.term, data_version: pane.data_version }),
+        pane_id: Some(pane.id),
+        hidden: false,
+        view: None,
+    };
+    let args = PopupArgs { width: Some("80%".into()), height: Some("80%".into()), ..Default::default() };
+    let area = app.last_window_area;
+    popup.place(geometry(&args, &Anchors::of(app)), (area.width, area.height));
+    resize_all_panes(app);
+    focus(app, popup);
+    Ok(())
+}
+
+/// `join-pane -s NAME`: put a popup back into the current window's layout,
+/// split from the active pane.
+pub fn join(app: &mut AppState, name: &str, kind: LayoutKind) -> Result<(), String> {
+    if target(app, Some(name))?.popup_pty.is_none() {
+        return Err(format!("popup {} has no command", name));
+    }
+    let Some(Popup { popup_pty: Some(pty), pane_id, title, .. }) = take(app, name) else {
+        return Err(format!("can't find popup: {}", name));
+    };
+    let id = pane_id.unwrap_or_else(|| {
+        app.next_pane_id += 1;
+        app.next_pane_id - 1
+    });
+    let (rows, cols) = pty.term.lock().map(|p| p.screen().size()).unwrap_or((24, 80));
+    let epoch = std::time::Instant::now() - std::time::Duration::from_secs(2);
+    let child_pid = crate::platform::mouse_inject::get_child_pid(&*pty.child);
+    let title = if title.is_empty() { format!("pane %{}", id) } else { title };
+    let pane = Pane { master: pty.master, writer: pty.writer, child: pty.child, term: pty.term, last_rows: rows, last_cols: cols, id, title, child_pid, data_version: pty.data_version, last_title_check: epoch, last_infer_title: epoch, dead: false, exit_status: None, dead_time: None, vt_bridge_cache: None, vti_mode_cache: None, mouse_input_cache: None, copy_state: None, copy_mark: None, pane_style: None };
+    let win = app.windows.get_mut(app.active_idx).ok_or("no window")?;
+    let path = win.active_path.clone();
+    crate::tree::replace_leaf_with_split(&mut win.root, &path, kind, Node::Leaf(pane));
+    win.active_path = path.into_iter().chain([1]).collect();
+    win.pane_mru.insert(0, id);
+    resize_all_panes(app);
+    Ok(())
+}
+
+/// Run the popup command in a PTY of `size`, in the `-d` directory (by
+/// default the active pane's) with the session's environment plus `-e`.
+fn spawn(app: &AppState, args: &PopupArgs, size: PtySize) -> Option<PopupPty> {
+    let pair = portable_pty::native_pty_system().openpty(size).ok()?;
+    let mut cmd = CommandBuilder::new(if cfg!(windows) { "pwsh" } else { "sh" });
+    if cfg!(windows) { cmd.args(["-NoProfile", "-Command", &args.command]); } else { cmd.args(["-c", &args.command]); }
+    let dir = expand_format(args.start_dir.as_deref().unwrap_or("#{pane_current_path}"), app);
+    if !dir.is_empty() && std::path::Path::new(&dir).is_dir() {
+        cmd.cwd(dir);
+    } else if let Ok(dir) = std::env::current_dir() {
+        cmd.cwd(dir);
+    }
+    let pane_id = app.windows.get(app.active_idx)
+        .and_then(|w| crate::tree::active_pane(&w.root, &w.active_path))
+        .map(|p| p.id).unwrap_or(0);
+    crate::pane::set_tmux_env(&mut cmd, pane_id, app.control_port, app.socket_name.as_deref(), &app.session_name, app.claude_code_fix_tty, app.claude_code_force_interactive);
+    let env: HashMap<String, String> = args.env.iter().cloned().collect();
+    crate::pane::apply_user_environment(&mut cmd, &app.environment);
+    crate::pane::apply_user_environment(&mut cmd, &env);
+    let child = pair.slave.spawn_command(cmd).ok()?;
+    // Close the slave handle immediately – required for ConPTY.
+    drop(pair.slave);
+    let term = std::sync::Arc::new(std::sync::Mutex::new(crate::pane::new_parser(size.rows, size.cols, 0, app.ambiguous_width, app.variation_selector_always_wide)));
+    let data_version = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+    // The same reader as a pane's, so the popup can become one (`join-pane`)
+    if let Ok(reader) = pair.master.try_clone_reader() {
+        crate::pane::spawn_reader_thread(reader, term.clone(), data_version.clone());
+    }
+    let mut writer = pair.master.take_writer().ok()?;
+    crate::pane::conpty_preemptive_dsr_response(&mut *writer);
+    Some(PopupPty { master: pair.master, writer, child, term, data_version })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchors() -> Anchors {
+        Anchors {
+            area: (100, 40),
+            pane: Rect { x: 50, y: 0, width: 50, height: 20 },
+            mouse: Some((10, 5)),
+            window_x: 12,
+            status_top: false,
+        }
+    }
+
+    #[test]
+    fn parses_tmux_flags_and_keeps_values_out_of_the_command() {
+        let a = parse_args(&["-w", "50%", "-h", "10", "-EE", "-d", "C:\\src", "-e", "A=1", "-T", "#S", "htop", "-d", "2"]);
+        assert_eq!(a.width.as_deref(), Some("50%"));
+        assert_eq!(a.height.as_deref(), Some("10"));
+        assert_eq!(a.close, PopupClose::OnSuccess);
+        assert_eq!(a.start_dir.as_deref(), Some("C:\\src"));
+        assert_eq!(a.env, vec![("A".to_string(), "1".to_string())]);
+        assert_eq!(a.title.as_deref(), Some("#S"));
+        assert_eq!(a.command, "htop -d 2");
+        let b = parse_args(&["-BEw80", "-xR", "fzf"]);
+        assert!(b.no_border);
+        assert_eq!(b.close, PopupClose::OnExit);
+        assert_eq!(b.width.as_deref(), Some("80"));
+        assert_eq!(b.x.as_deref(), Some("R"));
+        assert_eq!(b.command, "fzf");
+        assert_eq!(parse_args(&["-K", "ls"]).close, PopupClose::Keep);
+    }
+
+    fn boxed(x: u16, y: u16, width: u16, height: u16) -> Popup {
+        Popup {
+            name: Some("scratch".to_string()),
+            command: String::new(),
+            output: String::new(),
+            process: None,
+            x,
+            y,
+            width,
+            height,
+            close: PopupClose::Keep,
+            title: String::new(),
+            style: String::new(),
+            border_style: String::new(),
+            border_lines: "single".to_string(),
+            popup_pty: None,
+            pane_id: None,
+            hidden: false,
+            view: None,
+        }
+    }
+
+    #[test]
+    fn named_popups() {
+        let a = parse_args(&["-n", "scratch", "-E", "pwsh"]);
+        assert_eq!(a.name.as_deref(), Some("scratch"));
+        assert_eq!(a.command, "pwsh");
+    }
+
+    #[test]
+    fn placing_keeps_the_popup_inside_the_window() {
+        let mut p = boxed(0, 0, 20, 10);
+        p.place(Rect { x: 95, y: 38, width: 20, height: 10 }, (100, 40));
+        assert_eq!(p.rect(), Rect { x: 80, y: 30, width: 20, height: 10 });
+        // Never smaller than the border plus one cell, never larger than the window
+        p.place(Rect { x: 0, y: 0, width: 0, height: 500 }, (100, 40));
+        assert_eq!((p.width, p.height), (3, 40));
+    }
+
+    #[test]
+    fn border_presses_start_moves_and_resizes() {
+        let p = boxed(10, 5, 20, 10);
+        let drag = |x, y| border_drag(&p, x, y).map(|d| d.resize);
+        assert_eq!(drag(15, 5), Some(false));
+        assert_eq!(drag(29, 8), Some(true));
+        assert_eq!(drag(12, 14), Some(true));
+        assert_eq!(drag(15, 8), None);
+        let mut borderless = boxed(10, 5, 20, 10);
+        borderless.border_lines = "none".to_string();
+        assert!(border_drag(&borderless, 15, 5).is_none());
+    }
+
+    #[test]
+    fn sizes_are_cells_or_percentages() {
+        let g = geometry(&parse_args(&["-w", "50%", "-h", "25%"]), &anchors());
+        assert_eq!((g.x, g.y, g.width, g.height), (25, 15, 50, 10));
+        let g = geometry(&parse_args(&["-w", "500", "-h", "3"]), &anchors());
+        assert_eq!((g.x, g.width, g.height), (0, 100, 3));
+    }
+
+    #[test]
+    fn position_tokens() {
+        let at = anchors();
+        let pos = |x: &str, y: &str| {
+            let g = geometry(&parse_args(&["-w", "20", "-h", "10", "-x", x, "-y", y]), &at);
+            (g.x, g.y)
+        };
+        assert_eq!(pos("C", "C"), (40, 15));
+        assert_eq!(pos("R", "S"), (80, 30));
+        assert_eq!(pos("P", "P"), (50, 10));
+        assert_eq!(pos("M", "M"), (10, 6));
+        assert_eq!(pos("W", "W"), (12, 30));
+        assert_eq!(pos("95", "10%"), (80, 4));
+    }
+}
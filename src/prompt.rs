@@ -0,0 +1,782 @@
+//! `command-prompt` (`Prefix :`): a line editor on the status line with Tab
+//! completion, one or more fields asked in turn (`-p "a,b"`), and a command
+//! template run with the responses (`%1`..`%9`, `%%`).
+
+use std::io;
+use std::path::MAIN_SEPARATOR;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+use crate::commands::execute_command_prompt;
+use crate::config::format_key_binding;
+use crate::format::expand_format;
+use crate::server::helpers::TMUX_COMMANDS;
+use crate::tree::count_panes;
+use crate::types::{AppState, HistorySearch, Mode, PromptState};
+
+/// Flags of each command in getopt form: a `:` follows flags that take a
+/// value.
+const FLAGS: &[(&str, &str)] = &[
+    ("attach-session", "ErdD:f:c:t:x:"),
+    ("bind-key", "nrN:T:"),
+    ("break-pane", "abdPF:n:s:t:"),
+    ("capture-pane", "ab:CeE:JMNpPqS:Tt:"),
+    ("choose-buffer", "F:f:K:NO:rt:yZ"),
+    ("choose-client", "F:f:K:NO:rt:yZ"),
+    ("choose-session", "F:f:GK:NO:rst:wyZ"),
+    ("choose-tree", "F:f:GK:NO:rst:wyZ"),
+    ("choose-window", "F:f:GK:NO:rst:wyZ"),
+    ("clear-history", "Ht:"),
+    ("clock-mode", "t:"),
+    ("command-prompt", "1beFiklI:Np:t:T:"),
+    ("confirm-before", "bc:p:t:y"),
+    ("copy-mode", "deHMqSs:t:u"),
+    ("customize-mode", "F:f:Nt:yZ"),
+    ("delete-buffer", "b:"),
+    ("detach-client", "aE:s:t:P"),
+    ("display-menu", "b:c:C:H:s:S:MOt:T:x:y:"),
+    ("display-message", "aCc:d:lINpt:F:v"),
+    ("display-panes", "bd:Nt:"),
+    ("display-popup", "Bb:Cc:d:e:EKh:n:s:S:t:T:w:x:y:"),
+    ("find-window", "CiNrt:TZ"),
+    ("has-session", "t:"),
+    ("hide-popup", "n:"),
+    ("if-shell", "bFt:"),
+    ("join-pane", "bdfhvp:l:s:t:"),
+    ("kill-pane", "at:"),
+    ("kill-server", ""),
+    ("kill-session", "aCt:"),
+    ("kill-window", "at:"),
+    ("last-pane", "det:Z"),
+    ("last-window", "t:"),
+    ("link-window", "abdks:t:"),
+    ("list-buffers", "F:f:O:r"),
+    ("list-clients", "F:f:O:rt:"),
+    ("list-commands", "F:"),
+    ("list-keys", "1aNP:T:"),
+    ("list-panes", "aF:f:O:rst:"),
+    ("list-sessions", "F:f:O:r"),
+    ("list-windows", "aF:f:O:rt:"),
+    ("load-buffer", "b:t:w"),
+    ("lock-client", "t:"),
+    ("lock-server", ""),
+    ("lock-session", "t:"),
+    ("move-pane", "bdfhvp:l:s:t:"),
+    ("move-popup", "DLn:Rx:y:U"),
+    ("move-window", "abdkrs:t:"),
+    ("new-session", "Ac:dDe:EF:f:n:Ps:t:x:Xy:"),
+    ("new-window", "abc:de:F:kn:PSt:"),
+    ("next-layout", "t:"),
+    ("next-window", "at:"),
+    ("paste-buffer", "db:prs:t:"),
+    ("pipe-pane", "IOot:"),
+    ("previous-layout", "t:"),
+    ("previous-window", "at:"),
+    ("record-pane", "f:t:x"),
+    ("refresh-client", "A:B:cC:Df:r:F:lLRSt:U"),
+    ("rename-session", "t:"),
+    ("rename-window", "t:"),
+    ("resize-pane", "DLMRTt:Ux:y:Z"),
+    ("resize-popup", "Dh:Ln:Rw:U"),
+    ("resize-window", "aADLRt:Ux:y:"),
+    ("respawn-pane", "c:e:kt:"),
+    ("respawn-window", "c:e:kt:"),
+    ("rotate-window", "Dt:UZ"),
+    ("run-shell", "bd:Ct:Es:c:"),
+    ("save-buffer", "ab:"),
+    ("select-layout", "Enopt:"),
+    ("select-pane", "DdegLlMmP:RT:t:UZ"),
+    ("select-window", "lnpTt:"),
+    ("send-keys", "c:FHKlMN:Rt:X"),
+    ("send-prefix", "2t:"),
+    ("server-info", ""),
+    ("set-buffer", "ab:t:n:w"),
+    ("set-environment", "Fhgrt:u"),
+    ("set-hook", "agpRt:uw"),
+    ("set-option", "aFgopqst:uUw"),
+    ("set-window-option", "aFgoqt:u"),
+    ("show-buffer", "b:"),
+    ("show-environment", "hgst:"),
+    ("show-hooks", "gpt:w"),
+    ("show-messages", "JTt:"),
+    ("show-options", "AgHpqst:vw"),
+    ("show-window-options", "gvt:"),
+    ("source-file", "t:Fnqv"),
+    ("split-window", "bc:de:fF:hIl:p:Pt:vZ"),
+    ("start-server", ""),
+    ("suspend-client", "t:"),
+    ("swap-pane", "dDs:t:UZ"),
+    ("swap-window", "ds:t:"),
+    ("switch-client", "c:EFlnO:pt:rT:Z"),
+    ("unbind-key", "anqT:"),
+    ("unlink-window", "kt:"),
+    ("wait-for", "LSU"),
+];
+
+/// Commands whose first argument is an option name.
+const OPTION_COMMANDS: &[&str] = &["set-option", "set-window-option", "show-options", "show-window-options"];
+
+/// Commands whose argument is a file.
+const FILE_COMMANDS: &[&str] = &["source-file", "load-buffer", "save-buffer"];
+
+/// Open the prompt with the `command-prompt` flags (`-1`, `-F`, `-I inputs`,
+/// `-k`, `-l`, `-N`, `-p prompts`, `-T type`) and optional template.
+pub fn open(app: &mut AppState, args: &[&str]) {
+    let (mut prompts, mut inputs, mut kind) = (None, None, "command".to_string());
+    let (mut single, mut numeric, mut key_name, mut expand, mut literal) = (false, false, false, false, false);
+    let mut i = 0;
+    while i < args.len() {
+        let a = args[i];
+        if a == "--" { i += 1; break; }
+        if !a.starts_with('-') || a.len() < 2 { break; }
+        for (j, f) in a[1..].char_indices() {
+            if "IptT".contains(f) {
+                // The value is the rest of this word or the next argument
+                let rest = &a[j + 2..];
+                let value = if !rest.is_empty() {
+                    Some(rest.to_string())
+                } else {
+                    i += 1;
+                    args.get(i).map(|v| v.to_string())
+                };
+                match f {
+                    'I' => inputs = value,
+                    'p' => prompts = value,
+                    'T' => if let Some(v) = value { kind = v },
+                    _ => {}
+                }
+                break;
+            }
+            match f {
+                '1' => single = true,
+                'N' => numeric = true,
+                'k' => key_name = true,
+                'F' => expand = true,
+                'l' => literal = true,
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+    let rest = args[i.min(args.len())..].join(" ");
+    let template = (!rest.is_empty()).then(|| if expand { expand_format(&rest, app) } else { rest });
+    let split = |s: String| -> Vec<String> {
+        if literal { vec![s] } else { s.split(',').map(|p| p.to_string()).collect() }
+    };
+    let prompts = match prompts {
+        Some(p) => split(expand_format(&p, app)).into_iter().map(|p| format!("{} ", p)).collect(),
+        None => match &template {
+            Some(t) => vec![format!("({}) ", t.split_whitespace().next().unwrap_or_default())],
+            None => vec![":".to_string()],
+        },
+    };
+    let inputs = inputs.map(|v| split(expand_format(&v, app))).unwrap_or_default();
+    let input = inputs.first().cloned().unwrap_or_default();
+    app.prompt_history_idx = app.prompt_history.get(&kind).map_or(0, |h| h.len());
+    app.mode = Mode::CommandPrompt { prompt: PromptState {
+        prompts,
+        inputs,
+        answers: Vec::new(),
+        template,
+        cursor: input.len(),
+        input,
+        single,
+        numeric,
+        key_name,
+        kind,
+        completions: Vec::new(),
+        completion: None,
+        completion_start: 0,
+        search: None,
+    } };
+}
+
+/// Replace `%1`..`%9` with that response and each `%%` with the next
+/// response in turn; `%%%` is like `%%` with double quotes escaped.
+fn substitute(template: &str, answers: &[String]) -> String {
+    let mut out = String::new();
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(i) = rest.find('%') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let (escape, tail) = match after.strip_prefix("%%") {
+            Some(tail) => (true, Some(tail)),
+            None => (false, after.strip_prefix('%')),
+        };
+        if let Some(tail) = tail {
+            match answers.get(next) {
+                Some(a) if escape => out.push_str(&a.replace('"', "\\\"")),
+                Some(a) => out.push_str(a),
+                None => out.push_str(&rest[i..rest.len() - tail.len()]),
+            }
+            next += 1;
+            rest = tail;
+        } else if let Some(n) = after.chars().next().and_then(|c| c.to_digit(10)).filter(|n| *n > 0) {
+            out.push_str(answers.get(n as usize - 1).map_or("", |a| a.as_str()));
+            rest = &after[1..];
+        } else {
+            out.push('%');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Take the current response; ask the next field or run the command.
+fn accept(app: &mut AppState) -> io::Result<()> {
+    let Mode::CommandPrompt { mut prompt } = std::mem::replace(&mut app.mode, Mode::Passthrough) else { return Ok(()) };
+    let answer = std::mem::take(&mut prompt.input);
+    if !prompt.key_name {
+        add_history(app, &prompt.kind, &answer);
+    }
+    app.prompt_history_idx = app.prompt_history.get(&prompt.kind).map_or(0, |h| h.len());
+    prompt.answers.push(answer);
+    let field = prompt.answers.len();
+    if field < prompt.prompts.len() {
+        prompt.input = prompt.inputs.get(field).cloned().unwrap_or_default();
+        prompt.cursor = prompt.input.len();
+        app.mode = Mode::CommandPrompt { prompt };
+        return Ok(());
+    }
+    let command = match &prompt.template {
+        Some(t) => substitute(t, &prompt.answers),
+        None => prompt.answers.swap_remove(0),
+    };
+    execute_command_prompt(app, &command)
+}
+
+// ─── History ────────────────────────────────────────────────────────────────
+
+fn history_path(app: &AppState) -> Option<String> {
+    if app.history_file.is_empty() { return None; }
+    let home = std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")).unwrap_or_default();
+    Some(match app.history_file.strip_prefix('~') {
+        Some(rest) => format!("{}{}", home, rest),
+        None => app.history_file.clone(),
+    })
+}
+
+/// Append `entry` to the history of `kind`, dropping older copies of it
+/// and the oldest entries beyond `prompt-history-limit`.
+fn push_history(app: &mut AppState, kind: &str, entry: &str) {
+    let limit = app.prompt_history_limit;
+    let history = app.prompt_history.entry(kind.to_string()).or_default();
+    history.retain(|e| e != entry);
+    history.push(entry.to_string());
+    let excess = history.len().saturating_sub(limit);
+    history.drain(..excess);
+}
+
+/// The entries of the `history-file` as (type, entry), oldest first.  Each
+/// line is `type:entry`, as tmux writes it.
+fn read_history(app: &AppState) -> Option<Vec<(String, String)>> {
+    let text = std::fs::read_to_string(history_path(app)?).ok()?;
+    Some(text.lines().filter_map(|line| {
+        let (kind, entry) = match line.split_once(':') {
+            Some((k, e)) if matches!(k, "command" | "search" | "target" | "window-target") => (k, e),
+            _ => ("command", line),
+        };
+        (!entry.is_empty()).then(|| (kind.to_string(), entry.to_string()))
+    }).collect())
+}
+
+/// Merge the `history-file` with the in-memory history when the option is
+/// set, and write the result back.
+pub fn load_history(app: &mut AppState) {
+    let Some(entries) = read_history(app) else { return };
+    let mine = std::mem::take(&mut app.prompt_history);
+    for (kind, entry) in entries {
+        push_history(app, &kind, &entry);
+    }
+    for (kind, entries) in mine {
+        for e in entries { push_history(app, &kind, &e); }
+    }
+    save_history(app);
+}
+
+fn save_history(app: &AppState) {
+    let Some(path) = history_path(app) else { return };
+    let mut kinds: Vec<&String> = app.prompt_history.keys().collect();
+    kinds.sort();
+    let mut text = String::new();
+    for kind in kinds {
+        for e in &app.prompt_history[kind] {
+            text.push_str(&format!("{}:{}\n", kind, e));
+        }
+    }
+    // Write a new file and rename it over the old one, so another server
+    // reading the file never sees it half written
+    let tmp = format!("{}.{}.tmp", path, std::process::id());
+    if std::fs::write(&tmp, text).is_err() || std::fs::rename(&tmp, &path).is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+}
+
+/// Record a prompt response in the history of its type, sharing it with
+/// other servers through the `history-file`.
+pub fn add_history(app: &mut AppState, kind: &str, entry: &str) {
+    if entry.is_empty() { return; }
+    // Start from the file, which has what other servers added since
+    if let Some(entries) = read_history(app) {
+        app.prompt_history.clear();
+        for (k, e) in entries { push_history(app, &k, &e); }
+    }
+    push_history(app, kind, entry);
+    save_history(app);
+}
+
+// ─── Completion ─────────────────────────────────────────────────────────────
+
+/// `arg` is a flag word ending in a flag that takes a value, so the next
+/// word is that value.
+fn takes_value(getopt: &str, arg: &str) -> bool {
+    let Some(flags) = arg.strip_prefix('-').filter(|f| !f.is_empty() && *f != "-") else { return false };
+    for (j, f) in flags.char_indices() {
+        if getopt.find(f).is_some_and(|i| getopt[i + 1..].starts_with(':')) {
+            return j + 1 == flags.len();
+        }
+    }
+    false
+}
+
+fn command_name(word: &str) -> Option<&'static str> {
+    TMUX_COMMANDS.iter().find_map(|c| {
+        let (name, alias) = c.split_once(" (").map_or((*c, ""), |(n, a)| (n, a.trim_end_matches(')')));
+        (name == word || alias == word).then_some(name)
+    })
+}
+
+fn targets(app: &AppState, word: &str, panes: bool) -> Vec<String> {
+    let Some((session, _)) = word.split_once(':') else {
+        let prefix = app.socket_name.as_ref().map(|sn| format!("{}__", sn));
+        let others = crate::choose_tree::servers(prefix.as_deref(), &app.port_file_base());
+        return std::iter::once(app.session_name.clone()).chain(others.into_iter().map(|(_, n)| n))
+            .map(|n| format!("{}:", n))
+            .collect();
+    };
+    // Windows and panes are only known for this session
+    if !session.is_empty() && session != app.session_name { return Vec::new(); }
+    let mut out = Vec::new();
+    for (i, win) in app.windows.iter().enumerate() {
+        let target = format!("{}:{}", session, i + app.window_base_index);
+        if panes {
+            out.extend((0..count_panes(&win.root)).map(|p| format!("{}.{}", target, p)));
+        }
+        out.push(target);
+    }
+    out
+}
+
+fn looks_like_path(word: &str) -> bool {
+    word.starts_with(['~', '.']) || word.contains(['/', '\\'])
+}
+
+fn paths(word: &str) -> Vec<String> {
+    if word == "~" { return vec![format!("~{}", MAIN_SEPARATOR)]; }
+    let (dir, name) = word.split_at(word.rfind(['/', '\\']).map_or(0, |i| i + 1));
+    let sep = dir.chars().last().unwrap_or(MAIN_SEPARATOR);
+    let read = match dir.strip_prefix('~') {
+        _ if dir.is_empty() => ".".to_string(),
+        Some(rest) => {
+            let home = std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")).unwrap_or_default();
+            format!("{}{}", home, rest)
+        }
+        None => dir.to_string(),
+    };
+    let Ok(entries) = std::fs::read_dir(read) else { return Vec::new() };
+    entries.flatten().filter_map(|e| {
+        let file = e.file_name().to_str()?.to_string();
+        // Hidden files only once their dot is typed
+        if !file.starts_with(name) || (name.is_empty() && file.starts_with('.')) { return None; }
+        let slash = if e.path().is_dir() { sep.to_string() } else { String::new() };
+        Some(format!("{}{}{}", dir, file, slash))
+    }).collect()
+}
+
+/// Completions of `word`, given the words before it on the line.
+fn candidates(app: &AppState, kind: &str, words: &[&str], word: &str) -> Vec<String> {
+    let found = match kind {
+        "command" => command_candidates(app, words, word),
+        "target" => targets(app, word, true),
+        "window-target" => targets(app, word, false),
+        _ => Vec::new(),
+    };
+    found.into_iter().filter(|c| c.starts_with(word)).collect()
+}
+
+fn command_candidates(app: &AppState, words: &[&str], word: &str) -> Vec<String> {
+    // Only the command after the last `;` matters
+    let args = words.rsplit(|w| *w == ";").next().unwrap_or_default();
+    let Some(first) = args.first() else {
+        return TMUX_COMMANDS.iter().map(|c| c.split(' ').next().unwrap_or_default().to_string()).collect();
+    };
+    let Some(name) = command_name(first) else { return Vec::new() };
+    let getopt = FLAGS.iter().find(|(c, _)| *c == name).map_or("", |(_, f)| *f);
+    if word.starts_with('-') {
+        return getopt.chars().filter(|c| *c != ':').map(|c| format!("-{}", c)).collect();
+    }
+    let mut positional = 0;
+    let mut i = 1;
+    while i < args.len() {
+        if takes_value(getopt, args[i]) {
+            if i + 1 == args.len() {
+                // `word` is this flag's value
+                return match args[i].chars().last() {
+                    Some('t' | 's') => targets(app, word, true),
+                    _ if looks_like_path(word) => paths(word),
+                    _ => Vec::new(),
+                };
+            }
+            i += 1;
+        } else if !args[i].starts_with('-') {
+            positional += 1;
+        }
+        i += 1;
+    }
+    if OPTION_COMMANDS.contains(&name) && positional == 0 {
+        return crate::help::OPTIONS_REF.iter().map(|(o, _)| o.to_string()).collect();
+    }
+    if FILE_COMMANDS.contains(&name) || looks_like_path(word) {
+        return paths(word);
+    }
+    Vec::new()
+}
+
+fn common_prefix(words: &[String]) -> &str {
+    let first = &words[0];
+    let mut len = first.len();
+    for w in &words[1..] {
+        let same = first.char_indices().zip(w.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8());
+        len = len.min(same);
+    }
+    &first[..len]
+}
+
+fn replace_word(p: &mut PromptState, start: usize, text: &str) {
+    p.input.replace_range(start..p.cursor, text);
+    p.cursor = start + text.len();
+}
+
+/// Tab: complete the word before the cursor.  A single candidate is
+/// inserted; several extend the word to their common prefix, then are
+/// listed and cycled through by further presses.
+fn complete(app: &mut AppState) {
+    let Mode::CommandPrompt { prompt } = &app.mode else { return };
+    if !prompt.completions.is_empty() {
+        let i = prompt.completion.map_or(0, |i| (i + 1) % prompt.completions.len());
+        let Mode::CommandPrompt { prompt } = &mut app.mode else { return };
+        let (text, start) = (prompt.completions[i].clone(), prompt.completion_start);
+        prompt.completion = Some(i);
+        replace_word(prompt, start, &text);
+        return;
+    }
+    let before = &prompt.input[..prompt.cursor];
+    let start = before.rfind(' ').map_or(0, |i| i + 1);
+    let word = before[start..].to_string();
+    let words: Vec<&str> = before[..start].split_whitespace().collect();
+    let mut found = candidates(app, &prompt.kind, &words, &word);
+    found.sort();
+    found.dedup();
+    let Mode::CommandPrompt { prompt } = &mut app.mode else { return };
+    match found.len() {
+        0 => {}
+        1 => {
+            let done = !found[0].ends_with([':', '/', '\\']);
+            let text = if done { format!("{} ", found[0]) } else { found[0].clone() };
+            replace_word(prompt, start, &text);
+        }
+        _ => {
+            let common = common_prefix(&found).to_string();
+            if common.len() > word.len() {
+                replace_word(prompt, start, &common);
+            } else {
+                prompt.completions = found;
+                prompt.completion = None;
+                prompt.completion_start = start;
+            }
+        }
+    }
+}
+
+// ─── Keys ───────────────────────────────────────────────────────────────────
+
+fn prev_char(p: &PromptState) -> usize {
+    p.input[..p.cursor].chars().next_back().map_or(0, |c| p.cursor - c.len_utf8())
+}
+
+fn next_char(p: &PromptState) -> usize {
+    p.input[p.cursor..].chars().next().map_or(p.cursor, |c| p.cursor + c.len_utf8())
+}
+
+fn edit(p: &mut PromptState, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Char('a') if ctrl => p.cursor = 0,
+        KeyCode::Char('e') if ctrl => p.cursor = p.input.len(),
+        KeyCode::Char('u') if ctrl => { p.input.drain(..p.cursor); p.cursor = 0; }
+        KeyCode::Char('k') if ctrl => p.input.truncate(p.cursor),
+        KeyCode::Char('w') if ctrl => {
+            let start = p.input[..p.cursor].trim_end_matches(' ').rfind(' ').map_or(0, |i| i + 1);
+            p.input.drain(start..p.cursor);
+            p.cursor = start;
+        }
+        KeyCode::Char(_) if ctrl => {}
+        KeyCode::Char(c) => { p.input.insert(p.cursor, c); p.cursor += c.len_utf8(); }
+        KeyCode::Backspace => { let i = prev_char(p); p.input.drain(i..p.cursor); p.cursor = i; }
+        KeyCode::Delete => { let i = next_char(p); p.input.drain(p.cursor..i); }
+        KeyCode::Left => p.cursor = prev_char(p),
+        KeyCode::Right => p.cursor = next_char(p),
+        KeyCode::Home => p.cursor = 0,
+        KeyCode::End => p.cursor = p.input.len(),
+        _ => {}
+    }
+}
+
+/// Up and Down: the next older or newer entry in the history of `kind`,
+/// or an empty input past the newest one.  None at either end.
+pub fn step_history(app: &mut AppState, kind: &str, older: bool) -> Option<String> {
+    let history = app.prompt_history.get(kind).map_or(&[][..], |h| h.as_slice());
+    let idx = if older {
+        app.prompt_history_idx.checked_sub(1)
+    } else {
+        Some(app.prompt_history_idx + 1).filter(|i| *i <= history.len())
+    }?;
+    app.prompt_history_idx = idx;
+    Some(history.get(idx).cloned().unwrap_or_default())
+}
+
+fn history(app: &mut AppState, older: bool) {
+    let Mode::CommandPrompt { prompt } = &app.mode else { return };
+    let kind = prompt.kind.clone();
+    let Some(text) = step_history(app, &kind, older) else { return };
+    let Mode::CommandPrompt { prompt } = &mut app.mode else { return };
+    prompt.input = text;
+    prompt.cursor = prompt.input.len();
+}
+
+/// The newest entry before `before` containing `query`.
+fn find_history(history: &[String], query: &str, before: usize) -> Option<usize> {
+    (0..before.min(history.len())).rev().find(|&i| history[i].contains(query))
+}
+
+/// Start a C-r search from the current input.
+pub fn start_search(input: &str) -> HistorySearch {
+    HistorySearch { query: String::new(), index: None, saved: input.to_string() }
+}
+
+/// A key while C-r is searching `history`, showing the match in `input`.
+/// Returns false when the search ended and the key should be handled as
+/// usual.
+pub fn search_key(history: &[String], search: &mut Option<HistorySearch>, input: &mut String, key: KeyEvent) -> bool {
+    let Some(s) = search else { return false };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let found = match key.code {
+        KeyCode::Char('r') if ctrl => find_history(history, &s.query, s.index.unwrap_or(history.len())),
+        KeyCode::Char('c' | 'g') if ctrl => {
+            *input = std::mem::take(&mut s.saved);
+            *search = None;
+            return true;
+        }
+        KeyCode::Esc => {
+            *input = std::mem::take(&mut s.saved);
+            *search = None;
+            return true;
+        }
+        KeyCode::Backspace => {
+            s.query.pop();
+            find_history(history, &s.query, history.len())
+        }
+        KeyCode::Char(c) if !ctrl => {
+            s.query.push(c);
+            find_history(history, &s.query, s.index.map_or(history.len(), |i| i + 1))
+        }
+        _ => {
+            *search = None;
+            return false;
+        }
+    };
+    if let Some(i) = found {
+        s.index = Some(i);
+        *input = history[i].clone();
+    }
+    true
+}
+
+/// The prompt label shown while searching.
+pub fn search_label(search: &HistorySearch, input: &str) -> String {
+    let failed = !input.contains(&search.query);
+    format!("({}reverse-i-search)`{}': ", if failed { "failed " } else { "" }, search.query)
+}
+
+pub fn key(app: &mut AppState, key: KeyEvent) -> io::Result<()> {
+    let Mode::CommandPrompt { prompt } = &mut app.mode else { return Ok(()) };
+    if prompt.key_name {
+        prompt.input = format_key_binding(&(key.code, key.modifiers));
+        return accept(app);
+    }
+    if key.code != KeyCode::Tab {
+        prompt.completions.clear();
+        prompt.completion = None;
+    }
+    let entries = app.prompt_history.get(&prompt.kind).map_or(&[][..], |h| h.as_slice());
+    if search_key(entries, &mut prompt.search, &mut prompt.input, key) {
+        prompt.cursor = match &prompt.search {
+            Some(s) => prompt.input.find(&s.query).unwrap_or(prompt.input.len()),
+            None => prompt.input.len(),
+        };
+        return Ok(());
+    }
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let digit = matches!(key.code, KeyCode::Char(c) if c.is_ascii_digit() && !ctrl);
+    if prompt.numeric && !digit && !matches!(key.code, KeyCode::Esc | KeyCode::Backspace) {
+        return accept(app);
+    }
+    match key.code {
+        KeyCode::Esc => app.mode = Mode::Passthrough,
+        KeyCode::Char('c' | 'g') if ctrl => app.mode = Mode::Passthrough,
+        KeyCode::Char('r') if ctrl => prompt.search = Some(start_search(&prompt.input)),
+        KeyCode::Enter => return accept(app),
+        KeyCode::Tab => complete(app),
+        KeyCode::Up => history(app, true),
+        KeyCode::Down => history(app, false),
+        _ => {
+            edit(prompt, key);
+            if prompt.single && matches!(key.code, KeyCode::Char(_)) && !ctrl {
+                return accept(app);
+            }
+        }
+    }
+    Ok(())
+}
+
+// ─── View ───────────────────────────────────────────────────────────────────
+
+/// What the client draws for an open prompt.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct PromptView {
+    /// The label followed by the input.
+    pub text: String,
+    /// Column of the cursor in `text`.
+    pub cursor: usize,
+    /// message-command-style
+    pub style: String,
+    pub completions: Vec<String>,
+    pub completion: Option<usize>,
+}
+
+pub fn view(app: &AppState, prompt: &PromptState) -> PromptView {
+    let searching = prompt.search.as_ref().map(|s| search_label(s, &prompt.input));
+    let label = searching.as_deref().unwrap_or_else(|| prompt.prompts.get(prompt.answers.len()).map_or("", |l| l.as_str()));
+    let cursor = label.width() + prompt.input[..prompt.cursor].width();
+    PromptView {
+        text: format!("{}{}", label, prompt.input),
+        cursor,
+        style: app.message_command_style.clone(),
+        completions: prompt.completions.clone(),
+        completion: prompt.completion,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(a: &[&str]) -> Vec<String> {
+        a.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn substitutes_responses() {
+        assert_eq!(substitute("rename-window '%%'", &answers(&["dev"])), "rename-window 'dev'");
+        assert_eq!(substitute("move-window -s %2 -t %1", &answers(&["a", "b"])), "move-window -s b -t a");
+        assert_eq!(substitute("neww -n %% ; renamew %%", &answers(&["x", "y"])), "neww -n x ; renamew y");
+        assert_eq!(substitute("display \"%%%\"", &answers(&["say \"hi\""])), "display \"say \\\"hi\\\"\"");
+        assert_eq!(substitute("%% and %% at 100%", &answers(&["one"])), "one and %% at 100%");
+    }
+
+    #[test]
+    fn flag_values() {
+        let getopt = FLAGS.iter().find(|(c, _)| *c == "split-window").unwrap().1;
+        assert!(takes_value(getopt, "-t"));
+        assert!(takes_value(getopt, "-dc"));
+        assert!(!takes_value(getopt, "-tfoo"));
+        assert!(!takes_value(getopt, "-h"));
+        assert!(!takes_value(getopt, "--"));
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn input(app: &AppState) -> &str {
+        match &app.mode { Mode::CommandPrompt { prompt } => &prompt.input, _ => "" }
+    }
+
+    #[test]
+    fn history_dedups_and_limits() {
+        let mut app = AppState::new("t".to_string());
+        app.prompt_history_limit = 3;
+        for e in ["a", "b", "a", "c", "d", ""] {
+            add_history(&mut app, "command", e);
+        }
+        add_history(&mut app, "target", "s:1");
+        assert_eq!(app.prompt_history["command"], ["a", "c", "d"]);
+        assert_eq!(app.prompt_history["target"], ["s:1"]);
+    }
+
+    #[test]
+    fn history_file_is_shared() {
+        let path = std::env::temp_dir().join(format!("psmux-history-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut first = AppState::new("a".to_string());
+        first.history_file = path.to_string_lossy().into_owned();
+        add_history(&mut first, "command", "new-window");
+        let mut second = AppState::new("b".to_string());
+        add_history(&mut second, "command", "kill-pane");
+        second.history_file = first.history_file.clone();
+        load_history(&mut second);
+        add_history(&mut second, "search", "error");
+        add_history(&mut first, "command", "split-window");
+        assert_eq!(first.prompt_history["command"], ["new-window", "kill-pane", "split-window"]);
+        assert_eq!(first.prompt_history["search"], ["error"]);
+        // saved through a temporary file renamed into place
+        let tmp = format!("{}.{}.tmp", path.display(), std::process::id());
+        assert!(!std::path::Path::new(&tmp).exists());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reverse_search() {
+        let mut app = AppState::new("t".to_string());
+        for e in ["split-window -h", "new-window", "split-window -v"] {
+            add_history(&mut app, "command", e);
+        }
+        open(&mut app, &[]);
+        key(&mut app, ctrl('r')).unwrap();
+        for c in "split".chars() {
+            key(&mut app, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+        assert_eq!(input(&app), "split-window -v");
+        key(&mut app, ctrl('r')).unwrap();
+        assert_eq!(input(&app), "split-window -h");
+        key(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).unwrap();
+        assert_eq!(input(&app), "");
+        assert!(matches!(app.mode, Mode::CommandPrompt { .. }));
+    }
+
+    #[test]
+    fn names_and_prefixes() {
+        assert_eq!(command_name("renamew"), Some("rename-window"));
+        assert_eq!(command_name("set-option"), Some("set-option"));
+        assert_eq!(command_name("nope"), None);
+        assert_eq!(common_prefix(&answers(&["select-pane", "select-window", "select-layout"])), "select-");
+        assert_eq!(common_prefix(&answers(&["s:1", "t:1"])), "");
+    }
+}
@@ -0,0 +1,281 @@
+//! Pane recordings in asciicast v2 format (`record-pane`, `psmux play`).
+//!
+//! A recording is a JSON header line followed by one JSON array per event:
+//! `[seconds, "o", output]` for PTY output and `[seconds, "r", "COLSxROWS"]`
+//! for resizes.  The reader thread tees output into the pane's recorder,
+//! which lives in the parser callbacks so events are stamped under the same
+//! lock as the screen update.  The recorder only queues them: a writer
+//! thread owns the file, so the reader never waits on the disk while it
+//! holds the parser lock.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::tree::{active_pane, resolve_pane_target};
+use crate::types::{AppState, Node};
+
+/// How often the writer thread flushes queued events to disk.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+struct Event {
+    time: f64,
+    kind: &'static str,
+    data: String,
+}
+
+pub struct Recorder {
+    tx: Sender<Event>,
+    writer: std::thread::JoinHandle<()>,
+    /// First write error of the writer thread, which then stops.
+    error: Arc<Mutex<Option<String>>>,
+    start: Instant,
+    /// Bytes of a UTF-8 sequence split across reads, kept for the next event.
+    carry: Vec<u8>,
+    pub path: PathBuf,
+}
+
+impl Recorder {
+    /// Create `path`, write the asciicast header and start the writer thread.
+    pub fn create(path: &Path, cols: u16, rows: u16, title: &str) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut out = BufWriter::new(File::create(path)?);
+        let header = serde_json::json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": chrono::Local::now().timestamp(),
+            "title": title,
+            "env": { "TERM": "xterm-256color" },
+        });
+        writeln!(out, "{}", header)?;
+        out.flush()?;
+        let (tx, rx) = mpsc::channel();
+        let error = Arc::new(Mutex::new(None));
+        let writer_error = error.clone();
+        let writer = std::thread::spawn(move || {
+            if let Err(e) = write_events(out, rx) {
+                if let Ok(mut slot) = writer_error.lock() { *slot = Some(e.to_string()); }
+            }
+        });
+        Ok(Recorder { tx, writer, error, start: Instant::now(), carry: Vec::new(), path: path.to_path_buf() })
+    }
+
+    /// Record PTY output.
+    pub fn output(&mut self, bytes: &[u8]) {
+        self.carry.extend_from_slice(bytes);
+        let text = match std::str::from_utf8(&self.carry) {
+            Ok(s) => s.to_string(),
+            // Incomplete sequence at the end: keep it for the next read
+            Err(e) if e.error_len().is_none() => {
+                let valid = e.valid_up_to();
+                let s = String::from_utf8_lossy(&self.carry[..valid]).into_owned();
+                self.carry.drain(..valid);
+                if s.is_empty() { return; }
+                self.event("o", s);
+                return;
+            }
+            Err(_) => String::from_utf8_lossy(&self.carry).into_owned(),
+        };
+        self.carry.clear();
+        self.event("o", text);
+    }
+
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.event("r", format!("{}x{}", cols, rows));
+    }
+
+    fn event(&mut self, kind: &'static str, data: String) {
+        // Fails only once the writer has stopped on an error, which
+        // `check_recordings` reports
+        let _ = self.tx.send(Event { time: self.start.elapsed().as_secs_f64(), kind, data });
+    }
+
+    /// The writer thread's write error, if it has hit one.
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().ok().and_then(|e| e.clone())
+    }
+
+    /// Stop recording: write out everything queued and close the file.
+    pub fn finish(self) -> Result<(), String> {
+        let Recorder { tx, writer, error, .. } = self;
+        drop(tx);
+        let _ = writer.join();
+        let result = error.lock().ok().and_then(|e| e.clone());
+        result.map_or(Ok(()), Err)
+    }
+}
+
+/// The writer thread: append queued events to the file, flushing at most
+/// every `FLUSH_INTERVAL`, until the recorder is dropped.
+fn write_events(mut out: BufWriter<File>, rx: Receiver<Event>) -> io::Result<()> {
+    let mut last_flush = Instant::now();
+    loop {
+        match rx.recv_timeout(FLUSH_INTERVAL) {
+            Ok(ev) => {
+                writeln!(out, "[{:.6}, \"{}\", {}]", ev.time, ev.kind, serde_json::Value::from(ev.data))?;
+                if last_flush.elapsed() >= FLUSH_INTERVAL {
+                    out.flush()?;
+                    last_flush = Instant::now();
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                out.flush()?;
+                last_flush = Instant::now();
+            }
+            Err(RecvTimeoutError::Disconnected) => return out.flush(),
+        }
+    }
+}
+
+/// Stop any recording whose file can no longer be written and say so on
+/// the status line.  Returns true when one was stopped.
+pub fn check_recordings(app: &mut AppState) -> bool {
+    fn walk(node: &Node, failed: &mut Vec<(usize, PathBuf, String)>) {
+        match node {
+            Node::Leaf(p) => {
+                let Ok(mut parser) = p.term.lock() else { return };
+                let cb = parser.callbacks_mut();
+                let Some(error) = cb.recording.as_ref().and_then(|r| r.error()) else { return };
+                if let Some(rec) = cb.recording.take() {
+                    failed.push((p.id, rec.path.clone(), error));
+                }
+            }
+            Node::Split { children, .. } => {
+                for c in children { walk(c, failed); }
+            }
+        }
+    }
+    let mut failed = Vec::new();
+    for win in &app.windows {
+        walk(&win.root, &mut failed);
+    }
+    let Some((id, path, error)) = failed.pop() else { return false };
+    app.status_message = Some((format!("Recording pane %{} stopped: {}: {}", id, path.display(), error), Instant::now()));
+    true
+}
+
+/// Default recording file: `~/.psmux/recordings/<session>-<pane id>-<time>.cast`.
+fn default_path(app: &AppState, pane_id: usize) -> PathBuf {
+    let home = std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")).unwrap_or_default();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    PathBuf::from(home).join(".psmux").join("recordings")
+        .join(format!("{}-{}-{}.cast", app.session_name, pane_id, stamp))
+}
+
+/// `record-pane`: start recording the target pane to `file` (replacing a
+/// recording already running), or with `stop` (`-x`) stop it.  Returns the
+/// message to show.
+pub fn record_pane(app: &AppState, target: Option<&str>, file: Option<&str>, stop: bool) -> Result<String, String> {
+    let pane = match target {
+        Some(t) => resolve_pane_target(app, t)
+            .and_then(|(wi, path)| active_pane(&app.windows[wi].root, &path))
+            .ok_or_else(|| format!("can't find pane: {}", t))?,
+        None => app.windows.get(app.active_idx)
+            .and_then(|w| active_pane(&w.root, &w.active_path))
+            .ok_or_else(|| "no active pane".to_string())?,
+    };
+    let mut parser = pane.term.lock().map_err(|_| "pane is busy".to_string())?;
+    if stop {
+        let Some(rec) = parser.callbacks_mut().recording.take() else {
+            return Err(format!("pane %{} is not being recorded", pane.id));
+        };
+        drop(parser);
+        let path = rec.path.clone();
+        return match rec.finish() {
+            Ok(()) => Ok(format!("Recording saved to {}", path.display())),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        };
+    }
+    let path = match file {
+        Some(f) => PathBuf::from(crate::format::expand_format(f, app)),
+        None => default_path(app, pane.id),
+    };
+    let (rows, cols) = parser.screen().size();
+    let mut rec = Recorder::create(&path, cols, rows, &pane.title)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    // Start from what is on screen now, so playback is not a blank slate
+    let state = parser.screen().state_formatted();
+    rec.output(&state);
+    parser.callbacks_mut().recording = Some(rec);
+    Ok(format!("Recording pane %{} to {}", pane.id, path.display()))
+}
+
+/// Play `path` on stdout.  `speed` divides the delays between events and
+/// `idle_limit` (seconds) caps any single pause; without one the header's
+/// `idle_time_limit` applies.  Resize events are skipped.
+pub fn play(path: &Path, speed: f64, idle_limit: Option<f64>) -> io::Result<()> {
+    let file = io::BufReader::new(File::open(path)?);
+    let mut lines = file.lines();
+    let header: serde_json::Value = match lines.next() {
+        Some(l) => serde_json::from_str(&l?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        None => return Ok(()),
+    };
+    if header["version"] != 2 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not an asciicast v2 recording"));
+    }
+    let idle_limit = idle_limit.or_else(|| header["idle_time_limit"].as_f64());
+    let speed = if speed > 0.0 { speed } else { 1.0 };
+    let mut stdout = io::stdout();
+    let mut last = 0.0;
+    for line in lines {
+        let line = line?;
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(&line) else { continue };
+        let (Some(t), Some("o"), Some(data)) = (event[0].as_f64(), event[1].as_str(), event[2].as_str()) else { continue };
+        let mut delay = (t - last).max(0.0);
+        if let Some(limit) = idle_limit { delay = delay.min(limit); }
+        last = t;
+        std::thread::sleep(Duration::from_secs_f64(delay / speed));
+        stdout.write_all(data.as_bytes())?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_asciicast_events() {
+        let path = std::env::temp_dir().join(format!("psmux-record-test-{}.cast", std::process::id()));
+        let mut rec = Recorder::create(&path, 80, 24, "test").unwrap();
+        rec.output(b"hi \xe2\x82");
+        rec.output(b"\xac\x1b[0m\r\n");
+        rec.resize(100, 30);
+        rec.finish().unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "hi ");
+        assert_eq!(lines[2][2], "\u{20ac}\x1b[0m\r\n");
+        assert_eq!(lines[3][1], "r");
+        assert_eq!(lines[3][2], "100x30");
+    }
+
+    #[test]
+    fn write_error_stops_recording() {
+        let path = std::env::temp_dir().join(format!("psmux-record-err-{}.cast", std::process::id()));
+        let mut app = AppState::new("t".to_string());
+        crate::pane::test_window(&mut app, 5, 20, b"");
+        let rec = Recorder::create(&path, 20, 5, "test").unwrap();
+        *rec.error.lock().unwrap() = Some("disk full".to_string());
+        let Node::Leaf(pane) = &app.windows[0].root else { unreachable!() };
+        pane.term.lock().unwrap().callbacks_mut().recording = Some(rec);
+        assert!(check_recordings(&mut app));
+        let Node::Leaf(pane) = &app.windows[0].root else { unreachable!() };
+        assert!(pane.term.lock().unwrap().callbacks_mut().recording.is_none());
+        let msg = app.status_message.take().unwrap().0;
+        assert!(msg.ends_with(": disk full"), "{}", msg);
+        assert!(!check_recordings(&mut app));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -99,12 +99,92 @@ pub fn render_window(f: &mut Frame, app: &mut AppState, area: Rect) {
     let border_style = parse_tmux_style(&app.pane_border_style);
     let active_border_style = parse_tmux_style(&app.pane_active_border_style);
     let copy_cursor = if matches!(app.mode, Mode::CopyMode | Mode::CopySearch { .. }) { app.copy_pos } else { None };
+    let search_hl = if copy_cursor.is_some() { crate::copy_mode::visible_search_matches(app) } else { Vec::new() };
+    let hints = match &app.mode { Mode::HintMode { hints } => Some(crate::hints::hint_view(app, hints)), _ => None };
+    let position = if copy_cursor.is_some() && !app.copy_hide_position {
+        Some(crate::format::expand_format(&app.copy_mode_position_format, app))
+    } else { None };
+    let scrollbars = app.pane_scrollbars.clone();
+    let scrollbar_style = parse_tmux_style(&app.pane_scrollbars_style);
+    let reserve = if scrollbars == "on" { 1 } else { 0 };
     let win = &mut app.windows[app.active_idx];
     let active_rect = compute_active_rect(&win.root, &win.active_path, area);
-    render_node(f, &mut win.root, &win.active_path, &mut Vec::new(), area, dim_preds, border_style, active_border_style, copy_cursor, active_rect);
+    render_node(f, &mut win.root, &win.active_path, &mut Vec::new(), area, dim_preds, border_style, active_border_style, copy_cursor, active_rect, reserve);
+    if scrollbars == "on" || (scrollbars == "modal" && copy_cursor.is_some()) {
+        let mut rects = Vec::new();
+        crate::tree::compute_rects(&win.root, area, &mut rects);
+        for (path, rect) in rects {
+            let is_active = path == win.active_path;
+            if scrollbars == "modal" && !is_active { continue; }
+            let Some(p) = crate::tree::active_pane(&win.root, &path) else { continue };
+            let Ok(parser) = p.term.lock() else { continue };
+            let screen = parser.screen();
+            render_scrollbar(f.buffer_mut(), rect, screen.history_len(), screen.scrollback(), scrollbar_style);
+        }
+    }
+    if let Some(r) = active_rect {
+        let match_style = parse_tmux_style(&app.copy_mode_match_style);
+        let current_style = parse_tmux_style(&app.copy_mode_current_match_style);
+        let buf = f.buffer_mut();
+        for (row, start, end, current) in search_hl {
+            if row >= r.height { continue; }
+            for col in start..end.min(r.width) {
+                buf[(r.x + col, r.y + row)].set_style(if current { current_style } else { match_style });
+            }
+        }
+        if let Some(view) = hints { render_hints(buf, r, &view); }
+        if let Some(text) = position { render_copy_position(f, r, &text); }
+    }
     fix_border_intersections(f.buffer_mut());
 }
 
+/// Draw the copy-mode position indicator (copy-mode-position-format) at the
+/// top right of the pane at `area`.
+pub fn render_copy_position(f: &mut Frame, area: Rect, text: &str) {
+    let base = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let spans = crate::style::parse_inline_styles(text, base);
+    let width = spans.iter().map(|s| s.width()).sum::<usize>().min(area.width as usize) as u16;
+    if width == 0 { return; }
+    let la = Rect::new(area.x + area.width - width, area.y, width, 1);
+    f.render_widget(Paragraph::new(Line::from(spans)), la);
+}
+
+/// Draw a scrollbar in the rightmost column of the pane at `area`.  The
+/// pane has `history` lines of scrollback and is viewed `scroll` lines up
+/// from the bottom; the track takes the style's background and the slider
+/// its foreground.
+pub fn render_scrollbar(buf: &mut Buffer, area: Rect, history: usize, scroll: usize, style: Style) {
+    if area.width == 0 || area.height == 0 { return; }
+    let rows = area.height as usize;
+    let total = history + rows;
+    let len = (rows * rows / total).max(1);
+    let top = ((history - scroll.min(history)) * rows / total).min(rows - len);
+    let track = Style::default().bg(style.bg.unwrap_or(Color::Black));
+    let slider = Style::default().bg(style.fg.unwrap_or(Color::White));
+    let x = area.x + area.width - 1;
+    for i in 0..rows {
+        let cell = &mut buf[(x, area.y + i as u16)];
+        cell.set_symbol(" ");
+        cell.set_style(if i >= top && i < top + len { slider } else { track });
+    }
+}
+
+/// Draw hint-mode labels over the active pane at `area`: each match gets
+/// the match style and its label is written over the start of it.
+pub fn render_hints(buf: &mut Buffer, area: Rect, view: &crate::hints::HintView) {
+    let match_style = parse_tmux_style(&view.match_style);
+    let label_style = parse_tmux_style(&view.style);
+    for (row, start, end, label) in &view.items {
+        if *row >= area.height || *start >= area.width { continue; }
+        let y = area.y + row;
+        for col in *start..(*end).min(area.width) {
+            buf[(area.x + col, y)].set_style(match_style);
+        }
+        let room = (area.width - start) as usize;
+        buf.set_stringn(area.x + start, y, label, room, label_style);
+    }
+}
+
 /// Post-pass: fix border intersection characters where horizontal and vertical
 /// separator lines meet. Converts plain '│' and '─' to proper junction
 /// characters ('┼', '├', '┤', '┬', '┴') at intersection points.
@@ -167,6 +247,30 @@ pub fn fix_border_intersections(buf: &mut Buffer) {
     }
 }
 
+/// Draw a grapheme cluster whose parser-assigned width differs from what
+/// ratatui would measure for it (ambiguous-width characters with
+/// `ambiguous-width 2`, emoji presentation sequences with
+/// `variation-selector-always-wide off`).  The caller reserves `width` blank
+/// columns for it in the Paragraph; writing the cluster straight into the
+/// buffer keeps everything after it on the line in place.
+pub fn place_cluster(buf: &mut Buffer, x: u16, y: u16, text: &str, style: Style, width: u16) {
+    let area = buf.area;
+    if x < area.left() || x >= area.right() || y < area.top() || y >= area.bottom() { return; }
+    // A cluster kept narrow by the server would still be drawn wide by the
+    // terminal if it carries an emoji presentation selector, so drop it.
+    let symbol: String = if UnicodeWidthStr::width(text) as u16 > width {
+        text.chars().filter(|&ch| ch != '\u{fe0f}').collect()
+    } else {
+        text.to_string()
+    };
+    buf[(x, y)].set_symbol(&symbol).set_style(style);
+    for dx in 1..width {
+        if x + dx < area.right() {
+            buf[(x + dx, y)].set_symbol("").set_style(style);
+        }
+    }
+}
+
 pub fn render_node(
     f: &mut Frame,
     node: &mut Node,
@@ -178,13 +282,14 @@ pub fn render_node(
     active_border_style: Style,
     copy_cursor: Option<(u16, u16)>,
     active_rect: Option<Rect>,
+    scrollbar_cols: u16,
 ) {
     match node {
         Node::Leaf(pane) => {
             let is_active = *cur_path == *active_path;
             let inner = area;
             let target_rows = inner.height.max(1);
-            let target_cols = inner.width.max(1);
+            let target_cols = inner.width.saturating_sub(scrollbar_cols).max(1);
             if pane.last_rows != target_rows || pane.last_cols != target_cols {
                 let _ = pane.master.resize(PtySize { rows: target_rows, cols: target_cols, pixel_width: 0, pixel_height: 0 });
                 if let Ok(mut parser) = pane.term.lock() {
@@ -198,6 +303,7 @@ pub fn render_node(
             let screen = parser.screen();
             let (cur_r, cur_c) = screen.cursor_position();
             let mut lines: Vec<Line> = Vec::with_capacity(target_rows as usize);
+            let mut clusters: Vec<(u16, u16, String, Style, u16)> = Vec::new();
             for r in 0..target_rows {
                 let mut spans: Vec<Span> = Vec::with_capacity(target_cols as usize);
                 let mut c = 0;
@@ -218,18 +324,17 @@ pub fn render_node(
                         if cell.inverse() { style = style.add_modifier(Modifier::REVERSED); }
                         if cell.blink() { style = style.add_modifier(Modifier::SLOW_BLINK); }
                         if cell.hidden() { style = style.add_modifier(Modifier::HIDDEN); }
-                        let text = cell.contents().to_string();
-                        let w = UnicodeWidthStr::width(text.as_str()) as u16;
-                        if w == 0 {
-                            spans.push(Span::styled(" ", style));
-                            c += 1;
-                        } else if w >= 2 {
-                            spans.push(Span::styled(text, style));
-                            c += 2;
+                        let text = cell.contents();
+                        let w = crate::layout::cell_width(cell);
+                        if text.is_empty() {
+                            spans.push(Span::styled(" ".repeat(w as usize), style));
+                        } else if crate::layout::width_mismatch(text, w) {
+                            spans.push(Span::styled(" ".repeat(w as usize), style));
+                            clusters.push((inner.x + c, inner.y + r, text.to_string(), style, w));
                         } else {
-                            spans.push(Span::styled(text, style));
-                            c += 1;
+                            spans.push(Span::styled(text.to_string(), style));
                         }
+                        c += w;
                     } else {
                         spans.push(Span::raw(" "));
                         c += 1;
@@ -240,6 +345,11 @@ pub fn render_node(
             f.render_widget(Clear, inner);
             let para = Paragraph::new(Text::from(lines));
             f.render_widget(para, inner);
+            for (x, y, text, style, width) in clusters {
+                if x < inner.right() {
+                    place_cluster(f.buffer_mut(), x, y, &text, style, width);
+                }
+            }
             if is_active {
                 let (cr, cc) = copy_cursor.unwrap_or_else(|| screen.cursor_position());
                 let cr = cr.min(target_rows.saturating_sub(1));
@@ -265,7 +375,7 @@ pub fn render_node(
             for (i, child) in children.iter_mut().enumerate() {
                 cur_path.push(i);
                 if i < rects.len() {
-                    render_node(f, child, active_path, cur_path, rects[i], dim_preds, border_style, active_border_style, copy_cursor, active_rect);
+                    render_node(f, child, active_path, cur_path, rects[i], dim_preds, border_style, active_border_style, copy_cursor, active_rect, scrollbar_cols);
                 }
                 cur_path.pop();
             }
@@ -404,6 +514,144 @@ pub fn parse_status(fmt: &str, app: &AppState, time_str: &str) -> Vec<Span<'stat
 
 // ─── UI layout helpers ──────────────────────────────────────────────────────
 
+/// Draw the choose-buffer overlay: the item list with the selected row in
+/// `sel_style` and tagged rows marked, and the selected buffer's contents
+/// below it when the preview is on.
+pub fn render_buffer_chooser(f: &mut Frame, area: Rect, view: &crate::buffers::ChooserView, sel_style: Style) {
+    let list_h = (view.items.len().max(1) as u16).saturating_add(2);
+    let oa = if view.zoomed {
+        area
+    } else {
+        let preview_h = if view.preview.is_empty() { 0 } else { 8 };
+        centered_rect(80, (list_h + preview_h).min(area.height.saturating_sub(2)), area)
+    };
+    f.render_widget(Clear, oa);
+    let (list_area, preview_area) = if view.preview.is_empty() {
+        (oa, None)
+    } else {
+        let list_h = list_h.min(oa.height / 2).max(3);
+        let parts = Layout::default().direction(Direction::Vertical)
+            .constraints([Constraint::Length(list_h), Constraint::Min(0)]).split(oa);
+        (parts[0], Some(parts[1]))
+    };
+    let inner_h = list_area.height.saturating_sub(2) as usize;
+    let first = view.selected.saturating_sub(inner_h.saturating_sub(1));
+    let mut lines: Vec<Line> = Vec::new();
+    if view.items.is_empty() {
+        lines.push(Line::from("  (no buffers)"));
+    }
+    for (i, (text, tagged)) in view.items.iter().enumerate().skip(first).take(inner_h) {
+        let label = format!("{}{}", if *tagged { "* " } else { "  " }, text);
+        if i == view.selected {
+            lines.push(Line::from(Span::styled(label, sel_style)));
+        } else {
+            lines.push(Line::from(label));
+        }
+    }
+    let block = Block::default().borders(Borders::ALL).title(view.title.clone()).border_style(sel_style);
+    f.render_widget(Paragraph::new(Text::from(lines)).block(block), list_area);
+    if let Some(pa) = preview_area {
+        let lines: Vec<Line> = view.preview.iter().map(|l| Line::from(l.clone())).collect();
+        let block = Block::default().borders(Borders::ALL)
+            .title("enter=paste t=tag d/D=delete e=edit v=preview O=sort q=close");
+        f.render_widget(Paragraph::new(Text::from(lines)).block(block), pa);
+    }
+}
+
+/// Draw the command prompt over the status line, or over the last line of
+/// `content` when the status bar is off, with any listed Tab completions
+/// on the line beside it.
+pub fn render_command_prompt(f: &mut Frame, content: Rect, status: Rect, view: &crate::prompt::PromptView) {
+    let line = if status.height > 0 {
+        Rect { height: 1, ..status }
+    } else {
+        Rect::new(content.x, content.y + content.height.saturating_sub(1), content.width, 1)
+    };
+    let style = parse_tmux_style(&view.style);
+    f.render_widget(Clear, line);
+    f.render_widget(Paragraph::new(view.text.clone()).style(style), line);
+    let cx = (view.cursor as u16).min(line.width.saturating_sub(1));
+    f.set_cursor_position((line.x + cx, line.y));
+    if view.completions.is_empty() { return; }
+    let y = if status.height > 0 && status.y < content.y { line.y + 1 } else { line.y.saturating_sub(1) };
+    let mut spans = Vec::new();
+    for (i, c) in view.completions.iter().enumerate() {
+        let s = if view.completion == Some(i) { style.add_modifier(Modifier::REVERSED) } else { style };
+        spans.push(Span::styled(format!(" {} ", c), s));
+    }
+    let list = Rect { y, ..line };
+    f.render_widget(Clear, list);
+    f.render_widget(Paragraph::new(Line::from(spans)).style(style), list);
+}
+
+/// Draw the choose-tree overlay: the tree with each row's key, tag mark and
+/// tree lines, the selected row in `sel_style`, the prompt on the last
+/// line of the list, and the preview below.
+pub fn render_tree_chooser(f: &mut Frame, area: Rect, view: &crate::choose_tree::TreeView, sel_style: Style) {
+    let preview = !view.preview.is_empty();
+    let (list_area, preview_area) = crate::choose_tree::areas(area, view.rows.len(), view.zoomed, preview);
+    let outer = preview_area.map_or(list_area, |p| list_area.union(p));
+    f.render_widget(Clear, outer);
+    let inner_h = (list_area.height.saturating_sub(2) as usize).saturating_sub(usize::from(view.prompt.is_some()));
+    let first = view.selected.saturating_sub(inner_h.saturating_sub(1));
+    let key_w = view.rows.iter().map(|r| r.key.width()).max().unwrap_or(0);
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, row) in view.rows.iter().enumerate().skip(first).take(inner_h) {
+        let key = if key_w == 0 { String::new() } else { format!("({:>w$}) ", row.key, w = key_w) };
+        let label = format!("{}{}{}{}", if row.tagged { "* " } else { "  " }, key, row.lead, row.text);
+        let style = if i == view.selected {
+            sel_style
+        } else if row.current {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(Span::styled(label, style)));
+    }
+    if let Some(ref prompt) = view.prompt {
+        while lines.len() < inner_h { lines.push(Line::from("")); }
+        lines.push(Line::from(Span::styled(prompt.clone(), sel_style)));
+    }
+    let block = Block::default().borders(Borders::ALL).title(view.title.clone()).border_style(sel_style);
+    f.render_widget(Paragraph::new(Text::from(lines)).block(block), list_area);
+    if let Some(pa) = preview_area {
+        let lines: Vec<Line> = view.preview.iter().map(|l| Line::from(l.clone())).collect();
+        let block = Block::default().borders(Borders::ALL)
+            .title("enter=choose t=tag x=kill :=command /=search </>=scroll O=sort v=preview q=close");
+        f.render_widget(Paragraph::new(Text::from(lines)).block(block), pa);
+    }
+}
+
+/// Draw a `display-menu` at its position in `area`: each item's name on
+/// the left and its shortcut key on the right, separators as a line
+/// across, disabled items dimmed.
+pub fn render_menu(f: &mut Frame, area: Rect, view: &crate::menu::MenuView) {
+    let rect = Rect {
+        x: area.x + view.x.min(area.width),
+        y: area.y + view.y.min(area.height),
+        width: view.width.min(area.width.saturating_sub(view.x)),
+        height: view.height.min(area.height.saturating_sub(view.y)),
+    };
+    let block = crate::style::popup_block(view.title.clone(), &view.style, &view.border_style, &view.border_lines);
+    let inner = block.inner(rect);
+    let width = inner.width as usize;
+    let rule = crate::style::border_set(&view.border_lines).map_or("─", |set| set.horizontal_top);
+    let selected = parse_tmux_style(&view.selected_style);
+    let lines: Vec<Line> = view.items.iter().enumerate().map(|(i, (name, key, disabled))| {
+        if name.is_empty() {
+            return Line::from(Span::styled(rule.repeat(width), parse_tmux_style(&view.border_style)));
+        }
+        let key = if key.is_empty() { String::new() } else { format!("({}) ", key) };
+        let pad = width.saturating_sub(name.width() + key.width() + 1);
+        let text = format!(" {}{}{}", name, " ".repeat(pad), key);
+        let mut style = if i == view.selected && !disabled { selected } else { Style::default() };
+        if *disabled { style = style.add_modifier(Modifier::DIM); }
+        Line::from(Span::styled(text, style))
+    }).collect();
+    f.render_widget(Clear, rect);
+    f.render_widget(Paragraph::new(Text::from(lines)).block(block), rect);
+}
+
 pub fn centered_rect(percent_x: u16, height: u16, r: Rect) -> Rect {
     // Clamp requested height to the available area so we never
     // produce a Rect that extends beyond the buffer.
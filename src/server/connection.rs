@@ -217,10 +217,13 @@ let args: Vec<&str> = {
     }
     filtered
 };
+// Commands that resolve -t themselves and must not move focus at all
+let resolves_target = matches!(cmd, "capture-pane" | "capturep" | "record-pane" | "display-popup" | "popup"
+    | "switch-client" | "switchc" | "choose-tree" | "choose-window" | "choose-session");
 // Commands that should permanently change focus when used with -t
 let is_focus_cmd = matches!(cmd, "select-window" | "selectw" | "select-pane" | "selectp")
     || (matches!(cmd, "split-window" | "splitw") && !args.iter().any(|a| *a == "-d"));
-if let Some(wid) = target_win {
+if let Some(wid) = target_win.filter(|_| !resolves_target) {
     if is_focus_cmd {
         let _ = tx.send(CtrlReq::FocusWindow(wid));
     } else {
@@ -232,7 +235,7 @@ let targeted_kill_pane_id = if matches!(cmd, "kill-pane" | "killp") && pane_is_i
 } else {
     None
 };
-let skip_pane_focus = matches!(cmd, "display-message" | "display");
+let skip_pane_focus = resolves_target || matches!(cmd, "display-message" | "display");
 if !skip_pane_focus && targeted_kill_pane_id.is_none() {
     if let Some(pid) = target_pane {
         if is_focus_cmd {
@@ -313,44 +316,48 @@ match cmd {
         }
     }
     "capture-pane" | "capturep" => {
-        let print_stdout = args.iter().any(|a| *a == "-p");
-        let join_lines = args.iter().any(|a| *a == "-J");
-        let escape_seqs = args.iter().any(|a| *a == "-e");
-        // Parse -S start and -E end (negative = scrollback offset, - = entire scrollback)
-        let s_arg = args.windows(2).find(|w| w[0] == "-S").map(|w| w[1]);
-        let e_arg = args.windows(2).find(|w| w[0] == "-E").map(|w| w[1]);
-        let start: Option<i32> = match s_arg {
-            Some("-") => Some(0), // entire scrollback start
-            Some(v) => v.parse::<i32>().ok(),
-            None => None,
-        };
-        let end: Option<i32> = match e_arg {
-            Some("-") => None, // to end of visible
-            Some(v) => v.parse::<i32>().ok(),
-            None => None,
-        };
-        let (rtx, rrx) = mpsc::channel::<String>();
-        if escape_seqs {
-            let _ = tx.send(CtrlReq::CapturePaneStyled(rtx, start, end));
-        } else if s_arg.is_some() || e_arg.is_some() {
-            let _ = tx.send(CtrlReq::CapturePaneRange(rtx, start, end));
-        } else {
-            let _ = tx.send(CtrlReq::CapturePane(rtx));
+        // -t was stripped above; capture resolves it itself so focus never moves
+        let mut opts = crate::capture::parse_args(&args);
+        opts.target = raw_target.clone();
+        let (rtx, rrx) = mpsc::channel::<Result<String, String>>();
+        let _ = tx.send(CtrlReq::CapturePane(opts, rtx));
+        match rrx.recv() {
+            // Text already ends with \n from capture
+            Ok(Ok(text)) => { let _ = write_stream.write_all(text.as_bytes()); }
+            Ok(Err(e)) => { let _ = write!(write_stream, "{}\n", e); }
+            Err(_) => {}
         }
-        if let Ok(mut text) = rrx.recv() {
-            if join_lines {
-                // Remove trailing whitespace from each line (join wrapped lines)
-                text = text.lines().map(|l| l.trim_end()).collect::<Vec<_>>().join("\n");
+        let _ = write_stream.flush();
+        if !persistent { break; }
+    }
+    "record-pane" => {
+        let mut file: Option<String> = None;
+        let mut stop = false;
+        let mut i = 0;
+        while i < args.len() {
+            match args[i] {
+                "-f" => { i += 1; file = args.get(i).map(|s| s.to_string()); }
+                "-x" => { stop = true; }
+                _ => {}
             }
-            if print_stdout {
-                // Write text directly — it already ends with \n from capture
-                let _ = write_stream.write_all(text.as_bytes());
+            i += 1;
+        }
+        let (rtx, rrx) = mpsc::channel::<String>();
+        let _ = tx.send(CtrlReq::RecordPane(raw_target.clone(), file, stop, rtx));
+        if let Ok(err) = rrx.recv_timeout(Duration::from_millis(2000)) {
+            if !err.is_empty() {
+                let _ = write!(write_stream, "{}\n", err);
                 let _ = write_stream.flush();
-                if !persistent { break; }
-            } else {
-                let _ = tx.send(CtrlReq::SetBuffer(text));
             }
         }
+        if !persistent { break; }
+    }
+    "play" => {
+        // Run this binary's own player in the new pane
+        let exe = std::env::current_exe().map(|p| p.to_string_lossy().into_owned()).unwrap_or_else(|_| "psmux".into());
+        let mut argv = vec![exe, "play".to_string(), "-P".to_string(), "-w".to_string()];
+        argv.extend(args.iter().map(|s| s.to_string()));
+        let _ = tx.send(CtrlReq::PlayRecording(argv));
     }
     "dump-layout" => {
         let (rtx, rrx) = mpsc::channel::<String>();
@@ -401,6 +408,8 @@ match cmd {
     "copy-yank" => { let _ = tx.send(CtrlReq::CopyYank); }
     "client-size" => {
         if args.len() >= 2 { if let (Ok(w), Ok(h)) = (args[0].parse::<u16>(), args[1].parse::<u16>()) { let _ = tx.send(CtrlReq::ClientSize(client_id, w, h)); } }
+        // Optional cell pixel size: client-size W H CELL_W CELL_H
+        if args.len() >= 4 { if let (Ok(cw), Ok(ch)) = (args[2].parse::<u16>(), args[3].parse::<u16>()) { if cw > 0 && ch > 0 { let _ = tx.send(CtrlReq::ClientCellSize(cw, ch)); } } }
     }
     "focus-pane" => {
         if let Some(pid) = args.get(0).and_then(|s| s.parse::<usize>().ok()) { let _ = tx.send(CtrlReq::FocusPaneCmd(pid)); }
@@ -480,11 +489,8 @@ match cmd {
             }
         }
         if has_x {
-            // send-keys -X copy-mode-command
-            let cmd_parts: Vec<&str> = args.iter().filter(|a| **a != "-X" && !a.starts_with('-')).copied().collect();
-            for _ in 0..repeat_count {
-                let _ = tx.send(CtrlReq::SendKeysX(cmd_parts.join(" ")));
-            }
+            // send-keys -X [-N count] [-t target] copy-mode-command [argument]
+            let _ = tx.send(CtrlReq::SendKeysX(crate::copy_mode::send_keys_x_command(&args), repeat_count));
         } else {
             let keys: Vec<&str> = args.iter()
                 .enumerate()
@@ -632,15 +638,35 @@ match cmd {
             let _ = tx.send(CtrlReq::ResizePane(dir.to_string(), amount));
         }
     }
-    "set-buffer" => {
-        let content = args.iter().filter(|a| !a.starts_with('-')).cloned().collect::<Vec<&str>>().join(" ");
-        let _ = tx.send(CtrlReq::SetBuffer(content));
+    "set-buffer" | "setb" => {
+        let mut name: Option<String> = None;
+        let mut new_name: Option<String> = None;
+        let mut append = false;
+        let mut to_clipboard = false;
+        let mut data: Vec<&str> = Vec::new();
+        let mut i = 0;
+        while i < args.len() {
+            match args[i] {
+                "-b" => { name = args.get(i + 1).map(|s| s.to_string()); i += 1; }
+                "-n" => { new_name = args.get(i + 1).map(|s| s.to_string()); i += 1; }
+                "-a" => { append = true; }
+                "-w" => { to_clipboard = true; }
+                a if a.starts_with('-') && a.len() > 1 => {}
+                a => data.push(a),
+            }
+            i += 1;
+        }
+        if let Some(new_name) = new_name {
+            let _ = tx.send(CtrlReq::RenameBuffer(name, new_name));
+        } else if !data.is_empty() {
+            let _ = tx.send(CtrlReq::SetBuffer(name, data.join(" "), append, to_clipboard));
+        }
     }
     "paste-buffer" | "pasteb" => {
-        let buf_idx: Option<usize> = args.windows(2).find(|w| w[0] == "-b").and_then(|w| w[1].parse().ok());
+        let buf_name = args.windows(2).find(|w| w[0] == "-b").map(|w| w[1].to_string());
         let (rtx, rrx) = mpsc::channel::<String>();
-        if let Some(idx) = buf_idx {
-            let _ = tx.send(CtrlReq::ShowBufferAt(rtx, idx));
+        if let Some(name) = buf_name {
+            let _ = tx.send(CtrlReq::ShowNamedBuffer(rtx, name));
         } else {
             let _ = tx.send(CtrlReq::ShowBuffer(rtx));
         }
@@ -648,6 +674,11 @@ match cmd {
             let _ = tx.send(CtrlReq::SendText(text));
         }
     }
+    "paste-from-clipboard" => {
+        if let Some(text) = crate::copy_mode::read_from_system_clipboard() {
+            if !text.is_empty() { let _ = tx.send(CtrlReq::SendPaste(text)); }
+        }
+    }
     "list-buffers" | "lsb" => {
         let fmt = args.windows(2).find(|w| w[0] == "-F").map(|w| w[1].to_string());
         let (rtx, rrx) = mpsc::channel::<String>();
@@ -659,18 +690,28 @@ match cmd {
         if let Ok(text) = rrx.recv() { let _ = write!(write_stream, "{}\n", text); let _ = write_stream.flush(); }
         if !persistent { break; }
     }
-    "show-buffer" => {
+    "show-buffer" | "showb" => {
         let (rtx, rrx) = mpsc::channel::<String>();
-        let _ = tx.send(CtrlReq::ShowBuffer(rtx));
+        match args.windows(2).find(|w| w[0] == "-b") {
+            Some(w) => { let _ = tx.send(CtrlReq::ShowNamedBuffer(rtx, w[1].to_string())); }
+            None => { let _ = tx.send(CtrlReq::ShowBuffer(rtx)); }
+        }
         if let Ok(text) = rrx.recv() { let _ = write!(write_stream, "{}\n", text); let _ = write_stream.flush(); }
         if !persistent { break; }
     }
-    "delete-buffer" => { let _ = tx.send(CtrlReq::DeleteBuffer); }
-    "choose-buffer" => {
-        let (rtx, rrx) = mpsc::channel::<String>();
-        let _ = tx.send(CtrlReq::ChooseBuffer(rtx));
-        if let Ok(text) = rrx.recv() { let _ = write!(write_stream, "{}\n", text); let _ = write_stream.flush(); }
-        if !persistent { break; }
+    "delete-buffer" | "deleteb" => {
+        let name = args.windows(2).find(|w| w[0] == "-b").map(|w| w[1].to_string());
+        let _ = tx.send(CtrlReq::DeleteBuffer(name));
+    }
+    "choose-buffer" | "chooseb" => {
+        // -t was stripped above for focus handling; the chooser needs it as
+        // the paste target instead.
+        let mut flags: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        if let Some(t) = raw_target.clone() { flags.push("-t".into()); flags.push(t); }
+        let _ = tx.send(CtrlReq::ChooseBuffer(flags));
+    }
+    "hint-mode" => {
+        let _ = tx.send(CtrlReq::HintMode(args.iter().map(|a| a.to_string()).collect()));
     }
     "display-message" | "display" => {
         // Parse tmux-like display-message flags without dropping message text.
@@ -716,9 +757,21 @@ match cmd {
         let _ = tx.send(CtrlReq::RotateWindow(reverse));
     }
     "display-panes" | "displayp" => { let _ = tx.send(CtrlReq::DisplayPanes); }
-    "break-pane" | "breakp" => { let _ = tx.send(CtrlReq::BreakPane); }
+    "break-pane" | "breakp" => {
+        if args.iter().any(|a| *a == "-f") {
+            let name = args.iter().position(|a| *a == "-n").and_then(|i| args.get(i + 1)).map(|s| s.to_string());
+            let _ = tx.send(CtrlReq::BreakPaneFloat(name));
+        } else {
+            let _ = tx.send(CtrlReq::BreakPane);
+        }
+    }
     "join-pane" | "joinp" => {
-        if let Some(wid) = args.iter().find(|a| !a.starts_with('-')).and_then(|s| s.parse::<usize>().ok()) {
+        // A non-numeric -s names a floating popup
+        let float = args.iter().position(|a| *a == "-s").and_then(|i| args.get(i + 1))
+            .filter(|s| s.parse::<usize>().is_err());
+        if let Some(name) = float {
+            let _ = tx.send(CtrlReq::JoinFloat(name.to_string(), args.iter().any(|a| *a == "-h")));
+        } else if let Some(wid) = args.iter().find(|a| !a.starts_with('-')).and_then(|s| s.parse::<usize>().ok()) {
             let _ = tx.send(CtrlReq::JoinPane(wid));
         }
     }
@@ -774,7 +827,8 @@ match cmd {
     }
     "list-keys" | "lsk" => {
         let (rtx, rrx) = mpsc::channel::<String>();
-        let _ = tx.send(CtrlReq::ListKeys(rtx));
+        let table = args.iter().position(|a| *a == "-T").and_then(|i| args.get(i + 1)).map(|t| t.to_string());
+        let _ = tx.send(CtrlReq::ListKeys(table, rtx));
         if let Ok(text) = rrx.recv() { let _ = write!(write_stream, "{}\n", text); let _ = write_stream.flush(); }
         if !persistent { break; }
     }
@@ -937,7 +991,8 @@ match cmd {
             let table = args.windows(2).find(|w| w[0] == "-T").map(|w| w[1].to_string()).unwrap_or_default();
             let _ = tx.send(CtrlReq::SwitchClientTable(table));
         } else {
-            let target = args.iter().find(|a| !a.starts_with('-')).unwrap_or(&"").to_string();
+            let target = raw_target.clone()
+                .unwrap_or_else(|| args.iter().find(|a| !a.starts_with('-')).unwrap_or(&"").to_string());
             let _ = tx.send(CtrlReq::SwitchClient(target));
         }
     }
@@ -957,12 +1012,18 @@ match cmd {
         let _ = tx.send(CtrlReq::ClearHistory);
     }
     "save-buffer" | "saveb" => {
-        let path = args.iter().find(|a| !a.starts_with('-')).unwrap_or(&"").to_string();
-        let _ = tx.send(CtrlReq::SaveBuffer(path));
+        let name = args.windows(2).find(|w| w[0] == "-b").map(|w| w[1].to_string());
+        let path = args.iter().enumerate()
+            .find(|(i, a)| !a.starts_with('-') && (*i == 0 || args[i - 1] != "-b"))
+            .map(|(_, a)| a.to_string()).unwrap_or_default();
+        let _ = tx.send(CtrlReq::SaveBuffer(path, name));
     }
     "load-buffer" | "loadb" => {
-        let path = args.iter().find(|a| !a.starts_with('-')).unwrap_or(&"").to_string();
-        let _ = tx.send(CtrlReq::LoadBuffer(path));
+        let name = args.windows(2).find(|w| w[0] == "-b").map(|w| w[1].to_string());
+        let path = args.iter().enumerate()
+            .find(|(i, a)| !a.starts_with('-') && (*i == 0 || args[i - 1] != "-b"))
+            .map(|(_, a)| a.to_string()).unwrap_or_default();
+        let _ = tx.send(CtrlReq::LoadBuffer(path, name));
     }
     "set-environment" | "setenv" => {
         let has_u = args.iter().any(|a| *a == "-u");
@@ -1007,61 +1068,17 @@ match cmd {
         let _ = tx.send(CtrlReq::WaitFor(channel, op));
     }
     "display-menu" | "menu" => {
-        let mut x_pos: Option<i16> = None;
-        let mut y_pos: Option<i16> = None;
-        let mut title = String::new();
-        let mut skip_indices = std::collections::HashSet::new();
-        let mut i = 0;
-        while i < args.len() {
-            match args[i] {
-                "-x" => { if let Some(v) = args.get(i+1) { x_pos = v.parse().ok(); skip_indices.insert(i); skip_indices.insert(i+1); i += 1; } }
-                "-y" => { if let Some(v) = args.get(i+1) { y_pos = v.parse().ok(); skip_indices.insert(i); skip_indices.insert(i+1); i += 1; } }
-                "-T" => { if let Some(v) = args.get(i+1) { title = v.to_string(); skip_indices.insert(i); skip_indices.insert(i+1); i += 1; } }
-                _ => {}
-            }
-            i += 1;
-        }
-        // Collect remaining positional args (name, key, command triplets)
-        let positional: Vec<&str> = args.iter().enumerate()
-            .filter(|(idx, a)| !skip_indices.contains(idx) && !a.starts_with('-'))
-            .map(|(_, a)| *a).collect();
-        // Build menu from triplets
-        let mut menu = crate::types::Menu { title, items: Vec::new(), selected: 0, x: x_pos, y: y_pos };
-        let mut pi = 0;
-        while pi < positional.len() {
-            let name = positional[pi];
-            if name.is_empty() || name == "-" {
-                menu.items.push(crate::types::MenuItem { name: String::new(), key: None, command: String::new(), is_separator: true });
-                pi += 1;
-            } else {
-                let key = positional.get(pi + 1).and_then(|k| k.chars().next());
-                let command = positional.get(pi + 2).map(|c| c.to_string()).unwrap_or_default();
-                menu.items.push(crate::types::MenuItem { name: name.to_string(), key, command, is_separator: false });
-                pi += 3;
-            }
-        }
-        if !menu.items.is_empty() {
-            let _ = tx.send(CtrlReq::DisplayMenuDirect(menu));
-        }
+        let _ = tx.send(CtrlReq::DisplayMenu(crate::menu::parse_args(&args)));
     }
     "display-popup" | "popup" => {
-        // Default close-on-exit = true (tmux parity: popup closes when command finishes)
-        let close_on_exit = !args.iter().any(|a| *a == "-K");
-        let mut width: u16 = 80;
-        let mut height: u16 = 24;
-        let mut skip_indices = std::collections::HashSet::new();
-        let mut i = 0;
-        while i < args.len() {
-            match args[i] {
-                "-w" => { if let Some(v) = args.get(i+1) { width = v.trim_end_matches('%').parse().unwrap_or(80); skip_indices.insert(i); skip_indices.insert(i+1); i += 1; } }
-                "-h" => { if let Some(v) = args.get(i+1) { height = v.trim_end_matches('%').parse().unwrap_or(24); skip_indices.insert(i); skip_indices.insert(i+1); i += 1; } }
-                "-E" | "-K" => { skip_indices.insert(i); }
-                _ => {}
-            }
-            i += 1;
-        }
-        let content = args.iter().enumerate().filter(|(idx, a)| !skip_indices.contains(idx) && !a.starts_with('-')).map(|(_, a)| *a).collect::<Vec<&str>>().join(" ");
-        let _ = tx.send(CtrlReq::DisplayPopup(content, width, height, close_on_exit));
+        // Popups are shared by every attached client, so -c only needs parsing
+        let mut popup = crate::popup::parse_args(&args);
+        popup.target = raw_target.clone();
+        let _ = tx.send(CtrlReq::DisplayPopup(popup));
+    }
+    "hide-popup" | "move-popup" | "resize-popup" => {
+        let argv = std::iter::once(cmd).chain(args.iter().copied()).map(|s| s.to_string()).collect();
+        let _ = tx.send(CtrlReq::PopupCommand(argv));
     }
     "confirm-before" | "confirm" => {
         let mut prompt: Option<String> = None;
@@ -1090,12 +1107,14 @@ match cmd {
     }
     "kill-server" => { let _ = tx.send(CtrlReq::KillServer); }
     "choose-tree" | "choose-window" | "choose-session" => {
-        // These are interactive choosers — send a dump that client handles
-        // For now, map to listing which the client renders as a chooser
-        let (rtx, rrx) = mpsc::channel::<String>();
-        let _ = tx.send(CtrlReq::ListTree(rtx));
-        if let Ok(text) = rrx.recv() { let _ = write!(write_stream, "{}\n", text); let _ = write_stream.flush(); }
-        if !persistent { break; }
+        let mut flags: Vec<String> = match cmd {
+            "choose-session" => vec!["-s".into()],
+            "choose-window" => vec!["-w".into()],
+            _ => Vec::new(),
+        };
+        if let Some(t) = raw_target.clone() { flags.push("-t".into()); flags.push(t); }
+        flags.extend(args.iter().map(|a| a.to_string()));
+        let _ = tx.send(CtrlReq::ChooseTree(flags));
     }
     "copy-mode" => {
         if args.iter().any(|a| *a == "-u") {
@@ -1103,6 +1122,9 @@ match cmd {
         } else {
             let _ = tx.send(CtrlReq::CopyEnter);
         }
+        if args.iter().any(|a| *a == "-H") {
+            let _ = tx.send(CtrlReq::CopyHidePosition);
+        }
     }
     "clock-mode" => { let _ = tx.send(CtrlReq::ClockMode); }
     // Overlay interaction commands (sent by client during active overlays)
@@ -1142,8 +1164,7 @@ match cmd {
         if !persistent { break; }
     }
     "command-prompt" => {
-        let initial = args.windows(2).find(|w| w[0] == "-I").map(|w| w[1].to_string()).unwrap_or_default();
-        let _ = tx.send(CtrlReq::CommandPrompt(initial));
+        let _ = tx.send(CtrlReq::CommandPrompt(args.iter().map(|a| a.to_string()).collect()));
     }
     "run-shell" | "run" => {
         let background = args.iter().any(|a| *a == "-b");
@@ -1222,7 +1243,7 @@ match cmd {
         let fmt = args.windows(2).find(|w| w[0] == "-F").map(|w| w[1].to_string());
         if let Some(fmt_str) = fmt {
             let (rtx, rrx) = mpsc::channel::<String>();
-            let _ = tx.send(CtrlReq::DisplayMessage(rtx, fmt_str, None));
+            let _ = tx.send(CtrlReq::ListSessionsFormat(rtx, fmt_str));
             if let Ok(text) = rrx.recv() { let _ = write!(write_stream, "{}\n", text); let _ = write_stream.flush(); }
         } else {
             let (rtx, rrx) = mpsc::channel::<String>();
@@ -90,6 +90,10 @@ pub(crate) fn combined_data_version(app: &AppState) -> u64 {
     }
     if let Some(win) = app.windows.get(app.active_idx) {
         walk(&win.root, &mut v);
+        // Output in visible floating popups changes the frame too
+        for dv in win.floating.iter().filter(|p| !p.hidden).filter_map(|p| p.data_version()) {
+            v = v.wrapping_add(dv.load(std::sync::atomic::Ordering::Acquire));
+        }
     }
     // Include mode discriminant so overlay state changes (PopupMode, MenuMode,
     // ConfirmMode, PaneChooser, ClockMode) always invalidate the cached version.
@@ -154,7 +158,7 @@ pub(crate) const TMUX_COMMANDS: &[&str] = &[
     "delete-buffer (deleteb)", "detach-client (detach)",
     "display-menu (menu)", "display-message (display)",
     "display-panes (displayp)", "display-popup (popup)",
-    "find-window (findw)", "has-session (has)",
+    "find-window (findw)", "has-session (has)", "hide-popup",
     "if-shell (if)", "join-pane (joinp)",
     "kill-pane (killp)", "kill-server", "kill-session",
     "kill-window (killw)", "last-pane (lastp)", "last-window (last)",
@@ -163,13 +167,13 @@ pub(crate) const TMUX_COMMANDS: &[&str] = &[
     "list-sessions (ls)", "list-windows (lsw)",
     "load-buffer (loadb)", "lock-client (lockc)",
     "lock-server (lock)", "lock-session (locks)",
-    "move-pane (movep)", "move-window (movew)",
+    "move-pane (movep)", "move-popup", "move-window (movew)",
     "new-session (new)", "new-window (neww)",
     "next-layout (nextl)", "next-window (next)",
     "paste-buffer (pasteb)", "pipe-pane (pipep)",
     "previous-layout (prevl)", "previous-window (prev)",
-    "refresh-client (refresh)", "rename-session (rename)",
-    "rename-window (renamew)", "resize-pane (resizep)",
+    "record-pane", "refresh-client (refresh)", "rename-session (rename)",
+    "rename-window (renamew)", "resize-pane (resizep)", "resize-popup",
     "resize-window (resizew)", "respawn-pane (respawnp)",
     "respawn-window (respawnw)", "rotate-window (rotatew)",
     "run-shell (run)", "save-buffer (saveb)",
@@ -1,4 +1,4 @@
-mod helpers;
+pub(crate) mod helpers;
 mod options;
 mod connection;
 
@@ -13,7 +13,7 @@ use portable_pty::native_pty_system;
 use ratatui::prelude::Rect;
 
 use crate::types::{AppState, CtrlReq, Mode, FocusDir, LayoutKind, PipePaneState, VERSION,
-    WaitChannel, WaitForOp, Node, Action, Bind, PopupPty};
+    WaitChannel, WaitForOp, Node, Action, Bind};
 use crate::platform::install_console_ctrl_handler;
 use crate::pane::{create_window, create_window_raw, split_active_with_command, kill_active_pane, kill_pane_by_id, spawn_warm_pane};
 use crate::tree::{self, active_pane, active_pane_mut, resize_all_panes, kill_all_children,
@@ -26,8 +26,7 @@ use options::{get_option_value, get_window_option_value, render_window_options,
 
 use crate::input::{send_text_to_active, send_key_to_active, send_paste_to_active, move_focus, find_best_pane_in_direction};
 use crate::copy_mode::{enter_copy_mode, exit_copy_mode, move_copy_cursor, current_prompt_pos,
-    yank_selection, scroll_copy_up, scroll_copy_down, switch_with_copy_save,
-    capture_active_pane_text, capture_active_pane_range, capture_active_pane_styled};
+    yank_selection, scroll_copy_up, switch_with_copy_save};
 use crate::layout::{dump_layout_json, dump_layout_json_fast, apply_layout, cycle_layout,
     cycle_layout_reverse};
 use crate::window_ops::{toggle_zoom, remote_mouse_down, remote_mouse_drag, remote_mouse_up,
@@ -36,69 +35,80 @@ use crate::window_ops::{toggle_zoom, remote_mouse_down, remote_mouse_drag, remot
     resize_pane_horizontal, resize_pane_absolute, rotate_panes, respawn_active_pane};
 use crate::config::{load_config, parse_key_string, format_key_binding, normalize_key_for_binding,
     parse_config_content};
-use crate::commands::{parse_command_to_action, format_action, parse_menu_definition, execute_command_string};
+use crate::commands::{parse_command_to_action, format_action, execute_command_string};
 use crate::util::{list_windows_json, list_tree_json, list_windows_tmux, base64_encode};
 use crate::format::{expand_format, format_list_windows, format_list_panes, set_buffer_idx_override};
 use crate::help;
 
-/// Build a JSON fragment with overlay state (popup, menu, confirm, display_panes).
-/// Returns a string like `,"popup_active":true,"popup_command":"...","popup_lines":[...]`
-/// that can be injected into the dump-state JSON before the closing `}`.
-fn serialize_overlay_json(app: &AppState) -> String {
+/// Write a popup's fields as JSON members (each preceded by a comma), with
+/// keys prefixed by `prefix`: `popup_` for the focused popup, nothing for
+/// the entries of `floats`.
+fn popup_json(out: &mut String, popup: &crate::popup::Popup, prefix: &str) {
     use crate::server::helpers::json_escape_string;
-    let mut out = String::new();
-    match &app.mode {
-        Mode::PopupMode { command, output, width, height, popup_pty, .. } => {
-            out.push_str(",\"popup_active\":true");
-            out.push_str(",\"popup_command\":\"");
-            out.push_str(&json_escape_string(command));
-            out.push('"');
-            let _ = std::fmt::Write::write_fmt(&mut out, format_args!(",\"popup_width\":{},\"popup_height\":{}", width, height));
-            // Serialize popup screen content
-            out.push_str(",\"popup_lines\":[");
-            let inner_h = height.saturating_sub(2);
-            let inner_w = width.saturating_sub(2);
-            if let Some(pty) = popup_pty {
-                if let Ok(parser) = pty.term.lock() {
-                    let screen = parser.screen();
-                    for row in 0..inner_h {
-                        if row > 0 { out.push(','); }
-                        out.push('"');
-                        for col in 0..inner_w {
-                            if let Some(cell) = screen.cell(row, col) {
-                                let ch = cell.contents();
-                                if ch.is_empty() {
-                                    out.push(' ');
-                                } else {
-                                    // JSON-escape the cell content
-                                    for c in ch.chars() {
-                                        match c {
-                                            '"' => out.push_str("\\\""),
-                                            '\\' => out.push_str("\\\\"),
-                                            c if (c as u32) < 0x20 => {
-                                                let _ = std::fmt::Write::write_fmt(&mut out, format_args!("\\u{:04x}", c as u32));
-                                            }
-                                            c => out.push(c),
-                                        }
+    let crate::popup::Popup { command, output, x, y, width, height, title, style, border_style, border_lines, .. } = popup;
+    let _ = std::fmt::Write::write_fmt(out, format_args!(",\"{p}command\":\"{}\"", json_escape_string(command), p = prefix));
+    let _ = std::fmt::Write::write_fmt(out, format_args!(",\"{p}x\":{},\"{p}y\":{},\"{p}width\":{},\"{p}height\":{}", x, y, width, height, p = prefix));
+    for (key, value) in [("title", title), ("style", style), ("border_style", border_style), ("border_lines", border_lines)] {
+        let _ = std::fmt::Write::write_fmt(out, format_args!(",\"{}{}\":\"{}\"", prefix, key, json_escape_string(value)));
+    }
+    // Serialize popup screen content
+    let _ = std::fmt::Write::write_fmt(out, format_args!(",\"{}lines\":[", prefix));
+    let inset = popup.inset();
+    let inner_h = height.saturating_sub(inset);
+    let inner_w = width.saturating_sub(inset);
+    if let Some(term) = popup.term() {
+        if let Ok(parser) = term.lock() {
+            let screen = parser.screen();
+            for row in 0..inner_h {
+                if row > 0 { out.push(','); }
+                out.push('"');
+                for col in 0..inner_w {
+                    if let Some(cell) = screen.cell(row, col) {
+                        let ch = cell.contents();
+                        if ch.is_empty() {
+                            out.push(' ');
+                        } else {
+                            // JSON-escape the cell content
+                            for c in ch.chars() {
+                                match c {
+                                    '"' => out.push_str("\\\""),
+                                    '\\' => out.push_str("\\\\"),
+                                    c if (c as u32) < 0x20 => {
+                                        let _ = std::fmt::Write::write_fmt(out, format_args!("\\u{:04x}", c as u32));
                                     }
+                                    c => out.push(c),
                                 }
-                            } else {
-                                out.push(' ');
                             }
                         }
-                        out.push('"');
+                    } else {
+                        out.push(' ');
                     }
                 }
-            } else if !output.is_empty() {
-                // Non-PTY popup: serialize the output text as lines
-                for (i, line) in output.lines().take(inner_h as usize).enumerate() {
-                    if i > 0 { out.push(','); }
-                    out.push('"');
-                    out.push_str(&json_escape_string(line));
-                    out.push('"');
-                }
+                out.push('"');
             }
-            out.push(']');
+        }
+    } else if !output.is_empty() {
+        // Non-PTY popup: serialize the output text as lines
+        for (i, line) in output.lines().take(inner_h as usize).enumerate() {
+            if i > 0 { out.push(','); }
+            out.push('"');
+            out.push_str(&json_escape_string(line));
+            out.push('"');
+        }
+    }
+    out.push(']');
+}
+
+/// Build a JSON fragment with overlay state (popup, menu, confirm, display_panes).
+/// Returns a string like `,"popup_active":true,"popup_command":"...","popup_lines":[...]`
+/// that can be injected into the dump-state JSON before the closing `}`.
+fn serialize_overlay_json(app: &AppState) -> String {
+    use crate::server::helpers::json_escape_string;
+    let mut out = String::new();
+    match &app.mode {
+        Mode::PopupMode { popup } => {
+            out.push_str(",\"popup_active\":true");
+            popup_json(&mut out, popup, "popup_");
         }
         Mode::ConfirmMode { prompt, .. } => {
             out.push_str(",\"confirm_active\":true,\"confirm_prompt\":\"");
@@ -106,34 +116,88 @@ fn serialize_overlay_json(app: &AppState) -> String {
             out.push('"');
         }
         Mode::MenuMode { menu } => {
-            out.push_str(",\"menu_active\":true,\"menu_title\":\"");
-            out.push_str(&json_escape_string(&menu.title));
-            out.push('"');
-            let _ = std::fmt::Write::write_fmt(&mut out, format_args!(",\"menu_selected\":{}", menu.selected));
-            out.push_str(",\"menu_items\":[");
-            for (i, item) in menu.items.iter().enumerate() {
-                if i > 0 { out.push(','); }
-                if item.is_separator {
-                    out.push_str("{\"sep\":true}");
-                } else {
-                    out.push_str("{\"name\":\"");
-                    out.push_str(&json_escape_string(&item.name));
-                    out.push_str("\",\"key\":");
-                    if let Some(k) = item.key {
-                        let _ = std::fmt::Write::write_fmt(&mut out, format_args!("\"{}\"", k));
-                    } else {
-                        out.push_str("null");
-                    }
-                    out.push('}');
-                }
+            if let Ok(json) = serde_json::to_string(&crate::menu::views(menu)) {
+                out.push_str(",\"menu_active\":true,\"menus\":");
+                out.push_str(&json);
             }
-            out.push(']');
         }
         Mode::PaneChooser { .. } => {
             out.push_str(",\"display_panes\":true");
         }
+        Mode::BufferChooser { chooser } => {
+            let view = crate::buffers::chooser_view(app, chooser);
+            if let Ok(json) = serde_json::to_string(&view) {
+                out.push_str(",\"buffer_chooser\":");
+                out.push_str(&json);
+            }
+        }
+        Mode::CommandPrompt { prompt } => {
+            if let Ok(json) = serde_json::to_string(&crate::prompt::view(app, prompt)) {
+                out.push_str(",\"command_prompt\":");
+                out.push_str(&json);
+            }
+        }
+        Mode::WindowChooser { chooser } => {
+            let view = crate::choose_tree::view(app, chooser);
+            if let Ok(json) = serde_json::to_string(&view) {
+                out.push_str(",\"tree_chooser\":");
+                out.push_str(&json);
+            }
+        }
+        Mode::HintMode { hints } => {
+            let view = crate::hints::hint_view(app, hints);
+            if let Ok(json) = serde_json::to_string(&view) {
+                out.push_str(",\"hints\":");
+                out.push_str(&json);
+            }
+        }
+        Mode::CopySearch { input, forward, search } => {
+            out.push_str(",\"copy_search_prompt\":\"");
+            out.push_str(&json_escape_string(&crate::copy_mode::search_prompt_text(input, *forward, search.as_ref())));
+            out.push('"');
+        }
         _ => {}
     }
+    if matches!(app.mode, Mode::CopyMode | Mode::CopySearch { .. }) {
+        out.push_str(",\"copy_mode_match_style\":\"");
+        out.push_str(&json_escape_string(&app.copy_mode_match_style));
+        out.push_str("\",\"copy_mode_current_match_style\":\"");
+        out.push_str(&json_escape_string(&app.copy_mode_current_match_style));
+        out.push('"');
+        if !app.copy_hide_position {
+            out.push_str(",\"copy_position\":\"");
+            out.push_str(&json_escape_string(&crate::format::expand_format(&app.copy_mode_position_format, app)));
+            out.push('"');
+        }
+    }
+    if app.pane_scrollbars != "off" {
+        out.push_str(",\"pane_scrollbars\":\"");
+        out.push_str(&json_escape_string(&app.pane_scrollbars));
+        out.push_str("\",\"pane_scrollbars_style\":\"");
+        out.push_str(&json_escape_string(&app.pane_scrollbars_style));
+        out.push('"');
+    }
+    // Visible floating popups of the active window, bottom to top
+    if let Some(win) = app.windows.get(app.active_idx) {
+        let mut floats = win.floating.iter().filter(|p| !p.hidden).peekable();
+        if floats.peek().is_some() {
+            out.push_str(",\"floats\":[");
+            for (i, popup) in floats.enumerate() {
+                if i > 0 { out.push(','); }
+                out.push_str("{\"name\":\"");
+                out.push_str(&json_escape_string(popup.name.as_deref().unwrap_or("")));
+                out.push('"');
+                popup_json(&mut out, popup, "");
+                out.push('}');
+            }
+            out.push(']');
+        }
+    }
+    if let Some(ref name) = app.switch_client {
+        out.push_str(",\"switch_to\":\"");
+        out.push_str(&json_escape_string(name));
+        out.push('"');
+    }
     // Include status_message for display-message without -p (#110)
     if let Some((ref msg, since)) = app.status_message {
         let elapsed = since.elapsed().as_millis() as u64;
@@ -214,6 +278,25 @@ fn spawn_warm_server(app: &AppState) {
     }
 }
 
+/// Id of the pane that has focus: the active pane of the active window.
+fn focused_pane_id(app: &AppState) -> Option<usize> {
+    let win = app.windows.get(app.active_idx)?;
+    crate::tree::get_active_pane_id(&win.root, &win.active_path)
+}
+
+/// Write a focus report (`CSI I` / `CSI O`) to pane `id` if its application
+/// enabled focus reporting (DECSET 1004).
+fn send_focus_seq(app: &mut AppState, id: usize, seq: &[u8]) {
+    for win in app.windows.iter_mut() {
+        let Some(path) = crate::tree::find_path_by_id(&win.root, id) else { continue };
+        if let Some(p) = active_pane_mut(&mut win.root, &path) {
+            let wants = p.term.lock().map_or(false, |t| t.screen().focus_events());
+            if wants { let _ = p.writer.write_all(seq); let _ = p.writer.flush(); }
+        }
+        return;
+    }
+}
+
 /// Compute the effective display size from all connected clients' terminal sizes.
 /// Returns None if no clients have reported sizes.
 fn compute_effective_client_size(app: &AppState) -> Option<(u16, u16)> {
@@ -602,6 +685,10 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
     // (250ms is imperceptible to users).
     let mut last_reap = Instant::now();
 
+    // Whether the attached client's terminal has focus, as last reported by
+    // FocusIn/FocusOut.  Pane switches only send focus reports while it does.
+    let mut client_focused = true;
+
     loop {
         // Adaptive timeout: ramps from 1ms (active typing/echo) through
         // 5ms (client recently active) up to 50ms (fully idle).  This
@@ -656,6 +743,7 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                 // We store the pane ID (not path) because kill-pane
                 // restructures the tree, invalidating saved paths (#71).
                 let mut temp_focus_restore: Option<(usize, usize)> = None;
+                let focus_before = focused_pane_id(&app);
                 for req in pending {
                     let mutates_state = !matches!(&req,
                         CtrlReq::DumpState(..)
@@ -855,14 +943,31 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                 }
                 CtrlReq::KillPane => { unzoom_if_zoomed(&mut app); let _ = kill_active_pane(&mut app); resize_all_panes(&mut app); meta_dirty = true; hook_event = Some("after-kill-pane"); }
                 CtrlReq::KillPaneById(pid) => { unzoom_if_zoomed(&mut app); let _ = kill_pane_by_id(&mut app, pid); resize_all_panes(&mut app); meta_dirty = true; hook_event = Some("after-kill-pane"); }
-                CtrlReq::CapturePane(resp) => {
-                    if let Some(text) = capture_active_pane_text(&mut app)? { let _ = resp.send(text); } else { let _ = resp.send(String::new()); }
+                CtrlReq::CapturePane(opts, resp) => {
+                    match crate::capture::capture_pane(&app, &opts) {
+                        Ok(text) if !opts.print => {
+                            crate::buffers::set_buffer(&mut app, opts.buffer.as_deref(), &text, false);
+                            let _ = resp.send(Ok(String::new()));
+                        }
+                        result => { let _ = resp.send(result); }
+                    }
                 }
-                CtrlReq::CapturePaneStyled(resp, s, e) => {
-                    if let Some(text) = capture_active_pane_styled(&mut app, s, e)? { let _ = resp.send(text); } else { let _ = resp.send(String::new()); }
+                CtrlReq::RecordPane(target, file, stop, resp) => {
+                    match crate::record::record_pane(&app, target.as_deref(), file.as_deref(), stop) {
+                        Ok(msg) => {
+                            app.status_message = Some((msg, Instant::now()));
+                            let _ = resp.send(String::new());
+                        }
+                        Err(e) => { let _ = resp.send(e); }
+                    }
                 }
-                CtrlReq::CapturePaneRange(resp, s, e) => {
-                    if let Some(text) = capture_active_pane_range(&mut app, s, e)? { let _ = resp.send(text); } else { let _ = resp.send(String::new()); }
+                CtrlReq::PlayRecording(argv) => {
+                    if create_window_raw(&*pty_system, &mut app, &argv).is_ok() {
+                        if let Some(w) = app.windows.last_mut() { w.name = "play".to_string(); }
+                        resize_all_panes(&mut app);
+                        meta_dirty = true;
+                        hook_event = Some("after-new-window");
+                    }
                 }
                 CtrlReq::FocusWindow(wid) => {
                     // wid is a display index (same as tmux window number), convert to internal array index
@@ -946,6 +1051,7 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                 CtrlReq::ClientAttach(cid) => { app.attached_clients = app.attached_clients.saturating_add(1); app.latest_client_id = Some(cid); hook_event = Some("client-attached"); }
                 CtrlReq::ClientDetach(cid) => {
                     app.attached_clients = app.attached_clients.saturating_sub(1);
+                    app.switch_client = None;
                     app.client_sizes.remove(&cid);
                     if app.latest_client_id == Some(cid) {
                         app.latest_client_id = None;
@@ -1131,6 +1237,7 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                 CtrlReq::SendPaste(s) => { send_paste_to_active(&mut app, &s)?; echo_pending_until = Some(Instant::now()); }
                 CtrlReq::ZoomPane => { toggle_zoom(&mut app); hook_event = Some("after-resize-pane"); }
                 CtrlReq::CopyEnter => { enter_copy_mode(&mut app); }
+                CtrlReq::CopyHidePosition => { app.copy_hide_position = true; }
                 CtrlReq::CopyEnterPageUp => {
                     enter_copy_mode(&mut app);
                     let half = app.windows.get(app.active_idx)
@@ -1167,6 +1274,9 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                         }
                     }
                 }
+                CtrlReq::ClientCellSize(cw, ch) => {
+                    app.client_cell_size = Some((cw, ch));
+                }
                 CtrlReq::FocusPaneCmd(pid) => {
                     let old_path = app.windows[app.active_idx].active_path.clone();
                     switch_with_copy_save(&mut app, |app| { focus_pane_by_id(app, pid); });
@@ -1348,223 +1458,8 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     }
                     echo_pending_until = Some(Instant::now());
                 }
-                CtrlReq::SendKeysX(cmd) => {
-                    // send-keys -X: dispatch copy-mode commands by name
-                    // This is the primary mechanism used by tmux-yank and other plugins
-                    let in_copy = matches!(app.mode, Mode::CopyMode | Mode::CopySearch { .. });
-                    if !in_copy {
-                        // Auto-enter copy mode for commands that require it
-                        enter_copy_mode(&mut app);
-                    }
-                    match cmd.as_str() {
-                        "cancel" => {
-                            app.mode = Mode::Passthrough;
-                            app.copy_anchor = None;
-                            app.copy_pos = None;
-                            app.copy_scroll_offset = 0;
-                            let win = &mut app.windows[app.active_idx];
-                            if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) {
-                                if let Ok(mut parser) = p.term.lock() {
-                                    parser.screen_mut().set_scrollback(0);
-                                }
-                            }
-                        }
-                        "begin-selection" => {
-                            if let Some((r,c)) = crate::copy_mode::get_copy_pos(&mut app) {
-                                app.copy_anchor = Some((r,c));
-                                app.copy_anchor_scroll_offset = app.copy_scroll_offset;
-                                app.copy_pos = Some((r,c));
-                                app.copy_selection_mode = crate::types::SelectionMode::Char;
-                            }
-                        }
-                        "select-line" => {
-                            if let Some((r,c)) = crate::copy_mode::get_copy_pos(&mut app) {
-                                app.copy_anchor = Some((r,c));
-                                app.copy_anchor_scroll_offset = app.copy_scroll_offset;
-                                app.copy_pos = Some((r,c));
-                                app.copy_selection_mode = crate::types::SelectionMode::Line;
-                            }
-                        }
-                        "rectangle-toggle" => {
-                            app.copy_selection_mode = match app.copy_selection_mode {
-                                crate::types::SelectionMode::Rect => crate::types::SelectionMode::Char,
-                                _ => crate::types::SelectionMode::Rect,
-                            };
-                        }
-                        "copy-selection" => {
-                            let _ = yank_selection(&mut app);
-                        }
-                        "copy-selection-and-cancel" => {
-                            let _ = yank_selection(&mut app);
-                            app.mode = Mode::Passthrough;
-                            app.copy_scroll_offset = 0;
-                            app.copy_pos = None;
-                        }
-                        "copy-selection-no-clear" => {
-                            let _ = yank_selection(&mut app);
-                        }
-                        s if s.starts_with("copy-pipe-and-cancel") || s.starts_with("copy-pipe") => {
-                            // copy-pipe[-and-cancel] [command] — yank + pipe to command
-                            let _ = yank_selection(&mut app);
-                            // Extract pipe command from argument if present
-                            let cancel = s.contains("cancel");
-                            let pipe_cmd = cmd.strip_prefix("copy-pipe-and-cancel")
-                                .or_else(|| cmd.strip_prefix("copy-pipe"))
-                                .unwrap_or("")
-                                .trim();
-                            if !pipe_cmd.is_empty() {
-                                if let Some(text) = app.paste_buffers.first().cloned() {
-                                    // Pipe yanked text to the command's stdin
-                                    if let Ok(mut child) = std::process::Command::new(if cfg!(windows) { "pwsh" } else { "sh" })
-                                        .args(if cfg!(windows) { vec!["-NoProfile", "-Command", pipe_cmd] } else { vec!["-c", pipe_cmd] })
-                                        .stdin(std::process::Stdio::piped())
-                                        .stdout(std::process::Stdio::null())
-                                        .stderr(std::process::Stdio::null())
-                                        .spawn() {
-                                        if let Some(mut stdin) = child.stdin.take() {
-                                            use std::io::Write;
-                                            let _ = stdin.write_all(text.as_bytes());
-                                        }
-                                        let _ = child.wait();
-                                    }
-                                }
-                            }
-                            if cancel {
-                                app.mode = Mode::Passthrough;
-                                app.copy_scroll_offset = 0;
-                                app.copy_pos = None;
-                            }
-                        }
-                        "cursor-up" => { move_copy_cursor(&mut app, 0, -1); }
-                        "cursor-down" => { move_copy_cursor(&mut app, 0, 1); }
-                        "cursor-left" => { move_copy_cursor(&mut app, -1, 0); }
-                        "cursor-right" => { move_copy_cursor(&mut app, 1, 0); }
-                        "start-of-line" => { crate::copy_mode::move_to_line_start(&mut app); }
-                        "end-of-line" => { crate::copy_mode::move_to_line_end(&mut app); }
-                        "back-to-indentation" => { crate::copy_mode::move_to_first_nonblank(&mut app); }
-                        "next-word" => { crate::copy_mode::move_word_forward(&mut app); }
-                        "previous-word" => { crate::copy_mode::move_word_backward(&mut app); }
-                        "next-word-end" => { crate::copy_mode::move_word_end(&mut app); }
-                        "next-space" => { crate::copy_mode::move_word_forward_big(&mut app); }
-                        "previous-space" => { crate::copy_mode::move_word_backward_big(&mut app); }
-                        "next-space-end" => { crate::copy_mode::move_word_end_big(&mut app); }
-                        "top-line" => { crate::copy_mode::move_to_screen_top(&mut app); }
-                        "middle-line" => { crate::copy_mode::move_to_screen_middle(&mut app); }
-                        "bottom-line" => { crate::copy_mode::move_to_screen_bottom(&mut app); }
-                        "history-top" => { crate::copy_mode::scroll_to_top(&mut app); }
-                        "history-bottom" => { crate::copy_mode::scroll_to_bottom(&mut app); }
-                        "halfpage-up" => {
-                            let half = app.windows.get(app.active_idx)
-                                .and_then(|w| active_pane(&w.root, &w.active_path))
-                                .map(|p| (p.last_rows / 2) as usize).unwrap_or(10);
-                            scroll_copy_up(&mut app, half);
-                        }
-                        "halfpage-down" => {
-                            let half = app.windows.get(app.active_idx)
-                                .and_then(|w| active_pane(&w.root, &w.active_path))
-                                .map(|p| (p.last_rows / 2) as usize).unwrap_or(10);
-                            scroll_copy_down(&mut app, half);
-                        }
-                        "page-up" => { scroll_copy_up(&mut app, 20); }
-                        "page-down" => { scroll_copy_down(&mut app, 20); }
-                        "scroll-up" => { scroll_copy_up(&mut app, 1); }
-                        "scroll-down" => { scroll_copy_down(&mut app, 1); }
-                        "search-forward" | "search-forward-incremental" => {
-                            app.mode = Mode::CopySearch { input: String::new(), forward: true };
-                        }
-                        "search-backward" | "search-backward-incremental" => {
-                            app.mode = Mode::CopySearch { input: String::new(), forward: false };
-                        }
-                        "search-again" => { crate::copy_mode::search_next(&mut app); }
-                        "search-reverse" => { crate::copy_mode::search_prev(&mut app); }
-                        "copy-end-of-line" => { let _ = crate::copy_mode::copy_end_of_line(&mut app); app.mode = Mode::Passthrough; app.copy_scroll_offset = 0; app.copy_pos = None; }
-                        "select-word" => {
-                            // Select the word under cursor
-                            crate::copy_mode::move_word_backward(&mut app);
-                            if let Some((r,c)) = crate::copy_mode::get_copy_pos(&mut app) {
-                                app.copy_anchor = Some((r,c));
-                                app.copy_anchor_scroll_offset = app.copy_scroll_offset;
-                                app.copy_selection_mode = crate::types::SelectionMode::Char;
-                            }
-                            crate::copy_mode::move_word_end(&mut app);
-                        }
-                        "other-end" => {
-                            if let (Some(a), Some(p)) = (app.copy_anchor, app.copy_pos) {
-                                app.copy_anchor = Some(p);
-                                app.copy_anchor_scroll_offset = app.copy_scroll_offset;
-                                app.copy_pos = Some(a);
-                            }
-                        }
-                        "clear-selection" => {
-                            app.copy_anchor = None;
-                            app.copy_selection_mode = crate::types::SelectionMode::Char;
-                        }
-                        "append-selection" => {
-                            // Append to existing buffer instead of replacing
-                            let _ = yank_selection(&mut app);
-                            if app.paste_buffers.len() >= 2 {
-                                let appended = format!("{}{}", app.paste_buffers[1], app.paste_buffers[0]);
-                                app.paste_buffers[0] = appended;
-                            }
-                        }
-                        "append-selection-and-cancel" => {
-                            let _ = yank_selection(&mut app);
-                            if app.paste_buffers.len() >= 2 {
-                                let appended = format!("{}{}", app.paste_buffers[1], app.paste_buffers[0]);
-                                app.paste_buffers[0] = appended;
-                            }
-                            app.mode = Mode::Passthrough;
-                            app.copy_scroll_offset = 0;
-                            app.copy_pos = None;
-                        }
-                        "copy-line" => {
-                            // Select entire current line and yank
-                            if let Some((r, _)) = crate::copy_mode::get_copy_pos(&mut app) {
-                                app.copy_anchor = Some((r, 0));
-                                app.copy_anchor_scroll_offset = app.copy_scroll_offset;
-                                app.copy_selection_mode = crate::types::SelectionMode::Line;
-                                let cols = app.windows.get(app.active_idx)
-                                    .and_then(|w| active_pane(&w.root, &w.active_path))
-                                    .map(|p| p.last_cols).unwrap_or(80);
-                                app.copy_pos = Some((r, cols.saturating_sub(1)));
-                                let _ = yank_selection(&mut app);
-                            }
-                            app.mode = Mode::Passthrough;
-                            app.copy_scroll_offset = 0;
-                            app.copy_pos = None;
-                        }
-                        s if s.starts_with("goto-line") => {
-                            // goto-line <N> — jump to line N in scrollback
-                            let n = s.strip_prefix("goto-line").unwrap_or("").trim()
-                                .parse::<u16>().unwrap_or(0);
-                            app.copy_pos = Some((n, 0));
-                        }
-                        "jump-forward" => { app.copy_find_char_pending = Some(0); }
-                        "jump-backward" => { app.copy_find_char_pending = Some(1); }
-                        "jump-to-forward" => { app.copy_find_char_pending = Some(2); }
-                        "jump-to-backward" => { app.copy_find_char_pending = Some(3); }
-                        "jump-again" => {
-                            // Repeat last find-char in same direction
-                            // We'd need to store last char; for now emit the pending
-                        }
-                        "jump-reverse" => {
-                            // Repeat last find-char in reverse direction
-                        }
-                        "next-paragraph" => {
-                            crate::copy_mode::move_next_paragraph(&mut app);
-                        }
-                        "previous-paragraph" => {
-                            crate::copy_mode::move_prev_paragraph(&mut app);
-                        }
-                        "next-matching-bracket" => {
-                            crate::copy_mode::move_matching_bracket(&mut app);
-                        }
-                        "stop-selection" => {
-                            // Keep cursor position but stop extending selection
-                            app.copy_anchor = None;
-                        }
-                        _ => {} // ignore unknown copy-mode commands
-                    }
+                CtrlReq::SendKeysX(cmd, count) => {
+                    crate::copy_mode::run_copy_mode_command(&mut app, &cmd, count);
                 }
                 CtrlReq::SelectPane(dir) => {
                     // Auto-unzoom when navigating to another pane (tmux behavior).
@@ -1886,15 +1781,23 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     }
                     hook_event = Some("after-resize-pane");
                 }
-                CtrlReq::SetBuffer(content) => {
-                    app.paste_buffers.insert(0, content);
-                    if app.paste_buffers.len() > 10 { app.paste_buffers.pop(); }
+                CtrlReq::SetBuffer(name, content, append, to_clipboard) => {
+                    let text = crate::buffers::set_buffer(&mut app, name.as_deref(), &content, append);
+                    if to_clipboard {
+                        crate::copy_mode::send_to_clipboard(&app, &text);
+                        if app.set_clipboard != "off" { app.clipboard_osc52 = Some(text); }
+                    }
+                }
+                CtrlReq::RenameBuffer(name, new_name) => {
+                    if let Err(e) = crate::buffers::rename(&mut app, name.as_deref(), &new_name) {
+                        app.status_message = Some((e, Instant::now()));
+                    }
                 }
                 CtrlReq::ListBuffers(resp) => {
                     let mut output = String::new();
-                    for (i, buf) in app.paste_buffers.iter().enumerate() {
-                        let preview: String = buf.chars().take(50).collect();
-                        output.push_str(&format!("buffer{}: {} bytes: \"{}\"\n", i, buf.len(), preview));
+                    for buf in app.paste_buffers.iter() {
+                        let preview: String = buf.data.chars().take(50).collect();
+                        output.push_str(&format!("{}: {} bytes: \"{}\"\n", buf.name, buf.data.len(), preview));
                     }
                     let _ = resp.send(output);
                 }
@@ -1908,15 +1811,15 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     let _ = resp.send(output.join("\n"));
                 }
                 CtrlReq::ShowBuffer(resp) => {
-                    let content = app.paste_buffers.first().cloned().unwrap_or_default();
+                    let content = crate::buffers::get_data(&app, None).unwrap_or_default();
                     let _ = resp.send(content);
                 }
-                CtrlReq::ShowBufferAt(resp, idx) => {
-                    let content = app.paste_buffers.get(idx).cloned().unwrap_or_default();
+                CtrlReq::ShowNamedBuffer(resp, name) => {
+                    let content = crate::buffers::get_data(&app, Some(&name)).unwrap_or_default();
                     let _ = resp.send(content);
                 }
-                CtrlReq::DeleteBuffer => {
-                    if !app.paste_buffers.is_empty() { app.paste_buffers.remove(0); }
+                CtrlReq::DeleteBuffer(name) => {
+                    crate::buffers::delete(&mut app, name.as_deref());
                 }
                 CtrlReq::DisplayMessage(resp, fmt, target_pane_idx) => {
                     let result = if let Some(pane_idx) = target_pane_idx {
@@ -1970,6 +1873,23 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     hook_event = Some("after-break-pane");
                     meta_dirty = true;
                 }
+                CtrlReq::BreakPaneFloat(name) => {
+                    unzoom_if_zoomed(&mut app);
+                    match crate::popup::float_active_pane(&mut app, name) {
+                        Ok(()) => hook_event = Some("after-break-pane"),
+                        Err(e) => app.status_message = Some((e, Instant::now())),
+                    }
+                    meta_dirty = true;
+                }
+                CtrlReq::JoinFloat(name, horizontal) => {
+                    unzoom_if_zoomed(&mut app);
+                    let kind = if horizontal { LayoutKind::Horizontal } else { LayoutKind::Vertical };
+                    match crate::popup::join(&mut app, &name, kind) {
+                        Ok(()) => hook_event = Some("after-join-pane"),
+                        Err(e) => app.status_message = Some((e, Instant::now())),
+                    }
+                    meta_dirty = true;
+                }
                 CtrlReq::JoinPane(target_win) => {
                     unzoom_if_zoomed(&mut app);
                     // Real join-pane: extract active pane from current window and
@@ -2044,7 +1964,7 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     meta_dirty = true;
                     state_dirty = true;
                 }
-                CtrlReq::ListKeys(resp) => {
+                CtrlReq::ListKeys(table, resp) => {
                     // Build list-keys output from the canonical help module
                     let user_iter = app.key_tables.iter().flat_map(|(table_name, binds)| {
                         binds.iter().map(move |bind| {
@@ -2053,7 +1973,7 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                             (table_name.as_str(), key_str, action_str, bind.repeat)
                         })
                     });
-                    let output = help::build_list_keys_output(user_iter);
+                    let output = help::build_list_keys_output(user_iter, table.as_deref());
                     let _ = resp.send(output);
                 }
                 CtrlReq::SetOption(option, value) => {
@@ -2104,6 +2024,30 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                             "status-style" => { app.status_style = String::new(); }
                             "renumber-windows" => { app.renumber_windows = false; }
                             "remain-on-exit" => { app.remain_on_exit = false; }
+                            "remain-on-exit-format" => { app.remain_on_exit_format = crate::types::DEFAULT_REMAIN_ON_EXIT_FORMAT.to_string(); }
+                            "ambiguous-width" => { app.ambiguous_width = 1; crate::pane::refresh_width_options(&mut app); }
+                            "variation-selector-always-wide" => { app.variation_selector_always_wide = true; crate::pane::refresh_width_options(&mut app); }
+                            "wheel-scroll-lines" => { app.wheel_scroll_lines = 3; }
+                            "set-clipboard" => { app.set_clipboard = "on".to_string(); }
+                            "allow-clipboard-read" => { app.allow_clipboard_read = false; }
+                            "hint-alphabet" => { app.hint_alphabet = crate::hints::DEFAULT_ALPHABET.to_string(); }
+                            "hint-patterns" => { app.hint_patterns = crate::hints::default_patterns(); }
+                            "hint-style" => { app.hint_style = "fg=black,bg=yellow,bold".to_string(); }
+                            "buffer-limit" => { app.buffer_limit = crate::buffers::DEFAULT_BUFFER_LIMIT; }
+                            "history-file" => { app.history_file = String::new(); }
+                            "prompt-history-limit" => { app.prompt_history_limit = 100; }
+                            "copy-mode-match-style" => { app.copy_mode_match_style = "bg=cyan,fg=black".to_string(); }
+                            "copy-mode-current-match-style" => { app.copy_mode_current_match_style = "bg=magenta,fg=black".to_string(); }
+                            "copy-mode-position-format" => { app.copy_mode_position_format = crate::types::DEFAULT_COPY_MODE_POSITION_FORMAT.to_string(); }
+                            "pane-scrollbars" => { app.pane_scrollbars = "off".to_string(); crate::tree::resize_all_panes(&mut app); }
+                            "pane-scrollbars-style" => { app.pane_scrollbars_style = "bg=black,fg=white".to_string(); }
+                            "popup-style" => { app.popup_style = "default".to_string(); }
+                            "popup-border-style" => { app.popup_border_style = "fg=yellow".to_string(); }
+                            "popup-border-lines" => { app.popup_border_lines = "single".to_string(); }
+                            "menu-style" => { app.menu_style = "default".to_string(); }
+                            "menu-selected-style" => { app.menu_selected_style = "bg=yellow,fg=black".to_string(); }
+                            "menu-border-style" => { app.menu_border_style = "default".to_string(); }
+                            "menu-border-lines" => { app.menu_border_lines = "single".to_string(); }
                             "destroy-unattached" => { app.destroy_unattached = false; }
                             "exit-empty" => { app.exit_empty = true; }
                             "automatic-rename" => { app.automatic_rename = true; }
@@ -2155,11 +2099,15 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     output.push_str(&format!("display-panes-time {}\n", app.display_panes_time_ms));
                     output.push_str(&format!("mode-keys {}\n", app.mode_keys));
                     output.push_str(&format!("focus-events {}\n", if app.focus_events { "on" } else { "off" }));
+                    output.push_str(&format!("ambiguous-width {}\n", app.ambiguous_width));
+                    output.push_str(&format!("variation-selector-always-wide {}\n", if app.variation_selector_always_wide { "on" } else { "off" }));
+                    output.push_str(&format!("wheel-scroll-lines {}\n", app.wheel_scroll_lines));
                     output.push_str(&format!("renumber-windows {}\n", if app.renumber_windows { "on" } else { "off" }));
                     output.push_str(&format!("automatic-rename {}\n", if app.automatic_rename { "on" } else { "off" }));
                     output.push_str(&format!("monitor-activity {}\n", if app.monitor_activity { "on" } else { "off" }));
                     output.push_str(&format!("synchronize-panes {}\n", if app.sync_input { "on" } else { "off" }));
                     output.push_str(&format!("remain-on-exit {}\n", if app.remain_on_exit { "on" } else { "off" }));
+                    output.push_str(&format!("remain-on-exit-format \"{}\"\n", app.remain_on_exit_format));
                     output.push_str(&format!("destroy-unattached {}\n", if app.destroy_unattached { "on" } else { "off" }));
                     output.push_str(&format!("exit-empty {}\n", if app.exit_empty { "on" } else { "off" }));
                     output.push_str(&format!("set-titles {}\n", if app.set_titles { "on" } else { "off" }));
@@ -2213,6 +2161,18 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     if !app.mode_style.is_empty() {
                         output.push_str(&format!("mode-style \"{}\"\n", app.mode_style));
                     }
+                    output.push_str(&format!("copy-mode-match-style \"{}\"\n", app.copy_mode_match_style));
+                    output.push_str(&format!("copy-mode-current-match-style \"{}\"\n", app.copy_mode_current_match_style));
+                    output.push_str(&format!("copy-mode-position-format \"{}\"\n", app.copy_mode_position_format));
+                    output.push_str(&format!("pane-scrollbars {}\n", app.pane_scrollbars));
+                    output.push_str(&format!("pane-scrollbars-style \"{}\"\n", app.pane_scrollbars_style));
+                    output.push_str(&format!("popup-style \"{}\"\n", app.popup_style));
+                    output.push_str(&format!("popup-border-style \"{}\"\n", app.popup_border_style));
+                    output.push_str(&format!("popup-border-lines {}\n", app.popup_border_lines));
+                    output.push_str(&format!("menu-style \"{}\"\n", app.menu_style));
+                    output.push_str(&format!("menu-selected-style \"{}\"\n", app.menu_selected_style));
+                    output.push_str(&format!("menu-border-style \"{}\"\n", app.menu_border_style));
+                    output.push_str(&format!("menu-border-lines {}\n", app.menu_border_lines));
                     // Include @user-options (used by plugins)
                     for (key, val) in &app.user_options {
                         output.push_str(&format!("{} \"{}\"\n", key, val));
@@ -2225,6 +2185,13 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     output.push_str(&format!("window-size {}\n", app.window_size));
                     output.push_str(&format!("allow-passthrough {}\n", app.allow_passthrough));
                     output.push_str(&format!("set-clipboard {}\n", app.set_clipboard));
+                    output.push_str(&format!("buffer-limit {}\n", app.buffer_limit));
+                    output.push_str(&format!("allow-clipboard-read {}\n", if app.allow_clipboard_read { "on" } else { "off" }));
+                    output.push_str(&format!("hint-alphabet \"{}\"\n", app.hint_alphabet));
+                    output.push_str(&format!("hint-style \"{}\"\n", app.hint_style));
+                    for (name, regex) in &app.hint_patterns {
+                        output.push_str(&format!("hint-patterns \"{}={}\"\n", name, regex));
+                    }
                     if !app.copy_command.is_empty() {
                         output.push_str(&format!("copy-command \"{}\"\n", app.copy_command));
                     }
@@ -2400,7 +2367,12 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     ));
                     let _ = resp.send(output);
                 }
-                CtrlReq::SwitchClient(_target) => {}
+                CtrlReq::SwitchClient(target) => {
+                    crate::commands::switch_client(&mut app, &target);
+                    resize_all_panes(&mut app);
+                    state_dirty = true;
+                    meta_dirty = true;
+                }
                 CtrlReq::SwitchClientTable(table) => {
                     app.current_key_table = Some(table);
                     state_dirty = true;
@@ -2420,21 +2392,18 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     let win = &mut app.windows[app.active_idx];
                     if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) {
                         if let Ok(mut parser) = p.term.lock() {
-                            *parser = vt100::Parser::new(p.last_rows, p.last_cols, app.history_limit);
+                            *parser = crate::pane::new_parser(p.last_rows, p.last_cols, app.history_limit, app.ambiguous_width, app.variation_selector_always_wide);
                         }
                     }
                 }
-                CtrlReq::SaveBuffer(path) => {
-                    if let Some(content) = app.paste_buffers.first() {
-                        let _ = std::fs::write(&path, content);
+                CtrlReq::SaveBuffer(path, name) => {
+                    if let Some(buf) = crate::buffers::get(&app, name.as_deref()) {
+                        let _ = std::fs::write(&path, &buf.data);
                     }
                 }
-                CtrlReq::LoadBuffer(path) => {
+                CtrlReq::LoadBuffer(path, name) => {
                     if let Ok(content) = std::fs::read_to_string(&path) {
-                        app.paste_buffers.insert(0, content);
-                        if app.paste_buffers.len() > 10 {
-                            app.paste_buffers.pop();
-                        }
+                        crate::buffers::set_buffer(&mut app, name.as_deref(), &content, false);
                     }
                 }
                 CtrlReq::SetEnvironment(key, value) => {
@@ -2541,72 +2510,20 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                         }
                     }
                 }
-                CtrlReq::DisplayMenu(menu_def, x, y) => {
-                    let menu = parse_menu_definition(&menu_def, x, y);
-                    if !menu.items.is_empty() {
-                        app.mode = Mode::MenuMode { menu };
-                        state_dirty = true;
-                    }
+                CtrlReq::DisplayMenu(args) => {
+                    crate::menu::open(&mut app, args);
+                    state_dirty = true;
                 }
-                CtrlReq::DisplayMenuDirect(menu) => {
-                    if !menu.items.is_empty() {
-                        app.mode = Mode::MenuMode { menu };
-                        state_dirty = true;
-                    }
+                CtrlReq::DisplayPopup(args) => {
+                    crate::popup::open(&mut app, args);
+                    state_dirty = true;
                 }
-                CtrlReq::DisplayPopup(command, width, height, close_on_exit) => {
-                    if !command.is_empty() {
-                        // Try to spawn with PTY for interactive programs (fzf, etc.)
-                        let pty_result = Some(portable_pty::native_pty_system())
-                            .and_then(|pty_sys| {
-                                let pty_size = portable_pty::PtySize { rows: height.saturating_sub(2), cols: width.saturating_sub(2), pixel_width: 0, pixel_height: 0 };
-                                let pair = pty_sys.openpty(pty_size).ok()?;
-                                let mut cmd_builder = portable_pty::CommandBuilder::new(if cfg!(windows) { "pwsh" } else { "sh" });
-                                if let Ok(dir) = std::env::current_dir() { cmd_builder.cwd(dir); }
-                                if cfg!(windows) { cmd_builder.args(["-NoProfile", "-Command", &command]); } else { cmd_builder.args(["-c", &command]); }
-                                let child = pair.slave.spawn_command(cmd_builder).ok()?;
-                                // Close the slave handle immediately – required for ConPTY.
-                                drop(pair.slave);
-                                let term = std::sync::Arc::new(std::sync::Mutex::new(vt100::Parser::new(pty_size.rows, pty_size.cols, 0)));
-                                let term_reader = term.clone();
-                                if let Ok(mut reader) = pair.master.try_clone_reader() {
-                                    std::thread::spawn(move || {
-                                        let mut buf = [0u8; 8192];
-                                        loop {
-                                            match reader.read(&mut buf) {
-                                                Ok(n) if n > 0 => { if let Ok(mut p) = term_reader.lock() { p.process(&buf[..n]); } }
-                                                _ => break,
-                                            }
-                                        }
-                                    });
-                                }
-                                let mut pty_writer = pair.master.take_writer().ok()?;
-                                crate::pane::conpty_preemptive_dsr_response(&mut *pty_writer);
-                                Some(PopupPty { master: pair.master, writer: pty_writer, child, term })
-                            });
-                        
-                        app.mode = Mode::PopupMode {
-                            command: command.clone(),
-                            output: String::new(),
-                            process: None,
-                            width,
-                            height,
-                            close_on_exit,
-                            popup_pty: pty_result,
-                        };
-                        state_dirty = true;
-                    } else {
-                        app.mode = Mode::PopupMode {
-                            command: String::new(),
-                            output: "Press 'q' or Escape to close\n".to_string(),
-                            process: None,
-                            width,
-                            height,
-                            close_on_exit: true,
-                            popup_pty: None,
-                        };
-                        state_dirty = true;
+                CtrlReq::PopupCommand(argv) => {
+                    let argv: Vec<&str> = argv.iter().map(|s| s.as_str()).collect();
+                    if let Err(e) = crate::popup::run(&mut app, &argv) {
+                        app.status_message = Some((e, Instant::now()));
                     }
+                    state_dirty = true;
                 }
                 CtrlReq::ConfirmBefore(prompt, cmd) => {
                     let prompt_text = if prompt.is_empty() {
@@ -2650,14 +2567,22 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                 CtrlReq::ShowWindowOptions(resp) => {
                     let _ = resp.send(render_window_options(&app));
                 }
-                CtrlReq::ChooseBuffer(resp) => {
-                    let mut output = String::new();
-                    for (i, buf) in app.paste_buffers.iter().enumerate() {
-                        let preview: String = buf.chars().take(50).collect();
-                        let preview = preview.replace('\n', "\\n").replace('\r', "");
-                        output.push_str(&format!("buffer{}: {} bytes: \"{}\"\n", i, buf.len(), preview));
-                    }
-                    let _ = resp.send(output);
+                CtrlReq::ChooseTree(args) => {
+                    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+                    crate::choose_tree::open(&mut app, &args);
+                    state_dirty = true;
+                }
+                CtrlReq::ListSessionsFormat(resp, fmt) => {
+                    let _ = resp.send(crate::format::format_list_session(&app, &fmt));
+                }
+                CtrlReq::ChooseBuffer(args) => {
+                    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+                    crate::buffers::open_chooser(&mut app, &args);
+                }
+                CtrlReq::HintMode(args) => {
+                    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+                    crate::hints::open(&mut app, &args);
+                    state_dirty = true;
                 }
                 CtrlReq::ServerInfo(resp) => {
                     let info = format!(
@@ -2698,34 +2623,23 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     state_dirty = true;
                 }
                 CtrlReq::FocusIn => {
+                    client_focused = true;
                     if app.focus_events {
-                        // Forward focus-in escape sequence to all panes in active window
-                        let win = &mut app.windows[app.active_idx];
-                        fn send_focus_seq(node: &mut Node, seq: &[u8]) {
-                            match node {
-                                Node::Leaf(p) => { let _ = p.writer.write_all(seq); let _ = p.writer.flush(); }
-                                Node::Split { children, .. } => { for c in children { send_focus_seq(c, seq); } }
-                            }
-                        }
-                        send_focus_seq(&mut win.root, b"\x1b[I");
+                        // Only the focused pane hears about the client gaining focus
+                        if let Some(id) = focused_pane_id(&app) { send_focus_seq(&mut app, id, b"\x1b[I"); }
                     }
                     hook_event = Some("pane-focus-in");
                 }
                 CtrlReq::FocusOut => {
+                    client_focused = false;
                     if app.focus_events {
-                        let win = &mut app.windows[app.active_idx];
-                        fn send_focus_seq(node: &mut Node, seq: &[u8]) {
-                            match node {
-                                Node::Leaf(p) => { let _ = p.writer.write_all(seq); let _ = p.writer.flush(); }
-                                Node::Split { children, .. } => { for c in children { send_focus_seq(c, seq); } }
-                            }
-                        }
-                        send_focus_seq(&mut win.root, b"\x1b[O");
+                        if let Some(id) = focused_pane_id(&app) { send_focus_seq(&mut app, id, b"\x1b[O"); }
                     }
                     hook_event = Some("pane-focus-out");
                 }
-                CtrlReq::CommandPrompt(initial) => {
-                    app.mode = Mode::CommandPrompt { input: initial.clone(), cursor: initial.len() };
+                CtrlReq::CommandPrompt(args) => {
+                    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+                    crate::prompt::open(&mut app, &args);
                     state_dirty = true;
                 }
                 CtrlReq::ShowMessages(resp) => {
@@ -2742,15 +2656,14 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     state_dirty = true;
                 }
                 CtrlReq::PopupInput(data) => {
-                    if let Mode::PopupMode { ref mut popup_pty, .. } = app.mode {
-                        if let Some(ref mut pty) = popup_pty {
+                    if let Mode::PopupMode { ref mut popup } = app.mode {
+                        if popup.term().is_some() {
                             // If child has exited, 'q' closes the popup
-                            let child_exited = matches!(pty.child.try_wait(), Ok(Some(_)));
+                            let child_exited = popup.exit_status().is_some();
                             if child_exited && data == b"q" {
                                 app.mode = Mode::Passthrough;
                             } else if !child_exited {
-                                let _ = pty.writer.write_all(&data);
-                                let _ = pty.writer.flush();
+                                popup.input(&data);
                             }
                         } else {
                             // No PTY means static popup — 'q' closes it
@@ -2762,7 +2675,17 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     state_dirty = true;
                 }
                 CtrlReq::OverlayClose => {
+                    // A named popup is a terminal the user toggles away with
+                    // its key, so Escape belongs to the program in it
+                    let mut escape_to_popup = false;
+                    if let Mode::PopupMode { ref mut popup } = app.mode {
+                        if popup.name.is_some() && popup.exit_status().is_none() && popup.term().is_some() {
+                            popup.input(b"\x1b");
+                            escape_to_popup = true;
+                        }
+                    }
                     match app.mode {
+                        _ if escape_to_popup => {}
                         Mode::PopupMode { .. } | Mode::MenuMode { .. } | Mode::ConfirmMode { .. } | Mode::PaneChooser { .. } | Mode::ClockMode => {
                             app.mode = Mode::Passthrough;
                             state_dirty = true;
@@ -2781,42 +2704,13 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                     }
                 }
                 CtrlReq::MenuSelect(idx) => {
-                    if let Mode::MenuMode { ref menu } = app.mode {
-                        if let Some(item) = menu.items.get(idx) {
-                            if !item.is_separator && !item.command.is_empty() {
-                                let cmd = item.command.clone();
-                                app.mode = Mode::Passthrough;
-                                let _ = execute_command_string(&mut app, &cmd);
-                                state_dirty = true;
-                            }
-                        }
-                    }
+                    crate::menu::choose(&mut app, idx);
+                    state_dirty = true;
                 }
                 CtrlReq::MenuNavigate(delta) => {
                     if let Mode::MenuMode { ref mut menu } = app.mode {
-                        let len = menu.items.len();
-                        if len > 0 {
-                            if delta > 0 {
-                                // Move down, skipping separators
-                                let mut next = (menu.selected + 1) % len;
-                                let start = next;
-                                while menu.items[next].is_separator {
-                                    next = (next + 1) % len;
-                                    if next == start { break; }
-                                }
-                                menu.selected = next;
-                            } else {
-                                // Move up, skipping separators
-                                let mut next = if menu.selected == 0 { len - 1 } else { menu.selected - 1 };
-                                let start = next;
-                                while menu.items[next].is_separator {
-                                    next = if next == 0 { len - 1 } else { next - 1 };
-                                    if next == start { break; }
-                                }
-                                menu.selected = next;
-                            }
-                            state_dirty = true;
-                        }
+                        crate::menu::navigate(menu, delta);
+                        state_dirty = true;
                     }
                 }
             }
@@ -2870,6 +2764,12 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
                         }
                     }
                 }
+                // Report a pane switch to the panes losing and gaining focus
+                let focus_after = focused_pane_id(&app);
+                if app.focus_events && client_focused && focus_after != focus_before {
+                    if let Some(id) = focus_before { send_focus_seq(&mut app, id, b"\x1b[O"); }
+                    if let Some(id) = focus_after { send_focus_seq(&mut app, id, b"\x1b[I"); }
+                }
             }
         }
         // ── Server-push: proactively send frames to attached clients ──
@@ -2971,18 +2871,30 @@ pub fn run_server(session_name: String, socket_name: Option<String>, initial_com
         }
         // ── Popup child exit detection ──
         // Check if popup PTY's child process has exited; if so, auto-close.
-        if let Mode::PopupMode { ref mut popup_pty, close_on_exit, .. } = app.mode {
-            let should_close = if let Some(ref mut pty) = popup_pty {
-                matches!(pty.child.try_wait(), Ok(Some(_)))
-            } else { false };
-            if should_close && close_on_exit {
+        if let Mode::PopupMode { ref mut popup } = app.mode {
+            if popup.exit_status().is_some_and(|ok| popup.close.closes(ok)) {
                 app.mode = Mode::Passthrough;
                 state_dirty = true;
             }
         }
+        if crate::popup::reap_floats(&mut app) {
+            state_dirty = true;
+        }
+        if crate::buffers::finish_edit(&mut app) {
+            state_dirty = true;
+        }
+        if crate::choose_tree::tick(&mut app) {
+            state_dirty = true;
+        }
         // Check if all windows/panes have exited (throttled to every 250ms)
         if last_reap.elapsed() >= Duration::from_millis(100) {
             last_reap = Instant::now();
+            if crate::copy_mode::drain_pane_clipboard(&mut app) {
+                state_dirty = true;
+            }
+            if crate::record::check_recordings(&mut app) {
+                state_dirty = true;
+            }
             let (all_empty, any_pruned) = tree::reap_children(&mut app)?;
             if any_pruned {
                 // A pane exited naturally - resize remaining panes to fill the space
@@ -43,11 +43,15 @@ pub(crate) fn get_option_value(app: &AppState, name: &str) -> String {
         "display-panes-time" => app.display_panes_time_ms.to_string(),
         "mode-keys" => app.mode_keys.clone(),
         "focus-events" => if app.focus_events { "on".into() } else { "off".into() },
+        "ambiguous-width" => app.ambiguous_width.to_string(),
+        "variation-selector-always-wide" => if app.variation_selector_always_wide { "on".into() } else { "off".into() },
+        "wheel-scroll-lines" => app.wheel_scroll_lines.to_string(),
         "renumber-windows" => if app.renumber_windows { "on".into() } else { "off".into() },
         "automatic-rename" => if app.automatic_rename { "on".into() } else { "off".into() },
         "monitor-activity" => if app.monitor_activity { "on".into() } else { "off".into() },
         "synchronize-panes" => if app.sync_input { "on".into() } else { "off".into() },
         "remain-on-exit" => if app.remain_on_exit { "on".into() } else { "off".into() },
+        "remain-on-exit-format" => app.remain_on_exit_format.clone(),
         "destroy-unattached" => if app.destroy_unattached { "on".into() } else { "off".into() },
         "exit-empty" => if app.exit_empty { "on".into() } else { "off".into() },
         "set-titles" => if app.set_titles { "on".into() } else { "off".into() },
@@ -71,6 +75,18 @@ pub(crate) fn get_option_value(app: &AppState, name: &str) -> String {
         "message-style" => app.message_style.clone(),
         "message-command-style" => app.message_command_style.clone(),
         "mode-style" => app.mode_style.clone(),
+        "copy-mode-match-style" => app.copy_mode_match_style.clone(),
+        "copy-mode-current-match-style" => app.copy_mode_current_match_style.clone(),
+        "copy-mode-position-format" => app.copy_mode_position_format.clone(),
+        "pane-scrollbars" => app.pane_scrollbars.clone(),
+        "pane-scrollbars-style" => app.pane_scrollbars_style.clone(),
+        "popup-style" => app.popup_style.clone(),
+        "popup-border-style" => app.popup_border_style.clone(),
+        "popup-border-lines" => app.popup_border_lines.clone(),
+        "menu-style" => app.menu_style.clone(),
+        "menu-selected-style" => app.menu_selected_style.clone(),
+        "menu-border-style" => app.menu_border_style.clone(),
+        "menu-border-lines" => app.menu_border_lines.clone(),
         "status-left-style" => app.status_left_style.clone(),
         "status-right-style" => app.status_right_style.clone(),
         "status-interval" => app.status_interval.to_string(),
@@ -84,6 +100,18 @@ pub(crate) fn get_option_value(app: &AppState, name: &str) -> String {
         "allow-passthrough" => app.allow_passthrough.clone(),
         "copy-command" => app.copy_command.clone(),
         "set-clipboard" => app.set_clipboard.clone(),
+        "buffer-limit" => app.buffer_limit.to_string(),
+        "history-file" => app.history_file.clone(),
+        "prompt-history-limit" => app.prompt_history_limit.to_string(),
+        "allow-clipboard-read" => if app.allow_clipboard_read { "on".into() } else { "off".into() },
+        "hint-alphabet" => app.hint_alphabet.clone(),
+        "hint-patterns" => {
+            app.hint_patterns.iter()
+                .map(|(n, r)| format!("{}={}", n, r))
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+        "hint-style" => app.hint_style.clone(),
         "main-pane-width" => app.main_pane_width.to_string(),
         "main-pane-height" => app.main_pane_height.to_string(),
         "command-alias" => {
@@ -240,8 +268,24 @@ pub(crate) fn apply_set_option(app: &mut AppState, option: &str, value: &str, qu
             };
         }
         "focus-events" => { app.focus_events = matches!(value, "on" | "true" | "1"); }
+        "ambiguous-width" => {
+            if let Ok(n @ 1..=2) = value.parse::<u8>() {
+                app.ambiguous_width = n;
+                crate::pane::refresh_width_options(app);
+            }
+        }
+        "variation-selector-always-wide" => {
+            app.variation_selector_always_wide = matches!(value, "on" | "true" | "1");
+            crate::pane::refresh_width_options(app);
+        }
+        "wheel-scroll-lines" => {
+            if let Ok(n) = value.parse::<u16>() {
+                app.wheel_scroll_lines = n.max(1);
+            }
+        }
         "renumber-windows" => { app.renumber_windows = matches!(value, "on" | "true" | "1"); }
         "remain-on-exit" => { app.remain_on_exit = matches!(value, "on" | "true" | "1"); }
+        "remain-on-exit-format" => { app.remain_on_exit_format = value.to_string(); }
         "destroy-unattached" => { app.destroy_unattached = matches!(value, "on" | "true" | "1"); }
         "exit-empty" => { app.exit_empty = matches!(value, "on" | "true" | "1"); }
         "set-titles" => { app.set_titles = matches!(value, "on" | "true" | "1"); }
@@ -291,6 +335,23 @@ pub(crate) fn apply_set_option(app: &mut AppState, option: &str, value: &str, qu
         "window-status-bell-style" => { app.window_status_bell_style = value.to_string(); }
         "window-status-last-style" => { app.window_status_last_style = value.to_string(); }
         "mode-style" => { app.mode_style = value.to_string(); }
+        "copy-mode-match-style" => { app.copy_mode_match_style = value.to_string(); }
+        "copy-mode-current-match-style" => { app.copy_mode_current_match_style = value.to_string(); }
+        "copy-mode-position-format" => { app.copy_mode_position_format = value.to_string(); }
+        "popup-style" => { app.popup_style = value.to_string(); }
+        "popup-border-style" => { app.popup_border_style = value.to_string(); }
+        "popup-border-lines" => { app.popup_border_lines = value.to_string(); }
+        "menu-style" => { app.menu_style = value.to_string(); }
+        "menu-selected-style" => { app.menu_selected_style = value.to_string(); }
+        "menu-border-style" => { app.menu_border_style = value.to_string(); }
+        "menu-border-lines" => { app.menu_border_lines = value.to_string(); }
+        "pane-scrollbars" => {
+            if matches!(value, "off" | "modal" | "on") {
+                app.pane_scrollbars = value.to_string();
+                crate::tree::resize_all_panes(app);
+            }
+        }
+        "pane-scrollbars-style" => { app.pane_scrollbars_style = value.to_string(); }
         "message-style" => { app.message_style = value.to_string(); }
         "message-command-style" => { app.message_command_style = value.to_string(); }
         "status-left-style" => { app.status_left_style = value.to_string(); }
@@ -309,6 +370,23 @@ pub(crate) fn apply_set_option(app: &mut AppState, option: &str, value: &str, qu
         "allow-passthrough" => { app.allow_passthrough = value.to_string(); }
         "copy-command" => { app.copy_command = value.to_string(); }
         "set-clipboard" => { app.set_clipboard = value.to_string(); }
+        "buffer-limit" => {
+            if let Ok(n) = value.parse::<usize>() {
+                app.buffer_limit = n.max(1);
+                crate::buffers::enforce_limit(app);
+            }
+        }
+        "history-file" => {
+            app.history_file = value.to_string();
+            crate::prompt::load_history(app);
+        }
+        "prompt-history-limit" => {
+            if let Ok(n) = value.parse::<usize>() { app.prompt_history_limit = n; }
+        }
+        "allow-clipboard-read" => { app.allow_clipboard_read = matches!(value, "on" | "true" | "1"); }
+        "hint-alphabet" => { app.hint_alphabet = value.to_string(); }
+        "hint-patterns" => { crate::hints::set_pattern(app, value); }
+        "hint-style" => { app.hint_style = value.to_string(); }
         "command-alias" => {
             // Format: "alias=expansion" e.g. "splitp=split-window"
             if let Some(pos) = value.find('=') {
@@ -250,117 +250,6 @@ pub fn resolve_default_session_name() -> Option<String> {
 
 pub fn reap_children_placeholder() -> io::Result<bool> { Ok(false) }
 
-/// A tree entry used by choose-tree: either a session header or a window under a session.
-#[derive(Clone, Debug)]
-pub struct TreeEntry {
-    pub session_name: String,
-    pub session_port: u16,
-    pub is_session_header: bool,
-    pub window_index: Option<usize>,
-    pub window_name: String,
-    pub window_panes: usize,
-    pub window_size: String,
-    pub is_current_session: bool,
-    pub is_active_window: bool,
-}
-
-/// List all running sessions and their windows for choose-tree display.
-/// Queries each running server via its TCP port for window list info.
-pub fn list_all_sessions_tree(current_session: &str, current_windows: &[(String, usize, String, bool)]) -> Vec<TreeEntry> {
-    let home = match env::var("USERPROFILE").or_else(|_| env::var("HOME")) {
-        Ok(h) => h,
-        Err(_) => return vec![],
-    };
-    let psmux_dir = format!("{}\\.psmux", home);
-    let mut sessions: Vec<(String, u16, std::time::SystemTime)> = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(&psmux_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().map(|e| e == "port").unwrap_or(false) {
-                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    // Hide warm (standby) sessions from choose-tree
-                    if is_warm_session(stem) { continue; }
-                    if let Ok(port_str) = std::fs::read_to_string(&path) {
-                        if let Ok(port) = port_str.trim().parse::<u16>() {
-                            let mtime = entry.metadata()
-                                .and_then(|m| m.modified())
-                                .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-                            sessions.push((stem.to_string(), port, mtime));
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    sessions.sort_by_key(|(name, _, _)| name.clone());
-
-    let mut tree = Vec::new();
-    for (name, port, _) in &sessions {
-        let is_current = name == current_session;
-        // Session header
-        tree.push(TreeEntry {
-            session_name: name.clone(),
-            session_port: *port,
-            is_session_header: true,
-            window_index: None,
-            window_name: String::new(),
-            window_panes: 0,
-            window_size: String::new(),
-            is_current_session: is_current,
-            is_active_window: false,
-        });
-
-        if is_current {
-            // Use local data for the current session (fast, no IPC)
-            for (i, (wname, panes, size, is_active)) in current_windows.iter().enumerate() {
-                tree.push(TreeEntry {
-                    session_name: name.clone(),
-                    session_port: *port,
-                    is_session_header: false,
-                    window_index: Some(i),
-                    window_name: wname.clone(),
-                    window_panes: *panes,
-                    window_size: size.clone(),
-                    is_current_session: true,
-                    is_active_window: *is_active,
-                });
-            }
-        } else {
-            // Query remote session for its window list
-            let key = read_session_key(name).unwrap_or_default();
-            let addr = format!("127.0.0.1:{}", port);
-            if let Ok(resp) = send_auth_cmd_response(&addr, &key, b"list-windows -F \"#{window_index}:#{window_name}:#{window_panes}:#{window_width}x#{window_height}:#{window_active}\"\n") {
-                for line in resp.lines() {
-                    let line = line.trim();
-                    if line.is_empty() { continue; }
-                    let parts: Vec<&str> = line.splitn(5, ':').collect();
-                    if parts.len() >= 5 {
-                        let wi = parts[0].parse::<usize>().unwrap_or(0);
-                        let wn = parts[1].to_string();
-                        let wp = parts[2].parse::<usize>().unwrap_or(1);
-                        let ws = parts[3].to_string();
-                        let wa = parts[4] == "1";
-                        tree.push(TreeEntry {
-                            session_name: name.clone(),
-                            session_port: *port,
-                            is_session_header: false,
-                            window_index: Some(wi),
-                            window_name: wn,
-                            window_panes: wp,
-                            window_size: ws,
-                            is_current_session: false,
-                            is_active_window: wa,
-                        });
-                    }
-                }
-            }
-        }
-    }
-    tree
-}
-
 /// Force-kill any remaining psmux/pmux/tmux server processes that didn't
 /// exit via the TCP kill-server command.  This is the nuclear fallback that
 /// guarantees kill-server always succeeds.
@@ -0,0 +1,403 @@
+//! A popup showing another session: `display-popup -t NAME[:WINDOW[.PANE]]`.
+//!
+//! Sessions are separate servers, so instead of nesting a whole client in
+//! the popup's PTY (`display-popup -E "psmux attach -t NAME"`, with its own
+//! status line and prefix key) the popup attaches to the other server the
+//! way a client does.  Its window is sized to the popup, its frames are
+//! drawn into a parser like the one behind a PTY popup, and keys typed in
+//! the popup are sent to it as `send-key`/`send-text`.  A session that does
+//! not exist yet is created detached first.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ratatui::layout::Rect;
+
+use crate::layout::{collect_leaf_rects_json, CellRunJson, LayoutJson};
+use crate::pane::PaneParser;
+use crate::types::{AppState, ParsedTarget};
+
+enum Msg {
+    Input(Vec<u8>),
+    Resize(u16, u16),
+}
+
+/// The connection behind a session popup.  Dropping it detaches.
+pub struct SessionView {
+    pub session: String,
+    pub term: Arc<Mutex<PaneParser>>,
+    pub data_version: Arc<AtomicU64>,
+    tx: mpsc::Sender<Msg>,
+    ended: Arc<AtomicBool>,
+}
+
+impl SessionView {
+    /// Attach to the session named in `target` with a window of `rows` by
+    /// `cols`, creating the session if needed.  Connecting happens in the
+    /// background; errors are shown in the popup.
+    pub fn start(app: &AppState, target: &ParsedTarget, rows: u16, cols: u16) -> Self {
+        let session = target.session.clone().unwrap_or_default();
+        let base = match app.socket_name.as_deref() {
+            Some(sn) => format!("{}__{}", sn, session),
+            None => session.clone(),
+        };
+        let term = Arc::new(Mutex::new(crate::pane::new_parser(rows, cols, 0, app.ambiguous_width, app.variation_selector_always_wide)));
+        let data_version = Arc::new(AtomicU64::new(0));
+        let ended = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let view = SessionView { session: session.clone(), term: term.clone(), data_version: data_version.clone(), tx, ended: ended.clone() };
+        let select = select_commands(target);
+        let socket_name = app.socket_name.clone();
+        std::thread::spawn(move || {
+            let conn = connect(&base, &session, socket_name.as_deref(), rows, cols);
+            let (mut writer, reader) = match conn {
+                Ok(c) => c,
+                Err(e) => {
+                    show(&term, &data_version, format!("\x1b[H\x1b[2J{}", e).as_bytes());
+                    ended.store(true, Ordering::Release);
+                    return;
+                }
+            };
+            let mut setup = format!("PERSISTENT\nclient-attach\nclient-size {} {}\n", cols, rows);
+            for cmd in select { setup.push_str(&cmd); setup.push('\n'); }
+            setup.push_str("dump-state\n");
+            if writer.write_all(setup.as_bytes()).is_err() { return; }
+            let _ = writer.flush();
+            spawn_reader(reader, term, data_version, ended);
+            // The server pushes a frame whenever something changes; asking
+            // after input only makes the echo show up sooner.
+            while let Ok(msg) = rx.recv() {
+                let mut batch = String::new();
+                match msg {
+                    Msg::Input(bytes) => {
+                        for cmd in key_commands(&bytes) { batch.push_str(&cmd); batch.push('\n'); }
+                    }
+                    Msg::Resize(rows, cols) => batch.push_str(&format!("client-size {} {}\n", cols, rows)),
+                }
+                batch.push_str("dump-state\n");
+                if writer.write_all(batch.as_bytes()).is_err() || writer.flush().is_err() { break; }
+            }
+            let _ = writer.shutdown(std::net::Shutdown::Both);
+        });
+        view
+    }
+
+    pub fn input(&self, bytes: &[u8]) {
+        let _ = self.tx.send(Msg::Input(bytes.to_vec()));
+    }
+
+    pub fn resize(&self, rows: u16, cols: u16) {
+        if let Ok(mut parser) = self.term.lock() {
+            if parser.screen().size() != (rows, cols) { parser.screen_mut().set_size(rows, cols); }
+        }
+        let _ = self.tx.send(Msg::Resize(rows, cols));
+    }
+
+    /// True once the other session has gone away (or could not be reached).
+    pub fn ended(&self) -> bool {
+        self.ended.load(Ordering::Acquire)
+    }
+}
+
+/// Put the target's window and pane in front, like `attach -t` does.
+pub fn select_commands(t: &ParsedTarget) -> Vec<String> {
+    let mut out = Vec::new();
+    match t.window {
+        Some(w) if t.window_is_id => out.push(format!("select-window -t @{}", w)),
+        Some(w) => out.push(format!("select-window -t :{}", w)),
+        None => {}
+    }
+    match t.pane {
+        Some(p) if t.pane_is_id => out.push(format!("select-pane -t %{}", p)),
+        Some(p) => out.push(format!("select-pane -t :.{}", p)),
+        None => {}
+    }
+    out
+}
+
+/// Open an authenticated connection to the server of `base`, starting the
+/// session first if it has no server.
+fn connect(base: &str, name: &str, socket_name: Option<&str>, rows: u16, cols: u16) -> io::Result<(TcpStream, BufReader<TcpStream>)> {
+    let home = std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")).unwrap_or_default();
+    let port_path = format!("{}\\.psmux\\{}.port", home, base);
+    let read_port = || std::fs::read_to_string(&port_path).ok().and_then(|s| s.trim().parse::<u16>().ok());
+    let mut port = read_port();
+    if port.is_none() {
+        start_server(name, socket_name, rows, cols);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while port.is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            port = read_port().filter(|_| crate::session::read_session_key(base).is_ok());
+        }
+    }
+    let port = port.ok_or_else(|| io::Error::new(io::ErrorKind::Other, format!("can't create session '{}'", name)))?;
+    let key = crate::session::read_session_key(base).unwrap_or_default();
+    let stream = TcpStream::connect(("127.0.0.1", port))?;
+    stream.set_nodelay(true)?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    writer.write_all(format!("AUTH {}\n", key).as_bytes())?;
+    writer.flush()?;
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if !line.trim().starts_with("OK") {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("can't attach to session '{}'", name)));
+    }
+    Ok((writer, reader))
+}
+
+/// `new-session -d -s NAME`, sized like the popup.
+fn start_server(name: &str, socket_name: Option<&str>, rows: u16, cols: u16) {
+    let exe = std::env::current_exe().unwrap_or_else(|_| std::path::PathBuf::from("psmux"));
+    let mut args: Vec<String> = vec!["server".into(), "-s".into(), name.into()];
+    if let Some(sn) = socket_name {
+        args.push("-L".into());
+        args.push(sn.into());
+    }
+    args.extend(["-x".into(), cols.to_string(), "-y".into(), rows.to_string()]);
+    #[cfg(windows)]
+    { let _ = crate::platform::spawn_server_hidden(&exe, &args); }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = std::process::Command::new(&exe);
+        cmd.args(&args);
+        cmd.stdin(std::process::Stdio::null());
+        cmd.stdout(std::process::Stdio::null());
+        cmd.stderr(std::process::Stdio::null());
+        let _ = cmd.spawn();
+    }
+}
+
+fn show(term: &Mutex<PaneParser>, data_version: &AtomicU64, bytes: &[u8]) {
+    if let Ok(mut parser) = term.lock() { parser.process(bytes); }
+    data_version.fetch_add(1, Ordering::Release);
+    crate::types::PTY_DATA_READY.store(true, Ordering::Release);
+}
+
+/// Draw every frame the server sends until it disconnects.
+fn spawn_reader(mut reader: BufReader<TcpStream>, term: Arc<Mutex<PaneParser>>, data_version: Arc<AtomicU64>, ended: Arc<AtomicBool>) {
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match reader.read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            if !line.starts_with('{') { continue; }
+            let Ok(mut frame) = serde_json::from_str::<serde_json::Value>(&line) else { continue };
+            let Ok(layout) = serde_json::from_value::<LayoutJson>(frame["layout"].take()) else { continue };
+            let size = term.lock().map(|p| p.screen().size()).unwrap_or((0, 0));
+            show(&term, &data_version, render(&layout, size.0, size.1).as_bytes());
+        }
+        ended.store(true, Ordering::Release);
+        crate::types::PTY_DATA_READY.store(true, Ordering::Release);
+    });
+}
+
+/// The escape sequences that paint `layout` on a screen of `rows` by
+/// `cols`: each pane's rows, with a line in the gap before it, and the
+/// cursor left in the active pane.
+fn render(layout: &LayoutJson, rows: u16, cols: u16) -> String {
+    let mut leaves = Vec::new();
+    collect_leaf_rects_json(layout, Rect { x: 0, y: 0, width: cols, height: rows }, &mut leaves);
+    let mut out = String::from("\x1b[?25l\x1b[0m\x1b[H\x1b[2J");
+    let mut cursor = None;
+    for (r, leaf) in leaves {
+        let LayoutJson::Leaf { rows_v2, cursor_row, cursor_col, active, hide_cursor, .. } = leaf else { continue };
+        if r.y > 0 {
+            out.push_str(&format!("\x1b[{};{}H{}", r.y, r.x + 1, "─".repeat(r.width as usize)));
+        }
+        if r.x > 0 {
+            for y in r.y..r.y + r.height { out.push_str(&format!("\x1b[{};{}H│", y + 1, r.x)); }
+        }
+        for (y, row) in (r.y..r.y + r.height).zip(rows_v2) {
+            out.push_str(&format!("\x1b[{};{}H", y + 1, r.x + 1));
+            let mut used = 0;
+            for run in &row.runs {
+                if used + run.width > r.width { break; }
+                out.push_str(&sgr(run));
+                out.push_str(&run.text);
+                used += run.width;
+            }
+            out.push_str("\x1b[0m");
+        }
+        if *active && !*hide_cursor {
+            cursor = Some((r.y + *cursor_row, r.x + *cursor_col));
+        }
+    }
+    if let Some((y, x)) = cursor {
+        out.push_str(&format!("\x1b[{};{}H\x1b[?25h", y + 1, x + 1));
+    }
+    out
+}
+
+/// SGR for a run's colours (`idx:N`, `rgb:R,G,B` or `default`) and flags.
+fn sgr(run: &CellRunJson) -> String {
+    use crate::layout::{FLAG_BLINK, FLAG_BOLD, FLAG_DIM, FLAG_HIDDEN, FLAG_INVERSE, FLAG_ITALIC, FLAG_UNDERLINE};
+    let mut params = vec!["0".to_string()];
+    for (flag, code) in [(FLAG_BOLD, "1"), (FLAG_DIM, "2"), (FLAG_ITALIC, "3"), (FLAG_UNDERLINE, "4"), (FLAG_BLINK, "5"), (FLAG_INVERSE, "7"), (FLAG_HIDDEN, "8")] {
+        if run.flags & flag != 0 { params.push(code.to_string()); }
+    }
+    for (color, base) in [(&run.fg, 38), (&run.bg, 48)] {
+        if let Some(n) = color.strip_prefix("idx:") {
+            params.push(format!("{};5;{}", base, n));
+        } else if let Some(rgb) = color.strip_prefix("rgb:") {
+            params.push(format!("{};2;{}", base, rgb.replace(',', ";")));
+        }
+    }
+    format!("\x1b[{}m", params.join(";"))
+}
+
+/// The key named by the escape sequence at the start of `rest`, and the
+/// number of bytes it takes.  Complete CSI (`ESC [ ... final`) and SS3
+/// (`ESC O x`) sequences are read whole; ones without a key name give
+/// `None` and are dropped rather than typed as text.  Any other character
+/// after ESC is Meta plus that character.
+fn escape_key(rest: &str) -> (Option<String>, usize) {
+    let bytes = rest.as_bytes();
+    match bytes.get(1) {
+        Some(b'[') => {
+            let Some(end) = bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b)) else {
+                return if bytes.len() == 2 { (Some("M-[".to_string()), 2) } else { (None, bytes.len()) };
+            };
+            let params = &rest[2..2 + end];
+            let len = 3 + end;
+            if params.bytes().any(|b| !(b.is_ascii_digit() || b == b';')) { return (None, len); }
+            let mut nums = params.split(';').map(|p| p.parse::<u32>().unwrap_or(1));
+            let first = nums.next().unwrap_or(1);
+            let mods = nums.next().unwrap_or(1);
+            let name = match bytes[2 + end] {
+                b'~' => match first {
+                    1 | 7 => Some("home".to_string()),
+                    2 => Some("insert".to_string()),
+                    3 => Some("delete".to_string()),
+                    4 | 8 => Some("end".to_string()),
+                    5 => Some("pageup".to_string()),
+                    6 => Some("pagedown".to_string()),
+                    11..=15 => Some(format!("F{}", first - 10)),
+                    17..=21 => Some(format!("F{}", first - 11)),
+                    23 | 24 => Some(format!("F{}", first - 12)),
+                    _ => None,
+                },
+                b'Z' => Some("btab".to_string()),
+                c => plain_key(c),
+            };
+            (name.map(|n| with_modifiers(&n, mods)), len)
+        }
+        Some(b'O') => match bytes.get(2) {
+            Some(&c) => (plain_key(c), 3),
+            None => (Some("M-O".to_string()), 2),
+        },
+        _ => match rest[1..].chars().next() {
+            Some(m) if !m.is_control() => (Some(format!("M-{}", m)), 1 + m.len_utf8()),
+            _ => (Some("esc".to_string()), 1),
+        },
+    }
+}
+
+/// Keys named by the final byte of a CSI or SS3 sequence.
+fn plain_key(c: u8) -> Option<String> {
+    let name = match c {
+        b'A' => "up", b'B' => "down", b'C' => "right", b'D' => "left",
+        b'H' => "home", b'F' => "end",
+        b'P' => "F1", b'Q' => "F2", b'R' => "F3", b'S' => "F4",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Prefix a key name with the modifiers of an xterm `;N` parameter.
+fn with_modifiers(name: &str, param: u32) -> String {
+    let bits = param.saturating_sub(1);
+    let mut out = String::new();
+    if bits & 4 != 0 { out.push_str("C-"); }
+    if bits & 2 != 0 { out.push_str("M-"); }
+    if bits & 1 != 0 { out.push_str("S-"); }
+    out.push_str(name);
+    out
+}
+
+/// Translate bytes typed into the popup into the commands a client sends
+/// for them: named keys for control characters and escape sequences,
+/// `send-text` for everything else.
+pub fn key_commands(bytes: &[u8]) -> Vec<String> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut out = Vec::new();
+    let mut pending = String::new();
+    let mut rest: &str = &text;
+    while let Some(c) = rest.chars().next() {
+        let key = if c == '\x1b' {
+            match escape_key(rest) {
+                (Some(name), len) => Some((name, len)),
+                (None, len) => { rest = &rest[len..]; continue; }
+            }
+        } else {
+            let name = match c {
+                '\r' | '\n' => Some("enter".to_string()),
+                '\t' => Some("tab".to_string()),
+                ' ' => Some("space".to_string()),
+                '\x7f' | '\x08' => Some("backspace".to_string()),
+                '\x00' => Some("C-space".to_string()),
+                '\x01'..='\x1a' => Some(format!("C-{}", (c as u8 + b'a' - 1) as char)),
+                _ => None,
+            };
+            name.map(|n| (n, 1))
+        };
+        match key {
+            Some((name, len)) => {
+                flush(&mut pending, &mut out);
+                out.push(format!("send-key {}", name));
+                rest = &rest[len..];
+            }
+            None => {
+                pending.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    flush(&mut pending, &mut out);
+    out
+}
+
+fn flush(pending: &mut String, out: &mut Vec<String>) {
+    if !pending.is_empty() {
+        out.push(format!("send-text \"{}\"", pending.replace('\\', "\\\\").replace('"', "\\\"")));
+        pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_bytes_become_client_commands() {
+        assert_eq!(key_commands(b"ls -l\r"), vec![
+            "send-text \"ls\"", "send-key space", "send-text \"-l\"", "send-key enter",
+        ]);
+        assert_eq!(key_commands(b"\x1b[A\x1b\x1bx\x02\""), vec![
+            "send-key up", "send-key esc", "send-key M-x", "send-key C-b", "send-text \"\\\"\"",
+        ]);
+        assert_eq!(key_commands("é\x7f\x1b[5~".as_bytes()), vec![
+            "send-text \"é\"", "send-key backspace", "send-key pageup",
+        ]);
+        assert_eq!(key_commands(b"\x1bOP\x1b[15~\x1b[24~\x1b[1;5C\x1b[3;2~\x1b[1;3P"), vec![
+            "send-key F1", "send-key F5", "send-key F12", "send-key C-right", "send-key S-delete", "send-key M-F1",
+        ]);
+        // unknown sequences are dropped whole, not typed
+        assert_eq!(key_commands(b"a\x1b[200~b\x1b[?1;2cc\x1bOz"), vec!["send-text \"abc\""]);
+        assert_eq!(key_commands(b"\x1b["), vec!["send-key M-["]);
+    }
+
+    #[test]
+    fn targets_select_their_window_and_pane() {
+        let t = crate::cli::parse_target("scratch:2.1");
+        assert_eq!(t.session.as_deref(), Some("scratch"));
+        assert_eq!(select_commands(&t), vec!["select-window -t :2", "select-pane -t :.1"]);
+        assert!(select_commands(&crate::cli::parse_target("scratch")).is_empty());
+    }
+}
@@ -128,6 +128,39 @@ pub fn parse_tmux_style_components(style: &str) -> (Option<Color>, Option<Color>
     (fg, bg, bold)
 }
 
+/// The border characters for a `popup-border-lines` value; `None` for
+/// `none` (no border at all).
+pub fn border_set(lines: &str) -> Option<symbols::border::Set<'static>> {
+    use symbols::border;
+    Some(match lines {
+        "none" => return None,
+        "rounded" => border::ROUNDED,
+        "double" => border::DOUBLE,
+        "heavy" => border::THICK,
+        "simple" => border::Set {
+            top_left: "+", top_right: "+", bottom_left: "+", bottom_right: "+",
+            vertical_left: "|", vertical_right: "|", horizontal_top: "-", horizontal_bottom: "-",
+        },
+        "padded" => border::EMPTY,
+        _ => border::PLAIN,
+    })
+}
+
+/// The frame of a `display-popup`: `style` fills the popup, `border_style`
+/// colours the border and title.
+pub fn popup_block(title: String, style: &str, border_style: &str, lines: &str) -> ratatui::widgets::Block<'static> {
+    use ratatui::widgets::{Block, Borders};
+    let block = Block::default().style(parse_tmux_style(style));
+    match border_set(lines) {
+        Some(set) => block
+            .borders(Borders::ALL)
+            .border_set(set)
+            .border_style(parse_tmux_style(border_style))
+            .title(title),
+        None => block,
+    }
+}
+
 /// Apply a modifier token (e.g. "bold", "nobold", "italic") to a `Style`.
 fn apply_modifier(token: &str, style: &mut Style) {
     match token {
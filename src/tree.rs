@@ -215,7 +215,7 @@ pub fn resize_all_panes(app: &mut AppState) {
     let area = app.last_window_area;
     if area.width == 0 || area.height == 0 { return; }
     
-    fn resize_node(node: &mut Node, rects: &[(Vec<usize>, Rect)], path: &mut Vec<usize>) {
+    fn resize_node(node: &mut Node, rects: &[(Vec<usize>, Rect)], path: &mut Vec<usize>, scrollbar_cols: u16) {
         match node {
             Node::Leaf(pane) => {
                 if let Some((_, rect)) = rects.iter().find(|(p, _)| p == path) {
@@ -229,7 +229,7 @@ pub fn resize_all_panes(app: &mut AppState) {
                     // Clamp to MIN_PANE_DIM so ConPTY never receives a
                     // dimension small enough to crash the child process.
                     let inner_height = rect.height.max(crate::pane::MIN_PANE_DIM);
-                    let inner_width = rect.width.max(crate::pane::MIN_PANE_DIM);
+                    let inner_width = rect.width.saturating_sub(scrollbar_cols).max(crate::pane::MIN_PANE_DIM);
                     
                     if pane.last_rows != inner_height || pane.last_cols != inner_width {
                         let _ = pane.master.resize(portable_pty::PtySize { 
@@ -240,6 +240,9 @@ pub fn resize_all_panes(app: &mut AppState) {
                         });
                         if let Ok(mut parser) = pane.term.lock() {
                             parser.screen_mut().set_size(inner_height, inner_width);
+                            if let Some(rec) = parser.callbacks_mut().recording.as_mut() {
+                                rec.resize(inner_width, inner_height);
+                            }
                         }
                         pane.last_rows = inner_height;
                         pane.last_cols = inner_width;
@@ -249,7 +252,7 @@ pub fn resize_all_panes(app: &mut AppState) {
             Node::Split { children, .. } => {
                 for (i, child) in children.iter_mut().enumerate() {
                     path.push(i);
-                    resize_node(child, rects, path);
+                    resize_node(child, rects, path, scrollbar_cols);
                     path.pop();
                 }
             }
@@ -260,11 +263,13 @@ pub fn resize_all_panes(app: &mut AppState) {
     // resized lazily when switched to.  This avoids O(total_panes) ConPTY
     // resize syscalls on every structural change.
     if app.active_idx < app.windows.len() {
+        // pane-scrollbars on: keep the rightmost column for the scrollbar
+        let scrollbar_cols = if app.pane_scrollbars == "on" { 1 } else { 0 };
         let win = &mut app.windows[app.active_idx];
         let mut rects: Vec<(Vec<usize>, Rect)> = Vec::new();
         compute_rects(&win.root, area, &mut rects);
         let mut path = Vec::new();
-        resize_node(&mut win.root, &rects, &mut path);
+        resize_node(&mut win.root, &rects, &mut path, scrollbar_cols);
     }
 }
 
@@ -363,14 +368,20 @@ pub fn get_split_mut<'a>(node: &'a mut Node, path: &Vec<usize>) -> Option<&'a mu
     Some(cur)
 }
 
-pub fn prune_exited(n: Node, remain_on_exit: bool) -> Option<Node> {
+/// Remove panes whose child has exited (or mark them dead when
+/// `remain_on_exit` is set).  Each exited pane is appended to `exited` as
+/// `(pane_id, kept)`, where `kept` is true when the pane remains as dead.
+pub fn prune_exited(n: Node, remain_on_exit: bool, exited: &mut Vec<(usize, bool)>) -> Option<Node> {
     match n {
         Node::Leaf(mut p) => {
             if p.dead { return Some(Node::Leaf(p)); }
             match p.child.try_wait() {
-                Ok(Some(_)) => {
+                Ok(Some(status)) => {
+                    exited.push((p.id, remain_on_exit));
                     if remain_on_exit {
                         p.dead = true;
+                        p.exit_status = Some(status);
+                        p.dead_time = Some(chrono::Local::now());
                         Some(Node::Leaf(p))
                     } else {
                         None
@@ -383,7 +394,7 @@ pub fn prune_exited(n: Node, remain_on_exit: bool) -> Option<Node> {
             let mut new_children: Vec<Node> = Vec::new();
             let mut new_sizes: Vec<u16> = Vec::new();
             for (i, child) in children.into_iter().enumerate() {
-                if let Some(c) = prune_exited(child, remain_on_exit) {
+                if let Some(c) = prune_exited(child, remain_on_exit, exited) {
                     new_children.push(c);
                     new_sizes.push(sizes.get(i).copied().unwrap_or(0));
                 }
@@ -592,6 +603,31 @@ pub fn focus_pane_by_id(app: &mut AppState, pid: usize) {
     }
 }
 
+/// Resolve a `-t` pane target (`%id`, `:window.pane`, `.pane`, `@id`) to a
+/// window index and the pane's path in that window.
+pub fn resolve_pane_target(app: &AppState, target: &str) -> Option<(usize, Vec<usize>)> {
+    let t = crate::cli::parse_target(target);
+    if t.pane_is_id {
+        let pid = t.pane?;
+        return app.windows.iter().enumerate()
+            .find_map(|(wi, w)| find_path_by_id(&w.root, pid).map(|p| (wi, p)));
+    }
+    let win_idx = match t.window {
+        Some(w) if t.window_is_id => find_window_index_by_id(app, w)?,
+        Some(w) => w.checked_sub(app.window_base_index).filter(|&i| i < app.windows.len())?,
+        None => app.active_idx,
+    };
+    let win = &app.windows[win_idx];
+    let path = match t.pane {
+        Some(n) => {
+            let id = get_nth_pane(&win.root, n)?.id;
+            find_path_by_id(&win.root, id)?
+        }
+        None => win.active_path.clone(),
+    };
+    Some((win_idx, path))
+}
+
 pub fn focus_pane_by_index(app: &mut AppState, idx: usize) {
     fn collect_pane_paths(node: &Node, path: &mut Vec<usize>, panes: &mut Vec<Vec<usize>>) {
         match node {
@@ -676,6 +712,7 @@ fn has_any_exited(node: &mut Node) -> bool {
 pub fn reap_children(app: &mut AppState) -> io::Result<(bool, bool)> {
     let remain = app.remain_on_exit;
     let mut any_pruned = false;
+    let mut exited: Vec<(usize, bool)> = Vec::new();
     for i in (0..app.windows.len()).rev() {
         // Fast path: skip full tree rebuild if no panes have exited
         if !has_any_exited(&mut app.windows[i].root) {
@@ -684,7 +721,7 @@ pub fn reap_children(app: &mut AppState) -> io::Result<(bool, bool)> {
         let leaves_before = count_panes(&app.windows[i].root);
         let active_pane_id = get_active_pane_id(&app.windows[i].root, &app.windows[i].active_path);
         let root = std::mem::replace(&mut app.windows[i].root, Node::Split { kind: LayoutKind::Horizontal, sizes: vec![], children: vec![] });
-        match prune_exited(root, remain) {
+        match prune_exited(root, remain, &mut exited) {
             Some(new_root) => {
                 let leaves_after = count_panes(&new_root);
                 if leaves_after < leaves_before {
@@ -724,9 +761,34 @@ pub fn reap_children(app: &mut AppState) -> io::Result<(bool, bool)> {
             }
         }
     }
+    for &(pane_id, kept) in &exited {
+        if kept {
+            draw_dead_pane_banner(app, pane_id);
+            crate::commands::fire_hooks(app, "pane-died");
+        } else {
+            crate::commands::fire_hooks(app, "pane-exited");
+        }
+    }
     Ok((app.windows.is_empty(), any_pruned))
 }
 
+/// Write the expanded `remain-on-exit-format` on a fresh line at the bottom
+/// of a dead pane, the way tmux does.
+fn draw_dead_pane_banner(app: &AppState, pane_id: usize) {
+    for (wi, win) in app.windows.iter().enumerate() {
+        let Some(pos) = get_pane_position_in_window(&win.root, pane_id) else { continue };
+        let text = crate::format::expand_format_for_pane(&app.remain_on_exit_format, app, wi, pos);
+        if let Some(p) = get_nth_pane(&win.root, pos) {
+            if let Ok(mut parser) = p.term.lock() {
+                let rows = parser.screen().size().0;
+                parser.process(format!("\x1b[0m\x1b[r\x1b[{};1H\n{}", rows, text).as_bytes());
+            }
+            p.data_version.fetch_add(1, std::sync::atomic::Ordering::Release);
+        }
+        return;
+    }
+}
+
 /// Collect all leaf (Pane) nodes from the tree, consuming it.
 /// Returns them in DFS (left-to-right) order.
 pub fn collect_leaves(node: Node) -> Vec<Node> {
@@ -741,3 +803,51 @@ pub fn collect_leaves(node: Node) -> Vec<Node> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A window whose pane runs a command that exits at once, with hooks
+    /// that record which event fired in the session name.
+    fn exiting_app(remain_on_exit: bool) -> AppState {
+        let mut app = AppState::new("t".to_string());
+        app.remain_on_exit = remain_on_exit;
+        crate::pane::test_window(&mut app, 5, 30, b"");
+        for event in ["pane-died", "pane-exited"] {
+            app.hooks.insert(event.to_string(), vec![format!("rename-session {}", event)]);
+        }
+        app
+    }
+
+    /// Reap until the pane's child has been seen to exit.
+    fn reap(app: &mut AppState) {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while std::time::Instant::now() < deadline {
+            reap_children(app).unwrap();
+            if app.session_name != "t" { return; }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("no hook fired");
+    }
+
+    #[test]
+    fn dead_panes_fire_pane_died_and_show_the_banner() {
+        let mut app = exiting_app(true);
+        reap(&mut app);
+        assert_eq!(app.session_name, "pane-died");
+        assert_eq!(app.windows.len(), 1);
+        let p = active_pane(&app.windows[0].root, &app.windows[0].active_path).unwrap();
+        assert!(p.dead);
+        let text = p.term.lock().unwrap().screen().contents();
+        assert!(text.contains("Pane is dead (status 0, "), "{text:?}");
+    }
+
+    #[test]
+    fn exited_panes_fire_pane_exited() {
+        let mut app = exiting_app(false);
+        reap(&mut app);
+        assert_eq!(app.session_name, "pane-exited");
+        assert!(app.windows.is_empty());
+    }
+}
@@ -8,11 +8,19 @@ use chrono::Local;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Default `copy-mode-position-format`.
+pub const DEFAULT_COPY_MODE_POSITION_FORMAT: &str =
+    "[#{scroll_position}/#{history_size}]#{?search_count, [#{search_index}/#{search_count}],}";
+
+/// Default `remain-on-exit-format` (tmux parity).
+pub const DEFAULT_REMAIN_ON_EXIT_FORMAT: &str =
+    "Pane is dead (#{?pane_dead_signal,signal #{pane_dead_signal},status #{pane_dead_status}}, #{t:pane_dead_time})";
+
 pub struct Pane {
     pub master: Box<dyn MasterPty>,
     pub writer: Box<dyn std::io::Write + Send>,
     pub child: Box<dyn portable_pty::Child>,
-    pub term: Arc<Mutex<vt100::Parser>>,
+    pub term: Arc<Mutex<crate::pane::PaneParser>>,
     pub last_rows: u16,
     pub last_cols: u16,
     pub id: usize,
@@ -30,6 +38,10 @@ pub struct Pane {
     pub last_infer_title: Instant,
     /// True when the child process has exited but remain-on-exit keeps the pane visible.
     pub dead: bool,
+    /// Exit status of the child once it has exited (`#{pane_dead_status}` / `#{pane_dead_signal}`).
+    pub exit_status: Option<portable_pty::ExitStatus>,
+    /// When the child exited (`#{pane_dead_time}`).
+    pub dead_time: Option<chrono::DateTime<Local>>,
     /// Cached VT bridge detection result (for mouse injection).
     /// Updated on first mouse event and refreshed every 2 seconds.
     pub vt_bridge_cache: Option<(Instant, bool)>,
@@ -43,12 +55,13 @@ pub struct Pane {
     /// When false, the child expects VT SGR mouse sequences (nvim, vim).
     /// Refreshed every 2 seconds.
     pub mouse_input_cache: Option<(Instant, bool)>,
-    /// Last cursor shape requested by the child process via DECSCUSR (`\x1b[N q`).
-    /// 0 = no override (use PSMUX_CURSOR_STYLE default), 1-6 = DECSCUSR values.
-    pub cursor_shape: std::sync::Arc<std::sync::atomic::AtomicU8>,
     /// Per-pane copy mode state (tmux-style pane-local copy mode).
     /// Some(_) when this pane is in copy mode, None otherwise.
     pub copy_state: Option<CopyModeState>,
+    /// Copy-mode mark (set-mark) as (absolute line, col), where the absolute
+    /// line is the history line plus `history_dropped()`, so the mark stays
+    /// on its text while new output scrolls the history.
+    pub copy_mark: Option<(usize, u16)>,
     /// Per-pane style string (set via `select-pane -P "bg=...,fg=..."`).
     /// Matches tmux's `window-style` / `window-active-style` pane option.
     /// Stored for API compatibility; ConPTY rendering doesn't support
@@ -64,9 +77,8 @@ pub struct WarmPane {
     pub master: Box<dyn MasterPty>,
     pub writer: Box<dyn std::io::Write + Send>,
     pub child: Box<dyn portable_pty::Child>,
-    pub term: Arc<Mutex<vt100::Parser>>,
+    pub term: Arc<Mutex<crate::pane::PaneParser>>,
     pub data_version: std::sync::Arc<std::sync::atomic::AtomicU64>,
-    pub cursor_shape: std::sync::Arc<std::sync::atomic::AtomicU8>,
     pub child_pid: Option<u32>,
     pub pane_id: usize,
     pub rows: u16,
@@ -106,6 +118,9 @@ pub struct Window {
     ///  - Directional navigation tie-breaking (issue #70)
     ///  - Focus selection after kill-pane (issue #71)
     pub pane_mru: Vec<usize>,
+    /// Floating popups that belong to this window but are not focused;
+    /// visible ones are drawn above the tiled layout.
+    pub floating: Vec<crate::popup::Popup>,
 }
 
 /// A menu item for display-menu
@@ -115,6 +130,8 @@ pub struct MenuItem {
     pub key: Option<char>,
     pub command: String,
     pub is_separator: bool,
+    /// Named with a leading `-`: shown dimmed and never chosen.
+    pub disabled: bool,
 }
 
 /// A parsed menu structure
@@ -123,8 +140,22 @@ pub struct Menu {
     pub title: String,
     pub items: Vec<MenuItem>,
     pub selected: usize,
-    pub x: Option<i16>,
-    pub y: Option<i16>,
+    /// Position and size within the window area
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub style: String,
+    pub selected_style: String,
+    pub border_style: String,
+    pub border_lines: String,
+    /// `-O`: releasing the mouse button does not close the menu.
+    pub stay_open: bool,
+    /// A mouse button was pressed since the menu opened, so a release is
+    /// not just the end of the click that opened it.
+    pub pressed: bool,
+    /// The menu this one was opened from as a submenu.
+    pub parent: Option<Box<Menu>>,
 }
 
 /// Hook definition - command to run on certain events
@@ -139,7 +170,18 @@ pub struct PopupPty {
     pub master: Box<dyn portable_pty::MasterPty>,
     pub writer: Box<dyn std::io::Write + Send>,
     pub child: Box<dyn portable_pty::Child>,
-    pub term: std::sync::Arc<std::sync::Mutex<vt100::Parser>>,
+    pub term: std::sync::Arc<std::sync::Mutex<crate::pane::PaneParser>>,
+    pub data_version: std::sync::Arc<std::sync::atomic::AtomicU64>,
+}
+
+/// A paste buffer.  Automatic buffers come from copies and are named
+/// `bufferN`; named buffers are created with `set-buffer -b` or `-n`.
+#[derive(Clone)]
+pub struct PasteBuffer {
+    pub name: String,
+    pub data: String,
+    pub created: chrono::DateTime<Local>,
+    pub automatic: bool,
 }
 
 /// Pipe pane state - process piping pane output
@@ -159,25 +201,18 @@ pub struct WaitChannel {
 pub enum Mode {
     Passthrough,
     Prefix { armed_at: Instant },
-    CommandPrompt { input: String, cursor: usize },
-    WindowChooser { selected: usize, tree: Vec<crate::session::TreeEntry> },
+    /// Status-line prompt (prefix :, command-prompt)
+    CommandPrompt { prompt: PromptState },
+    /// Session, window and pane tree (prefix w, choose-tree)
+    WindowChooser { chooser: TreeChooser },
     RenamePrompt { input: String },
     RenameSessionPrompt { input: String },
     CopyMode,
     PaneChooser { opened_at: Instant },
     /// Interactive menu mode
     MenuMode { menu: Menu },
-    /// Popup window running a command (with optional PTY for interactive programs)
-    PopupMode { 
-        command: String, 
-        output: String, 
-        process: Option<std::process::Child>,
-        width: u16,
-        height: u16,
-        close_on_exit: bool,
-        /// Optional: interactive PTY for the popup (fzf, etc.)  
-        popup_pty: Option<PopupPty>,
-    },
+    /// Focused popup window running a command (with optional PTY for interactive programs)
+    PopupMode { popup: crate::popup::Popup },
     /// Confirmation prompt before command
     ConfirmMode { 
         prompt: String, 
@@ -188,11 +223,185 @@ pub enum Mode {
     CopySearch {
         input: String,
         forward: bool,
+        /// C-r search through the search history.
+        search: Option<HistorySearch>,
     },
     /// Big clock display (tmux clock-mode)
     ClockMode,
-    /// Interactive buffer chooser (prefix =)
-    BufferChooser { selected: usize },
+    /// Interactive buffer chooser (prefix =, choose-buffer)
+    BufferChooser { chooser: BufferChooser },
+    /// Labelled pattern matches on the active pane (hint-mode, prefix F)
+    HintMode { hints: HintState },
+}
+
+/// State of an open `hint-mode`.
+pub struct HintState {
+    /// Visible matches as (row, col_start, col_end, index into `labels`).
+    pub matches: Vec<(u16, u16, u16, usize)>,
+    /// (label, text) for each distinct match text.
+    pub labels: Vec<(String, String)>,
+    /// Label letters typed so far.
+    pub input: String,
+    /// -c: command upper-case labels pipe the match to, instead of pasting.
+    pub command: Option<String>,
+    /// Entered from copy mode, which is restored on exit.
+    pub from_copy: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum TreeKind { Session, Window, Pane }
+
+/// One session, window or pane in choose-tree.
+#[derive(Debug, Clone)]
+pub struct TreeItem {
+    pub kind: TreeKind,
+    /// Target that replaces `%%` in the command: `s`, `s:W` or `s:W.P`.
+    pub target: String,
+    pub session: String,
+    /// Window or pane index (0 for sessions).
+    pub index: usize,
+    /// Window or pane id, used to find local items for the preview.
+    pub id: usize,
+    /// Session name, window name or pane title, for `-O name`.
+    pub name: String,
+    /// Last activity as a Unix timestamp, for `-O time`.
+    pub activity: i64,
+    /// The expanded `-F` format.
+    pub text: String,
+    /// The `-f` filter was true for this item.
+    pub matches: bool,
+    /// This session, its active window or that window's active pane.
+    pub current: bool,
+    /// Port file base of the item's server; None for this session.
+    pub server: Option<String>,
+}
+
+/// A line being typed at the bottom of choose-tree.
+pub enum TreePrompt {
+    /// `/`: narrow the tree to items containing the text.
+    Search(String),
+    /// `:`: command run for each tagged item.
+    Command(String),
+    /// `x`/`X`: waiting for y to kill these targets.
+    Kill(Vec<String>),
+}
+
+/// Sessions, windows and panes reported by other servers, and the
+/// captured contents of the selected remote item.
+pub struct TreeFetch {
+    pub items: Vec<TreeItem>,
+    pub preview: Option<(String, Vec<String>)>,
+}
+
+/// State of an open `command-prompt`.
+pub struct PromptState {
+    /// -p: label of each field, shown before its input.
+    pub prompts: Vec<String>,
+    /// -I: initial text of each field.
+    pub inputs: Vec<String>,
+    /// Responses to the fields already answered.
+    pub answers: Vec<String>,
+    /// Command run with `%1`..`%9` and `%%` replaced by the responses;
+    /// None runs the response itself.
+    pub template: Option<String>,
+    pub input: String,
+    /// Byte offset of the cursor in `input`.
+    pub cursor: usize,
+    /// -1: accept after a single key.
+    pub single: bool,
+    /// -N: only digits are typed, any other key accepts.
+    pub numeric: bool,
+    /// -k: the response is the name of the next key pressed.
+    pub key_name: bool,
+    /// -T: "command", "search", "target" or "window-target".
+    pub kind: String,
+    /// Candidates listed by Tab, the one inserted and where the
+    /// completed word starts.
+    pub completions: Vec<String>,
+    pub completion: Option<usize>,
+    pub completion_start: usize,
+    /// C-r reverse incremental search through the history.
+    pub search: Option<HistorySearch>,
+}
+
+/// A C-r search in the command prompt or the copy-mode search prompt.
+#[derive(Clone)]
+pub struct HistorySearch {
+    pub query: String,
+    /// History entry shown as the input.
+    pub index: Option<usize>,
+    /// Input before the search, restored when it is cancelled.
+    pub saved: String,
+}
+
+/// State of an open `choose-tree`.
+pub struct TreeChooser {
+    /// Target of the highlighted item.
+    pub selected: String,
+    /// Targets tagged with `t`.
+    pub tagged: Vec<String>,
+    /// Targets expanded or collapsed away from the default.
+    pub toggled: Vec<String>,
+    /// Items of this kind and below start collapsed (-s sessions, -w windows).
+    pub collapse: TreeKind,
+    /// -F: format for each item.
+    pub format: String,
+    /// -f: only show items for which this format is true.
+    pub filter: String,
+    /// -K: format for each item's key.
+    pub key_format: Option<String>,
+    /// -O: "index", "name" or "time".
+    pub sort_order: String,
+    /// -r: reverse the sort order.
+    pub reverse: bool,
+    /// Text typed after `/`.
+    pub search: String,
+    pub prompt: Option<TreePrompt>,
+    /// Command run for the chosen items, `%%` replaced by each target.
+    pub template: String,
+    /// -Z: fill the window instead of a centred box.
+    pub zoomed: bool,
+    /// Show the selected item under the tree (-N hides, `v` toggles).
+    pub preview: bool,
+    /// First window shown in a session preview (`<` and `>`).
+    pub preview_offset: usize,
+    /// Items of other sessions from the last fetch.
+    pub remote: Vec<TreeItem>,
+    /// Captured contents of the selected remote item.
+    pub remote_preview: Option<(String, Vec<String>)>,
+    /// Background fetch in flight, and when the last one started.
+    pub fetch: Option<mpsc::Receiver<TreeFetch>>,
+    pub fetched_at: Option<Instant>,
+}
+
+/// A paste buffer open in `$EDITOR` from choose-buffer.  The chooser is
+/// reopened when the editor popup closes.
+pub struct BufferEdit {
+    pub name: String,
+    pub path: std::path::PathBuf,
+    pub chooser: BufferChooser,
+}
+
+/// State of an open `choose-buffer`.
+pub struct BufferChooser {
+    /// Row of the highlighted item in the visible (filtered, sorted) list.
+    pub selected: usize,
+    /// Names of buffers tagged with `t`.
+    pub tagged: Vec<String>,
+    /// -F: format for each item, expanded per buffer.
+    pub format: String,
+    /// -O: "time", "name" or "size".
+    pub sort_order: String,
+    /// -r: reverse the sort order.
+    pub reverse: bool,
+    /// -f: only show buffers for which this format is true.
+    pub filter: String,
+    /// -t: pane the chosen buffer is pasted into (active pane if None).
+    pub target: Option<String>,
+    /// -Z: fill the window instead of a centred box.
+    pub zoomed: bool,
+    /// Show the selected buffer's contents under the list (`v` toggles).
+    pub preview: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -209,9 +418,12 @@ pub struct CopyModeState {
     pub selection_mode: SelectionMode,
     pub search_query: String,
     pub count: Option<usize>,
-    pub search_matches: Vec<(u16, u16, u16)>,
+    pub search_matches: Vec<(usize, u16, u16)>,
     pub search_idx: usize,
     pub search_forward: bool,
+    pub search_regex: bool,
+    pub search_incremental: bool,
+    pub search_origin: Option<(usize, Option<(u16, u16)>)>,
     pub find_char_pending: Option<u8>,
     pub text_object_pending: Option<u8>,
     pub register_pending: bool,
@@ -241,7 +453,12 @@ pub struct AppState {
     pub drag: Option<DragState>,
     pub last_window_area: Rect,
     pub mouse_enabled: bool,
-    pub paste_buffers: Vec<String>,
+    /// Paste buffers, most recent first (see `crate::buffers`).
+    pub paste_buffers: Vec<PasteBuffer>,
+    /// Number used for the next automatic `bufferN` name.
+    pub next_buffer_id: usize,
+    /// buffer-limit: maximum number of automatic buffers (default 50)
+    pub buffer_limit: usize,
     pub status_left: String,
     pub status_right: String,
     pub window_base_index: usize,
@@ -254,14 +471,42 @@ pub struct AppState {
     pub copy_selection_mode: SelectionMode,
     /// Copy-mode search query
     pub copy_search_query: String,    /// Numeric prefix count for copy-mode motions (vi-style)
-    pub copy_count: Option<usize>,    /// Copy-mode search matches: (row, col_start, col_end) in screen coords
-    pub copy_search_matches: Vec<(u16, u16, u16)>,
+    pub copy_count: Option<usize>,
+    /// Search matches as (absolute line, col_start, col_end) in document
+    /// order.  The absolute line is the history line (0 = oldest scrollback
+    /// line, see `Screen::history_cell`) plus `history_dropped()`, so matches
+    /// stay on their text while new output trims the history.
+    pub copy_search_matches: Vec<(usize, u16, u16)>,
     /// Current match index in copy_search_matches
     pub copy_search_idx: usize,
     /// Search direction: true = forward (/), false = backward (?)
     pub copy_search_forward: bool,
+    /// Treat the search query as a regex (search-forward/backward) rather
+    /// than literal text (the -text and -incremental variants).
+    pub copy_search_regex: bool,
+    /// Incremental search: matches are refreshed on every keystroke while
+    /// the search prompt is open (search-*-incremental, emacs C-s/C-r).
+    pub copy_search_incremental: bool,
+    /// (scroll offset, cursor) when the search prompt was opened; Escape
+    /// returns there.
+    pub copy_search_origin: Option<(usize, Option<(u16, u16)>)>,
+    /// Style for every visible search match (copy-mode-match-style)
+    pub copy_mode_match_style: String,
+    /// Style for the current search match (copy-mode-current-match-style)
+    pub copy_mode_current_match_style: String,
+    /// copy-mode-position-format: indicator drawn at the top right of a
+    /// pane in copy mode
+    pub copy_mode_position_format: String,
+    /// copy-mode -H: hide the position indicator until copy mode is left
+    pub copy_hide_position: bool,
+    /// pane-scrollbars: "off", "modal" (copy mode only) or "on"
+    pub pane_scrollbars: String,
+    /// pane-scrollbars-style: track in bg, slider in fg
+    pub pane_scrollbars_style: String,
     /// Pending find-char operation: (f=0,F=1,t=2,T=3) for next char input
     pub copy_find_char_pending: Option<u8>,
+    /// Last find-char motion (kind, char) for jump-again / jump-reverse
+    pub copy_last_find: Option<(u8, char)>,
     /// Pending text-object prefix: 0 = 'a' (a-word), 1 = 'i' (inner-word)
     pub copy_text_object_pending: Option<u8>,
     /// Pending register selection: true when '"' was pressed, waiting for a-z
@@ -287,6 +532,9 @@ pub struct AppState {
     pub attached_clients: usize,
     /// Per-client terminal sizes for multi-client resize tracking.
     pub client_sizes: std::collections::HashMap<u64, (u16, u16)>,
+    /// Pixel size of one cell as reported by the latest client, when its
+    /// terminal exposes it.  Used for SGR-pixel mouse reports (DECSET 1016).
+    pub client_cell_size: Option<(u16, u16)>,
     /// The most recently active client ID (for window_size="latest").
     pub latest_client_id: Option<u64>,
     pub created_at: chrono::DateTime<Local>,
@@ -316,6 +564,12 @@ pub struct AppState {
     pub pane_base_index: usize,
     /// focus-events: pass focus events to apps
     pub focus_events: bool,
+    /// ambiguous-width: columns used for East Asian ambiguous-width characters (1 or 2)
+    pub ambiguous_width: u8,
+    /// variation-selector-always-wide: a character followed by VS16 (U+FE0F) is two columns wide
+    pub variation_selector_always_wide: bool,
+    /// wheel-scroll-lines: lines scrolled per mouse wheel notch (default 3)
+    pub wheel_scroll_lines: u16,
     /// mode-keys: vi or emacs (stored for compat, default emacs)
     pub mode_keys: String,
     /// status: whether status bar is shown
@@ -337,6 +591,8 @@ pub struct AppState {
     pub visual_activity: bool,
     /// remain-on-exit: keep panes open after process exits
     pub remain_on_exit: bool,
+    /// remain-on-exit-format: line drawn into a pane when its process exits
+    pub remain_on_exit_format: String,
     /// destroy-unattached: exit server when no clients remain attached
     pub destroy_unattached: bool,
     /// exit-empty: exit server when all panes/windows are empty
@@ -379,6 +635,20 @@ pub struct AppState {
     pub message_command_style: String,
     /// mode-style: style for copy-mode highlighting
     pub mode_style: String,
+    /// popup-style: style for popup contents
+    pub popup_style: String,
+    /// popup-border-style: style for popup borders
+    pub popup_border_style: String,
+    /// popup-border-lines: single, rounded, double, heavy, simple, padded or none
+    pub popup_border_lines: String,
+    /// menu-style: style for menu items
+    pub menu_style: String,
+    /// menu-selected-style: style for the selected menu item
+    pub menu_selected_style: String,
+    /// menu-border-style: style for menu borders
+    pub menu_border_style: String,
+    /// menu-border-lines: border characters, as for popup-border-lines
+    pub menu_border_lines: String,
     /// status-left-style: style for status-left area
     pub status_left_style: String,
     /// status-right-style: style for status-right area
@@ -391,10 +661,14 @@ pub struct AppState {
     pub bell_action: String,
     /// visual-bell: show visual indicator on bell
     pub visual_bell: bool,
-    /// Command prompt history
-    pub command_history: Vec<String>,
-    /// Command prompt history index (for up/down navigation)
-    pub command_history_idx: usize,
+    /// Prompt history per prompt type ("command", "search", "target", ...), oldest first
+    pub prompt_history: std::collections::HashMap<String, Vec<String>>,
+    /// Prompt history index (for up/down navigation)
+    pub prompt_history_idx: usize,
+    /// history-file: file prompt history is kept in across servers ("" = none)
+    pub history_file: String,
+    /// prompt-history-limit: entries kept per prompt type
+    pub prompt_history_limit: usize,
     /// status-interval: seconds between status-line refreshes (default 15)
     pub status_interval: u64,
     /// Last time the status-interval hook was fired
@@ -423,6 +697,19 @@ pub struct AppState {
     pub command_aliases: std::collections::HashMap<String, String>,
     /// set-clipboard: "on", "off", "external" (default "on")
     pub set_clipboard: String,
+    /// Buffer being edited in a popup from choose-buffer (`e`).
+    pub buffer_edit: Option<BufferEdit>,
+    /// Session `switch-client` asked the attached client to move to.
+    pub switch_client: Option<String>,
+    /// allow-clipboard-read: answer OSC 52 `?` requests from panes with the
+    /// top paste buffer (default off)
+    pub allow_clipboard_read: bool,
+    /// hint-alphabet: letters used for hint-mode labels
+    pub hint_alphabet: String,
+    /// hint-patterns: (name, regex) pairs scanned by hint-mode, in priority order
+    pub hint_patterns: Vec<(String, String)>,
+    /// hint-style: style of hint-mode labels
+    pub hint_style: String,
     /// One-shot clipboard text to be sent to the client via OSC 52 (set by yank, consumed by dump-state).
     pub clipboard_osc52: Option<String>,
     /// env-shim: inject a Unix-compatible `env` function into PowerShell panes
@@ -447,6 +734,10 @@ pub struct AppState {
     /// Last mouse hover position (col, row) for same-coordinate deduplication.
     /// Windows Terminal suppresses consecutive MOUSE_MOVED at the same position.
     pub last_hover_pos: Option<(u16, u16)>,
+    /// Position (col, row) of the last mouse button press, for `display-popup -x M`.
+    pub last_mouse_down: Option<(u16, u16)>,
+    /// A popup being moved or resized with the mouse.
+    pub popup_drag: Option<crate::popup::PopupDrag>,
     /// Transient status-bar message from display-message (without -p).
     /// Tuple of (message_text, timestamp_when_set).
     pub status_message: Option<(String, std::time::Instant)>,
@@ -477,6 +768,8 @@ impl AppState {
             last_window_area: Rect { x: 0, y: 0, width: 120, height: 30 },
             mouse_enabled: true,
             paste_buffers: Vec::new(),
+            next_buffer_id: 0,
+            buffer_limit: crate::buffers::DEFAULT_BUFFER_LIMIT,
             status_left: "[#S] ".to_string(),
             status_right: "#{?window_bigger,[#{window_offset_x}#,#{window_offset_y}] ,}\"#{=21:pane_title}\" %H:%M %d-%b-%y".to_string(),
             window_base_index: 0,
@@ -490,7 +783,17 @@ impl AppState {
             copy_search_matches: Vec::new(),
             copy_search_idx: 0,
             copy_search_forward: true,
+            copy_search_regex: true,
+            copy_search_incremental: false,
+            copy_search_origin: None,
+            copy_mode_match_style: "bg=cyan,fg=black".to_string(),
+            copy_mode_current_match_style: "bg=magenta,fg=black".to_string(),
+            copy_mode_position_format: DEFAULT_COPY_MODE_POSITION_FORMAT.to_string(),
+            copy_hide_position: false,
+            pane_scrollbars: "off".to_string(),
+            pane_scrollbars_style: "bg=black,fg=white".to_string(),
             copy_find_char_pending: None,
+            copy_last_find: None,
             copy_text_object_pending: None,
             copy_register_pending: false,
             copy_register: None,
@@ -509,6 +812,7 @@ impl AppState {
             socket_name: None,
             attached_clients: 0,
             client_sizes: std::collections::HashMap::new(),
+            client_cell_size: None,
             latest_client_id: None,
             created_at: Local::now(),
             next_win_id: 1,
@@ -526,6 +830,9 @@ impl AppState {
             display_panes_time_ms: 1000,
             pane_base_index: 0,
             focus_events: false,
+            ambiguous_width: 1,
+            variation_selector_always_wide: true,
+            wheel_scroll_lines: 3,
             mode_keys: "emacs".to_string(),
             status_visible: true,
             status_position: "bottom".to_string(),
@@ -537,6 +844,7 @@ impl AppState {
             monitor_activity: false,
             visual_activity: false,
             remain_on_exit: false,
+            remain_on_exit_format: DEFAULT_REMAIN_ON_EXIT_FORMAT.to_string(),
             destroy_unattached: false,
             exit_empty: true,
             aggressive_resize: false,
@@ -557,14 +865,23 @@ impl AppState {
             message_style: "bg=yellow,fg=black".to_string(),
             message_command_style: "bg=black,fg=yellow".to_string(),
             mode_style: "bg=yellow,fg=black".to_string(),
+            popup_style: "default".to_string(),
+            popup_border_style: "fg=yellow".to_string(),
+            popup_border_lines: "single".to_string(),
+            menu_style: "default".to_string(),
+            menu_selected_style: "bg=yellow,fg=black".to_string(),
+            menu_border_style: "default".to_string(),
+            menu_border_lines: "single".to_string(),
             status_left_style: String::new(),
             status_right_style: String::new(),
             marked_pane: None,
             monitor_silence: 0,
             bell_action: "any".to_string(),
             visual_bell: false,
-            command_history: Vec::new(),
-            command_history_idx: 0,
+            prompt_history: std::collections::HashMap::new(),
+            prompt_history_idx: 0,
+            history_file: String::new(),
+            prompt_history_limit: 100,
             status_interval: 15,
             last_status_interval_fire: std::time::Instant::now(),
             status_justify: "left".to_string(),
@@ -579,11 +896,19 @@ impl AppState {
             copy_command: String::new(),
             command_aliases: std::collections::HashMap::new(),
             set_clipboard: "on".to_string(),
+            allow_clipboard_read: false,
+            hint_alphabet: crate::hints::DEFAULT_ALPHABET.to_string(),
+            hint_patterns: crate::hints::default_patterns(),
+            hint_style: "fg=black,bg=yellow,bold".to_string(),
+            buffer_edit: None,
+            switch_client: None,
             clipboard_osc52: None,
             env_shim: true,
             claude_code_fix_tty: true,
             claude_code_force_interactive: true,
             last_hover_pos: None,
+            last_mouse_down: None,
+            popup_drag: None,
             status_message: None,
             warm_pane: None,
             pending_plugin_scripts: Vec::new(),
@@ -649,8 +974,10 @@ pub enum CtrlReq {
     SplitWindowPrint(LayoutKind, Option<String>, bool, Option<String>, Option<u16>, Option<String>, mpsc::Sender<String>),  // kind, cmd, detached, start_dir, size_percent, format, resp
     KillPane,
     KillPaneById(usize),
-    CapturePane(mpsc::Sender<String>),
-    CapturePaneStyled(mpsc::Sender<String>, Option<i32>, Option<i32>),
+    CapturePane(crate::capture::CaptureOptions, mpsc::Sender<Result<String, String>>),
+    RecordPane(Option<String>, Option<String>, bool, mpsc::Sender<String>),  // target, file, stop (-x), error_resp
+    /// `psmux play`: argv of the player to run in a new window
+    PlayRecording(Vec<String>),
     FocusWindow(usize),
     /// Temporary focus for -t targeting: server saves/restores active_idx
     FocusWindowTemp(usize),
@@ -660,7 +987,6 @@ pub enum CtrlReq {
     FocusPaneTemp(usize),
     FocusPaneByIndexTemp(usize),
     SessionInfo(mpsc::Sender<String>),
-    CapturePaneRange(mpsc::Sender<String>, Option<i32>, Option<i32>),
     ClientAttach(u64),
     ClientDetach(u64),
     DumpLayout(mpsc::Sender<String>),
@@ -671,11 +997,13 @@ pub enum CtrlReq {
     ZoomPane,
     CopyEnter,
     CopyEnterPageUp,
+    CopyHidePosition,
     CopyMove(i16, i16),
     CopyAnchor,
     CopyYank,
     CopyRectToggle,
     ClientSize(u64, u16, u16),
+    ClientCellSize(u16, u16),
     FocusPaneCmd(usize),
     FocusWindowCmd(usize),
     MouseDown(u16,u16),
@@ -699,7 +1027,7 @@ pub enum CtrlReq {
     SetPaneTitle(String),
     SetPaneStyle(String),
     SendKeys(String, bool),
-    SendKeysX(String),  // send-keys -X copy-mode-command
+    SendKeysX(String, usize),  // send-keys -X copy-mode-command, -N count
     SelectPane(String),
     SelectWindow(usize),
     ListPanes(mpsc::Sender<String>),
@@ -714,23 +1042,28 @@ pub enum CtrlReq {
     ClaimSession(String, mpsc::Sender<String>),
     SwapPane(String),
     ResizePane(String, u16),
-    SetBuffer(String),
+    SetBuffer(Option<String>, String, bool, bool),  // name, data, append (-a), send to clipboard (-w)
+    RenameBuffer(Option<String>, String),  // buffer (most recent if None), new name
     ListBuffers(mpsc::Sender<String>),
     ListBuffersFormat(mpsc::Sender<String>, String),
     ShowBuffer(mpsc::Sender<String>),
-    ShowBufferAt(mpsc::Sender<String>, usize),
-    DeleteBuffer,
+    ShowNamedBuffer(mpsc::Sender<String>, String),
+    DeleteBuffer(Option<String>),
     DisplayMessage(mpsc::Sender<String>, String, Option<usize>),  // resp, format, target_pane_idx
     LastWindow,
     LastPane,
     RotateWindow(bool),
     DisplayPanes,
     BreakPane,
+    /// `break-pane -f [-n name]`: make the active pane a floating popup.
+    BreakPaneFloat(Option<String>),
     JoinPane(usize),
+    /// `join-pane [-h] -s NAME`: tile a floating popup back into the window.
+    JoinFloat(String, bool),
     RespawnPane,
     BindKey(String, String, String, bool),  // table, key, command, repeat
     UnbindKey(String),
-    ListKeys(mpsc::Sender<String>),
+    ListKeys(Option<String>, mpsc::Sender<String>),  // -T table filter
     SetOption(String, String),
     SetOptionQuiet(String, String, bool),  // set-option with quiet flag
     SetOptionUnset(String),  // set-option -u
@@ -754,8 +1087,8 @@ pub enum CtrlReq {
     SuspendClient,
     CopyModePageUp,
     ClearHistory,
-    SaveBuffer(String),
-    LoadBuffer(String),
+    SaveBuffer(String, Option<String>),  // path, buffer name
+    LoadBuffer(String, Option<String>),  // path, buffer name
     SetEnvironment(String, String),
     UnsetEnvironment(String),
     ShowEnvironment(mpsc::Sender<String>),
@@ -764,16 +1097,20 @@ pub enum CtrlReq {
     RemoveHook(String),
     KillServer,
     WaitFor(String, WaitForOp),
-    DisplayMenu(String, Option<i16>, Option<i16>),
-    DisplayMenuDirect(Menu),
-    DisplayPopup(String, u16, u16, bool),
+    DisplayMenu(crate::menu::MenuArgs),
+    DisplayPopup(crate::popup::PopupArgs),
+    /// `hide-popup`, `move-popup` or `resize-popup` with its arguments.
+    PopupCommand(Vec<String>),
     ConfirmBefore(String, String),
     ClockMode,
     ResizePaneAbsolute(String, u16),
     ResizePanePercent(String, u8), // axis, percentage (0-100)
     ShowOptionValue(mpsc::Sender<String>, String),
     ShowWindowOptionValue(mpsc::Sender<String>, String),
-    ChooseBuffer(mpsc::Sender<String>),
+    ChooseBuffer(Vec<String>),  // choose-buffer flags
+    ChooseTree(Vec<String>),    // choose-tree flags and template
+    ListSessionsFormat(mpsc::Sender<String>, String),
+    HintMode(Vec<String>),      // hint-mode flags
     ServerInfo(mpsc::Sender<String>),
     SendPrefix,
     PrevLayout,
@@ -783,7 +1120,7 @@ pub enum CtrlReq {
     RespawnWindow,
     FocusIn,
     FocusOut,
-    CommandPrompt(String),
+    CommandPrompt(Vec<String>),
     ShowMessages(mpsc::Sender<String>),
     /// Forward raw bytes to the popup PTY (base64-decoded by connection handler)
     PopupInput(Vec<u8>),
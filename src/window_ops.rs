@@ -5,7 +5,7 @@ use portable_pty::{PtySize, native_pty_system};
 use ratatui::prelude::*;
 
 use crate::types::{AppState, Mode, Pane, Node, LayoutKind, DragState, Window, FocusDir};
-use crate::tree::{active_pane, active_pane_mut, compute_rects, compute_split_borders,
+use crate::tree::{active_pane_mut, compute_rects, compute_split_borders,
     split_sizes_at, adjust_split_sizes, get_split_mut, resize_all_panes};
 use crate::pane::{detect_shell, build_default_shell, set_tmux_env};
 use crate::copy_mode::{enter_copy_mode, exit_copy_mode, scroll_copy_up, scroll_copy_down, yank_selection};
@@ -47,14 +47,42 @@ fn pane_inner_cell(area: Rect, abs_x: u16, abs_y: u16) -> (u16, u16) {
     (col, row)
 }
 
+/// Cell size assumed for SGR-pixel mouse reports when the client's terminal
+/// doesn't report its pixel dimensions.
+const DEFAULT_CELL_PIXELS: (u16, u16) = (8, 16);
+
+/// Convert 1-based cell coordinates to 1-based pixel coordinates of the
+/// cell's top-left corner.
+fn cell_to_pixel(col: u16, row: u16, cell_px: Option<(u16, u16)>) -> (u32, u32) {
+    let (cw, ch) = cell_px.unwrap_or(DEFAULT_CELL_PIXELS);
+    let x = col.saturating_sub(1) as u32 * cw as u32 + 1;
+    let y = row.saturating_sub(1) as u32 * ch as u32 + 1;
+    (x, y)
+}
+
 /// Write a mouse event to the child PTY using the encoding the child requested.
-pub fn write_mouse_event_remote(master: &mut dyn std::io::Write, button: u8, col: u16, row: u16, press: bool, enc: vt100::MouseProtocolEncoding) {
+///
+/// `col`/`row` are 1-based cells; `cell_px` is the client's cell size in
+/// pixels, used only for SGR-pixel (DECSET 1016) reports.
+pub fn write_mouse_event_remote(master: &mut dyn std::io::Write, button: u8, col: u16, row: u16, press: bool,
+                                enc: vt100::MouseProtocolEncoding, cell_px: Option<(u16, u16)>) {
+    let ch = if press { 'M' } else { 'm' };
     match enc {
         vt100::MouseProtocolEncoding::Sgr => {
-            let ch = if press { 'M' } else { 'm' };
             let _ = write!(master, "\x1b[<{};{};{}{}", button, col, row, ch);
             let _ = master.flush();
         }
+        vt100::MouseProtocolEncoding::SgrPixels => {
+            let (px, py) = cell_to_pixel(col, row, cell_px);
+            let _ = write!(master, "\x1b[<{};{};{}{}", button, px, py, ch);
+            let _ = master.flush();
+        }
+        vt100::MouseProtocolEncoding::Urxvt => {
+            // Decimal X10 layout: releases are reported as button 3.
+            let cb = if press { button as u16 } else { 3 + (button & !3) as u16 } + 32;
+            let _ = write!(master, "\x1b[{};{};{}M", cb, col, row);
+            let _ = master.flush();
+        }
         _ => {
             if press {
                 let cb = (button + 32) as u8;
@@ -255,17 +283,24 @@ fn detect_mouse_input(pane: &mut Pane) -> bool {
 ///
 /// Used ONLY for WSL/SSH bridge children where the PTY pipe doesn't reach
 /// the remote TUI.  For native ConPTY children, use write_mouse_to_pty().
-fn inject_sgr_mouse(pane: &mut Pane, col: i16, row: i16, vt_button: u8, press: bool) -> bool {
+fn inject_sgr_mouse(pane: &mut Pane, col: i16, row: i16, vt_button: u8, press: bool,
+                    enc: vt100::MouseProtocolEncoding, cell_px: Option<(u16, u16)>) -> bool {
     let vt_col = (col + 1).max(1) as u16;
     let vt_row = (row + 1).max(1) as u16;
-    let ch = if press { 'M' } else { 'm' };
-    let sgr_seq = format!("\x1b[<{};{};{}{}", vt_button, vt_col, vt_row, ch);
-    mouse_log(&format!("  -> Console VT injection (KEY_EVENTs): seq={:?}", sgr_seq));
+    // The remote app sees its own DECSET encoding; default to SGR since
+    // the bridge relays whatever we inject verbatim.
+    let enc = match enc {
+        vt100::MouseProtocolEncoding::Urxvt | vt100::MouseProtocolEncoding::SgrPixels => enc,
+        _ => vt100::MouseProtocolEncoding::Sgr,
+    };
+    let mut seq: Vec<u8> = Vec::with_capacity(32);
+    write_mouse_event_remote(&mut seq, vt_button, vt_col, vt_row, press, enc, cell_px);
+    mouse_log(&format!("  -> Console VT injection (KEY_EVENTs): seq={:?}", String::from_utf8_lossy(&seq)));
     if pane.child_pid.is_none() {
         pane.child_pid = mouse_inject::get_child_pid(&*pane.child);
     }
     if let Some(pid) = pane.child_pid {
-        let ok = mouse_inject::send_vt_sequence(pid, sgr_seq.as_bytes());
+        let ok = mouse_inject::send_vt_sequence(pid, &seq);
         mouse_log(&format!("  -> Console VT inject result: {}", ok));
         ok
     } else {
@@ -284,10 +319,19 @@ fn inject_sgr_mouse(pane: &mut Pane, col: i16, row: i16, vt_button: u8, press: b
 ///
 /// This works universally for ALL native ConPTY children — no need to
 /// distinguish between crossterm vs nvim.  (fixes #60)
-fn write_mouse_to_pty(pane: &mut Pane, col: i16, row: i16, vt_button: u8, press: bool) {
+///
+/// Children that explicitly asked for urxvt (1015) or SGR-pixel (1016)
+/// reports get those instead; ConPTY passes them through untranslated.
+fn write_mouse_to_pty(pane: &mut Pane, col: i16, row: i16, vt_button: u8, press: bool,
+                      enc: vt100::MouseProtocolEncoding, cell_px: Option<(u16, u16)>) {
     use std::io::Write as _;
     let vt_col = (col + 1).max(1) as u16;
     let vt_row = (row + 1).max(1) as u16;
+    if matches!(enc, vt100::MouseProtocolEncoding::Urxvt | vt100::MouseProtocolEncoding::SgrPixels) {
+        mouse_log(&format!("  -> PTY pipe {:?} mouse: btn={} col={} row={} press={}", enc, vt_button, vt_col, vt_row, press));
+        write_mouse_event_remote(&mut pane.writer, vt_button, vt_col, vt_row, press, enc, cell_px);
+        return;
+    }
     let ch = if press { b'M' } else { b'm' };
     // Stack-allocated buffer — avoids heap allocation per mouse event.
     // Max SGR sequence: ESC[<btn;col;rowM = ~20 bytes worst case.
@@ -320,8 +364,11 @@ fn write_mouse_to_pty(pane: &mut Pane, col: i16, row: i16, vt_button: u8, press:
 ///   doesn't handle mouse events.  Callers should handle shell-level
 ///   behavior (right-click=paste, scroll=copy-mode) before calling this.
 pub(crate) fn inject_mouse_combined(pane: &mut Pane, col: i16, row: i16, vt_button: u8, press: bool,
-                          _button_state: u32, _event_flags: u32, win_name: &str) {
+                          _button_state: u32, _event_flags: u32, win_name: &str,
+                          cell_px: Option<(u16, u16)>) {
     let vt_bridge = detect_vt_bridge(pane);
+    let enc = pane.term.lock().ok()
+        .map_or(vt100::MouseProtocolEncoding::Default, |t| t.screen().mouse_protocol_encoding());
 
     if vt_bridge {
         // WSL/SSH bridge — bypass ConPTY, inject as KEY_EVENT records.
@@ -348,7 +395,7 @@ pub(crate) fn inject_mouse_combined(pane: &mut Pane, col: i16, row: i16, vt_butt
         }
         mouse_log(&format!("inject_mouse_combined: col={} row={} vt_btn={} press={} win={} vt_bridge=true -> WriteConsoleInputW KEY_EVENT injection",
             col, row, vt_button, press, win_name));
-        inject_sgr_mouse(pane, col, row, vt_button, press);
+        inject_sgr_mouse(pane, col, row, vt_button, press, enc, cell_px);
     } else {
         // Native ConPTY child — write SGR mouse to PTY pipe.
         // This is the same mechanism Windows Terminal uses.
@@ -356,7 +403,7 @@ pub(crate) fn inject_mouse_combined(pane: &mut Pane, col: i16, row: i16, vt_butt
         // and passes VT through for nvim/vim.
         mouse_log(&format!("inject_mouse_combined: col={} row={} vt_btn={} press={} win={} -> PTY pipe SGR mouse (Windows Terminal method)",
             col, row, vt_button, press, win_name));
-        write_mouse_to_pty(pane, col, row, vt_button, press);
+        write_mouse_to_pty(pane, col, row, vt_button, press, enc, cell_px);
     }
 }
 
@@ -443,6 +490,10 @@ pub fn update_tab_positions(app: &mut AppState) {
 }
 
 pub fn remote_mouse_down(app: &mut AppState, x: u16, y: u16) {
+    app.last_mouse_down = Some((x, y));
+    if crate::menu::mouse_down(app, x, y) {
+        return;
+    }
     // Recompute tab positions to match client rendering
     update_tab_positions(app);
 
@@ -464,6 +515,10 @@ pub fn remote_mouse_down(app: &mut AppState, x: u16, y: u16) {
         return;
     }
 
+    if crate::popup::mouse_down(app, x, y) {
+        return;
+    }
+
     let win = &mut app.windows[app.active_idx];
     let mut rects: Vec<(Vec<usize>, Rect)> = Vec::new();
     compute_rects(&win.root, app.last_window_area, &mut rects);
@@ -512,7 +567,7 @@ pub fn remote_mouse_down(app: &mut AppState, x: u16, y: u16) {
             if let Some(active) = active_pane_mut(&mut win.root, &win.active_path) {
                 if pane_wants_mouse(active) {
                     inject_mouse_combined(active, col, row, 0, true,
-                        mouse_inject::FROM_LEFT_1ST_BUTTON_PRESSED, 0, &win_name);
+                        mouse_inject::FROM_LEFT_1ST_BUTTON_PRESSED, 0, &win_name, app.client_cell_size);
                 }
             }
         }
@@ -520,6 +575,9 @@ pub fn remote_mouse_down(app: &mut AppState, x: u16, y: u16) {
 }
 
 pub fn remote_mouse_drag(app: &mut AppState, x: u16, y: u16) {
+    if crate::menu::mouse_move(app, x, y) || crate::popup::mouse_drag(app, x, y) {
+        return;
+    }
     let win = &mut app.windows[app.active_idx];
     let mut rects: Vec<(Vec<usize>, Rect)> = Vec::new();
     compute_rects(&win.root, app.last_window_area, &mut rects);
@@ -548,7 +606,7 @@ pub fn remote_mouse_drag(app: &mut AppState, x: u16, y: u16) {
             if let Some(active) = active_pane_mut(&mut win.root, &win.active_path) {
                 if pane_wants_mouse(active) {
                     inject_mouse_combined(active, col, row, 32, true,
-                        mouse_inject::FROM_LEFT_1ST_BUTTON_PRESSED, mouse_inject::MOUSE_MOVED, &win_name);
+                        mouse_inject::FROM_LEFT_1ST_BUTTON_PRESSED, mouse_inject::MOUSE_MOVED, &win_name, app.client_cell_size);
                 }
             }
         }
@@ -556,6 +614,9 @@ pub fn remote_mouse_drag(app: &mut AppState, x: u16, y: u16) {
 }
 
 pub fn remote_mouse_up(app: &mut AppState, x: u16, y: u16) {
+    if crate::menu::mouse_up(app, x, y) || crate::popup::mouse_up(app) {
+        return;
+    }
     let win = &mut app.windows[app.active_idx];
     let mut rects: Vec<(Vec<usize>, Rect)> = Vec::new();
     compute_rects(&win.root, app.last_window_area, &mut rects);
@@ -594,7 +655,7 @@ pub fn remote_mouse_up(app: &mut AppState, x: u16, y: u16) {
         if let Some(active) = active_pane_mut(&mut win.root, &win.active_path) {
             if pane_wants_mouse(active) {
                 inject_mouse_combined(active, col, row, 0, false,
-                    0, 0, &win_name);
+                    0, 0, &win_name, app.client_cell_size);
             }
         }
     }
@@ -602,6 +663,7 @@ pub fn remote_mouse_up(app: &mut AppState, x: u16, y: u16) {
 
 /// Forward a non-left mouse button press/release to the child.
 pub fn remote_mouse_button(app: &mut AppState, x: u16, y: u16, button: u8, press: bool) {
+    if press { app.last_mouse_down = Some((x, y)); }
     let win = &mut app.windows[app.active_idx];
     let mut rects: Vec<(Vec<usize>, Rect)> = Vec::new();
     compute_rects(&win.root, app.last_window_area, &mut rects);
@@ -625,7 +687,7 @@ pub fn remote_mouse_button(app: &mut AppState, x: u16, y: u16, button: u8, press
                     0
                 };
                 inject_mouse_combined(active, col, row, sgr_btn, press,
-                    button_state, 0, &win_name);
+                    button_state, 0, &win_name, app.client_cell_size);
             }
         }
     }
@@ -649,6 +711,9 @@ pub fn remote_mouse_motion(app: &mut AppState, x: u16, y: u16) {
         return;
     }
     app.last_hover_pos = Some((x, y));
+    if crate::menu::mouse_move(app, x, y) {
+        return;
+    }
 
     let win = &mut app.windows[app.active_idx];
     let mut rects: Vec<(Vec<usize>, Rect)> = Vec::new();
@@ -665,7 +730,7 @@ pub fn remote_mouse_motion(app: &mut AppState, x: u16, y: u16) {
         if let Some(active) = active_pane_mut(&mut win.root, &win.active_path) {
             if pane_wants_mouse(active) {
                 inject_mouse_combined(active, col, row, 35, true,
-                    0, mouse_inject::MOUSE_MOVED, &win_name);
+                    0, mouse_inject::MOUSE_MOVED, &win_name, app.client_cell_size);
             }
         }
     }
@@ -700,13 +765,15 @@ fn remote_scroll_wheel(app: &mut AppState, x: u16, y: u16, up: bool) {
         return;
     }
 
+    let lines = app.wheel_scroll_lines.max(1) as usize;
+
     // Handle scroll while already in copy mode
     if matches!(app.mode, Mode::CopyMode | Mode::CopySearch { .. }) {
         mouse_log("  -> already in copy mode, scrolling within");
         if up {
-            scroll_copy_up(app, 3);
+            scroll_copy_up(app, lines);
         } else {
-            scroll_copy_down(app, 3);
+            scroll_copy_down(app, lines);
             // Auto-exit copy mode when scrolled back to live output
             if app.copy_scroll_offset == 0 && app.copy_anchor.is_none() {
                 exit_copy_mode(app);
@@ -730,7 +797,11 @@ fn remote_scroll_wheel(app: &mut AppState, x: u16, y: u16, up: bool) {
     // alternate_screen() is reliable: all modern TUI apps (nvim, htop, vim,
     // opencode) correctly report alternate screen through ConPTY.  Testing
     // confirms nvim shows alternate_on=1.  Shell prompts always show 0.
-    let (child_in_alt_screen, target_area_opt, sgr_btn, button_state) = {
+    //
+    // Alternate scroll (xterm DECSET 1007, on by default): an alt-screen
+    // app that never asked for mouse input (less, man, git's pager) gets
+    // the wheel as cursor up/down keys instead of mouse reports it ignores.
+    let (child_in_alt_screen, arrow_keys, target_area_opt, sgr_btn, button_state) = {
         let win = &mut app.windows[app.active_idx];
         let mut rects: Vec<(Vec<usize>, Rect)> = Vec::new();
         compute_rects(&win.root, app.last_window_area, &mut rects);
@@ -750,22 +821,47 @@ fn remote_scroll_wheel(app: &mut AppState, x: u16, y: u16, up: bool) {
                 .map(|(_, area)| *area);
         }
 
-        let alt = active_pane(&win.root, &win.active_path)
-            .map_or(false, |p| {
-                if let Ok(parser) = p.term.lock() {
-                    return parser.screen().alternate_screen();
-                }
-                false
+        let (alt, arrow_keys) = active_pane_mut(&mut win.root, &win.active_path)
+            .map_or((false, None), |p| {
+                let (alt, alt_scroll, mouse_mode, app_cursor) = match p.term.lock() {
+                    Ok(parser) => {
+                        let screen = parser.screen();
+                        (screen.alternate_screen(), screen.alternate_scroll(),
+                         screen.mouse_protocol_mode() != vt100::MouseProtocolMode::None,
+                         screen.application_cursor())
+                    }
+                    Err(_) => return (false, None),
+                };
+                // ConPTY may strip DECSET 1000 from native apps, so also
+                // honour a console that has ENABLE_MOUSE_INPUT set.
+                let arrows = if alt && alt_scroll && !mouse_mode && !detect_mouse_input(p) {
+                    Some(match (up, app_cursor) {
+                        (true, false) => &b"\x1b[A"[..],
+                        (true, true) => &b"\x1bOA"[..],
+                        (false, false) => &b"\x1b[B"[..],
+                        (false, true) => &b"\x1bOB"[..],
+                    })
+                } else {
+                    None
+                };
+                (alt, arrows)
             });
         let sgr_btn: u8 = if up { 64 } else { 65 };
         let wheel_delta: i16 = if up { 120 } else { -120 };
         let bs = ((wheel_delta as i32) << 16) as u32;
-        (alt, target_area, sgr_btn, bs)
+        (alt, arrow_keys, target_area, sgr_btn, bs)
     };
 
-    mouse_log(&format!("  -> alt_screen={}", child_in_alt_screen));
+    mouse_log(&format!("  -> alt_screen={} alt_scroll_keys={}", child_in_alt_screen, arrow_keys.is_some()));
 
-    if child_in_alt_screen {
+    if let Some(seq) = arrow_keys {
+        mouse_log(&format!("  -> alternate scroll: {} x {:?}", lines, seq));
+        let win = &mut app.windows[app.active_idx];
+        if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) {
+            let _ = p.writer.write_all(&seq.repeat(lines));
+            let _ = p.writer.flush();
+        }
+    } else if child_in_alt_screen {
         // Forward scroll to child TUI app (alternate screen = real TUI)
         mouse_log("  -> forwarding scroll to child TUI (alt screen)");
         let win = &mut app.windows[app.active_idx];
@@ -773,13 +869,13 @@ fn remote_scroll_wheel(app: &mut AppState, x: u16, y: u16, up: bool) {
         let win_name = win.name.clone();
         if let Some(p) = active_pane_mut(&mut win.root, &win.active_path) {
             inject_mouse_combined(p, col, row, sgr_btn, true,
-                button_state, mouse_inject::MOUSE_WHEELED, &win_name);
+                button_state, mouse_inject::MOUSE_WHEELED, &win_name, app.client_cell_size);
         }
     } else if up {
         // Shell prompt — auto-enter copy mode and scroll up (tmux parity)
         mouse_log("  -> entering copy mode (shell scroll-up)");
         enter_copy_mode(app);
-        scroll_copy_up(app, 3);
+        scroll_copy_up(app, lines);
     } else {
         mouse_log("  -> scroll-down at shell (no-op)");
     }
@@ -950,6 +1046,7 @@ pub fn break_pane_to_window(app: &mut AppState) {
             manual_rename: false,
             layout_index: 0,
             pane_mru: initial_mru,
+            floating: Vec::new(),
         });
         app.next_win_id += 1;
         
@@ -992,16 +1089,13 @@ pub fn respawn_active_pane(app: &mut AppState, pty_system_ref: Option<&dyn porta
     let child = pair.slave.spawn_command(shell_cmd).map_err(|e| io::Error::new(io::ErrorKind::Other, format!("spawn shell error: {e}")))?;
     // Close the slave handle immediately – required for ConPTY.
     drop(pair.slave);
-    let term: Arc<Mutex<vt100::Parser>> = Arc::new(Mutex::new(vt100::Parser::new(size.rows, size.cols, app.history_limit)));
+    let term: Arc<Mutex<crate::pane::PaneParser>> = Arc::new(Mutex::new(crate::pane::new_parser(size.rows, size.cols, app.history_limit, app.ambiguous_width, app.variation_selector_always_wide)));
     let term_reader = term.clone();
     let reader = pair.master.try_clone_reader().map_err(|e| io::Error::new(io::ErrorKind::Other, format!("clone reader error: {e}")))?;
     
     let data_version = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
     let dv_writer = data_version.clone();
-    let cursor_shape = std::sync::Arc::new(std::sync::atomic::AtomicU8::new(crate::pane::CURSOR_SHAPE_UNSET));
-    let cs_writer = cursor_shape.clone();
-    
-    crate::pane::spawn_reader_thread(reader, term_reader, dv_writer, cs_writer);
+    crate::pane::spawn_reader_thread(reader, term_reader, dv_writer);
     
     let mut pty_writer = pair.master.take_writer().map_err(|e| io::Error::new(io::ErrorKind::Other, format!("take writer error: {e}")))?;
     crate::pane::conpty_preemptive_dsr_response(&mut *pty_writer);
@@ -1011,12 +1105,47 @@ pub fn respawn_active_pane(app: &mut AppState, pty_system_ref: Option<&dyn porta
     pane.child = child;
     pane.term = term;
     pane.data_version = data_version;
-    pane.cursor_shape = cursor_shape;
     pane.child_pid = None;
     pane.vt_bridge_cache = None;
     pane.vti_mode_cache = None;
     pane.mouse_input_cache = None;
     pane.dead = false;
+    pane.exit_status = None;
+    pane.dead_time = None;
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(button: u8, col: u16, row: u16, press: bool,
+              enc: vt100::MouseProtocolEncoding, cell_px: Option<(u16, u16)>) -> String {
+        let mut out = Vec::new();
+        write_mouse_event_remote(&mut out, button, col, row, press, enc, cell_px);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn urxvt_reports_decimal_x10_buttons() {
+        use vt100::MouseProtocolEncoding::Urxvt;
+        assert_eq!(encode(0, 5, 3, true, Urxvt, None), "\x1b[32;5;3M");
+        assert_eq!(encode(2, 300, 40, true, Urxvt, None), "\x1b[34;300;40M");
+        // Releases are button 3, wheel and drag keep their high bits
+        assert_eq!(encode(0, 5, 3, false, Urxvt, None), "\x1b[35;5;3M");
+        assert_eq!(encode(64, 1, 1, true, Urxvt, None), "\x1b[96;1;1M");
+        assert_eq!(encode(32, 2, 2, true, Urxvt, None), "\x1b[64;2;2M");
+    }
+
+    #[test]
+    fn sgr_pixels_reports_the_cell_origin_in_pixels() {
+        use vt100::MouseProtocolEncoding::{Sgr, SgrPixels};
+        assert_eq!(encode(0, 5, 3, true, Sgr, None), "\x1b[<0;5;3M");
+        // Falls back to 8x16 cells when the client size is unknown
+        assert_eq!(encode(0, 1, 1, true, SgrPixels, None), "\x1b[<0;1;1M");
+        assert_eq!(encode(0, 5, 3, true, SgrPixels, None), "\x1b[<0;33;33M");
+        assert_eq!(encode(2, 5, 3, false, SgrPixels, Some((10, 20))), "\x1b[<2;41;41m");
+        assert_eq!(encode(65, 2, 2, true, SgrPixels, Some((9, 18))), "\x1b[<65;10;19M");
+    }
+}